@@ -7,12 +7,14 @@ use std::process::Command;
 use glob::glob;
 
 fn main() {
+    let out_dir = path::PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR was not defined"));
+
+    // OUT_DIR is at <profile>/build/<package>/out, and the natives library is built in <profile>
+    let profile_dir = out_dir.ancestors().nth(3).expect("OUT_DIR is not in a build profile");
+    println!("cargo:rustc-env=PROFILE_DIR={}", profile_dir.display());
+
     // The output directory is at OUT_DIR/runtime
-    let out = {
-        let mut out = path::PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR was not defined"));
-        out.push("runtime");
-        out
-    };
+    let out = out_dir.join("runtime");
     if !out.is_dir() {
         fs::create_dir(&out).expect("failed to create runtime output directory");
     }
@@ -21,14 +23,19 @@ fn main() {
     for entry in glob("runtime/**/*.java").expect("Failed to read glob pattern") {
         match entry {
             Ok(path) => {
-                Command::new("javac")
+                let success = Command::new("javac")
                     .current_dir("runtime")
+                    .arg("--release")
+                    .arg("8")
                     .arg("-d")
                     .arg(&out)
                     .arg(path.strip_prefix("runtime").expect("failed to strip runtime"))
                     .status()
                     .expect("failed to execute javac")
                     .success();
+                if !success {
+                    panic!("failed to compile {}", path.display());
+                }
                 println!("cargo:rerun-if-changed={}", path.display());
             }
             Err(e) => {
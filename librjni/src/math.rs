@@ -1,6 +1,10 @@
 use rust_jvm::vm::value::Value;
 
+/// # Safety
+///
+/// `argv` must point to `argc` arguments.
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 pub unsafe extern "C" fn java_lang_Math_log10(argc: usize, argv: *const Value) -> Option<Value> {
     match argc {
         1 => {
@@ -13,7 +17,11 @@ pub unsafe extern "C" fn java_lang_Math_log10(argc: usize, argv: *const Value) -
     }
 }
 
+/// # Safety
+///
+/// `argv` must point to `argc` arguments.
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 pub unsafe extern "C" fn java_lang_Math_pow(argc: usize, argv: *const Value) -> Option<Value> {
     match argc {
         2 => {
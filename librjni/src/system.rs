@@ -4,27 +4,29 @@ use rust_jvm::vm::value::Value;
 
 use std::num::Wrapping;
 
+/// # Safety
+///
+/// `argv` must point to `argc` arguments.
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 pub unsafe extern "C" fn java_lang_System_write(argc: usize, argv: *const Value) -> Option<Value> {
-    match argc {
-        1 => {
-            let arg = &*argv.offset(0);
-            match *arg {
-                Value::Int(ref i) => {
-                    libc::putchar(i.0);
-                }
-                _ => (),
-            }
+    if argc == 1 {
+        let arg = &*argv.offset(0);
+        if let Value::Int(ref i) = *arg {
+            libc::putchar(i.0);
         }
-        _ => (),
     }
 
     None
 }
 
+/// # Safety
+///
+/// Takes no arguments, so `argv` is never read.
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 pub unsafe extern "C" fn java_lang_System_readInt(argc: usize,
-                                                  argv: *const Value)
+                                                  _argv: *const Value)
                                                   -> Option<Value> {
     match argc {
         0 => {
@@ -36,51 +38,66 @@ pub unsafe extern "C" fn java_lang_System_readInt(argc: usize,
     }
 }
 
+/// # Safety
+///
+/// `argv` must point to `argc` arguments.
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 pub unsafe extern "C" fn java_lang_System_arraycopy(argc: usize,
                                                     argv: *const Value)
                                                     -> Option<Value> {
-    match argc {
-        5 => {
-            let src = {
-                if let Value::ArrayReference(ref array) = *argv.offset(0) {
-                    array.clone()
-                } else {
-                    return None;
-                }
-            };
-            let srcPos = {
-                if let Value::Int(value) = *argv.offset(1) {
-                    value
-                } else {
-                    return None;
-                }
-            };
-            let dst = {
-                if let Value::ArrayReference(ref array) = *argv.offset(2) {
-                    array.clone()
-                } else {
-                    return None;
-                }
-            };
-            let dstPos = {
-                if let Value::Int(value) = *argv.offset(3) {
-                    value
-                } else {
-                    return None;
-                }
-            };
-            let count = {
-                if let Value::Int(value) = *argv.offset(4) {
-                    value
-                } else {
-                    return None;
-                }
-            };
+    if argc == 5 {
+        let src = {
+            if let Value::ArrayReference(ref array) = *argv.offset(0) {
+                array.clone()
+            } else {
+                return None;
+            }
+        };
+        let srcPos = {
+            if let Value::Int(value) = *argv.offset(1) {
+                value
+            } else {
+                return None;
+            }
+        };
+        let dst = {
+            if let Value::ArrayReference(ref array) = *argv.offset(2) {
+                array.clone()
+            } else {
+                return None;
+            }
+        };
+        let dstPos = {
+            if let Value::Int(value) = *argv.offset(3) {
+                value
+            } else {
+                return None;
+            }
+        };
+        let count = {
+            if let Value::Int(value) = *argv.offset(4) {
+                value
+            } else {
+                return None;
+            }
+        };
+
+        dst.borrow_mut().copy_from(src, srcPos.0, dstPos.0, count.0);
+    }
+    None
+}
 
-            dst.borrow_mut().copy_from(src, srcPos.0, dstPos.0, count.0);
+/// # Safety
+///
+/// `argv` must point to `argc` arguments.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn java_lang_System_exit(argc: usize, argv: *const Value) -> Option<Value> {
+    if argc == 1 {
+        if let Value::Int(status) = *argv.offset(0) {
+            libc::exit(status.0);
         }
-        _ => (),
     }
     None
 }
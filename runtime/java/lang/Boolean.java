@@ -0,0 +1,17 @@
+package java.lang;
+
+public final class Boolean {
+    private final boolean value;
+
+    public Boolean(boolean value) {
+        this.value = value;
+    }
+
+    public static Boolean valueOf(boolean value) {
+        return new Boolean(value);
+    }
+
+    public boolean booleanValue() {
+        return value;
+    }
+}
@@ -0,0 +1,17 @@
+package java.lang;
+
+public final class Character {
+    private final char value;
+
+    public Character(char value) {
+        this.value = value;
+    }
+
+    public static Character valueOf(char value) {
+        return new Character(value);
+    }
+
+    public char charValue() {
+        return value;
+    }
+}
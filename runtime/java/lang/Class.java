@@ -0,0 +1,13 @@
+package java.lang;
+
+public final class Class<T> {
+    /** Class objects are only created by the VM */
+    private Class() {
+    }
+
+    public native ClassLoader getClassLoader();
+
+    public native String getName();
+
+    public native T newInstance() throws InstantiationException, IllegalAccessException;
+}
@@ -0,0 +1,60 @@
+package java.lang;
+
+public abstract class ClassLoader {
+    /** The loader that classes are delegated to first, or null for the bootstrap class loader */
+    private final ClassLoader parent;
+
+    protected ClassLoader(ClassLoader parent) {
+        this.parent = parent;
+    }
+
+    protected ClassLoader() {
+        this(null);
+    }
+
+    public final ClassLoader getParent() {
+        return parent;
+    }
+
+    public Class<?> loadClass(String name) throws ClassNotFoundException {
+        return loadClass(name, false);
+    }
+
+    protected Class<?> loadClass(String name, boolean resolve) throws ClassNotFoundException {
+        Class<?> c = findLoadedClass(name);
+        if (c == null) {
+            if (parent != null) {
+                try {
+                    c = parent.loadClass(name, false);
+                } catch (ClassNotFoundException e) {
+                    // Not found by the parent, so this loader looks for it below
+                }
+            } else {
+                c = findBootstrapClassOrNull(name);
+            }
+            if (c == null) {
+                c = findClass(name);
+            }
+        }
+        return c;
+    }
+
+    protected Class<?> findClass(String name) throws ClassNotFoundException {
+        throw new ClassNotFoundException(name);
+    }
+
+    protected final Class<?> defineClass(String name, byte[] b, int off, int len)
+            throws ClassFormatError {
+        return defineClass1(name, b, off, len);
+    }
+
+    protected final Class<?> findLoadedClass(String name) {
+        return findLoadedClass0(name);
+    }
+
+    private native Class<?> defineClass1(String name, byte[] b, int off, int len);
+
+    private native Class<?> findLoadedClass0(String name);
+
+    private static native Class<?> findBootstrapClassOrNull(String name);
+}
@@ -0,0 +1,26 @@
+package java.lang;
+
+public class ExceptionInInitializerError extends LinkageError {
+    private Throwable exception;
+
+    public ExceptionInInitializerError() {
+        super();
+    }
+
+    public ExceptionInInitializerError(Throwable thrown) {
+        super();
+        this.exception = thrown;
+    }
+
+    public ExceptionInInitializerError(String message) {
+        super(message);
+    }
+
+    public Throwable getException() {
+        return exception;
+    }
+
+    public Throwable getCause() {
+        return exception;
+    }
+}
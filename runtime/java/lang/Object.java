@@ -1,5 +1,5 @@
 package java.lang;
 
 public class Object {
-
+    public final native Class<?> getClass();
 }
@@ -8,6 +8,26 @@ public final class String {
         System.arraycopy(bytes, 0, this.bytes, 0, bytes.length);
     }
 
+    public int length() {
+        return bytes.length;
+    }
+
+    public boolean equals(Object other) {
+        if (!(other instanceof String)) {
+            return false;
+        }
+        char[] otherBytes = ((String) other).bytes;
+        if (bytes.length != otherBytes.length) {
+            return false;
+        }
+        for (int i = 0; i < bytes.length; i++) {
+            if (bytes[i] != otherBytes[i]) {
+                return false;
+            }
+        }
+        return true;
+    }
+
     public byte[] getBytes() {
         byte[] b = new byte[2 * bytes.length];
         for (int i = 0; i < bytes.length; i++) {
@@ -1,8 +1,31 @@
 package java.lang;
 
+import java.util.Objects;
+
 public class System {
+    /** System properties as alternating keys and values, set by the VM before main runs */
+    private static String[] props;
+
     public static native void arraycopy(Object src, int srcPos, Object dest, int destPos, int length);
 
+    public static native void exit(int status);
+
+    public static String getProperty(String key) {
+        return getProperty(key, null);
+    }
+
+    public static String getProperty(String key, String def) {
+        Objects.requireNonNull(key);
+        if (props != null) {
+            for (int i = 0; i < props.length; i += 2) {
+                if (key.equals(props[i])) {
+                    return props[i + 1];
+                }
+            }
+        }
+        return def;
+    }
+
     public static native int readInt();
     public static native void write(byte b);
 
@@ -0,0 +1,45 @@
+package java.lang;
+
+public class Throwable {
+    private String message;
+    private Throwable cause;
+    private Suppressed suppressed;
+
+    public Throwable() {
+    }
+
+    public Throwable(String message) {
+        this.message = message;
+    }
+
+    public Throwable(String message, Throwable cause) {
+        this.message = message;
+        this.cause = cause;
+    }
+
+    public Throwable(Throwable cause) {
+        this.cause = cause;
+    }
+
+    public String getMessage() {
+        return message;
+    }
+
+    public Throwable getCause() {
+        return cause;
+    }
+
+    public final void addSuppressed(Throwable exception) {
+        suppressed = new Suppressed(exception, suppressed);
+    }
+
+    private static final class Suppressed {
+        private final Throwable exception;
+        private final Suppressed next;
+
+        Suppressed(Throwable exception, Suppressed next) {
+            this.exception = exception;
+            this.next = next;
+        }
+    }
+}
@@ -0,0 +1,19 @@
+package java.lang.invoke;
+
+public abstract class MethodHandle {
+    private MethodType type;
+
+    /** MethodHandle objects are only created by the VM */
+    private MethodHandle() {
+    }
+
+    public MethodType type() {
+        return type;
+    }
+
+    /** Signature polymorphic: the VM invokes the handle with the arguments of each call site */
+    public final native Object invokeExact(Object... args) throws Throwable;
+
+    /** Signature polymorphic, like invokeExact */
+    public final native Object invoke(Object... args) throws Throwable;
+}
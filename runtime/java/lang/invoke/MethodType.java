@@ -0,0 +1,13 @@
+package java.lang.invoke;
+
+public final class MethodType {
+    private String descriptor;
+
+    /** MethodType objects are only created by the VM */
+    private MethodType() {
+    }
+
+    public String toMethodDescriptorString() {
+        return descriptor;
+    }
+}
@@ -1,16 +1,238 @@
 extern crate rust_jvm;
 
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
 
 use rust_jvm::vm;
+use rust_jvm::vm::archive::Archive;
 
-const RUNTIME_PATH: &'static str = concat!(env!("OUT_DIR"), "/runtime");
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+/// The stack size of the thread running `main` when `-Xss` is not given
+const DEFAULT_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+const USAGE: &str = "Usage: rust-jvm [options] <mainclass> [args...]
+           (to execute a class)
+   or  rust-jvm [options] -jar <jarfile> [args...]
+           (to execute a jar file)
+
+where options include:
+    -cp <class search path of directories and zip/jar files>
+    -classpath <class search path of directories and zip/jar files>
+                  A : separated list of directories, JAR archives,
+                  and ZIP archives to search for class files.
+                  A dir/* entry includes every JAR file in dir.
+    -D<name>=<value>
+                  set a system property
+    -Xss<size>    set the stack size of the main thread
+    -Xmx<size>    set the maximum heap size
+    -help, -h     print this help message";
+
+/// What the launcher should run
+enum Target {
+    Class(String),
+    Jar(PathBuf),
+}
+
+struct Options {
+    class_path: Option<String>,
+    properties: Vec<(String, String)>,
+    stack_size: usize,
+    target: Target,
+    args: Vec<String>,
+}
+
+/// Parses a memory size such as `512k`, `64m` or `1g` into a number of bytes.
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, multiplier) = match size.chars().last() {
+        Some('k') | Some('K') => (&size[..size.len() - 1], 1 << 10),
+        Some('m') | Some('M') => (&size[..size.len() - 1], 1 << 20),
+        Some('g') | Some('G') => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    digits.parse::<usize>().ok().and_then(|n| n.checked_mul(multiplier)).filter(|&n| n > 0)
+}
+
+/// Parses the launcher's command line, returning `None` if help was requested.
+fn parse_options<I>(mut args: I) -> Result<Option<Options>, String>
+    where I: Iterator<Item = String>
+{
+    let mut class_path = None;
+    let mut properties = Vec::new();
+    let mut stack_size = DEFAULT_STACK_SIZE;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-cp" | "-classpath" | "--class-path" => {
+                match args.next() {
+                    Some(path) => class_path = Some(path),
+                    None => return Err(format!("{} requires class path specification", arg)),
+                }
+            }
+            "-jar" => {
+                return match args.next() {
+                    Some(jar) => {
+                        Ok(Some(Options {
+                            class_path,
+                            properties,
+                            stack_size,
+                            target: Target::Jar(PathBuf::from(jar)),
+                            args: args.collect(),
+                        }))
+                    }
+                    None => Err(String::from("-jar requires jar file specification")),
+                };
+            }
+            "-h" | "-help" | "--help" | "-?" => return Ok(None),
+            _ if arg.starts_with("-D") => {
+                let property = &arg[2..];
+                let (key, value) = match property.find('=') {
+                    Some(index) => (&property[..index], &property[index + 1..]),
+                    None => (property, ""),
+                };
+                properties.push((String::from(key), String::from(value)));
+            }
+            _ if arg.starts_with("-Xss") => {
+                stack_size = parse_size(&arg[4..])
+                    .ok_or_else(|| format!("Invalid thread stack size: {}", arg))?;
+            }
+            _ if arg.starts_with("-Xmx") => {
+                // Objects are reference counted rather than allocated in a fixed size heap, so
+                // the maximum heap size is validated but has no effect
+                parse_size(&arg[4..])
+                    .ok_or_else(|| format!("Invalid maximum heap size: {}", arg))?;
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unrecognized option: {}", arg)),
+            _ => {
+                return Ok(Some(Options {
+                    class_path,
+                    properties,
+                    stack_size,
+                    target: Target::Class(arg),
+                    args: args.collect(),
+                }));
+            }
+        }
+    }
+    Err(String::from("no main class specified"))
+}
+
+/// Expands a `:` separated class path. Empty entries refer to the current directory, and
+/// entries ending in `*` refer to every JAR file in that directory.
+fn expand_class_path(class_path: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for entry in class_path.split(':') {
+        if entry.is_empty() {
+            paths.push(PathBuf::from("."));
+        } else if entry == "*" || entry.ends_with("/*") {
+            let dir = Path::new(&entry[..entry.len() - 1]);
+            let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+            let mut jars: Vec<PathBuf> = fs::read_dir(dir)
+                .into_iter()
+                .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("jar"))
+                })
+                .collect();
+            jars.sort();
+            paths.extend(jars);
+        } else {
+            paths.push(PathBuf::from(entry));
+        }
+    }
+    paths
+}
+
+/// Reads the `Main-Class` attribute from the manifest of a JAR file.
+fn jar_main_class(jar: &Path) -> Result<String, String> {
+    let archive = Archive::open(jar)
+        .map_err(|e| format!("Invalid or corrupt jarfile {}: {}", jar.display(), e))?;
+    let manifest = archive.manifest()
+        .map_err(|e| format!("Invalid or corrupt jarfile {}: {}", jar.display(), e))?;
+    manifest.as_ref()
+        .and_then(|manifest| manifest.get("Main-Class"))
+        .map(|main_class| String::from(main_class.trim()))
+        .ok_or_else(|| format!("no main manifest attribute, in {}", jar.display()))
+}
+
+/// Runs the VM, returning the exit status of the process.
+fn run(options: Options) -> Result<i32, String> {
+    let (class_path, main_class) = match options.target {
+        Target::Class(main_class) => {
+            let class_path = options.class_path
+                .or_else(|| env::var("CLASSPATH").ok())
+                .unwrap_or_else(|| String::from("."));
+            (class_path, main_class)
+        }
+        Target::Jar(jar) => {
+            // As with java, the class path is ignored when running a JAR file
+            let main_class = jar_main_class(&jar)?;
+            (jar.to_string_lossy().into_owned(), main_class)
+        }
+    };
+
+    let mut class_paths = vec![PathBuf::from(RUNTIME_PATH)];
+    class_paths.extend(expand_class_path(&class_path));
+    let mut runtime = vm::Runtime::new(class_paths);
+    runtime.set_stack_size(options.stack_size);
+    runtime.set_property("java.class.path", &class_path);
+    for (key, value) in &options.properties {
+        runtime.set_property(key, value);
+    }
+
+    let sig = vm::sig::Class::Scalar(main_class.replace('.', "/"));
+    match runtime.start(vm::symref::Class { sig }, &options.args) {
+        Ok(()) => Ok(0),
+        Err(vm::StartError::MainClassNotLoaded(exception)) => {
+            eprintln!("Error: Could not find or load main class {}", main_class);
+            eprintln!("Caused by: {}", vm::exception::describe(&exception.borrow()));
+            Ok(1)
+        }
+        Err(vm::StartError::MainMethodNotFound) => {
+            eprintln!("Error: Main method not found in class {}", main_class);
+            Ok(1)
+        }
+        Err(vm::StartError::Exception(exception)) => {
+            eprintln!("Exception in thread \"main\" {}",
+                      vm::exception::describe(&exception.borrow()));
+            Ok(1)
+        }
+    }
+}
 
 fn main() {
-    let class_file = std::env::args().nth(1).unwrap();
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
 
-    let runtime = vm::Runtime::new(vec![RUNTIME_PATH.into(),
-                                        env::current_dir()
-                                            .expect("Could not get current working directory")]);
-    runtime.start(vm::symref::Class { sig: vm::sig::Class::Scalar(class_file) });
+    // The VM runs on its own thread so that its stack size can be set by -Xss
+    let stack_size = options.stack_size;
+    let vm = thread::Builder::new()
+        .name(String::from("main"))
+        .stack_size(stack_size)
+        .spawn(move || run(options))
+        .expect("Could not start the main thread");
+    let status = match vm.join() {
+        Ok(Ok(status)) => status,
+        Ok(Err(message)) => {
+            eprintln!("Error: {}", message);
+            1
+        }
+        // The panic has already been reported by the thread
+        Err(_) => 1,
+    };
+    process::exit(status);
 }
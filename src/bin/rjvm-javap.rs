@@ -0,0 +1,885 @@
+extern crate rust_jvm;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::slice;
+
+use rust_jvm::model::{class, Class};
+use rust_jvm::model::info::{field, method, Attribute, Constant};
+use rust_jvm::model::info::attribute::ExceptionHandler;
+use rust_jvm::model::info::stack_map::{StackMapFrame, VerificationType};
+use rust_jvm::vm::opcode;
+
+const USAGE: &str = "Usage: rjvm-javap <classfile>...
+Prints the constant pool, fields, methods, bytecode and attributes of each class file, in the
+format of javap -c -v -p.";
+
+const CLASS_FLAGS: &[(u16, &str)] = &[(class::ACC_PUBLIC, "ACC_PUBLIC"),
+                                      (class::ACC_FINAL, "ACC_FINAL"),
+                                      (class::ACC_SUPER, "ACC_SUPER"),
+                                      (class::ACC_INTERFACE, "ACC_INTERFACE"),
+                                      (class::ACC_ABSTRACT, "ACC_ABSTRACT"),
+                                      (class::ACC_SYNTHETIC, "ACC_SYNTHETIC"),
+                                      (class::ACC_ANNOTATION, "ACC_ANNOTATION"),
+                                      (class::ACC_ENUM, "ACC_ENUM"),
+                                      (0x8000, "ACC_MODULE")];
+
+const FIELD_FLAGS: &[(u16, &str)] = &[(field::ACC_PUBLIC, "ACC_PUBLIC"),
+                                      (field::ACC_PRIVATE, "ACC_PRIVATE"),
+                                      (field::ACC_PROTECTED, "ACC_PROTECTED"),
+                                      (field::ACC_STATIC, "ACC_STATIC"),
+                                      (field::ACC_FINAL, "ACC_FINAL"),
+                                      (field::ACC_VOLATILE, "ACC_VOLATILE"),
+                                      (field::ACC_TRANSIENT, "ACC_TRANSIENT"),
+                                      (field::ACC_SYNTEHTIC, "ACC_SYNTHETIC"),
+                                      (field::ACC_ENUM, "ACC_ENUM")];
+
+const METHOD_FLAGS: &[(u16, &str)] = &[(method::ACC_PUBLIC, "ACC_PUBLIC"),
+                                       (method::ACC_PRIVATE, "ACC_PRIVATE"),
+                                       (method::ACC_PROTECTED, "ACC_PROTECTED"),
+                                       (method::ACC_STATIC, "ACC_STATIC"),
+                                       (method::ACC_FINAL, "ACC_FINAL"),
+                                       (method::ACC_SYNCHRONIZED, "ACC_SYNCHRONIZED"),
+                                       (method::ACC_BRIDGE, "ACC_BRIDGE"),
+                                       (method::ACC_VARARGS, "ACC_VARARGS"),
+                                       (method::ACC_NATIVE, "ACC_NATIVE"),
+                                       (method::ACC_ABSTRACT, "ACC_ABSTRACT"),
+                                       (method::ACC_STRICT, "ACC_STRICT"),
+                                       (method::ACC_SYNTHETIC, "ACC_SYNTHETIC")];
+
+/// The modifiers of a declaration, in the order javap prints them
+const MODIFIERS: &[(u16, &str)] = &[(0x0001, "public"),
+                                    (0x0002, "private"),
+                                    (0x0004, "protected"),
+                                    (0x0008, "static"),
+                                    (0x0010, "final"),
+                                    (0x0020, "synchronized"),
+                                    (0x0040, "volatile"),
+                                    (0x0080, "transient"),
+                                    (0x0100, "native"),
+                                    (0x0400, "abstract")];
+
+const PARAMETER_FLAGS: &[(u16, &str)] = &[(0x0010, "final"),
+                                          (0x1000, "synthetic"),
+                                          (0x8000, "mandated")];
+
+const REFERENCE_KINDS: [&str; 10] = ["",
+                                     "REF_getField",
+                                     "REF_getStatic",
+                                     "REF_putField",
+                                     "REF_putStatic",
+                                     "REF_invokeVirtual",
+                                     "REF_invokeStatic",
+                                     "REF_invokeSpecial",
+                                     "REF_newInvokeSpecial",
+                                     "REF_invokeInterface"];
+
+/// The element types of `newarray`, from `T_BOOLEAN` (4) to `T_LONG` (11)
+const ARRAY_TYPES: [&str; 8] =
+    ["boolean", "char", "float", "double", "byte", "short", "int", "long"];
+
+fn flags(access_flags: u16, names: &[(u16, &str)]) -> String {
+    let names = names.iter()
+        .filter(|&&(flag, _)| access_flags & flag != 0)
+        .map(|&(_, name)| name)
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        format!("({:#06x})", access_flags)
+    } else {
+        format!("({:#06x}) {}", access_flags, names.join(", "))
+    }
+}
+
+/// Returns the modifiers of a declaration, followed by a space if there are any. `mask` selects
+/// the modifiers that apply to the kind of declaration.
+fn modifiers(access_flags: u16, mask: u16) -> String {
+    MODIFIERS.iter()
+        .filter(|&&(flag, _)| access_flags & mask & flag != 0)
+        .map(|&(_, name)| format!("{} ", name))
+        .collect()
+}
+
+/// Escapes control characters and unpaired surrogates, as javap does.
+fn escape(chars: &[u16]) -> String {
+    let mut escaped = String::new();
+    for c in std::char::decode_utf16(chars.iter().cloned()) {
+        match c {
+            Ok('\t') => escaped.push_str("\\t"),
+            Ok('\n') => escaped.push_str("\\n"),
+            Ok('\r') => escaped.push_str("\\r"),
+            Ok('\u{8}') => escaped.push_str("\\b"),
+            Ok('\u{c}') => escaped.push_str("\\f"),
+            Ok(c) if c < ' ' || c == '\u{7f}' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            Ok(c) => escaped.push(c),
+            Err(e) => escaped.push_str(&format!("\\u{:04x}", e.unpaired_surrogate())),
+        }
+    }
+    escaped
+}
+
+/// Formats a floating point number as `Double.toString` does, which switches to scientific
+/// notation outside [10^-3, 10^7). `plain` and `scientific` are the shortest representations of
+/// `value` in its original precision.
+fn java_number(value: f64, plain: String, scientific: String) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if value != 0.0 && (value.abs() < 1e-3 || value.abs() >= 1e7) {
+        let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+        if mantissa.contains('.') {
+            format!("{}E{}", mantissa, &exponent[1..])
+        } else {
+            format!("{}.0E{}", mantissa, &exponent[1..])
+        }
+    } else if plain.contains('.') {
+        plain
+    } else {
+        format!("{}.0", plain)
+    }
+}
+
+fn java_double(value: f64) -> String {
+    java_number(value, format!("{:?}", value), format!("{:e}", value))
+}
+
+fn java_float(value: f32) -> String {
+    java_number(value as f64, format!("{:?}", value), format!("{:e}", value))
+}
+
+/// Converts the field descriptor at the start of `descriptor` into a Java type, returning it and
+/// the rest of the descriptor.
+fn java_type(descriptor: &str) -> (String, &str) {
+    let mut chars = descriptor.chars();
+    let ty = match chars.next() {
+        Some('B') => "byte",
+        Some('C') => "char",
+        Some('D') => "double",
+        Some('F') => "float",
+        Some('I') => "int",
+        Some('J') => "long",
+        Some('S') => "short",
+        Some('Z') => "boolean",
+        Some('V') => "void",
+        Some('L') => {
+            let end = descriptor.find(';').unwrap_or(descriptor.len());
+            let name = descriptor[1..end].replace('/', ".");
+            return (name, &descriptor[(end + 1).min(descriptor.len())..]);
+        }
+        Some('[') => {
+            let (component, rest) = java_type(&descriptor[1..]);
+            return (format!("{}[]", component), rest);
+        }
+        _ => return (String::from(descriptor), ""),
+    };
+    (String::from(ty), &descriptor[1..])
+}
+
+/// Converts a method descriptor into the Java types of its parameters and its return type.
+fn java_method_type(descriptor: &str) -> (Vec<String>, String) {
+    let mut params = Vec::new();
+    let mut rest = descriptor.trim_start_matches('(');
+    while !rest.is_empty() && !rest.starts_with(')') {
+        let (param, next) = java_type(rest);
+        params.push(param);
+        rest = next;
+    }
+    (params, java_type(rest.trim_start_matches(')')).0)
+}
+
+/// Returns the `args_size` of a method. Like javap, this counts parameters rather than the local
+/// variable slots they take up.
+fn args_size(descriptor: &str, is_static: bool) -> usize {
+    java_method_type(descriptor).0.len() + if is_static { 0 } else { 1 }
+}
+
+/// Quotes a member name that is not a Java identifier, such as `<init>`.
+fn member_name(name: &str) -> String {
+    if name.starts_with('<') { format!("\"{}\"", name) } else { String::from(name) }
+}
+
+/// Appends a `//` comment to `text`, aligned at column `column`.
+fn with_comment(text: &str, column: usize, comment: &str) -> String {
+    format!("{:<width$} // {}", text, comment, width = column - 1)
+}
+
+struct Printer<'a> {
+    class: &'a Class,
+}
+
+impl<'a> Printer<'a> {
+    fn constant(&self, index: u16) -> Option<&'a Constant> {
+        self.class.constant_pool.get((index as usize).wrapping_sub(1))
+    }
+
+    fn utf8(&self, index: u16) -> String {
+        match self.constant(index) {
+            Some(Constant::Utf8 { chars, .. }) => escape(chars),
+            _ => format!("<invalid #{}>", index),
+        }
+    }
+
+    fn class_name(&self, index: u16) -> String {
+        match self.constant(index) {
+            Some(&Constant::Class { name_index }) => self.utf8(name_index),
+            _ => format!("<invalid #{}>", index),
+        }
+    }
+
+    fn java_class_name(&self, index: u16) -> String {
+        self.class_name(index).replace('/', ".")
+    }
+
+    fn name_and_type(&self, index: u16) -> String {
+        match self.constant(index) {
+            Some(&Constant::NameAndType { name_index, descriptor_index }) => {
+                format!("{}:{}", member_name(&self.utf8(name_index)), self.utf8(descriptor_index))
+            }
+            _ => format!("<invalid #{}>", index),
+        }
+    }
+
+    /// Describes the constant at `index`, as in the comments of the constant pool. In code,
+    /// `in_code` omits the class of members of this class.
+    fn describe(&self, index: u16, in_code: bool) -> String {
+        let constant = match self.constant(index) {
+            Some(constant) => constant,
+            None => return format!("<invalid #{}>", index),
+        };
+        match *constant {
+            Constant::Class { .. } => {
+                let name = self.class_name(index);
+                if name.starts_with('[') { format!("\"{}\"", name) } else { name }
+            }
+            Constant::Fieldref { class_index, name_and_type_index } |
+            Constant::Methodref { class_index, name_and_type_index } |
+            Constant::InterfaceMethodref { class_index, name_and_type_index } => {
+                if in_code && class_index == self.class.this_class {
+                    self.name_and_type(name_and_type_index)
+                } else {
+                    format!("{}.{}",
+                            self.describe(class_index, in_code),
+                            self.name_and_type(name_and_type_index))
+                }
+            }
+            Constant::String { string_index } => self.utf8(string_index),
+            Constant::Integer { value } => value.to_string(),
+            Constant::Float { value } => format!("{}f", java_float(value)),
+            Constant::Long { value } => format!("{}l", value),
+            Constant::Double { value } => format!("{}d", java_double(value)),
+            Constant::NameAndType { .. } => self.name_and_type(index),
+            Constant::Utf8 { ref chars, .. } => escape(chars),
+            Constant::MethodHandle { reference_kind, reference_index } => {
+                format!("{} {}",
+                        REFERENCE_KINDS.get(reference_kind as usize).unwrap_or(&"REF_?"),
+                        self.describe(reference_index, false))
+            }
+            Constant::MethodType { descriptor_index } => self.utf8(descriptor_index),
+            Constant::Dynamic { bootstrap_method_attr_index, name_and_type_index } |
+            Constant::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                format!("#{}:{}",
+                        bootstrap_method_attr_index,
+                        self.name_and_type(name_and_type_index))
+            }
+            Constant::Module { name_index } |
+            Constant::Package { name_index } => self.utf8(name_index),
+            Constant::Nothing => String::new(),
+        }
+    }
+
+    /// Describes the constant at `index` as an operand of an instruction, prefixed by its kind.
+    fn describe_operand(&self, index: u16) -> String {
+        let kind = match self.constant(index) {
+            Some(&Constant::Class { .. }) => "class",
+            Some(&Constant::Fieldref { .. }) => "Field",
+            Some(&Constant::Methodref { .. }) => "Method",
+            Some(&Constant::InterfaceMethodref { .. }) => "InterfaceMethod",
+            Some(&Constant::Integer { .. }) => "int",
+            Some(&Constant::Float { .. }) => "float",
+            Some(&Constant::Long { .. }) => "long",
+            Some(&Constant::Double { .. }) => "double",
+            Some(constant) => constant.kind(),
+            None => return format!("<invalid #{}>", index),
+        };
+        format!("{} {}", kind, self.describe(index, true))
+    }
+
+    fn print(&self, path: &Path) {
+        let class = self.class;
+        println!("Classfile {}", path.display());
+        for attribute in class.attributes.iter() {
+            if let Attribute::SourceFile { sourcefile_index } = *attribute {
+                println!("  Compiled from \"{}\"", self.utf8(sourcefile_index));
+            }
+        }
+        println!("{}", self.class_declaration());
+        println!("  minor version: {}", class.minor_version);
+        println!("  major version: {}", class.major_version);
+        println!("  flags: {}", flags(class.access_flags, CLASS_FLAGS));
+        println!("{}",
+                 with_comment(&format!("  this_class: #{}", class.this_class),
+                              42,
+                              &self.describe(class.this_class, false)));
+        if class.super_class == 0 {
+            println!("  super_class: #0");
+        } else {
+            println!("{}",
+                     with_comment(&format!("  super_class: #{}", class.super_class),
+                                  42,
+                                  &self.describe(class.super_class, false)));
+        }
+        println!("  interfaces: {}, fields: {}, methods: {}, attributes: {}",
+                 class.interfaces.len(),
+                 class.fields.len(),
+                 class.methods.len(),
+                 class.attributes.len());
+        self.print_constant_pool();
+
+        println!("{{");
+        let mut first = true;
+        for field in class.fields.iter() {
+            if !first {
+                println!();
+            }
+            first = false;
+            let descriptor = self.utf8(field.descriptor_index);
+            println!("  {}{} {};",
+                     modifiers(field.access_flags, 0x00df),
+                     java_type(&descriptor).0,
+                     self.utf8(field.name_index));
+            println!("    descriptor: {}", descriptor);
+            println!("    flags: {}", flags(field.access_flags, FIELD_FLAGS));
+            for attribute in field.attributes.iter() {
+                self.print_member_attribute(attribute, "", false);
+            }
+        }
+        for method in class.methods.iter() {
+            if !first {
+                println!();
+            }
+            first = false;
+            let descriptor = self.utf8(method.descriptor_index);
+            println!("  {};", self.method_declaration(method, &descriptor));
+            println!("    descriptor: {}", descriptor);
+            println!("    flags: {}", flags(method.access_flags, METHOD_FLAGS));
+            let is_static = method.access_flags & method::ACC_STATIC != 0;
+            for attribute in method.attributes.iter() {
+                self.print_member_attribute(attribute, &descriptor, is_static);
+            }
+        }
+        println!("}}");
+
+        for attribute in class.attributes.iter() {
+            self.print_class_attribute(attribute);
+        }
+    }
+
+    fn class_declaration(&self) -> String {
+        let class = self.class;
+        let is_interface = class.access_flags & class::ACC_INTERFACE != 0;
+        let mut declaration = if is_interface {
+            format!("{}interface {}",
+                    modifiers(class.access_flags, 0x0001),
+                    self.java_class_name(class.this_class))
+        } else {
+            format!("{}class {}",
+                    modifiers(class.access_flags, 0x0411),
+                    self.java_class_name(class.this_class))
+        };
+        if !is_interface && class.super_class != 0 {
+            let super_class = self.java_class_name(class.super_class);
+            if super_class != "java.lang.Object" {
+                declaration.push_str(&format!(" extends {}", super_class));
+            }
+        }
+        if !class.interfaces.is_empty() {
+            let interfaces = class.interfaces
+                .iter()
+                .map(|&interface| self.java_class_name(interface))
+                .collect::<Vec<_>>();
+            let keyword = if is_interface { "extends" } else { "implements" };
+            declaration.push_str(&format!(" {} {}", keyword, interfaces.join(",")));
+        }
+        declaration
+    }
+
+    fn method_declaration(&self, method: &method::Method, descriptor: &str) -> String {
+        let name = self.utf8(method.name_index);
+        if name == "<clinit>" {
+            return String::from("static {}");
+        }
+        let (mut params, return_type) = java_method_type(descriptor);
+        if method.access_flags & method::ACC_VARARGS != 0 {
+            if let Some(last) = params.last_mut() {
+                if last.ends_with("[]") {
+                    let length = last.len();
+                    last.replace_range(length - 2.., "...");
+                }
+            }
+        }
+        let mut declaration = modifiers(method.access_flags, 0x053f);
+        // Instance methods of interfaces with a body are default methods
+        let body_flags = method::ACC_STATIC | method::ACC_PRIVATE | method::ACC_ABSTRACT;
+        if self.class.access_flags & class::ACC_INTERFACE != 0 &&
+           method.access_flags & body_flags == 0 {
+            declaration.push_str("default ");
+        }
+        if name == "<init>" {
+            declaration.push_str(&self.java_class_name(self.class.this_class));
+        } else {
+            declaration.push_str(&format!("{} {}", return_type, name));
+        }
+        declaration.push_str(&format!("({})", params.join(", ")));
+        for attribute in method.attributes.iter() {
+            if let Attribute::Exceptions { ref exception_index_table, .. } = *attribute {
+                let exceptions = exception_index_table.iter()
+                    .map(|&exception| self.java_class_name(exception))
+                    .collect::<Vec<_>>();
+                declaration.push_str(&format!(" throws {}", exceptions.join(", ")));
+            }
+        }
+        declaration
+    }
+
+    fn print_constant_pool(&self) {
+        let pool = &self.class.constant_pool;
+        let width = format!("#{}", pool.len()).len().max(3) + 2;
+        println!("Constant pool:");
+        for (i, constant) in pool.iter().enumerate() {
+            let index = format!("#{}", i + 1);
+            let operands = match *constant {
+                Constant::Nothing => continue,
+                Constant::Class { name_index } |
+                Constant::Module { name_index } |
+                Constant::Package { name_index } => format!("#{}", name_index),
+                Constant::Fieldref { class_index, name_and_type_index } |
+                Constant::Methodref { class_index, name_and_type_index } |
+                Constant::InterfaceMethodref { class_index, name_and_type_index } => {
+                    format!("#{}.#{}", class_index, name_and_type_index)
+                }
+                Constant::String { string_index } => format!("#{}", string_index),
+                Constant::NameAndType { name_index, descriptor_index } => {
+                    format!("#{}:#{}", name_index, descriptor_index)
+                }
+                Constant::MethodHandle { reference_kind, reference_index } => {
+                    format!("{}:#{}", reference_kind, reference_index)
+                }
+                Constant::MethodType { descriptor_index } => format!("#{}", descriptor_index),
+                Constant::Dynamic { bootstrap_method_attr_index, name_and_type_index } |
+                Constant::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                    format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index)
+                }
+                // Literals are printed without a comment
+                _ => {
+                    println!("{:>width$} = {:<18} {}",
+                             index,
+                             constant.kind(),
+                             self.describe(i as u16 + 1, false),
+                             width = width);
+                    continue;
+                }
+            };
+            let line = format!("{:>width$} = {:<18} {}",
+                               index,
+                               constant.kind(),
+                               operands,
+                               width = width);
+            let mut comment = self.describe(i as u16 + 1, false);
+            if let Constant::MethodType { .. } = *constant {
+                // javap separates method types by an extra space in the constant pool
+                comment.insert(0, ' ');
+            }
+            println!("{}", with_comment(&line, 42, &comment));
+        }
+    }
+
+    fn print_member_attribute(&self, attribute: &Attribute, descriptor: &str, is_static: bool) {
+        match *attribute {
+            Attribute::ConstantValue { value_index } => {
+                println!("    ConstantValue: {}", self.describe_operand(value_index));
+            }
+            Attribute::Code { max_stack,
+                              max_locals,
+                              ref code,
+                              ref exception_table,
+                              ref attributes,
+                              .. } => {
+                println!("    Code:");
+                println!("      stack={}, locals={}, args_size={}",
+                         max_stack,
+                         max_locals,
+                         args_size(descriptor, is_static));
+                self.print_code(code);
+                self.print_exception_table(exception_table);
+                for attribute in attributes.iter() {
+                    self.print_code_attribute(attribute);
+                }
+            }
+            Attribute::Exceptions { ref exception_index_table, .. } => {
+                println!("    Exceptions:");
+                let exceptions = exception_index_table.iter()
+                    .map(|&exception| self.java_class_name(exception))
+                    .collect::<Vec<_>>();
+                println!("      throws {}", exceptions.join(", "));
+            }
+            Attribute::Signature { signature_index } => {
+                println!("{}",
+                         with_comment(&format!("    Signature: #{}", signature_index),
+                                      44,
+                                      &self.utf8(signature_index)));
+            }
+            Attribute::MethodParameters { ref parameters, .. } => {
+                println!("    MethodParameters:");
+                println!("      Name                           Flags");
+                for parameter in parameters.iter() {
+                    let name = if parameter.name_index == 0 {
+                        String::from("<no name>")
+                    } else {
+                        self.utf8(parameter.name_index)
+                    };
+                    let flags = PARAMETER_FLAGS.iter()
+                        .filter(|&&(flag, _)| parameter.access_flags & flag != 0)
+                        .map(|&(_, name)| name)
+                        .collect::<Vec<_>>();
+                    println!("{}", format!("      {:<31}{}", name, flags.join(" ")).trim_end());
+                }
+            }
+            Attribute::Deprecated => println!("    Deprecated: true"),
+            Attribute::Synthetic => println!("    Synthetic: true"),
+            _ => (),
+        }
+    }
+
+    fn print_code(&self, code: &[u8]) {
+        let u16_at = |at: usize| u16::from_be_bytes([code[at], code[at + 1]]);
+        let i32_at = |at: usize| {
+            i32::from_be_bytes([code[at], code[at + 1], code[at + 2], code[at + 3]])
+        };
+        let mut pc = 0;
+        while pc < code.len() {
+            let length = match opcode::instruction_length(code, pc) {
+                Some(length) => length,
+                None => {
+                    println!("{:>10}: <invalid instruction {:#04x}>", pc, code[pc]);
+                    return;
+                }
+            };
+            let op = code[pc];
+            let mnemonic = opcode::mnemonic(op).unwrap();
+            let target = |offset: i32| (pc as i64 + offset as i64).to_string();
+            let (operands, comment) = match op {
+                opcode::BIPUSH => ((code[pc + 1] as i8).to_string(), None),
+                opcode::SIPUSH => ((u16_at(pc + 1) as i16).to_string(), None),
+                opcode::LDC => {
+                    let index = code[pc + 1] as u16;
+                    (format!("#{}", index), Some(self.describe_operand(index)))
+                }
+                opcode::ILOAD..=opcode::ALOAD |
+                opcode::ISTORE..=opcode::ASTORE |
+                opcode::RET => (code[pc + 1].to_string(), None),
+                opcode::IINC => (format!("{}, {}", code[pc + 1], code[pc + 2] as i8), None),
+                opcode::IFEQ..=opcode::JSR | opcode::IFNULL | opcode::IFNONNULL => {
+                    (target(u16_at(pc + 1) as i16 as i32), None)
+                }
+                opcode::GOTO_W | opcode::JSR_W => (target(i32_at(pc + 1)), None),
+                opcode::LDC_W |
+                opcode::LDC2_W |
+                opcode::GETSTATIC..=opcode::INVOKESTATIC |
+                opcode::NEW |
+                opcode::ANEWARRAY |
+                opcode::CHECKCAST |
+                opcode::INSTANCEOF => {
+                    let index = u16_at(pc + 1);
+                    (format!("#{}", index), Some(self.describe_operand(index)))
+                }
+                opcode::INVOKEINTERFACE | opcode::INVOKEDYNAMIC | opcode::MULTIANEWARRAY => {
+                    let index = u16_at(pc + 1);
+                    (format!("#{},  {}", index, code[pc + 3]), Some(self.describe_operand(index)))
+                }
+                opcode::NEWARRAY => {
+                    let atype = code[pc + 1] as usize;
+                    let name = ARRAY_TYPES.get(atype.wrapping_sub(4)).unwrap_or(&"?");
+                    // javap separates the array type by one more space than other operands
+                    (format!(" {}", name), None)
+                }
+                opcode::TABLESWITCH => {
+                    let operands = (pc + 4) & !3;
+                    let default = i32_at(operands);
+                    let low = i32_at(operands + 4);
+                    let high = i32_at(operands + 8);
+                    println!("{:>10}: {:<13} {{ // {} to {}", pc, mnemonic, low, high);
+                    for (i, key) in (low as i64..=high as i64).enumerate() {
+                        println!("{:>24}: {}", key, target(i32_at(operands + 12 + i * 4)));
+                    }
+                    println!("{:>24}: {}", "default", target(default));
+                    println!("            }}");
+                    pc += length;
+                    continue;
+                }
+                opcode::LOOKUPSWITCH => {
+                    let operands = (pc + 4) & !3;
+                    let default = i32_at(operands);
+                    let npairs = i32_at(operands + 4) as usize;
+                    println!("{:>10}: {:<13} {{ // {}", pc, mnemonic, npairs);
+                    for i in 0..npairs {
+                        let pair = operands + 8 + i * 8;
+                        println!("{:>24}: {}", i32_at(pair), target(i32_at(pair + 4)));
+                    }
+                    println!("{:>24}: {}", "default", target(default));
+                    println!("            }}");
+                    pc += length;
+                    continue;
+                }
+                opcode::WIDE => {
+                    let modified = opcode::mnemonic(code[pc + 1]).unwrap_or("?");
+                    let operands = if code[pc + 1] == opcode::IINC {
+                        format!("{}, {}", u16_at(pc + 2), u16_at(pc + 4) as i16)
+                    } else {
+                        u16_at(pc + 2).to_string()
+                    };
+                    println!("{:>10}: {:<13} {}", pc, format!("{}_w", modified), operands);
+                    pc += length;
+                    continue;
+                }
+                _ => (String::new(), None),
+            };
+            let text = if operands.is_empty() {
+                String::from(mnemonic)
+            } else {
+                format!("{:<13} {}", mnemonic, operands)
+            };
+            match comment {
+                Some(comment) => println!("{:>10}: {}", pc, with_comment(&text, 34, &comment)),
+                None => println!("{:>10}: {}", pc, text),
+            }
+            pc += length;
+        }
+    }
+
+    fn print_exception_table(&self, exception_table: &[ExceptionHandler]) {
+        if exception_table.is_empty() {
+            return;
+        }
+        println!("      Exception table:");
+        println!("         from    to  target type");
+        for handler in exception_table {
+            let catch_type = if handler.catch_type == 0 {
+                String::from("any")
+            } else {
+                format!("Class {}", self.class_name(handler.catch_type))
+            };
+            println!("{:>14}{:>6}{:>6}   {}",
+                     handler.start_pc,
+                     handler.end_pc,
+                     handler.handler_pc,
+                     catch_type);
+        }
+    }
+
+    fn print_code_attribute(&self, attribute: &Attribute) {
+        match *attribute {
+            Attribute::LineNumberTable { ref line_number_table, .. } => {
+                println!("      LineNumberTable:");
+                for line in line_number_table.iter() {
+                    println!("        line {}: {}", line.line_number, line.start_pc);
+                }
+            }
+            Attribute::LocalVariableTable { ref local_variable_table, .. } => {
+                println!("      LocalVariableTable:");
+                println!("        Start  Length  Slot  Name   Signature");
+                for variable in local_variable_table.iter() {
+                    println!("{:>13}{:>8}{:>6} {:>5}   {}",
+                             variable.start_pc,
+                             variable.length,
+                             variable.index,
+                             self.utf8(variable.name_index),
+                             self.utf8(variable.descriptor_index));
+                }
+            }
+            Attribute::LocalVariableTypeTable { ref local_variable_type_table, .. } => {
+                println!("      LocalVariableTypeTable:");
+                println!("        Start  Length  Slot  Name   Signature");
+                for variable in local_variable_type_table.iter() {
+                    println!("{:>13}{:>8}{:>6} {:>5}   {}",
+                             variable.start_pc,
+                             variable.length,
+                             variable.index,
+                             self.utf8(variable.name_index),
+                             self.utf8(variable.signature_index));
+                }
+            }
+            Attribute::StackMapTable { ref entries, .. } => {
+                println!("      StackMapTable: number_of_entries = {}", entries.len());
+                for entry in entries.iter() {
+                    self.print_stack_map_frame(entry);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn verification_types(&self, types: &[VerificationType]) -> String {
+        if types.is_empty() {
+            return String::from("[]");
+        }
+        let types = types.iter()
+            .map(|ty| match *ty {
+                VerificationType::Top => String::from("top"),
+                VerificationType::Integer => String::from("int"),
+                VerificationType::Float => String::from("float"),
+                VerificationType::Double => String::from("double"),
+                VerificationType::Long => String::from("long"),
+                VerificationType::Null => String::from("null"),
+                VerificationType::UninitializedThis => String::from("this"),
+                VerificationType::Object { cpool_index } => {
+                    format!("class {}", self.describe(cpool_index, false))
+                }
+                VerificationType::Uninitialized { offset } => format!("uninitialized {}", offset),
+            })
+            .collect::<Vec<_>>();
+        format!("[ {} ]", types.join(", "))
+    }
+
+    fn print_stack_map_frame(&self, frame: &StackMapFrame) {
+        match *frame {
+            StackMapFrame::Same { frame_type } => {
+                println!("        frame_type = {} /* same */", frame_type);
+            }
+            StackMapFrame::SameLocals1StackItem { frame_type, ref stack } => {
+                println!("        frame_type = {} /* same_locals_1_stack_item */", frame_type);
+                println!("          stack = {}", self.verification_types(slice::from_ref(stack)));
+            }
+            StackMapFrame::SameLocals1StackItemExtended { offset_delta, ref stack } => {
+                println!("        frame_type = 247 /* same_locals_1_stack_item_frame_extended */");
+                println!("          offset_delta = {}", offset_delta);
+                println!("          stack = {}", self.verification_types(slice::from_ref(stack)));
+            }
+            StackMapFrame::Chop { frame_type, offset_delta } => {
+                println!("        frame_type = {} /* chop */", frame_type);
+                println!("          offset_delta = {}", offset_delta);
+            }
+            StackMapFrame::SameExtended { offset_delta } => {
+                println!("        frame_type = 251 /* same_frame_extended */");
+                println!("          offset_delta = {}", offset_delta);
+            }
+            StackMapFrame::Append { frame_type, offset_delta, ref locals } => {
+                println!("        frame_type = {} /* append */", frame_type);
+                println!("          offset_delta = {}", offset_delta);
+                println!("          locals = {}", self.verification_types(locals));
+            }
+            StackMapFrame::Full { offset_delta, ref locals, ref stack, .. } => {
+                println!("        frame_type = 255 /* full_frame */");
+                println!("          offset_delta = {}", offset_delta);
+                println!("          locals = {}", self.verification_types(locals));
+                println!("          stack = {}", self.verification_types(stack));
+            }
+        }
+    }
+
+    fn print_class_attribute(&self, attribute: &Attribute) {
+        match *attribute {
+            Attribute::SourceFile { sourcefile_index } => {
+                println!("SourceFile: \"{}\"", self.utf8(sourcefile_index));
+            }
+            Attribute::Signature { signature_index } => {
+                println!("{}",
+                         with_comment(&format!("Signature: #{}", signature_index),
+                                      40,
+                                      &self.utf8(signature_index)));
+            }
+            Attribute::EnclosingMethod { class_index, method_index } => {
+                let mut comment = self.class_name(class_index);
+                let method = self.constant(method_index);
+                if let Some(&Constant::NameAndType { name_index, .. }) = method {
+                    comment.push_str(&format!(".{}", self.utf8(name_index)));
+                }
+                let text = format!("EnclosingMethod: #{}.#{}", class_index, method_index);
+                println!("{}", with_comment(&text, 40, &comment));
+            }
+            Attribute::NestHost { host_class_index } => {
+                println!("NestHost: class {}", self.class_name(host_class_index));
+            }
+            Attribute::NestMembers { ref classes, .. } => {
+                println!("NestMembers:");
+                for &class in classes.iter() {
+                    println!("  {}", self.class_name(class));
+                }
+            }
+            Attribute::PermittedSubclasses { ref classes, .. } => {
+                println!("PermittedSubclasses:");
+                for &class in classes.iter() {
+                    println!("  {}", self.class_name(class));
+                }
+            }
+            Attribute::BootstrapMethods { ref bootstrap_methods, .. } => {
+                println!("BootstrapMethods:");
+                for (i, method) in bootstrap_methods.iter().enumerate() {
+                    println!("  {}: #{} {}",
+                             i,
+                             method.bootstrap_method_ref,
+                             self.describe(method.bootstrap_method_ref, false));
+                    println!("    Method arguments:");
+                    for &argument in method.bootstrap_arguments.iter() {
+                        println!("      #{} {}", argument, self.describe(argument, false));
+                    }
+                }
+            }
+            Attribute::InnerClasses { ref classes, .. } => {
+                println!("InnerClasses:");
+                for class in classes.iter() {
+                    let flags = class.inner_class_access_flags;
+                    // Interfaces are implicitly abstract
+                    let mask = if flags & class::ACC_INTERFACE != 0 { 0x001f } else { 0x041f };
+                    let mut text = format!("  {}", modifiers(flags, mask));
+                    let mut comment = String::new();
+                    if class.inner_name_index != 0 {
+                        text.push_str(&format!("#{}= ", class.inner_name_index));
+                        comment.push_str(&format!("{}=", self.utf8(class.inner_name_index)));
+                    }
+                    text.push_str(&format!("#{}", class.inner_class_info_index));
+                    comment.push_str(&format!("class {}",
+                                              self.describe(class.inner_class_info_index, false)));
+                    if class.outer_class_info_index != 0 {
+                        text.push_str(&format!(" of #{}", class.outer_class_info_index));
+                        comment.push_str(&format!(" of class {}",
+                                                  self.describe(class.outer_class_info_index,
+                                                                false)));
+                    }
+                    text.push(';');
+                    println!("{}", with_comment(&text, 42, &comment));
+                }
+            }
+            Attribute::Deprecated => println!("Deprecated: true"),
+            _ => (),
+        }
+    }
+}
+
+fn main() {
+    let paths = env::args().skip(1).collect::<Vec<_>>();
+    if paths.is_empty() || paths.iter().any(|path| path == "-h" || path == "-help") {
+        println!("{}", USAGE);
+        process::exit(if paths.is_empty() { 1 } else { 0 });
+    }
+
+    let mut status = 0;
+    for path in &paths {
+        let path = Path::new(path);
+        let class = fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Class::parse(bytes).map_err(|e| e.to_string()));
+        match class {
+            Ok(class) => {
+                let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+                Printer { class: &class }.print(&path);
+            }
+            Err(message) => {
+                eprintln!("Error: {}: {}", path.display(), message);
+                status = 1;
+            }
+        }
+    }
+    process::exit(status);
+}
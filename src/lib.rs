@@ -1,5 +1,7 @@
+
 extern crate byteorder;
 extern crate libloading as lib;
+extern crate miniz_oxide;
 
 pub mod model;
 pub mod vm;
@@ -1,11 +1,22 @@
 use std::io::Cursor;
 
+use super::error::{ClassFormatError, Context, ErrorKind, Result};
 use super::info::{Attribute, Constant, Field, Method};
-
-use byteorder::{BigEndian, ReadBytesExt};
+use super::info::constant;
+use super::read;
+use super::write;
 
 const MAGIC_VALUE: u32 = 0xCAFEBABE;
 
+pub const ACC_PUBLIC: u16 = 0x0001;
+pub const ACC_FINAL: u16 = 0x0010;
+pub const ACC_SUPER: u16 = 0x0020;
+pub const ACC_INTERFACE: u16 = 0x0200;
+pub const ACC_ABSTRACT: u16 = 0x0400;
+pub const ACC_SYNTHETIC: u16 = 0x1000;
+pub const ACC_ANNOTATION: u16 = 0x2000;
+pub const ACC_ENUM: u16 = 0x4000;
+
 #[derive(Debug)]
 pub struct Class {
     pub minor_version: u16,
@@ -26,18 +37,27 @@ pub struct Class {
 }
 
 impl Class {
-    pub fn new(data: Vec<u8>) -> Class {
+    /// Parses the contents of a class file, as described in JVMS 4.
+    pub fn parse(data: Vec<u8>) -> Result<Class> {
+        let length = data.len() as u64;
         let mut cur = Cursor::new(data);
-        assert_eq!(MAGIC_VALUE, cur.read_u32::<BigEndian>().unwrap());
+        let magic = read::u32(&mut cur)?;
+        if magic != MAGIC_VALUE {
+            return Err(ClassFormatError::new(ErrorKind::BadMagic(magic), 0));
+        }
 
-        let minor_version = cur.read_u16::<BigEndian>().unwrap();
-        let major_version = cur.read_u16::<BigEndian>().unwrap();
+        let minor_version = read::u16(&mut cur)?;
+        let major_version = read::u16(&mut cur)?;
 
-        let constant_pool_count = cur.read_u16::<BigEndian>().unwrap();
+        let offset = cur.position();
+        let constant_pool_count = read::u16(&mut cur)?;
+        if constant_pool_count == 0 {
+            return Err(ClassFormatError::new(ErrorKind::EmptyConstantPool, offset));
+        }
         let mut constant_pool = Vec::with_capacity((constant_pool_count - 1) as usize);
         let mut i = 1;
         while i < constant_pool_count {
-            let constant = Constant::new(&mut cur);
+            let constant = Constant::new(&mut cur).map_err(|e| e.within(Context::Constant(i)))?;
             match constant {
                 Constant::Long { .. } |
                 Constant::Double { .. } => {
@@ -51,49 +71,114 @@ impl Class {
         }
         let constant_pool = constant_pool.into_boxed_slice();
 
-        let access_flags = cur.read_u16::<BigEndian>().unwrap();
-        let this_class = cur.read_u16::<BigEndian>().unwrap();
-        let super_class = cur.read_u16::<BigEndian>().unwrap();
+        let access_flags = read::u16(&mut cur)?;
+        let offset = cur.position();
+        let this_class = read::u16(&mut cur)?;
+        constant::lookup(&constant_pool, this_class, "Class", offset)?;
+        let offset = cur.position();
+        let super_class = read::u16(&mut cur)?;
+        if super_class != 0 {
+            constant::lookup(&constant_pool, super_class, "Class", offset)?;
+        }
 
-        let interfaces_count = cur.read_u16::<BigEndian>().unwrap();
+        let interfaces_count = read::u16(&mut cur)?;
         let mut interfaces = Vec::with_capacity(interfaces_count as usize);
         for _ in 0..interfaces_count {
-            interfaces.push(cur.read_u16::<BigEndian>().unwrap());
+            let offset = cur.position();
+            let interface = read::u16(&mut cur)?;
+            constant::lookup(&constant_pool, interface, "Class", offset)?;
+            interfaces.push(interface);
         }
 
-        let fields_count = cur.read_u16::<BigEndian>().unwrap();
-        let mut fields = Vec::with_capacity(interfaces_count as usize);
-        for _ in 0..fields_count {
-            fields.push(Field::new(&constant_pool, &mut cur));
+        let fields_count = read::u16(&mut cur)?;
+        let mut fields = Vec::with_capacity(fields_count as usize);
+        for i in 0..fields_count {
+            fields.push(Field::new(&constant_pool, &mut cur)
+                .map_err(|e| e.within(Context::Field(i)))?);
         }
 
-        let methods_count = cur.read_u16::<BigEndian>().unwrap();
+        let methods_count = read::u16(&mut cur)?;
         let mut methods = Vec::with_capacity(methods_count as usize);
-        for _ in 0..methods_count {
-            methods.push(Method::new(&constant_pool, &mut cur));
+        for i in 0..methods_count {
+            methods.push(Method::new(&constant_pool, &mut cur)
+                .map_err(|e| e.within(Context::Method(i)))?);
         }
 
-        let attributes_count = cur.read_u16::<BigEndian>().unwrap();
+        let attributes_count = read::u16(&mut cur)?;
         let mut attributes = Vec::with_capacity(attributes_count as usize);
         for _ in 0..attributes_count {
-            attributes.push(Attribute::new(&constant_pool, &mut cur));
+            attributes.push(Attribute::new(&constant_pool, &mut cur)?);
         }
-        Class {
-            minor_version: minor_version,
-            major_version: major_version,
-            constant_pool_count: constant_pool_count,
-            constant_pool: constant_pool,
-            access_flags: access_flags,
-            this_class: this_class,
-            super_class: super_class,
-            interfaces_count: interfaces_count,
+
+        if cur.position() != length {
+            return Err(ClassFormatError::new(ErrorKind::ExtraBytes, cur.position()));
+        }
+        Ok(Class {
+            minor_version,
+            major_version,
+            constant_pool_count,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces_count,
             interfaces: interfaces.into_boxed_slice(),
-            fields_count: fields_count,
+            fields_count,
             fields: fields.into_boxed_slice(),
-            methods_count: methods_count,
+            methods_count,
             methods: methods.into_boxed_slice(),
-            attributes_count: attributes_count,
+            attributes_count,
             attributes: attributes.into_boxed_slice(),
+        })
+    }
+
+    /// Serializes the class into the contents of a class file. Counts and lengths are recomputed
+    /// from the tables, so the constant pool, fields, methods and attributes can be modified before
+    /// writing the class.
+    pub fn write(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        write::u32(&mut out, MAGIC_VALUE);
+        write::u16(&mut out, self.minor_version);
+        write::u16(&mut out, self.major_version);
+
+        write::count_u16(&mut out, self.constant_pool.len() + 1)?;
+        for (i, constant) in self.constant_pool.iter().enumerate() {
+            constant.write(&mut out).map_err(|e| e.within(Context::Constant(i as u16 + 1)))?;
+        }
+
+        write::u16(&mut out, self.access_flags);
+        write::u16(&mut out, self.this_class);
+        write::u16(&mut out, self.super_class);
+        write::u16_table(&mut out, &self.interfaces)?;
+
+        write::count_u16(&mut out, self.fields.len())?;
+        for (i, field) in self.fields.iter().enumerate() {
+            field.write(&self.constant_pool, &mut out)
+                .map_err(|e| e.within(Context::Field(i as u16)))?;
+        }
+
+        write::count_u16(&mut out, self.methods.len())?;
+        for (i, method) in self.methods.iter().enumerate() {
+            method.write(&self.constant_pool, &mut out)
+                .map_err(|e| e.within(Context::Method(i as u16)))?;
+        }
+
+        write::count_u16(&mut out, self.attributes.len())?;
+        for attribute in self.attributes.iter() {
+            attribute.write(&self.constant_pool, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    /// Returns the name of the class, as given by `this_class`.
+    pub fn name(&self) -> Option<&str> {
+        let name_index = match self.constant_pool[self.this_class as usize - 1] {
+            Constant::Class { name_index } => name_index,
+            _ => return None,
+        };
+        match self.constant_pool.get((name_index as usize).wrapping_sub(1)) {
+            Some(Constant::Utf8 { value, .. }) => Some(value),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,67 @@
+use super::error::{ClassFormatError, ErrorKind, Result};
+
+/// The most dimensions an array type can have, as described in JVMS 4.3.2
+const MAX_DIMENSIONS: usize = 255;
+
+/// Checks that `descriptor` is a field descriptor, as described in JVMS 4.3.2. `offset` is the
+/// position of the descriptor's index in the class file.
+pub fn check_field(descriptor: &str, offset: u64) -> Result<()> {
+    match field_type_length(descriptor) {
+        Some(length) if length == descriptor.len() => Ok(()),
+        _ => Err(bad_descriptor(descriptor, offset)),
+    }
+}
+
+/// Checks that `descriptor` is a method descriptor, as described in JVMS 4.3.3.
+pub fn check_method(descriptor: &str, offset: u64) -> Result<()> {
+    if !descriptor.starts_with('(') {
+        return Err(bad_descriptor(descriptor, offset));
+    }
+    let mut rest = &descriptor[1..];
+    while !rest.starts_with(')') {
+        match field_type_length(rest) {
+            Some(length) => rest = &rest[length..],
+            None => return Err(bad_descriptor(descriptor, offset)),
+        }
+    }
+    let return_type = &rest[1..];
+    if return_type == "V" || check_field(return_type, offset).is_ok() {
+        Ok(())
+    } else {
+        Err(bad_descriptor(descriptor, offset))
+    }
+}
+
+fn bad_descriptor(descriptor: &str, offset: u64) -> ClassFormatError {
+    ClassFormatError::new(ErrorKind::BadDescriptor(String::from(descriptor)), offset)
+}
+
+/// Returns the length of the field type at the start of `descriptor`, or `None` if it does not
+/// start with one.
+fn field_type_length(descriptor: &str) -> Option<usize> {
+    let dimensions = descriptor.bytes().take_while(|&b| b == b'[').count();
+    if dimensions > MAX_DIMENSIONS {
+        return None;
+    }
+    let component = &descriptor[dimensions..];
+    let length = match component.bytes().next() {
+        Some(b'B') | Some(b'C') | Some(b'D') | Some(b'F') | Some(b'I') | Some(b'J') |
+        Some(b'S') | Some(b'Z') => 1,
+        Some(b'L') => {
+            let end = component.find(';')?;
+            if !is_binary_name(&component[1..end]) {
+                return None;
+            }
+            end + 1
+        }
+        _ => return None,
+    };
+    Some(dimensions + length)
+}
+
+/// Returns whether `name` is a binary class name in internal form, as described in JVMS 4.2.1.
+fn is_binary_name(name: &str) -> bool {
+    name.split('/').all(|identifier| {
+        !identifier.is_empty() && !identifier.contains(&['.', ';', '['][..])
+    })
+}
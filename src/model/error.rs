@@ -0,0 +1,138 @@
+use std::error;
+use std::fmt;
+use std::result;
+
+pub type Result<T> = result::Result<T, ClassFormatError>;
+
+/// What was malformed in a class file
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    /// The file did not start with `0xCAFEBABE`
+    BadMagic(u32),
+    /// The file ended in the middle of a structure
+    Truncated,
+    /// The file continued after the end of the `ClassFile` structure
+    ExtraBytes,
+    /// A `constant_pool_count` of 0, which leaves no room for the unused first entry
+    EmptyConstantPool,
+    UnknownConstantTag(u8),
+    /// An unknown tag in the named structure, such as `element_value`
+    UnknownTag(&'static str, u8),
+    /// A `CONSTANT_Utf8` whose bytes are not valid modified UTF-8
+    BadUtf8,
+    /// A constant pool index that is out of range, or refers to the wrong kind of constant. The
+    /// string names the expected constant.
+    BadConstantIndex(u16, &'static str),
+    /// A field or method descriptor that does not follow the grammar of JVMS 4.3
+    BadDescriptor(String),
+    /// A method that is neither native nor abstract, but has no `Code` attribute
+    MissingCode,
+    /// An attribute whose contents did not match its `attribute_length`
+    BadAttributeLength { expected: u32, actual: u64 },
+    /// A table with more entries or bytes than its count or length item can hold, when writing a
+    /// class file
+    TooLong(usize),
+    /// A standard attribute whose name is not a `Utf8` constant, when writing a class file
+    MissingAttributeName(&'static str),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorKind::BadMagic(magic) => write!(f, "Incompatible magic value {:#010x}", magic),
+            ErrorKind::Truncated => write!(f, "Truncated class file"),
+            ErrorKind::ExtraBytes => write!(f, "Extra bytes at the end of class file"),
+            ErrorKind::EmptyConstantPool => write!(f, "Illegal constant pool size 0"),
+            ErrorKind::UnknownConstantTag(tag) => write!(f, "Unknown constant tag {}", tag),
+            ErrorKind::UnknownTag(structure, tag) => write!(f, "Unknown {} tag {}", structure, tag),
+            ErrorKind::BadUtf8 => write!(f, "Illegal UTF8 string in constant pool"),
+            ErrorKind::BadConstantIndex(index, expected) => {
+                write!(f, "Invalid constant pool index {}, expected {}", index, expected)
+            }
+            ErrorKind::BadDescriptor(ref descriptor) => {
+                write!(f, "Illegal descriptor \"{}\"", descriptor)
+            }
+            ErrorKind::MissingCode => {
+                write!(f, "Absent Code attribute in method that is not native or abstract")
+            }
+            ErrorKind::BadAttributeLength { expected, actual } => {
+                write!(f,
+                       "Attribute length {} does not match its contents of {} bytes",
+                       expected,
+                       actual)
+            }
+            ErrorKind::TooLong(length) => write!(f, "Table of length {} is too long", length),
+            ErrorKind::MissingAttributeName(name) => {
+                write!(f, "No Utf8 constant for attribute name {}", name)
+            }
+        }
+    }
+}
+
+/// The structure of a class file that contained the error
+#[derive(Clone, Debug, PartialEq)]
+pub enum Context {
+    /// The constant at the given index in the constant pool
+    Constant(u16),
+    /// The field at the given index in the `fields` table
+    Field(u16),
+    /// The method at the given index in the `methods` table
+    Method(u16),
+    /// An attribute with the given name
+    Attribute(String),
+    /// The component at the given index in a `Record` attribute
+    RecordComponent(u16),
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Context::Constant(index) => write!(f, "constant {}", index),
+            Context::Field(index) => write!(f, "field {}", index),
+            Context::Method(index) => write!(f, "method {}", index),
+            Context::Attribute(ref name) => write!(f, "attribute {}", name),
+            Context::RecordComponent(index) => write!(f, "record component {}", index),
+        }
+    }
+}
+
+/// An error in the format of a class file, as detected while parsing or writing it. The
+/// `ClassLoader` reports parsing errors as `java.lang.ClassFormatError`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassFormatError {
+    pub kind: ErrorKind,
+    /// The offset of the malformed structure from the start of the file, or of the output when
+    /// writing
+    pub offset: u64,
+    /// The structures containing the error, outermost first
+    pub context: Vec<Context>,
+}
+
+impl ClassFormatError {
+    pub fn new(kind: ErrorKind, offset: u64) -> Self {
+        ClassFormatError {
+            kind,
+            offset,
+            context: Vec::new(),
+        }
+    }
+
+    /// Records that the error occurred within `context`, which contains the structures already
+    /// recorded.
+    pub fn within(mut self, context: Context) -> Self {
+        self.context.insert(0, context);
+        self
+    }
+}
+
+impl fmt::Display for ClassFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        for (i, context) in self.context.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " in " } else { ", " }, context)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ClassFormatError {}
@@ -0,0 +1,332 @@
+use std::io::Cursor;
+
+use super::super::error::{ClassFormatError, ErrorKind, Result};
+use super::super::read;
+use super::super::write;
+
+/// An annotation, as described in JVMS 4.7.16
+#[derive(Clone, Debug)]
+pub struct Annotation {
+    pub type_index: u16,
+    pub num_element_value_pairs: u16,
+    pub element_value_pairs: Box<[ElementValuePair]>,
+}
+
+impl Annotation {
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<Annotation> {
+        let type_index = read::u16(cur)?;
+        let (num_element_value_pairs, element_value_pairs) = ElementValuePair::read_all(cur)?;
+        Ok(Annotation {
+            type_index,
+            num_element_value_pairs,
+            element_value_pairs,
+        })
+    }
+
+    /// Reads a `u16` count followed by that many annotations.
+    pub fn read_all(cur: &mut Cursor<Vec<u8>>) -> Result<(u16, Box<[Annotation]>)> {
+        let count = read::u16(cur)?;
+        let mut annotations = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            annotations.push(Annotation::new(cur)?);
+        }
+        Ok((count, annotations.into_boxed_slice()))
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        write::u16(out, self.type_index);
+        ElementValuePair::write_all(&self.element_value_pairs, out)
+    }
+
+    /// Writes a `u16` count followed by `annotations`.
+    pub fn write_all(annotations: &[Annotation], out: &mut Vec<u8>) -> Result<()> {
+        write::count_u16(out, annotations.len())?;
+        for annotation in annotations {
+            annotation.write(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
+}
+
+impl ElementValuePair {
+    fn read_all(cur: &mut Cursor<Vec<u8>>) -> Result<(u16, Box<[ElementValuePair]>)> {
+        let count = read::u16(cur)?;
+        let mut pairs = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            pairs.push(ElementValuePair {
+                element_name_index: read::u16(cur)?,
+                value: ElementValue::new(cur)?,
+            });
+        }
+        Ok((count, pairs.into_boxed_slice()))
+    }
+
+    fn write_all(pairs: &[ElementValuePair], out: &mut Vec<u8>) -> Result<()> {
+        write::count_u16(out, pairs.len())?;
+        for pair in pairs {
+            write::u16(out, pair.element_name_index);
+            pair.value.write(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of an annotation element, as described in JVMS 4.7.16.1
+#[derive(Clone, Debug)]
+pub enum ElementValue {
+    /// A primitive or `String` constant. The tag is one of `BCDFIJSZs`, giving its type.
+    Const { tag: u8, const_value_index: u16 },
+    Enum {
+        type_name_index: u16,
+        const_name_index: u16,
+    },
+    Class { class_info_index: u16 },
+    Annotation(Annotation),
+    Array {
+        num_values: u16,
+        values: Box<[ElementValue]>,
+    },
+}
+
+impl ElementValue {
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<ElementValue> {
+        let offset = cur.position();
+        let tag = read::u8(cur)?;
+        let value = match tag {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+                ElementValue::Const {
+                    tag,
+                    const_value_index: read::u16(cur)?,
+                }
+            }
+            b'e' => {
+                ElementValue::Enum {
+                    type_name_index: read::u16(cur)?,
+                    const_name_index: read::u16(cur)?,
+                }
+            }
+            b'c' => ElementValue::Class { class_info_index: read::u16(cur)? },
+            b'@' => ElementValue::Annotation(Annotation::new(cur)?),
+            b'[' => {
+                let num_values = read::u16(cur)?;
+                let mut values = Vec::with_capacity(num_values as usize);
+                for _ in 0..num_values {
+                    values.push(ElementValue::new(cur)?);
+                }
+                ElementValue::Array {
+                    num_values,
+                    values: values.into_boxed_slice(),
+                }
+            }
+            _ => {
+                let kind = ErrorKind::UnknownTag("element_value", tag);
+                return Err(ClassFormatError::new(kind, offset));
+            }
+        };
+        Ok(value)
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            ElementValue::Const { tag, const_value_index } => {
+                write::u8(out, tag);
+                write::u16(out, const_value_index);
+            }
+            ElementValue::Enum { type_name_index, const_name_index } => {
+                write::u8(out, b'e');
+                write::u16(out, type_name_index);
+                write::u16(out, const_name_index);
+            }
+            ElementValue::Class { class_info_index } => {
+                write::u8(out, b'c');
+                write::u16(out, class_info_index);
+            }
+            ElementValue::Annotation(ref annotation) => {
+                write::u8(out, b'@');
+                annotation.write(out)?;
+            }
+            ElementValue::Array { ref values, .. } => {
+                write::u8(out, b'[');
+                write::count_u16(out, values.len())?;
+                for value in values.iter() {
+                    value.write(out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An annotation on a use of a type, as described in JVMS 4.7.20
+#[derive(Clone, Debug)]
+pub struct TypeAnnotation {
+    /// The kind of target, which determines the form of `target_info`
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: TypePath,
+    pub type_index: u16,
+    pub num_element_value_pairs: u16,
+    pub element_value_pairs: Box<[ElementValuePair]>,
+}
+
+impl TypeAnnotation {
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<TypeAnnotation> {
+        let offset = cur.position();
+        let target_type = read::u8(cur)?;
+        let target_info = match target_type {
+            0x00 | 0x01 => TargetInfo::TypeParameter { type_parameter_index: read::u8(cur)? },
+            0x10 => TargetInfo::Supertype { supertype_index: read::u16(cur)? },
+            0x11 | 0x12 => {
+                TargetInfo::TypeParameterBound {
+                    type_parameter_index: read::u8(cur)?,
+                    bound_index: read::u8(cur)?,
+                }
+            }
+            0x13..=0x15 => TargetInfo::Empty,
+            0x16 => TargetInfo::FormalParameter { formal_parameter_index: read::u8(cur)? },
+            0x17 => TargetInfo::Throws { throws_type_index: read::u16(cur)? },
+            0x40 | 0x41 => {
+                let table_length = read::u16(cur)?;
+                let mut table = Vec::with_capacity(table_length as usize);
+                for _ in 0..table_length {
+                    table.push(LocalVarTarget {
+                        start_pc: read::u16(cur)?,
+                        length: read::u16(cur)?,
+                        index: read::u16(cur)?,
+                    });
+                }
+                TargetInfo::Localvar {
+                    table_length,
+                    table: table.into_boxed_slice(),
+                }
+            }
+            0x42 => TargetInfo::Catch { exception_table_index: read::u16(cur)? },
+            0x43..=0x46 => TargetInfo::Offset { offset: read::u16(cur)? },
+            0x47..=0x4B => {
+                TargetInfo::TypeArgument {
+                    offset: read::u16(cur)?,
+                    type_argument_index: read::u8(cur)?,
+                }
+            }
+            _ => {
+                let kind = ErrorKind::UnknownTag("target_type", target_type);
+                return Err(ClassFormatError::new(kind, offset));
+            }
+        };
+
+        let path_length = read::u8(cur)?;
+        let mut path = Vec::with_capacity(path_length as usize);
+        for _ in 0..path_length {
+            path.push(TypePathEntry {
+                type_path_kind: read::u8(cur)?,
+                type_argument_index: read::u8(cur)?,
+            });
+        }
+        let type_index = read::u16(cur)?;
+        let (num_element_value_pairs, element_value_pairs) = ElementValuePair::read_all(cur)?;
+        Ok(TypeAnnotation {
+            target_type,
+            target_info,
+            target_path: TypePath {
+                path_length,
+                path: path.into_boxed_slice(),
+            },
+            type_index,
+            num_element_value_pairs,
+            element_value_pairs,
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        write::u8(out, self.target_type);
+        match self.target_info {
+            TargetInfo::TypeParameter { type_parameter_index } => {
+                write::u8(out, type_parameter_index)
+            }
+            TargetInfo::Supertype { supertype_index } => write::u16(out, supertype_index),
+            TargetInfo::TypeParameterBound { type_parameter_index, bound_index } => {
+                write::u8(out, type_parameter_index);
+                write::u8(out, bound_index);
+            }
+            TargetInfo::Empty => (),
+            TargetInfo::FormalParameter { formal_parameter_index } => {
+                write::u8(out, formal_parameter_index)
+            }
+            TargetInfo::Throws { throws_type_index } => write::u16(out, throws_type_index),
+            TargetInfo::Localvar { ref table, .. } => {
+                write::count_u16(out, table.len())?;
+                for target in table.iter() {
+                    write::u16(out, target.start_pc);
+                    write::u16(out, target.length);
+                    write::u16(out, target.index);
+                }
+            }
+            TargetInfo::Catch { exception_table_index } => write::u16(out, exception_table_index),
+            TargetInfo::Offset { offset } => write::u16(out, offset),
+            TargetInfo::TypeArgument { offset, type_argument_index } => {
+                write::u16(out, offset);
+                write::u8(out, type_argument_index);
+            }
+        }
+        write::count_u8(out, self.target_path.path.len())?;
+        for entry in self.target_path.path.iter() {
+            write::u8(out, entry.type_path_kind);
+            write::u8(out, entry.type_argument_index);
+        }
+        write::u16(out, self.type_index);
+        ElementValuePair::write_all(&self.element_value_pairs, out)
+    }
+}
+
+/// The annotated type within a declaration or expression, as described in JVMS 4.7.20.1
+#[derive(Clone, Debug)]
+pub enum TargetInfo {
+    TypeParameter { type_parameter_index: u8 },
+    /// A type in the `extends` or `implements` clause. An index of 65535 is the superclass.
+    Supertype { supertype_index: u16 },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    /// The type in a field declaration, the return type of a method, or the receiver type
+    Empty,
+    FormalParameter { formal_parameter_index: u8 },
+    Throws { throws_type_index: u16 },
+    /// The type of a local variable, which may live in several ranges of code
+    Localvar {
+        table_length: u16,
+        table: Box<[LocalVarTarget]>,
+    },
+    Catch { exception_table_index: u16 },
+    /// The type in an `instanceof`, `new` or method reference expression
+    Offset { offset: u16 },
+    /// A type argument of a cast, constructor call or method call
+    TypeArgument { offset: u16, type_argument_index: u8 },
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalVarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+/// The path to the annotated part of a type, such as an array component or type argument, as
+/// described in JVMS 4.7.20.2
+#[derive(Clone, Debug)]
+pub struct TypePath {
+    pub path_length: u8,
+    pub path: Box<[TypePathEntry]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
@@ -1,17 +1,107 @@
-use std::io::{Cursor, Read};
+use std::io::Cursor;
 
 use super::Constant;
+use super::annotation::{Annotation, ElementValue, TypeAnnotation};
+use super::constant;
+use super::stack_map::StackMapFrame;
+use super::super::error::{ClassFormatError, Context, ErrorKind, Result};
+use super::super::read;
+use super::super::write;
 
-use byteorder::{BigEndian, ReadBytesExt};
+/// The annotations of each parameter of a method
+pub type ParameterAnnotations = Box<[Box<[Annotation]>]>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ExceptionHandler {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16,
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct BootstrapMethod {
+    pub bootstrap_method_ref: u16,
+    pub num_bootstrap_arguments: u16,
+    pub bootstrap_arguments: Box<[u16]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct InnerClass {
+    pub inner_class_info_index: u16,
+    /// 0 if the class is not a member of another class
+    pub outer_class_info_index: u16,
+    /// 0 if the class is anonymous
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineNumber {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalVariable {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+/// An entry of a `LocalVariableTypeTable`, which gives the generic signature of a local variable
+#[derive(Clone, Debug)]
+pub struct LocalVariableType {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub signature_index: u16,
+    pub index: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct MethodParameter {
+    /// 0 if the parameter is unnamed
+    pub name_index: u16,
+    pub access_flags: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleRequires {
+    pub requires_index: u16,
+    pub requires_flags: u16,
+    pub requires_version_index: u16,
+}
+
+/// A package exported or opened by a module, to the modules in `to_index`, or to every module if
+/// there are none
+#[derive(Clone, Debug)]
+pub struct ModulePackage {
+    pub package_index: u16,
+    pub flags: u16,
+    pub to_count: u16,
+    pub to_index: Box<[u16]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleProvides {
+    pub provides_index: u16,
+    pub provides_with_count: u16,
+    pub provides_with_index: Box<[u16]>,
 }
 
+#[derive(Debug)]
+pub struct RecordComponent {
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes_count: u16,
+    pub attributes: Box<[Attribute]>,
+}
+
+/// An attribute of a class, field, method, code or record component, as described in JVMS 4.7.
+/// Attributes that are not defined by the JVMS are kept as `Unknown`.
 #[derive(Debug)]
 pub enum Attribute {
     ConstantValue { value_index: u16 },
@@ -26,6 +116,108 @@ pub enum Attribute {
         attributes_count: u16,
         attributes: Box<[Attribute]>,
     },
+    StackMapTable {
+        number_of_entries: u16,
+        entries: Box<[StackMapFrame]>,
+    },
+    Exceptions {
+        number_of_exceptions: u16,
+        exception_index_table: Box<[u16]>,
+    },
+    InnerClasses {
+        number_of_classes: u16,
+        classes: Box<[InnerClass]>,
+    },
+    EnclosingMethod {
+        class_index: u16,
+        /// 0 if the class is not enclosed by a method or constructor
+        method_index: u16,
+    },
+    Synthetic,
+    Signature { signature_index: u16 },
+    SourceFile { sourcefile_index: u16 },
+    SourceDebugExtension { debug_extension: Box<[u8]> },
+    LineNumberTable {
+        line_number_table_length: u16,
+        line_number_table: Box<[LineNumber]>,
+    },
+    LocalVariableTable {
+        local_variable_table_length: u16,
+        local_variable_table: Box<[LocalVariable]>,
+    },
+    LocalVariableTypeTable {
+        local_variable_type_table_length: u16,
+        local_variable_type_table: Box<[LocalVariableType]>,
+    },
+    Deprecated,
+    RuntimeVisibleAnnotations {
+        num_annotations: u16,
+        annotations: Box<[Annotation]>,
+    },
+    RuntimeInvisibleAnnotations {
+        num_annotations: u16,
+        annotations: Box<[Annotation]>,
+    },
+    RuntimeVisibleParameterAnnotations {
+        num_parameters: u8,
+        parameter_annotations: ParameterAnnotations,
+    },
+    RuntimeInvisibleParameterAnnotations {
+        num_parameters: u8,
+        parameter_annotations: ParameterAnnotations,
+    },
+    RuntimeVisibleTypeAnnotations {
+        num_annotations: u16,
+        annotations: Box<[TypeAnnotation]>,
+    },
+    RuntimeInvisibleTypeAnnotations {
+        num_annotations: u16,
+        annotations: Box<[TypeAnnotation]>,
+    },
+    /// The default value of an element of an annotation interface
+    AnnotationDefault { default_value: ElementValue },
+    BootstrapMethods {
+        num_bootstrap_methods: u16,
+        bootstrap_methods: Box<[BootstrapMethod]>,
+    },
+    MethodParameters {
+        parameters_count: u8,
+        parameters: Box<[MethodParameter]>,
+    },
+    Module {
+        module_name_index: u16,
+        module_flags: u16,
+        /// 0 if the module has no version
+        module_version_index: u16,
+        requires_count: u16,
+        requires: Box<[ModuleRequires]>,
+        exports_count: u16,
+        exports: Box<[ModulePackage]>,
+        opens_count: u16,
+        opens: Box<[ModulePackage]>,
+        uses_count: u16,
+        uses_index: Box<[u16]>,
+        provides_count: u16,
+        provides: Box<[ModuleProvides]>,
+    },
+    ModulePackages {
+        package_count: u16,
+        package_index: Box<[u16]>,
+    },
+    ModuleMainClass { main_class_index: u16 },
+    NestHost { host_class_index: u16 },
+    NestMembers {
+        number_of_classes: u16,
+        classes: Box<[u16]>,
+    },
+    Record {
+        components_count: u16,
+        components: Box<[RecordComponent]>,
+    },
+    PermittedSubclasses {
+        number_of_classes: u16,
+        classes: Box<[u16]>,
+    },
     Unknown {
         name_index: u16,
         length: u32,
@@ -33,71 +225,674 @@ pub enum Attribute {
     },
 }
 
+/// Reads a `u16` count followed by that many `u16` items, such as constant pool indices.
+fn read_u16_table(cur: &mut Cursor<Vec<u8>>) -> Result<(u16, Box<[u16]>)> {
+    let count = read::u16(cur)?;
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        items.push(read::u16(cur)?);
+    }
+    Ok((count, items.into_boxed_slice()))
+}
+
+/// Reads a `u16` count followed by that many attributes.
+fn read_attributes(constant_pool: &[Constant],
+                   cur: &mut Cursor<Vec<u8>>)
+                   -> Result<(u16, Box<[Attribute]>)> {
+    let count = read::u16(cur)?;
+    let mut attributes = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        attributes.push(Attribute::new(constant_pool, cur)?);
+    }
+    Ok((count, attributes.into_boxed_slice()))
+}
+
+fn read_parameter_annotations(cur: &mut Cursor<Vec<u8>>) -> Result<(u8, ParameterAnnotations)> {
+    let num_parameters = read::u8(cur)?;
+    let mut parameter_annotations = Vec::with_capacity(num_parameters as usize);
+    for _ in 0..num_parameters {
+        parameter_annotations.push(Annotation::read_all(cur)?.1);
+    }
+    Ok((num_parameters, parameter_annotations.into_boxed_slice()))
+}
+
+fn read_type_annotations(cur: &mut Cursor<Vec<u8>>) -> Result<(u16, Box<[TypeAnnotation]>)> {
+    let num_annotations = read::u16(cur)?;
+    let mut annotations = Vec::with_capacity(num_annotations as usize);
+    for _ in 0..num_annotations {
+        annotations.push(TypeAnnotation::new(cur)?);
+    }
+    Ok((num_annotations, annotations.into_boxed_slice()))
+}
+
+fn read_module_packages(cur: &mut Cursor<Vec<u8>>) -> Result<(u16, Box<[ModulePackage]>)> {
+    let count = read::u16(cur)?;
+    let mut packages = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let package_index = read::u16(cur)?;
+        let flags = read::u16(cur)?;
+        let (to_count, to_index) = read_u16_table(cur)?;
+        packages.push(ModulePackage {
+            package_index,
+            flags,
+            to_count,
+            to_index,
+        });
+    }
+    Ok((count, packages.into_boxed_slice()))
+}
+
+/// Writes a `u16` count followed by `attributes`.
+fn write_attributes(constant_pool: &[Constant],
+                    attributes: &[Attribute],
+                    out: &mut Vec<u8>)
+                    -> Result<()> {
+    write::count_u16(out, attributes.len())?;
+    for attribute in attributes {
+        attribute.write(constant_pool, out)?;
+    }
+    Ok(())
+}
+
+fn write_parameter_annotations(parameter_annotations: &[Box<[Annotation]>],
+                               out: &mut Vec<u8>)
+                               -> Result<()> {
+    write::count_u8(out, parameter_annotations.len())?;
+    for annotations in parameter_annotations {
+        Annotation::write_all(annotations, out)?;
+    }
+    Ok(())
+}
+
+fn write_type_annotations(annotations: &[TypeAnnotation], out: &mut Vec<u8>) -> Result<()> {
+    write::count_u16(out, annotations.len())?;
+    for annotation in annotations {
+        annotation.write(out)?;
+    }
+    Ok(())
+}
+
+fn write_module_packages(packages: &[ModulePackage], out: &mut Vec<u8>) -> Result<()> {
+    write::count_u16(out, packages.len())?;
+    for package in packages {
+        write::u16(out, package.package_index);
+        write::u16(out, package.flags);
+        write::u16_table(out, &package.to_index)?;
+    }
+    Ok(())
+}
+
 impl Attribute {
-    pub fn new(constant_pool: &Box<[Constant]>, cur: &mut Cursor<Vec<u8>>) -> Attribute {
-        let name_index = cur.read_u16::<BigEndian>().unwrap() - 1; // 1-indexed
-        let length = cur.read_u32::<BigEndian>().unwrap();
+    pub fn new(constant_pool: &[Constant], cur: &mut Cursor<Vec<u8>>) -> Result<Attribute> {
+        let offset = cur.position();
+        let name_index = read::u16(cur)?;
+        let name = match *constant::lookup(constant_pool, name_index, "Utf8", offset)? {
+            Constant::Utf8 { ref value, .. } => value,
+            _ => unreachable!(),
+        };
+        let length = read::u32(cur)?;
 
-        let name = match constant_pool[name_index as usize] {
-            Constant::Utf8 { length, ref value } => value,
-            _ => {
-                panic!("Attribute name_index({}) must point to Utf8", name_index);
+        let start = cur.position();
+        // Check the declared length first, so that its info is not parsed past the end of the
+        // class file
+        if length as u64 > (cur.get_ref().len() as u64).saturating_sub(start) {
+            let error = ClassFormatError::new(ErrorKind::Truncated, start);
+            return Err(error.within(Context::Attribute(name.clone())));
+        }
+        let attribute = Self::parse_info(constant_pool, cur, name, name_index - 1, length)
+            .map_err(|e| e.within(Context::Attribute(name.clone())))?;
+        let actual = cur.position() - start;
+        if actual != length as u64 {
+            let kind = ErrorKind::BadAttributeLength {
+                expected: length,
+                actual,
+            };
+            let error = ClassFormatError::new(kind, offset);
+            return Err(error.within(Context::Attribute(name.clone())));
+        }
+        Ok(attribute)
+    }
+
+    /// Returns the name of the attribute, or `None` if it is `Unknown`.
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match *self {
+            Attribute::ConstantValue { .. } => "ConstantValue",
+            Attribute::Code { .. } => "Code",
+            Attribute::StackMapTable { .. } => "StackMapTable",
+            Attribute::Exceptions { .. } => "Exceptions",
+            Attribute::InnerClasses { .. } => "InnerClasses",
+            Attribute::EnclosingMethod { .. } => "EnclosingMethod",
+            Attribute::Synthetic => "Synthetic",
+            Attribute::Signature { .. } => "Signature",
+            Attribute::SourceFile { .. } => "SourceFile",
+            Attribute::SourceDebugExtension { .. } => "SourceDebugExtension",
+            Attribute::LineNumberTable { .. } => "LineNumberTable",
+            Attribute::LocalVariableTable { .. } => "LocalVariableTable",
+            Attribute::LocalVariableTypeTable { .. } => "LocalVariableTypeTable",
+            Attribute::Deprecated => "Deprecated",
+            Attribute::RuntimeVisibleAnnotations { .. } => "RuntimeVisibleAnnotations",
+            Attribute::RuntimeInvisibleAnnotations { .. } => "RuntimeInvisibleAnnotations",
+            Attribute::RuntimeVisibleParameterAnnotations { .. } => {
+                "RuntimeVisibleParameterAnnotations"
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations { .. } => {
+                "RuntimeInvisibleParameterAnnotations"
+            }
+            Attribute::RuntimeVisibleTypeAnnotations { .. } => "RuntimeVisibleTypeAnnotations",
+            Attribute::RuntimeInvisibleTypeAnnotations { .. } => {
+                "RuntimeInvisibleTypeAnnotations"
+            }
+            Attribute::AnnotationDefault { .. } => "AnnotationDefault",
+            Attribute::BootstrapMethods { .. } => "BootstrapMethods",
+            Attribute::MethodParameters { .. } => "MethodParameters",
+            Attribute::Module { .. } => "Module",
+            Attribute::ModulePackages { .. } => "ModulePackages",
+            Attribute::ModuleMainClass { .. } => "ModuleMainClass",
+            Attribute::NestHost { .. } => "NestHost",
+            Attribute::NestMembers { .. } => "NestMembers",
+            Attribute::Record { .. } => "Record",
+            Attribute::PermittedSubclasses { .. } => "PermittedSubclasses",
+            Attribute::Unknown { .. } => return None,
+        };
+        Some(name)
+    }
+
+    /// Writes the attribute, whose name must be in `constant_pool`. Its length, and the counts of
+    /// its tables, are recomputed from what is written rather than taken from the stored fields.
+    pub fn write(&self, constant_pool: &[Constant], out: &mut Vec<u8>) -> Result<()> {
+        let (name_index, name) = match (self, self.name()) {
+            (Attribute::Unknown { name_index, .. }, _) => {
+                let name = match constant_pool.get(*name_index as usize) {
+                    Some(Constant::Utf8 { value, .. }) => value.clone(),
+                    _ => String::new(),
+                };
+                (*name_index + 1, name)
+            }
+            (_, Some(name)) => {
+                match constant::find_utf8(constant_pool, name) {
+                    Some(name_index) => (name_index, String::from(name)),
+                    None => {
+                        let kind = ErrorKind::MissingAttributeName(name);
+                        return Err(ClassFormatError::new(kind, out.len() as u64));
+                    }
+                }
             }
+            (_, None) => unreachable!(),
         };
+        write::u16(out, name_index);
+        write::with_u32_length(out, |out| self.write_info(constant_pool, out))
+            .map_err(|e| e.within(Context::Attribute(name)))
+    }
 
-        match name.as_ref() {
-            "ConstantValue" => {
-                Attribute::ConstantValue { value_index: cur.read_u16::<BigEndian>().unwrap() }
+    fn write_info(&self, constant_pool: &[Constant], out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            Attribute::ConstantValue { value_index } => write::u16(out, value_index),
+            Attribute::Code { max_stack,
+                              max_locals,
+                              ref code,
+                              ref exception_table,
+                              ref attributes,
+                              .. } => {
+                write::u16(out, max_stack);
+                write::u16(out, max_locals);
+                if code.len() > u32::MAX as usize {
+                    let kind = ErrorKind::TooLong(code.len());
+                    return Err(ClassFormatError::new(kind, out.len() as u64));
+                }
+                write::u32(out, code.len() as u32);
+                out.extend_from_slice(code);
+                write::count_u16(out, exception_table.len())?;
+                for handler in exception_table.iter() {
+                    write::u16(out, handler.start_pc);
+                    write::u16(out, handler.end_pc);
+                    write::u16(out, handler.handler_pc);
+                    write::u16(out, handler.catch_type);
+                }
+                write_attributes(constant_pool, attributes, out)?;
+            }
+            Attribute::StackMapTable { ref entries, .. } => {
+                write::count_u16(out, entries.len())?;
+                for entry in entries.iter() {
+                    entry.write(out)?;
+                }
+            }
+            Attribute::Exceptions { ref exception_index_table, .. } => {
+                write::u16_table(out, exception_index_table)?
+            }
+            Attribute::InnerClasses { ref classes, .. } => {
+                write::count_u16(out, classes.len())?;
+                for class in classes.iter() {
+                    write::u16(out, class.inner_class_info_index);
+                    write::u16(out, class.outer_class_info_index);
+                    write::u16(out, class.inner_name_index);
+                    write::u16(out, class.inner_class_access_flags);
+                }
             }
+            Attribute::EnclosingMethod { class_index, method_index } => {
+                write::u16(out, class_index);
+                write::u16(out, method_index);
+            }
+            Attribute::Synthetic | Attribute::Deprecated => (),
+            Attribute::Signature { signature_index } => write::u16(out, signature_index),
+            Attribute::SourceFile { sourcefile_index } => write::u16(out, sourcefile_index),
+            Attribute::SourceDebugExtension { ref debug_extension } => {
+                out.extend_from_slice(debug_extension)
+            }
+            Attribute::LineNumberTable { ref line_number_table, .. } => {
+                write::count_u16(out, line_number_table.len())?;
+                for line in line_number_table.iter() {
+                    write::u16(out, line.start_pc);
+                    write::u16(out, line.line_number);
+                }
+            }
+            Attribute::LocalVariableTable { ref local_variable_table, .. } => {
+                write::count_u16(out, local_variable_table.len())?;
+                for variable in local_variable_table.iter() {
+                    write::u16(out, variable.start_pc);
+                    write::u16(out, variable.length);
+                    write::u16(out, variable.name_index);
+                    write::u16(out, variable.descriptor_index);
+                    write::u16(out, variable.index);
+                }
+            }
+            Attribute::LocalVariableTypeTable { ref local_variable_type_table, .. } => {
+                write::count_u16(out, local_variable_type_table.len())?;
+                for variable in local_variable_type_table.iter() {
+                    write::u16(out, variable.start_pc);
+                    write::u16(out, variable.length);
+                    write::u16(out, variable.name_index);
+                    write::u16(out, variable.signature_index);
+                    write::u16(out, variable.index);
+                }
+            }
+            Attribute::RuntimeVisibleAnnotations { ref annotations, .. } |
+            Attribute::RuntimeInvisibleAnnotations { ref annotations, .. } => {
+                Annotation::write_all(annotations, out)?
+            }
+            Attribute::RuntimeVisibleParameterAnnotations { ref parameter_annotations, .. } |
+            Attribute::RuntimeInvisibleParameterAnnotations { ref parameter_annotations, .. } => {
+                write_parameter_annotations(parameter_annotations, out)?
+            }
+            Attribute::RuntimeVisibleTypeAnnotations { ref annotations, .. } |
+            Attribute::RuntimeInvisibleTypeAnnotations { ref annotations, .. } => {
+                write_type_annotations(annotations, out)?
+            }
+            Attribute::AnnotationDefault { ref default_value } => default_value.write(out)?,
+            Attribute::BootstrapMethods { ref bootstrap_methods, .. } => {
+                write::count_u16(out, bootstrap_methods.len())?;
+                for method in bootstrap_methods.iter() {
+                    write::u16(out, method.bootstrap_method_ref);
+                    write::u16_table(out, &method.bootstrap_arguments)?;
+                }
+            }
+            Attribute::MethodParameters { ref parameters, .. } => {
+                write::count_u8(out, parameters.len())?;
+                for parameter in parameters.iter() {
+                    write::u16(out, parameter.name_index);
+                    write::u16(out, parameter.access_flags);
+                }
+            }
+            Attribute::Module { module_name_index,
+                                module_flags,
+                                module_version_index,
+                                ref requires,
+                                ref exports,
+                                ref opens,
+                                ref uses_index,
+                                ref provides,
+                                .. } => {
+                write::u16(out, module_name_index);
+                write::u16(out, module_flags);
+                write::u16(out, module_version_index);
+                write::count_u16(out, requires.len())?;
+                for require in requires.iter() {
+                    write::u16(out, require.requires_index);
+                    write::u16(out, require.requires_flags);
+                    write::u16(out, require.requires_version_index);
+                }
+                write_module_packages(exports, out)?;
+                write_module_packages(opens, out)?;
+                write::u16_table(out, uses_index)?;
+                write::count_u16(out, provides.len())?;
+                for provide in provides.iter() {
+                    write::u16(out, provide.provides_index);
+                    write::u16_table(out, &provide.provides_with_index)?;
+                }
+            }
+            Attribute::ModulePackages { ref package_index, .. } => {
+                write::u16_table(out, package_index)?
+            }
+            Attribute::ModuleMainClass { main_class_index } => write::u16(out, main_class_index),
+            Attribute::NestHost { host_class_index } => write::u16(out, host_class_index),
+            Attribute::NestMembers { ref classes, .. } |
+            Attribute::PermittedSubclasses { ref classes, .. } => write::u16_table(out, classes)?,
+            Attribute::Record { ref components, .. } => {
+                write::count_u16(out, components.len())?;
+                for (i, component) in components.iter().enumerate() {
+                    write::u16(out, component.name_index);
+                    write::u16(out, component.descriptor_index);
+                    write_attributes(constant_pool, &component.attributes, out)
+                        .map_err(|e| e.within(Context::RecordComponent(i as u16)))?;
+                }
+            }
+            Attribute::Unknown { ref info, .. } => out.extend_from_slice(info),
+        }
+        Ok(())
+    }
+
+    /// Parses the `info` of an attribute called `name`, which is `length` bytes long.
+    fn parse_info(constant_pool: &[Constant],
+                  cur: &mut Cursor<Vec<u8>>,
+                  name: &str,
+                  name_index: u16,
+                  length: u32)
+                  -> Result<Attribute> {
+        let attribute = match name {
+            "ConstantValue" => Attribute::ConstantValue { value_index: read::u16(cur)? },
             "Code" => {
-                let max_stack = cur.read_u16::<BigEndian>().unwrap();
-                let max_locals = cur.read_u16::<BigEndian>().unwrap();
+                let max_stack = read::u16(cur)?;
+                let max_locals = read::u16(cur)?;
 
-                let code_length = cur.read_u32::<BigEndian>().unwrap();
-                let code = vec![0; code_length as usize];
-                let mut slice = code.into_boxed_slice();
-                cur.read_exact(&mut slice).unwrap();
+                let code_length = read::u32(cur)?;
+                let code = read::bytes(cur, code_length as usize)?;
 
-                let exception_table_length = cur.read_u16::<BigEndian>().unwrap();
+                let exception_table_length = read::u16(cur)?;
                 let mut exception_table = Vec::with_capacity(exception_table_length as usize);
                 for _ in 0..exception_table_length {
                     exception_table.push(ExceptionHandler {
-                        start_pc: cur.read_u16::<BigEndian>().unwrap(),
-                        end_pc: cur.read_u16::<BigEndian>().unwrap(),
-                        handler_pc: cur.read_u16::<BigEndian>().unwrap(),
-                        catch_type: cur.read_u16::<BigEndian>().unwrap(),
+                        start_pc: read::u16(cur)?,
+                        end_pc: read::u16(cur)?,
+                        handler_pc: read::u16(cur)?,
+                        catch_type: read::u16(cur)?,
                     });
                 }
 
-                let attributes_count = cur.read_u16::<BigEndian>().unwrap();
-                let mut attributes = Vec::with_capacity(attributes_count as usize);
-                for _ in 0..attributes_count {
-                    attributes.push(Attribute::new(constant_pool, cur));
-                }
+                let (attributes_count, attributes) = read_attributes(constant_pool, cur)?;
                 Attribute::Code {
-                    length: length,
-                    max_stack: max_stack,
-                    max_locals: max_locals,
-                    code_length: code_length,
-                    code: slice,
-                    exception_table_length: exception_table_length,
+                    length,
+                    max_stack,
+                    max_locals,
+                    code_length,
+                    code,
+                    exception_table_length,
                     exception_table: exception_table.into_boxed_slice(),
-                    attributes_count: attributes_count,
-                    attributes: attributes.into_boxed_slice(),
+                    attributes_count,
+                    attributes,
                 }
             }
-            _ => {
-                println!("Unknown attribute {}", name);
-                let bytes = vec![0 as u8; length as usize];
-                let mut slice = bytes.into_boxed_slice();
-                cur.read_exact(&mut slice).unwrap();
+            "StackMapTable" => {
+                let number_of_entries = read::u16(cur)?;
+                let mut entries = Vec::with_capacity(number_of_entries as usize);
+                for _ in 0..number_of_entries {
+                    entries.push(StackMapFrame::new(cur)?);
+                }
+                Attribute::StackMapTable {
+                    number_of_entries,
+                    entries: entries.into_boxed_slice(),
+                }
+            }
+            "Exceptions" => {
+                let (number_of_exceptions, exception_index_table) = read_u16_table(cur)?;
+                Attribute::Exceptions {
+                    number_of_exceptions,
+                    exception_index_table,
+                }
+            }
+            "InnerClasses" => {
+                let number_of_classes = read::u16(cur)?;
+                let mut classes = Vec::with_capacity(number_of_classes as usize);
+                for _ in 0..number_of_classes {
+                    classes.push(InnerClass {
+                        inner_class_info_index: read::u16(cur)?,
+                        outer_class_info_index: read::u16(cur)?,
+                        inner_name_index: read::u16(cur)?,
+                        inner_class_access_flags: read::u16(cur)?,
+                    });
+                }
+                Attribute::InnerClasses {
+                    number_of_classes,
+                    classes: classes.into_boxed_slice(),
+                }
+            }
+            "EnclosingMethod" => {
+                Attribute::EnclosingMethod {
+                    class_index: read::u16(cur)?,
+                    method_index: read::u16(cur)?,
+                }
+            }
+            "Synthetic" => Attribute::Synthetic,
+            "Signature" => Attribute::Signature { signature_index: read::u16(cur)? },
+            "SourceFile" => Attribute::SourceFile { sourcefile_index: read::u16(cur)? },
+            "SourceDebugExtension" => {
+                let debug_extension = read::bytes(cur, length as usize)?;
+                Attribute::SourceDebugExtension { debug_extension }
+            }
+            "LineNumberTable" => {
+                let line_number_table_length = read::u16(cur)?;
+                let mut line_number_table = Vec::with_capacity(line_number_table_length as usize);
+                for _ in 0..line_number_table_length {
+                    line_number_table.push(LineNumber {
+                        start_pc: read::u16(cur)?,
+                        line_number: read::u16(cur)?,
+                    });
+                }
+                Attribute::LineNumberTable {
+                    line_number_table_length,
+                    line_number_table: line_number_table.into_boxed_slice(),
+                }
+            }
+            "LocalVariableTable" => {
+                let table_length = read::u16(cur)?;
+                let mut table = Vec::with_capacity(table_length as usize);
+                for _ in 0..table_length {
+                    table.push(LocalVariable {
+                        start_pc: read::u16(cur)?,
+                        length: read::u16(cur)?,
+                        name_index: read::u16(cur)?,
+                        descriptor_index: read::u16(cur)?,
+                        index: read::u16(cur)?,
+                    });
+                }
+                Attribute::LocalVariableTable {
+                    local_variable_table_length: table_length,
+                    local_variable_table: table.into_boxed_slice(),
+                }
+            }
+            "LocalVariableTypeTable" => {
+                let table_length = read::u16(cur)?;
+                let mut table = Vec::with_capacity(table_length as usize);
+                for _ in 0..table_length {
+                    table.push(LocalVariableType {
+                        start_pc: read::u16(cur)?,
+                        length: read::u16(cur)?,
+                        name_index: read::u16(cur)?,
+                        signature_index: read::u16(cur)?,
+                        index: read::u16(cur)?,
+                    });
+                }
+                Attribute::LocalVariableTypeTable {
+                    local_variable_type_table_length: table_length,
+                    local_variable_type_table: table.into_boxed_slice(),
+                }
+            }
+            "Deprecated" => Attribute::Deprecated,
+            "RuntimeVisibleAnnotations" => {
+                let (num_annotations, annotations) = Annotation::read_all(cur)?;
+                Attribute::RuntimeVisibleAnnotations {
+                    num_annotations,
+                    annotations,
+                }
+            }
+            "RuntimeInvisibleAnnotations" => {
+                let (num_annotations, annotations) = Annotation::read_all(cur)?;
+                Attribute::RuntimeInvisibleAnnotations {
+                    num_annotations,
+                    annotations,
+                }
+            }
+            "RuntimeVisibleParameterAnnotations" => {
+                let (num_parameters, parameter_annotations) = read_parameter_annotations(cur)?;
+                Attribute::RuntimeVisibleParameterAnnotations {
+                    num_parameters,
+                    parameter_annotations,
+                }
+            }
+            "RuntimeInvisibleParameterAnnotations" => {
+                let (num_parameters, parameter_annotations) = read_parameter_annotations(cur)?;
+                Attribute::RuntimeInvisibleParameterAnnotations {
+                    num_parameters,
+                    parameter_annotations,
+                }
+            }
+            "RuntimeVisibleTypeAnnotations" => {
+                let (num_annotations, annotations) = read_type_annotations(cur)?;
+                Attribute::RuntimeVisibleTypeAnnotations {
+                    num_annotations,
+                    annotations,
+                }
+            }
+            "RuntimeInvisibleTypeAnnotations" => {
+                let (num_annotations, annotations) = read_type_annotations(cur)?;
+                Attribute::RuntimeInvisibleTypeAnnotations {
+                    num_annotations,
+                    annotations,
+                }
+            }
+            "AnnotationDefault" => {
+                Attribute::AnnotationDefault { default_value: ElementValue::new(cur)? }
+            }
+            "BootstrapMethods" => {
+                let num_bootstrap_methods = read::u16(cur)?;
+                let mut bootstrap_methods = Vec::with_capacity(num_bootstrap_methods as usize);
+                for _ in 0..num_bootstrap_methods {
+                    let bootstrap_method_ref = read::u16(cur)?;
+                    let num_bootstrap_arguments = read::u16(cur)?;
+                    let mut bootstrap_arguments =
+                        Vec::with_capacity(num_bootstrap_arguments as usize);
+                    for _ in 0..num_bootstrap_arguments {
+                        bootstrap_arguments.push(read::u16(cur)?);
+                    }
+                    bootstrap_methods.push(BootstrapMethod {
+                        bootstrap_method_ref,
+                        num_bootstrap_arguments,
+                        bootstrap_arguments: bootstrap_arguments.into_boxed_slice(),
+                    });
+                }
+                Attribute::BootstrapMethods {
+                    num_bootstrap_methods,
+                    bootstrap_methods: bootstrap_methods.into_boxed_slice(),
+                }
+            }
+            "MethodParameters" => {
+                let parameters_count = read::u8(cur)?;
+                let mut parameters = Vec::with_capacity(parameters_count as usize);
+                for _ in 0..parameters_count {
+                    parameters.push(MethodParameter {
+                        name_index: read::u16(cur)?,
+                        access_flags: read::u16(cur)?,
+                    });
+                }
+                Attribute::MethodParameters {
+                    parameters_count,
+                    parameters: parameters.into_boxed_slice(),
+                }
+            }
+            "Module" => {
+                let module_name_index = read::u16(cur)?;
+                let module_flags = read::u16(cur)?;
+                let module_version_index = read::u16(cur)?;
+
+                let requires_count = read::u16(cur)?;
+                let mut requires = Vec::with_capacity(requires_count as usize);
+                for _ in 0..requires_count {
+                    requires.push(ModuleRequires {
+                        requires_index: read::u16(cur)?,
+                        requires_flags: read::u16(cur)?,
+                        requires_version_index: read::u16(cur)?,
+                    });
+                }
+                let (exports_count, exports) = read_module_packages(cur)?;
+                let (opens_count, opens) = read_module_packages(cur)?;
+                let (uses_count, uses_index) = read_u16_table(cur)?;
 
+                let provides_count = read::u16(cur)?;
+                let mut provides = Vec::with_capacity(provides_count as usize);
+                for _ in 0..provides_count {
+                    let provides_index = read::u16(cur)?;
+                    let (provides_with_count, provides_with_index) = read_u16_table(cur)?;
+                    provides.push(ModuleProvides {
+                        provides_index,
+                        provides_with_count,
+                        provides_with_index,
+                    });
+                }
+                Attribute::Module {
+                    module_name_index,
+                    module_flags,
+                    module_version_index,
+                    requires_count,
+                    requires: requires.into_boxed_slice(),
+                    exports_count,
+                    exports,
+                    opens_count,
+                    opens,
+                    uses_count,
+                    uses_index,
+                    provides_count,
+                    provides: provides.into_boxed_slice(),
+                }
+            }
+            "ModulePackages" => {
+                let (package_count, package_index) = read_u16_table(cur)?;
+                Attribute::ModulePackages {
+                    package_count,
+                    package_index,
+                }
+            }
+            "ModuleMainClass" => Attribute::ModuleMainClass { main_class_index: read::u16(cur)? },
+            "NestHost" => Attribute::NestHost { host_class_index: read::u16(cur)? },
+            "NestMembers" => {
+                let (number_of_classes, classes) = read_u16_table(cur)?;
+                Attribute::NestMembers {
+                    number_of_classes,
+                    classes,
+                }
+            }
+            "Record" => {
+                let components_count = read::u16(cur)?;
+                let mut components = Vec::with_capacity(components_count as usize);
+                for i in 0..components_count {
+                    let name_index = read::u16(cur)?;
+                    let descriptor_index = read::u16(cur)?;
+                    let (attributes_count, attributes) = read_attributes(constant_pool, cur)
+                        .map_err(|e| e.within(Context::RecordComponent(i)))?;
+                    components.push(RecordComponent {
+                        name_index,
+                        descriptor_index,
+                        attributes_count,
+                        attributes,
+                    });
+                }
+                Attribute::Record {
+                    components_count,
+                    components: components.into_boxed_slice(),
+                }
+            }
+            "PermittedSubclasses" => {
+                let (number_of_classes, classes) = read_u16_table(cur)?;
+                Attribute::PermittedSubclasses {
+                    number_of_classes,
+                    classes,
+                }
+            }
+            _ => {
                 Attribute::Unknown {
-                    name_index: name_index,
-                    length: length,
-                    info: slice,
+                    name_index,
+                    length,
+                    info: read::bytes(cur, length as usize)?,
                 }
             }
-        }
+        };
+        Ok(attribute)
     }
 }
@@ -1,6 +1,8 @@
-use std::io::{Cursor, Read};
+use std::io::Cursor;
 
-use byteorder::{BigEndian, ReadBytesExt};
+use super::super::error::{ClassFormatError, ErrorKind, Result};
+use super::super::read;
+use super::super::write;
 
 #[derive(Debug)]
 pub enum Constant {
@@ -26,58 +28,286 @@ pub enum Constant {
         name_index: u16,
         descriptor_index: u16,
     },
-    Utf8 { length: u16, value: String },
+    /// A string in modified UTF-8. `chars` holds its exact UTF-16 code units, which may include
+    /// unpaired surrogates that `value` replaces with U+FFFD, and is what gets written.
+    Utf8 {
+        length: u16,
+        value: String,
+        chars: Box<[u16]>,
+    },
+    MethodHandle {
+        reference_kind: u8,
+        reference_index: u16,
+    },
+    MethodType { descriptor_index: u16 },
+    /// A dynamically-computed constant, whose value is produced by a bootstrap method
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        name_and_type_index: u16,
+    },
+    InvokeDynamic {
+        bootstrap_method_attr_index: u16,
+        name_and_type_index: u16,
+    },
+    Module { name_index: u16 },
+    Package { name_index: u16 },
     Nothing,
 }
 
 impl Constant {
-    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Constant {
-        let tag = cur.read_u8().unwrap();
-        match tag {
-            7 => Constant::Class { name_index: cur.read_u16::<BigEndian>().unwrap() },
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<Constant> {
+        let offset = cur.position();
+        let tag = read::u8(cur)?;
+        let constant = match tag {
+            7 => Constant::Class { name_index: read::u16(cur)? },
             9 => {
                 Constant::Fieldref {
-                    class_index: cur.read_u16::<BigEndian>().unwrap(),
-                    name_and_type_index: cur.read_u16::<BigEndian>().unwrap(),
+                    class_index: read::u16(cur)?,
+                    name_and_type_index: read::u16(cur)?,
                 }
             }
             10 => {
                 Constant::Methodref {
-                    class_index: cur.read_u16::<BigEndian>().unwrap(),
-                    name_and_type_index: cur.read_u16::<BigEndian>().unwrap(),
+                    class_index: read::u16(cur)?,
+                    name_and_type_index: read::u16(cur)?,
                 }
             }
             11 => {
                 Constant::InterfaceMethodref {
-                    class_index: cur.read_u16::<BigEndian>().unwrap(),
-                    name_and_type_index: cur.read_u16::<BigEndian>().unwrap(),
+                    class_index: read::u16(cur)?,
+                    name_and_type_index: read::u16(cur)?,
                 }
             }
-            8 => Constant::String { string_index: cur.read_u16::<BigEndian>().unwrap() },
-            3 => Constant::Integer { value: cur.read_i32::<BigEndian>().unwrap() },
-            4 => Constant::Float { value: cur.read_f32::<BigEndian>().unwrap() },
-            5 => Constant::Long { value: cur.read_i64::<BigEndian>().unwrap() },
-            6 => Constant::Double { value: cur.read_f64::<BigEndian>().unwrap() },
+            8 => Constant::String { string_index: read::u16(cur)? },
+            3 => Constant::Integer { value: read::i32(cur)? },
+            4 => Constant::Float { value: read::f32(cur)? },
+            5 => Constant::Long { value: read::i64(cur)? },
+            6 => Constant::Double { value: read::f64(cur)? },
             12 => {
                 Constant::NameAndType {
-                    name_index: cur.read_u16::<BigEndian>().unwrap(),
-                    descriptor_index: cur.read_u16::<BigEndian>().unwrap(),
+                    name_index: read::u16(cur)?,
+                    descriptor_index: read::u16(cur)?,
                 }
             }
             1 => {
-                let length = cur.read_u16::<BigEndian>().unwrap();
-                let bytes = vec![0 as u8; length as usize];
-                let mut slice = bytes.into_boxed_slice();
-                cur.read_exact(&mut slice).unwrap();
-                let bytes = slice.into_vec();
+                let length = read::u16(cur)?;
+                let bytes = read::bytes(cur, length as usize)?;
+                let chars = decode_modified_utf8(&bytes)
+                    .ok_or_else(|| ClassFormatError::new(ErrorKind::BadUtf8, offset))?;
                 Constant::Utf8 {
-                    length: length,
-                    value: String::from_utf8(bytes).unwrap(),
+                    length,
+                    value: String::from_utf16_lossy(&chars),
+                    chars: chars.into_boxed_slice(),
                 }
             }
+            15 => {
+                Constant::MethodHandle {
+                    reference_kind: read::u8(cur)?,
+                    reference_index: read::u16(cur)?,
+                }
+            }
+            16 => Constant::MethodType { descriptor_index: read::u16(cur)? },
+            17 => {
+                Constant::Dynamic {
+                    bootstrap_method_attr_index: read::u16(cur)?,
+                    name_and_type_index: read::u16(cur)?,
+                }
+            }
+            18 => {
+                Constant::InvokeDynamic {
+                    bootstrap_method_attr_index: read::u16(cur)?,
+                    name_and_type_index: read::u16(cur)?,
+                }
+            }
+            19 => Constant::Module { name_index: read::u16(cur)? },
+            20 => Constant::Package { name_index: read::u16(cur)? },
+            _ => return Err(ClassFormatError::new(ErrorKind::UnknownConstantTag(tag), offset)),
+        };
+        Ok(constant)
+    }
+
+    /// Creates a `Utf8` constant holding `value`.
+    pub fn utf8(value: &str) -> Constant {
+        let chars = value.encode_utf16().collect::<Vec<u16>>();
+        Constant::Utf8 {
+            length: encode_modified_utf8(&chars).len() as u16,
+            value: String::from(value),
+            chars: chars.into_boxed_slice(),
+        }
+    }
+
+    /// Writes the constant, recomputing the length of a `Utf8` constant from its `chars`.
+    /// `Nothing` writes nothing, since it only fills the second slot of a long or double.
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            Constant::Class { name_index } => {
+                write::u8(out, 7);
+                write::u16(out, name_index);
+            }
+            Constant::Fieldref { class_index, name_and_type_index } => {
+                write::u8(out, 9);
+                write::u16(out, class_index);
+                write::u16(out, name_and_type_index);
+            }
+            Constant::Methodref { class_index, name_and_type_index } => {
+                write::u8(out, 10);
+                write::u16(out, class_index);
+                write::u16(out, name_and_type_index);
+            }
+            Constant::InterfaceMethodref { class_index, name_and_type_index } => {
+                write::u8(out, 11);
+                write::u16(out, class_index);
+                write::u16(out, name_and_type_index);
+            }
+            Constant::String { string_index } => {
+                write::u8(out, 8);
+                write::u16(out, string_index);
+            }
+            Constant::Integer { value } => {
+                write::u8(out, 3);
+                write::i32(out, value);
+            }
+            Constant::Float { value } => {
+                write::u8(out, 4);
+                write::f32(out, value);
+            }
+            Constant::Long { value } => {
+                write::u8(out, 5);
+                write::i64(out, value);
+            }
+            Constant::Double { value } => {
+                write::u8(out, 6);
+                write::f64(out, value);
+            }
+            Constant::NameAndType { name_index, descriptor_index } => {
+                write::u8(out, 12);
+                write::u16(out, name_index);
+                write::u16(out, descriptor_index);
+            }
+            Constant::Utf8 { ref chars, .. } => {
+                write::u8(out, 1);
+                let bytes = encode_modified_utf8(chars);
+                write::count_u16(out, bytes.len())?;
+                out.extend_from_slice(&bytes);
+            }
+            Constant::MethodHandle { reference_kind, reference_index } => {
+                write::u8(out, 15);
+                write::u8(out, reference_kind);
+                write::u16(out, reference_index);
+            }
+            Constant::MethodType { descriptor_index } => {
+                write::u8(out, 16);
+                write::u16(out, descriptor_index);
+            }
+            Constant::Dynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                write::u8(out, 17);
+                write::u16(out, bootstrap_method_attr_index);
+                write::u16(out, name_and_type_index);
+            }
+            Constant::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                write::u8(out, 18);
+                write::u16(out, bootstrap_method_attr_index);
+                write::u16(out, name_and_type_index);
+            }
+            Constant::Module { name_index } => {
+                write::u8(out, 19);
+                write::u16(out, name_index);
+            }
+            Constant::Package { name_index } => {
+                write::u8(out, 20);
+                write::u16(out, name_index);
+            }
+            Constant::Nothing => (),
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the kind of constant, as used in `CONSTANT_<kind>_info`.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Constant::Class { .. } => "Class",
+            Constant::Fieldref { .. } => "Fieldref",
+            Constant::Methodref { .. } => "Methodref",
+            Constant::InterfaceMethodref { .. } => "InterfaceMethodref",
+            Constant::String { .. } => "String",
+            Constant::Integer { .. } => "Integer",
+            Constant::Float { .. } => "Float",
+            Constant::Long { .. } => "Long",
+            Constant::Double { .. } => "Double",
+            Constant::NameAndType { .. } => "NameAndType",
+            Constant::Utf8 { .. } => "Utf8",
+            Constant::MethodHandle { .. } => "MethodHandle",
+            Constant::MethodType { .. } => "MethodType",
+            Constant::Dynamic { .. } => "Dynamic",
+            Constant::InvokeDynamic { .. } => "InvokeDynamic",
+            Constant::Module { .. } => "Module",
+            Constant::Package { .. } => "Package",
+            Constant::Nothing => "Nothing",
+        }
+    }
+}
+
+/// Decodes a string in the modified UTF-8 of JVMS 4.4.7 into UTF-16 code units. Unlike standard
+/// UTF-8, NUL is encoded in two bytes and supplementary characters as two encoded surrogates, so
+/// every code unit takes one to three bytes. Returns `None` if `bytes` is not valid.
+pub fn decode_modified_utf8(bytes: &[u8]) -> Option<Vec<u16>> {
+    let mut chars = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().map(|&byte| byte as u16);
+    while let Some(first) = bytes.next() {
+        let mut continuation = || match bytes.next() {
+            Some(byte) if byte & 0xC0 == 0x80 => Some(byte & 0x3F),
+            _ => None,
+        };
+        let c = match first {
+            0x01..=0x7F => first,
+            0xC0..=0xDF => (first & 0x1F) << 6 | continuation()?,
+            0xE0..=0xEF => (first & 0x0F) << 12 | continuation()? << 6 | continuation()?,
+            _ => return None,
+        };
+        chars.push(c);
+    }
+    Some(chars)
+}
+
+/// Encodes UTF-16 code units in modified UTF-8, as read by `decode_modified_utf8`.
+pub fn encode_modified_utf8(chars: &[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(chars.len());
+    for &c in chars {
+        match c {
+            0x0001..=0x007F => bytes.push(c as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                bytes.push(0xC0 | (c >> 6) as u8);
+                bytes.push(0x80 | (c & 0x3F) as u8);
+            }
             _ => {
-                panic!("Unknown constant type {}", tag);
+                bytes.push(0xE0 | (c >> 12) as u8);
+                bytes.push(0x80 | (c >> 6 & 0x3F) as u8);
+                bytes.push(0x80 | (c & 0x3F) as u8);
             }
         }
     }
+    bytes
+}
+
+/// Returns the 1-based index of the first `Utf8` constant in `constant_pool` holding `value`.
+pub fn find_utf8(constant_pool: &[Constant], value: &str) -> Option<u16> {
+    constant_pool.iter()
+        .position(|constant| match *constant {
+            Constant::Utf8 { value: ref utf8, .. } => utf8 == value,
+            _ => false,
+        })
+        .map(|index| index as u16 + 1)
+}
+
+/// Returns the constant at the 1-based `index` of `constant_pool`, checking that it is of the
+/// `expected` kind. `offset` is the position of the index in the class file.
+pub fn lookup<'a>(constant_pool: &'a [Constant],
+                  index: u16,
+                  expected: &'static str,
+                  offset: u64)
+                  -> Result<&'a Constant> {
+    match constant_pool.get((index as usize).wrapping_sub(1)) {
+        Some(constant) if constant.kind() == expected => Ok(constant),
+        _ => Err(ClassFormatError::new(ErrorKind::BadConstantIndex(index, expected), offset)),
+    }
 }
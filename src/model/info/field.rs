@@ -1,8 +1,11 @@
 use std::io::Cursor;
 
 use super::{Attribute, Constant};
-
-use byteorder::{BigEndian, ReadBytesExt};
+use super::constant;
+use super::super::descriptor;
+use super::super::error::Result;
+use super::super::read;
+use super::super::write;
 
 pub const ACC_PUBLIC: u16 = 0x0001;
 pub const ACC_PRIVATE: u16 = 0x0002;
@@ -19,28 +22,46 @@ pub struct Field {
     pub access_flags: u16,
     pub name_index: u16,
     pub descriptor_index: u16,
-    attributes_count: u16,
+    pub attributes_count: u16,
     pub attributes: Box<[Attribute]>,
 }
 
 impl Field {
-    pub fn new(constant_pool: &Box<[Constant]>, cur: &mut Cursor<Vec<u8>>) -> Field {
-        let access_flags = cur.read_u16::<BigEndian>().unwrap();
-        let name_index = cur.read_u16::<BigEndian>().unwrap();
-        let descriptor_index = cur.read_u16::<BigEndian>().unwrap();
+    pub fn new(constant_pool: &[Constant], cur: &mut Cursor<Vec<u8>>) -> Result<Field> {
+        let access_flags = read::u16(cur)?;
+        let offset = cur.position();
+        let name_index = read::u16(cur)?;
+        constant::lookup(constant_pool, name_index, "Utf8", offset)?;
+        let offset = cur.position();
+        let descriptor_index = read::u16(cur)?;
+        if let Constant::Utf8 { ref value, .. } =
+            *constant::lookup(constant_pool, descriptor_index, "Utf8", offset)? {
+            descriptor::check_field(value, offset)?;
+        }
 
-        let attributes_count = cur.read_u16::<BigEndian>().unwrap();
+        let attributes_count = read::u16(cur)?;
         let mut attributes = Vec::with_capacity(attributes_count as usize);
         for _ in 0..attributes_count {
-            attributes.push(Attribute::new(constant_pool, cur));
+            attributes.push(Attribute::new(constant_pool, cur)?);
         }
 
-        Field {
-            access_flags: access_flags,
-            name_index: name_index,
-            descriptor_index: descriptor_index,
-            attributes_count: attributes_count,
+        Ok(Field {
+            access_flags,
+            name_index,
+            descriptor_index,
+            attributes_count,
             attributes: attributes.into_boxed_slice(),
+        })
+    }
+
+    pub fn write(&self, constant_pool: &[Constant], out: &mut Vec<u8>) -> Result<()> {
+        write::u16(out, self.access_flags);
+        write::u16(out, self.name_index);
+        write::u16(out, self.descriptor_index);
+        write::count_u16(out, self.attributes.len())?;
+        for attribute in self.attributes.iter() {
+            attribute.write(constant_pool, out)?;
         }
+        Ok(())
     }
 }
@@ -1,8 +1,11 @@
 use std::io::Cursor;
 
 use super::{Attribute, Constant};
-
-use byteorder::{BigEndian, ReadBytesExt};
+use super::constant;
+use super::super::descriptor;
+use super::super::error::{ClassFormatError, ErrorKind, Result};
+use super::super::read;
+use super::super::write;
 
 pub const ACC_PUBLIC: u16 = 0x0001;
 pub const ACC_PRIVATE: u16 = 0x0002;
@@ -22,28 +25,53 @@ pub struct Method {
     pub access_flags: u16,
     pub name_index: u16,
     pub descriptor_index: u16,
-    attributes_count: u16,
+    pub attributes_count: u16,
     pub attributes: Box<[Attribute]>,
 }
 
 impl Method {
-    pub fn new(constant_pool: &Box<[Constant]>, cur: &mut Cursor<Vec<u8>>) -> Method {
-        let access_flags = cur.read_u16::<BigEndian>().unwrap();
-        let name_index = cur.read_u16::<BigEndian>().unwrap();
-        let descriptor_index = cur.read_u16::<BigEndian>().unwrap();
+    pub fn new(constant_pool: &[Constant], cur: &mut Cursor<Vec<u8>>) -> Result<Method> {
+        let start = cur.position();
+        let access_flags = read::u16(cur)?;
+        let offset = cur.position();
+        let name_index = read::u16(cur)?;
+        constant::lookup(constant_pool, name_index, "Utf8", offset)?;
+        let offset = cur.position();
+        let descriptor_index = read::u16(cur)?;
+        if let Constant::Utf8 { ref value, .. } =
+            *constant::lookup(constant_pool, descriptor_index, "Utf8", offset)? {
+            descriptor::check_method(value, offset)?;
+        }
 
-        let attributes_count = cur.read_u16::<BigEndian>().unwrap();
+        let attributes_count = read::u16(cur)?;
         let mut attributes = Vec::with_capacity(attributes_count as usize);
         for _ in 0..attributes_count {
-            attributes.push(Attribute::new(constant_pool, cur));
+            attributes.push(Attribute::new(constant_pool, cur)?);
+        }
+
+        let has_code = attributes.iter()
+            .any(|attribute| matches!(*attribute, Attribute::Code { .. }));
+        if access_flags & (ACC_NATIVE | ACC_ABSTRACT) == 0 && !has_code {
+            return Err(ClassFormatError::new(ErrorKind::MissingCode, start));
         }
 
-        Method {
-            access_flags: access_flags,
-            name_index: name_index,
-            descriptor_index: descriptor_index,
-            attributes_count: attributes_count,
+        Ok(Method {
+            access_flags,
+            name_index,
+            descriptor_index,
+            attributes_count,
             attributes: attributes.into_boxed_slice(),
+        })
+    }
+
+    pub fn write(&self, constant_pool: &[Constant], out: &mut Vec<u8>) -> Result<()> {
+        write::u16(out, self.access_flags);
+        write::u16(out, self.name_index);
+        write::u16(out, self.descriptor_index);
+        write::count_u16(out, self.attributes.len())?;
+        for attribute in self.attributes.iter() {
+            attribute.write(constant_pool, out)?;
         }
+        Ok(())
     }
 }
@@ -1,7 +1,9 @@
+pub mod annotation;
 pub mod attribute;
 pub mod constant;
 pub mod field;
 pub mod method;
+pub mod stack_map;
 
 pub use self::attribute::Attribute;
 pub use self::constant::Constant;
@@ -0,0 +1,229 @@
+use std::io::Cursor;
+
+use super::super::error::{ClassFormatError, ErrorKind, Result};
+use super::super::read;
+use super::super::write;
+
+/// The type of a local variable or operand stack entry in a stack map frame, as described in
+/// JVMS 4.7.4
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    /// An instance of the class at `cpool_index` in the constant pool
+    Object { cpool_index: u16 },
+    /// An object created by the `new` instruction at `offset`, whose constructor has not been
+    /// called
+    Uninitialized { offset: u16 },
+}
+
+impl VerificationType {
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<VerificationType> {
+        let offset = cur.position();
+        let tag = read::u8(cur)?;
+        let ty = match tag {
+            0 => VerificationType::Top,
+            1 => VerificationType::Integer,
+            2 => VerificationType::Float,
+            3 => VerificationType::Double,
+            4 => VerificationType::Long,
+            5 => VerificationType::Null,
+            6 => VerificationType::UninitializedThis,
+            7 => VerificationType::Object { cpool_index: read::u16(cur)? },
+            8 => VerificationType::Uninitialized { offset: read::u16(cur)? },
+            _ => {
+                let kind = ErrorKind::UnknownTag("verification_type_info", tag);
+                return Err(ClassFormatError::new(kind, offset));
+            }
+        };
+        Ok(ty)
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        match *self {
+            VerificationType::Top => write::u8(out, 0),
+            VerificationType::Integer => write::u8(out, 1),
+            VerificationType::Float => write::u8(out, 2),
+            VerificationType::Double => write::u8(out, 3),
+            VerificationType::Long => write::u8(out, 4),
+            VerificationType::Null => write::u8(out, 5),
+            VerificationType::UninitializedThis => write::u8(out, 6),
+            VerificationType::Object { cpool_index } => {
+                write::u8(out, 7);
+                write::u16(out, cpool_index);
+            }
+            VerificationType::Uninitialized { offset } => {
+                write::u8(out, 8);
+                write::u16(out, offset);
+            }
+        }
+    }
+
+    fn read_all(cur: &mut Cursor<Vec<u8>>, count: usize) -> Result<Box<[VerificationType]>> {
+        let mut types = Vec::with_capacity(count);
+        for _ in 0..count {
+            types.push(VerificationType::new(cur)?);
+        }
+        Ok(types.into_boxed_slice())
+    }
+}
+
+/// An entry of a `StackMapTable` attribute, as described in JVMS 4.7.4. Each frame applies at
+/// the bytecode offset `offset_delta + 1` past the previous frame, or at `offset_delta` for the
+/// first frame.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StackMapFrame {
+    /// The same locals as the previous frame and an empty stack. The frame type is the offset
+    /// delta.
+    Same { frame_type: u8 },
+    /// The same locals as the previous frame and one stack item. The frame type is 64 more than
+    /// the offset delta.
+    SameLocals1StackItem {
+        frame_type: u8,
+        stack: VerificationType,
+    },
+    SameLocals1StackItemExtended {
+        offset_delta: u16,
+        stack: VerificationType,
+    },
+    /// The locals of the previous frame without the last `251 - frame_type`, and an empty stack
+    Chop { frame_type: u8, offset_delta: u16 },
+    SameExtended { offset_delta: u16 },
+    /// The locals of the previous frame followed by `frame_type - 251` more, and an empty stack
+    Append {
+        frame_type: u8,
+        offset_delta: u16,
+        locals: Box<[VerificationType]>,
+    },
+    Full {
+        offset_delta: u16,
+        number_of_locals: u16,
+        locals: Box<[VerificationType]>,
+        number_of_stack_items: u16,
+        stack: Box<[VerificationType]>,
+    },
+}
+
+impl StackMapFrame {
+    pub fn new(cur: &mut Cursor<Vec<u8>>) -> Result<StackMapFrame> {
+        let offset = cur.position();
+        let frame_type = read::u8(cur)?;
+        let frame = match frame_type {
+            0..=63 => StackMapFrame::Same { frame_type },
+            64..=127 => {
+                StackMapFrame::SameLocals1StackItem {
+                    frame_type,
+                    stack: VerificationType::new(cur)?,
+                }
+            }
+            247 => {
+                StackMapFrame::SameLocals1StackItemExtended {
+                    offset_delta: read::u16(cur)?,
+                    stack: VerificationType::new(cur)?,
+                }
+            }
+            248..=250 => {
+                StackMapFrame::Chop {
+                    frame_type,
+                    offset_delta: read::u16(cur)?,
+                }
+            }
+            251 => StackMapFrame::SameExtended { offset_delta: read::u16(cur)? },
+            252..=254 => {
+                let offset_delta = read::u16(cur)?;
+                StackMapFrame::Append {
+                    frame_type,
+                    offset_delta,
+                    locals: VerificationType::read_all(cur, frame_type as usize - 251)?,
+                }
+            }
+            255 => {
+                let offset_delta = read::u16(cur)?;
+                let number_of_locals = read::u16(cur)?;
+                let locals = VerificationType::read_all(cur, number_of_locals as usize)?;
+                let number_of_stack_items = read::u16(cur)?;
+                let stack = VerificationType::read_all(cur, number_of_stack_items as usize)?;
+                StackMapFrame::Full {
+                    offset_delta,
+                    number_of_locals,
+                    locals,
+                    number_of_stack_items,
+                    stack,
+                }
+            }
+            _ => {
+                let kind = ErrorKind::UnknownTag("stack_map_frame", frame_type);
+                return Err(ClassFormatError::new(kind, offset));
+            }
+        };
+        Ok(frame)
+    }
+
+    /// Writes the frame. The frame type of an `Append` frame and the counts of a `Full` frame are
+    /// recomputed from their types.
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<()> {
+        match *self {
+            StackMapFrame::Same { frame_type } => write::u8(out, frame_type),
+            StackMapFrame::SameLocals1StackItem { frame_type, ref stack } => {
+                write::u8(out, frame_type);
+                stack.write(out);
+            }
+            StackMapFrame::SameLocals1StackItemExtended { offset_delta, ref stack } => {
+                write::u8(out, 247);
+                write::u16(out, offset_delta);
+                stack.write(out);
+            }
+            StackMapFrame::Chop { frame_type, offset_delta } => {
+                write::u8(out, frame_type);
+                write::u16(out, offset_delta);
+            }
+            StackMapFrame::SameExtended { offset_delta } => {
+                write::u8(out, 251);
+                write::u16(out, offset_delta);
+            }
+            StackMapFrame::Append { offset_delta, ref locals, .. } => {
+                if locals.is_empty() || locals.len() > 3 {
+                    let kind = ErrorKind::TooLong(locals.len());
+                    return Err(ClassFormatError::new(kind, out.len() as u64));
+                }
+                write::u8(out, 251 + locals.len() as u8);
+                write::u16(out, offset_delta);
+                for local in locals.iter() {
+                    local.write(out);
+                }
+            }
+            StackMapFrame::Full { offset_delta, ref locals, ref stack, .. } => {
+                write::u8(out, 255);
+                write::u16(out, offset_delta);
+                write::count_u16(out, locals.len())?;
+                for local in locals.iter() {
+                    local.write(out);
+                }
+                write::count_u16(out, stack.len())?;
+                for item in stack.iter() {
+                    item.write(out);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the difference between the offset of this frame and the offset of the previous
+    /// frame, minus one.
+    pub fn offset_delta(&self) -> u16 {
+        match *self {
+            StackMapFrame::Same { frame_type } => frame_type as u16,
+            StackMapFrame::SameLocals1StackItem { frame_type, .. } => frame_type as u16 - 64,
+            StackMapFrame::SameLocals1StackItemExtended { offset_delta, .. } |
+            StackMapFrame::Chop { offset_delta, .. } |
+            StackMapFrame::SameExtended { offset_delta } |
+            StackMapFrame::Append { offset_delta, .. } |
+            StackMapFrame::Full { offset_delta, .. } => offset_delta,
+        }
+    }
+}
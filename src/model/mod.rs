@@ -1,4 +1,9 @@
 pub mod class;
+pub mod descriptor;
+pub mod error;
 pub mod info;
+mod read;
+mod write;
 
 pub use self::class::Class;
+pub use self::error::ClassFormatError;
@@ -0,0 +1,55 @@
+//! Reads the big-endian items of a class file, reporting the end of the file as a
+//! `ClassFormatError`.
+
+use std::io::{self, Cursor, Read};
+
+use super::error::{ClassFormatError, ErrorKind, Result};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+fn read<T, F>(cur: &mut Cursor<Vec<u8>>, f: F) -> Result<T>
+    where F: FnOnce(&mut Cursor<Vec<u8>>) -> io::Result<T>
+{
+    let position = cur.position();
+    f(cur).map_err(|_| ClassFormatError::new(ErrorKind::Truncated, position))
+}
+
+pub fn u8(cur: &mut Cursor<Vec<u8>>) -> Result<u8> {
+    read(cur, |cur| cur.read_u8())
+}
+
+pub fn u16(cur: &mut Cursor<Vec<u8>>) -> Result<u16> {
+    read(cur, |cur| cur.read_u16::<BigEndian>())
+}
+
+pub fn u32(cur: &mut Cursor<Vec<u8>>) -> Result<u32> {
+    read(cur, |cur| cur.read_u32::<BigEndian>())
+}
+
+pub fn i32(cur: &mut Cursor<Vec<u8>>) -> Result<i32> {
+    read(cur, |cur| cur.read_i32::<BigEndian>())
+}
+
+pub fn i64(cur: &mut Cursor<Vec<u8>>) -> Result<i64> {
+    read(cur, |cur| cur.read_i64::<BigEndian>())
+}
+
+pub fn f32(cur: &mut Cursor<Vec<u8>>) -> Result<f32> {
+    read(cur, |cur| cur.read_f32::<BigEndian>())
+}
+
+pub fn f64(cur: &mut Cursor<Vec<u8>>) -> Result<f64> {
+    read(cur, |cur| cur.read_f64::<BigEndian>())
+}
+
+/// Reads `length` bytes. The length is checked against the remaining input first, so that a
+/// corrupt length cannot cause a huge allocation.
+pub fn bytes(cur: &mut Cursor<Vec<u8>>, length: usize) -> Result<Box<[u8]>> {
+    let remaining = (cur.get_ref().len() as u64).saturating_sub(cur.position());
+    if length as u64 > remaining {
+        return Err(ClassFormatError::new(ErrorKind::Truncated, cur.position()));
+    }
+    let mut bytes = vec![0u8; length].into_boxed_slice();
+    read(cur, |cur| cur.read_exact(&mut bytes))?;
+    Ok(bytes)
+}
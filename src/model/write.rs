@@ -0,0 +1,78 @@
+//! Writes the big-endian items of a class file. Table lengths that do not fit in their count
+//! item are reported as a `ClassFormatError`.
+
+use super::error::{ClassFormatError, ErrorKind, Result};
+
+pub fn u8(out: &mut Vec<u8>, value: u8) {
+    out.push(value);
+}
+
+pub fn u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn i32(out: &mut Vec<u8>, value: i32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn i64(out: &mut Vec<u8>, value: i64) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn f32(out: &mut Vec<u8>, value: f32) {
+    out.extend_from_slice(&value.to_bits().to_be_bytes());
+}
+
+pub fn f64(out: &mut Vec<u8>, value: f64) {
+    out.extend_from_slice(&value.to_bits().to_be_bytes());
+}
+
+fn too_long(out: &[u8], length: usize) -> ClassFormatError {
+    ClassFormatError::new(ErrorKind::TooLong(length), out.len() as u64)
+}
+
+/// Writes the length of a table as a `u1` count.
+pub fn count_u8(out: &mut Vec<u8>, length: usize) -> Result<()> {
+    if length > u8::MAX as usize {
+        return Err(too_long(out, length));
+    }
+    u8(out, length as u8);
+    Ok(())
+}
+
+/// Writes the length of a table as a `u2` count.
+pub fn count_u16(out: &mut Vec<u8>, length: usize) -> Result<()> {
+    if length > u16::MAX as usize {
+        return Err(too_long(out, length));
+    }
+    u16(out, length as u16);
+    Ok(())
+}
+
+/// Writes a `u2` count followed by the `u2` items of a table, such as constant pool indices.
+pub fn u16_table(out: &mut Vec<u8>, items: &[u16]) -> Result<()> {
+    count_u16(out, items.len())?;
+    for &item in items {
+        u16(out, item);
+    }
+    Ok(())
+}
+
+/// Writes a `u4` length followed by the bytes `f` writes, such as the `info` of an attribute.
+pub fn with_u32_length<F>(out: &mut Vec<u8>, f: F) -> Result<()>
+    where F: FnOnce(&mut Vec<u8>) -> Result<()>
+{
+    let start = out.len();
+    u32(out, 0);
+    f(out)?;
+    let length = out.len() - start - 4;
+    if length > u32::MAX as usize {
+        return Err(ClassFormatError::new(ErrorKind::TooLong(length), start as u64));
+    }
+    out[start..start + 4].copy_from_slice(&(length as u32).to_be_bytes());
+    Ok(())
+}
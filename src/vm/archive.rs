@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use miniz_oxide::inflate;
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+const LOCAL_FILE_HEADER_SIZE: u64 = 30;
+const MAX_COMMENT_SIZE: usize = 0xffff;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+pub const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// An entry in the central directory of an archive
+#[derive(Debug)]
+struct Entry {
+    method: u16,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    local_header_offset: u32,
+}
+
+/// A zip archive, such as a JAR file. The central directory is read when the archive is opened,
+/// and entries are read and inflated on demand.
+#[derive(Debug)]
+pub struct Archive {
+    path: PathBuf,
+    data: Vec<u8>,
+    entries: HashMap<String, Entry>,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> io::Result<Archive> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        let entries = read_central_directory(&data)?;
+        Ok(Archive {
+            path: path.to_path_buf(),
+            data,
+            entries,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Reads the uncompressed contents of the entry called `name`.
+    pub fn read(&self, name: &str) -> io::Result<Vec<u8>> {
+        let entry = self.entries.get(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound,
+                           format!("{} is not in {}", name, self.path.display()))
+        })?;
+
+        let mut cur = Cursor::new(&self.data[..]);
+        cur.seek(SeekFrom::Start(entry.local_header_offset as u64))?;
+        if cur.read_u32::<LittleEndian>()? != LOCAL_FILE_HEADER_SIGNATURE {
+            return Err(invalid_data(format!("Bad local file header for {}", name)));
+        }
+        cur.seek(SeekFrom::Current(22))?;
+        let name_length = cur.read_u16::<LittleEndian>()? as u64;
+        let extra_length = cur.read_u16::<LittleEndian>()? as u64;
+
+        let start = entry.local_header_offset as u64 + LOCAL_FILE_HEADER_SIZE + name_length +
+                    extra_length;
+        let end = start + entry.compressed_size as u64;
+        if end > self.data.len() as u64 {
+            return Err(invalid_data(format!("Truncated entry {}", name)));
+        }
+        let compressed = &self.data[start as usize..end as usize];
+
+        let contents = match entry.method {
+            METHOD_STORED => compressed.to_vec(),
+            METHOD_DEFLATED => {
+                inflate::decompress_to_vec_with_limit(compressed,
+                                                      entry.uncompressed_size as usize)
+                    .map_err(|e| invalid_data(format!("Could not inflate {}: {}", name, e)))?
+            }
+            method => {
+                return Err(invalid_data(format!("Unsupported compression method {} for {}",
+                                                method,
+                                                name)))
+            }
+        };
+
+        if contents.len() != entry.uncompressed_size as usize || crc32(&contents) != entry.crc32 {
+            return Err(invalid_data(format!("Corrupt entry {}", name)));
+        }
+        Ok(contents)
+    }
+
+    /// Reads the manifest of a JAR file, if the archive has one.
+    pub fn manifest(&self) -> io::Result<Option<Manifest>> {
+        if !self.contains(MANIFEST_NAME) {
+            return Ok(None);
+        }
+        let bytes = self.read(MANIFEST_NAME)?;
+        let text = String::from_utf8(bytes)
+            .map_err(|_| invalid_data(String::from("Manifest is not valid UTF-8")))?;
+        Ok(Some(Manifest::new(&text)))
+    }
+}
+
+fn read_central_directory(data: &[u8]) -> io::Result<HashMap<String, Entry>> {
+    if data.len() < END_OF_CENTRAL_DIRECTORY_SIZE {
+        return Err(invalid_data(String::from("Not a zip archive")));
+    }
+
+    // The end of central directory record is followed by a variable length comment, so search
+    // backwards for its signature
+    let last = data.len() - END_OF_CENTRAL_DIRECTORY_SIZE;
+    let first = last.saturating_sub(MAX_COMMENT_SIZE);
+    let eocd = (first..last + 1)
+        .rev()
+        .find(|&offset| {
+            let mut signature = &data[offset..offset + 4];
+            signature.read_u32::<LittleEndian>().unwrap() == END_OF_CENTRAL_DIRECTORY_SIGNATURE
+        })
+        .ok_or_else(|| invalid_data(String::from("Could not find the central directory")))?;
+
+    let mut cur = Cursor::new(data);
+    cur.seek(SeekFrom::Start(eocd as u64 + 10))?;
+    let entry_count = cur.read_u16::<LittleEndian>()?;
+    let _directory_size = cur.read_u32::<LittleEndian>()?;
+    let directory_offset = cur.read_u32::<LittleEndian>()?;
+    if entry_count == 0xffff || directory_offset == 0xffffffff {
+        return Err(invalid_data(String::from("ZIP64 archives are not supported")));
+    }
+
+    let mut entries = HashMap::with_capacity(entry_count as usize);
+    cur.seek(SeekFrom::Start(directory_offset as u64))?;
+    for _ in 0..entry_count {
+        if cur.read_u32::<LittleEndian>()? != CENTRAL_DIRECTORY_HEADER_SIGNATURE {
+            return Err(invalid_data(String::from("Bad central directory header")));
+        }
+        let _version_made_by = cur.read_u16::<LittleEndian>()?;
+        let _version_needed = cur.read_u16::<LittleEndian>()?;
+        let _flags = cur.read_u16::<LittleEndian>()?;
+        let method = cur.read_u16::<LittleEndian>()?;
+        let _modification_time = cur.read_u16::<LittleEndian>()?;
+        let _modification_date = cur.read_u16::<LittleEndian>()?;
+        let crc32 = cur.read_u32::<LittleEndian>()?;
+        let compressed_size = cur.read_u32::<LittleEndian>()?;
+        let uncompressed_size = cur.read_u32::<LittleEndian>()?;
+        let name_length = cur.read_u16::<LittleEndian>()?;
+        let extra_length = cur.read_u16::<LittleEndian>()?;
+        let comment_length = cur.read_u16::<LittleEndian>()?;
+        let _disk_number = cur.read_u16::<LittleEndian>()?;
+        let _internal_attributes = cur.read_u16::<LittleEndian>()?;
+        let _external_attributes = cur.read_u32::<LittleEndian>()?;
+        let local_header_offset = cur.read_u32::<LittleEndian>()?;
+
+        let mut name = vec![0; name_length as usize];
+        cur.read_exact(&mut name)?;
+        let name = String::from_utf8_lossy(&name).into_owned();
+        cur.seek(SeekFrom::Current(extra_length as i64 + comment_length as i64))?;
+
+        entries.insert(name,
+                       Entry {
+                           method,
+                           crc32,
+                           compressed_size,
+                           uncompressed_size,
+                           local_header_offset,
+                       });
+    }
+    Ok(entries)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
+
+/// The main section of a JAR manifest
+#[derive(Debug)]
+pub struct Manifest {
+    attributes: Vec<(String, String)>,
+}
+
+impl Manifest {
+    pub fn new(text: &str) -> Manifest {
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                // The main section ends at the first blank line
+                break;
+            }
+            if let Some(continuation) = line.strip_prefix(' ') {
+                // Continuation of the previous value
+                if let Some(&mut (_, ref mut value)) = attributes.last_mut() {
+                    value.push_str(continuation);
+                }
+                continue;
+            }
+            if let Some(separator) = line.find(": ") {
+                let name = String::from(&line[..separator]);
+                let value = String::from(&line[separator + 2..]);
+                attributes.push((name, value));
+            }
+        }
+        Manifest { attributes }
+    }
+
+    /// Returns the value of the main attribute called `name`, which is matched case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
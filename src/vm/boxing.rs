@@ -0,0 +1,186 @@
+use super::class_loader::{ClassLoader, Loader};
+use super::exception;
+use super::sig;
+use super::symref;
+use super::value::{Scalar, Value};
+
+use std::cell::RefCell;
+use std::num::Wrapping;
+use std::rc::Rc;
+
+/// Returns the name of the wrapper class of a primitive type, such as `java/lang/Integer` for
+/// `int`, or `None` for reference types.
+fn wrapper_name(ty: &sig::Type) -> Option<&'static str> {
+    match *ty {
+        sig::Type::Char => Some("java/lang/Character"),
+        sig::Type::Byte => Some("java/lang/Byte"),
+        sig::Type::Short => Some("java/lang/Short"),
+        sig::Type::Int => Some("java/lang/Integer"),
+        sig::Type::Long => Some("java/lang/Long"),
+        sig::Type::Float => Some("java/lang/Float"),
+        sig::Type::Double => Some("java/lang/Double"),
+        sig::Type::Boolean => Some("java/lang/Boolean"),
+        sig::Type::Reference(_) => None,
+    }
+}
+
+/// Returns the primitive type wrapped by instances of `class_sig`, or `None` if it is not a
+/// wrapper class.
+fn wrapped_type(class_sig: &sig::Class) -> Option<sig::Type> {
+    let primitives = [sig::Type::Char,
+                      sig::Type::Byte,
+                      sig::Type::Short,
+                      sig::Type::Int,
+                      sig::Type::Long,
+                      sig::Type::Float,
+                      sig::Type::Double,
+                      sig::Type::Boolean];
+    match *class_sig {
+        sig::Class::Scalar(ref name) => {
+            primitives.iter().find(|ty| wrapper_name(ty) == Some(name.as_str())).cloned()
+        }
+        sig::Class::Array(_) => None,
+    }
+}
+
+/// The field holding the primitive value of an instance of a wrapper class
+fn value_field(ty: &sig::Type) -> symref::Field {
+    symref::Field {
+        class: symref::Class { sig: sig::Class::Scalar(String::from(wrapper_name(ty).unwrap())) },
+        sig: sig::Field::new(String::from("value"), ty.clone()),
+    }
+}
+
+/// Returns true if values of the primitive type `from` can be converted to `to` by a widening
+/// primitive conversion, as described in JLS 5.1.2. `int` also stands for the types that are
+/// represented as ints.
+fn is_widening(from: &sig::Type, to: &sig::Type) -> bool {
+    match (from, to) {
+        _ if from == to => true,
+        (&sig::Type::Byte, &sig::Type::Short) |
+        (&sig::Type::Byte, &sig::Type::Int) |
+        (&sig::Type::Short, &sig::Type::Int) |
+        (&sig::Type::Char, &sig::Type::Int) => true,
+        (&sig::Type::Byte, _) | (&sig::Type::Short, _) | (&sig::Type::Char, _) => {
+            is_widening(&sig::Type::Int, to)
+        }
+        (&sig::Type::Int, &sig::Type::Long) |
+        (&sig::Type::Int, &sig::Type::Float) |
+        (&sig::Type::Int, &sig::Type::Double) |
+        (&sig::Type::Long, &sig::Type::Float) |
+        (&sig::Type::Long, &sig::Type::Double) |
+        (&sig::Type::Float, &sig::Type::Double) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if a value of type `from` can be adapted to type `to` by `convert`, following the
+/// rules `LambdaMetafactory` applies to the arguments and results of lambdas.
+///
+/// Conversions between reference types are always allowed, since lambdas check them with `cast`
+/// when they are invoked.
+pub fn is_convertible(from: &sig::Type, to: &sig::Type) -> bool {
+    match (from, to) {
+        (_, &sig::Type::Reference(_)) => true,
+        (sig::Type::Reference(class_sig), _) => {
+            match wrapped_type(class_sig) {
+                Some(ty) => is_widening(&ty, to),
+                // The object must be an instance of a wrapper class, which is checked when the
+                // value is converted
+                None => true,
+            }
+        }
+        _ => is_widening(from, to),
+    }
+}
+
+/// Converts `value`, of type `from`, to type `to` by boxing, unboxing or widening it. The types
+/// must have been checked with `is_convertible`.
+pub fn convert(class_loader: &mut ClassLoader,
+               value: Value,
+               from: &sig::Type,
+               to: &sig::Type)
+               -> Result<Value, Rc<RefCell<Scalar>>> {
+    match (from, to) {
+        (&sig::Type::Reference(_), &sig::Type::Reference(_)) => Ok(value),
+        (_, &sig::Type::Reference(_)) => Ok(box_value(class_loader, from, value)),
+        (&sig::Type::Reference(_), _) => unbox(class_loader, value, to),
+        _ => Ok(widen(value, to)),
+    }
+}
+
+/// Checks that `value` is null or an instance of `ty`, as `checkcast` does, resolving the class
+/// of `ty` with `loader`. Values of primitive types are returned unchanged.
+pub fn cast(class_loader: &mut ClassLoader,
+            loader: &Loader,
+            value: Value,
+            ty: &sig::Type)
+            -> Result<Value, Rc<RefCell<Scalar>>> {
+    let class_sig = match *ty {
+        sig::Type::Reference(ref class_sig) => class_sig,
+        _ => return Ok(value),
+    };
+    let object_class = match value {
+        Value::Reference(ref object) => object.borrow().get_class().clone(),
+        Value::ArrayReference(ref array) => array.borrow().get_class().clone(),
+        _ => return Ok(value),
+    };
+    let class = class_loader.resolve_class_in(loader, class_sig)?;
+    if object_class.is_assignable_to(&class) {
+        Ok(value)
+    } else {
+        let message = format!("class {} cannot be cast to class {}",
+                              object_class.symref.sig,
+                              class.symref.sig);
+        Err(exception::new(class_loader, "java/lang/ClassCastException", Some(&message)))
+    }
+}
+
+/// Boxes the primitive `value` of type `ty` in a new instance of its wrapper class.
+///
+/// As with strings, the value field is filled in directly rather than through a constructor.
+fn box_value(class_loader: &mut ClassLoader, ty: &sig::Type, value: Value) -> Value {
+    let field = value_field(ty);
+    let class = class_loader.resolve_runtime_class(&field.class.sig);
+    let mut wrapper = Scalar::new(class);
+    wrapper.put_field(&field, value);
+    Value::Reference(Rc::new(RefCell::new(wrapper)))
+}
+
+/// Unboxes the wrapper object `value`, widening its primitive value to `ty`.
+fn unbox(class_loader: &mut ClassLoader,
+         value: Value,
+         ty: &sig::Type)
+         -> Result<Value, Rc<RefCell<Scalar>>> {
+    let object = match value {
+        Value::Reference(object) => object,
+        Value::NullReference => {
+            return Err(exception::new(class_loader, "java/lang/NullPointerException", None))
+        }
+        v => panic!("Expected a reference to unbox, found {:?}", v),
+    };
+    let class_sig = object.borrow().get_class().symref.sig.clone();
+    match wrapped_type(&class_sig) {
+        Some(ref wrapped) if is_widening(wrapped, ty) => {
+            let primitive = object.borrow().get_field(&value_field(wrapped));
+            Ok(widen(primitive, ty))
+        }
+        _ => {
+            let message = format!("{} cannot be converted to {}", class_sig, ty);
+            Err(exception::new(class_loader, "java/lang/ClassCastException", Some(&message)))
+        }
+    }
+}
+
+/// Widens the primitive `value` to `ty`.
+fn widen(value: Value, ty: &sig::Type) -> Value {
+    match (value, ty) {
+        (Value::Int(value), &sig::Type::Long) => Value::Long(Wrapping(value.0 as i64)),
+        (Value::Int(value), &sig::Type::Float) => Value::Float(value.0 as f32),
+        (Value::Int(value), &sig::Type::Double) => Value::Double(value.0 as f64),
+        (Value::Long(value), &sig::Type::Float) => Value::Float(value.0 as f32),
+        (Value::Long(value), &sig::Type::Double) => Value::Double(value.0 as f64),
+        (Value::Float(value), &sig::Type::Double) => Value::Double(value as f64),
+        (value, _) => value,
+    }
+}
@@ -0,0 +1,167 @@
+use super::class_loader::ClassLoader;
+use super::exception;
+use super::invoke;
+use super::super::model;
+use super::sig;
+use super::string;
+use super::symref;
+use super::value::{Scalar, Value};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A native method implemented by the VM itself rather than by a native library, for methods that
+/// need access to the class loader, such as those of `java.lang.Class` and
+/// `java.lang.ClassLoader`. Instance methods receive their receiver as the first argument.
+pub type Builtin = fn(&mut ClassLoader, Vec<Value>) -> Result<Option<Value>, Rc<RefCell<Scalar>>>;
+
+/// Returns the builtin implementing the native method `symref`, if there is one.
+pub fn find(symref: &symref::Method) -> Option<Builtin> {
+    let class_name = match symref.class.sig {
+        sig::Class::Scalar(ref name) => name.as_str(),
+        sig::Class::Array(_) => return None,
+    };
+    let builtin: Builtin = match (class_name, symref.sig.name.as_str()) {
+        ("java/lang/Object", "getClass") => object_get_class,
+        ("java/lang/Class", "getClassLoader") => class_get_class_loader,
+        ("java/lang/Class", "getName") => class_get_name,
+        ("java/lang/Class", "newInstance") => class_new_instance,
+        ("java/lang/ClassLoader", "defineClass1") => class_loader_define_class,
+        ("java/lang/ClassLoader", "findLoadedClass0") => class_loader_find_loaded_class,
+        ("java/lang/ClassLoader", "findBootstrapClassOrNull") => {
+            class_loader_find_bootstrap_class
+        }
+        _ => return None,
+    };
+    Some(builtin)
+}
+
+fn force_reference(value: &Value) -> &Rc<RefCell<Scalar>> {
+    match *value {
+        Value::Reference(ref object) => object,
+        ref v => panic!("Expected a reference, found {:?}", v),
+    }
+}
+
+/// Converts a binary name such as `java.lang.Object` to the internal form used by the VM.
+fn internal_name(class_loader: &mut ClassLoader,
+                 name: &Value)
+                 -> Result<String, Rc<RefCell<Scalar>>> {
+    match *name {
+        Value::Reference(ref name) => Ok(string::to_rust_string(&name.borrow()).replace('.', "/")),
+        _ => Err(exception::new(class_loader, "java/lang/NullPointerException", None)),
+    }
+}
+
+fn object_get_class(class_loader: &mut ClassLoader,
+                    args: Vec<Value>)
+                    -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class = match args[0] {
+        Value::Reference(ref object) => object.borrow().get_class().clone(),
+        Value::ArrayReference(ref array) => array.borrow().get_class().clone(),
+        ref v => panic!("getClass called on {:?}", v),
+    };
+    Ok(Some(Value::Reference(class_loader.get_mirror(&class))))
+}
+
+fn class_get_class_loader(class_loader: &mut ClassLoader,
+                          args: Vec<Value>)
+                          -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class = class_loader.get_mirror_class(force_reference(&args[0]));
+    match *class.get_defining_loader() {
+        Some(ref loader) => Ok(Some(Value::Reference(loader.clone()))),
+        None => Ok(Some(Value::NullReference)),
+    }
+}
+
+fn class_get_name(class_loader: &mut ClassLoader,
+                  args: Vec<Value>)
+                  -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class = class_loader.get_mirror_class(force_reference(&args[0]));
+    let name = format!("{}", class.symref.sig).replace('/', ".");
+    Ok(Some(string::new(class_loader, &name)))
+}
+
+fn class_new_instance(class_loader: &mut ClassLoader,
+                      args: Vec<Value>)
+                      -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class = class_loader.get_mirror_class(force_reference(&args[0]));
+    let is_scalar = match class.symref.sig {
+        sig::Class::Scalar(_) => true,
+        sig::Class::Array(_) => false,
+    };
+    if !is_scalar || class.is_interface() ||
+       class.access_flags & model::class::ACC_ABSTRACT != 0 {
+        let message = format!("{}", class.symref.sig).replace('/', ".");
+        return Err(exception::new(class_loader,
+                                  "java/lang/InstantiationException",
+                                  Some(&message)));
+    }
+    class.initialize(class_loader)?;
+    let object = Rc::new(RefCell::new(Scalar::new(class.clone())));
+    let constructor_symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method::new(String::from("<init>"), String::from("()V")),
+    };
+    invoke::invoke_special(class_loader,
+                           class.get_defining_loader(),
+                           &constructor_symref,
+                           vec![Value::Reference(object.clone())])?;
+    Ok(Some(Value::Reference(object)))
+}
+
+fn class_loader_define_class(class_loader: &mut ClassLoader,
+                             args: Vec<Value>)
+                             -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let loader = force_reference(&args[0]).clone();
+    let (offset, length) = match (&args[3], &args[4]) {
+        (Value::Int(offset), Value::Int(length)) => (offset.0, length.0),
+        (offset, length) => panic!("Expected int offset and length, found {:?} and {:?}",
+                                   offset,
+                                   length),
+    };
+    let bytes = match args[2] {
+        Value::ArrayReference(ref array) => {
+            let array = array.borrow();
+            if offset < 0 || length < 0 || offset > array.len() - length {
+                return Err(exception::new(class_loader,
+                                          "java/lang/IndexOutOfBoundsException",
+                                          None));
+            }
+            (offset..offset + length)
+                .map(|i| match array.get(i as usize) {
+                    Value::Int(byte) => byte.0 as u8,
+                    v => panic!("Expected a byte, found {:?}", v),
+                })
+                .collect::<Vec<u8>>()
+        }
+        _ => return Err(exception::new(class_loader, "java/lang/NullPointerException", None)),
+    };
+    let name = match args[1] {
+        Value::NullReference => None,
+        ref name => Some(internal_name(class_loader, name)?),
+    };
+    let class = class_loader.define_class_in(&Some(loader), name.as_deref(), bytes)?;
+    Ok(Some(Value::Reference(class_loader.get_mirror(&class))))
+}
+
+fn class_loader_find_loaded_class(class_loader: &mut ClassLoader,
+                                  args: Vec<Value>)
+                                  -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let loader = Some(force_reference(&args[0]).clone());
+    let name = internal_name(class_loader, &args[1])?;
+    match class_loader.find_loaded_class(&loader, &sig::Class::Scalar(name)) {
+        Some(class) => Ok(Some(Value::Reference(class_loader.get_mirror(&class)))),
+        None => Ok(Some(Value::NullReference)),
+    }
+}
+
+fn class_loader_find_bootstrap_class(class_loader: &mut ClassLoader,
+                                     args: Vec<Value>)
+                                     -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let name = internal_name(class_loader, &args[0])?;
+    match class_loader.find_bootstrap_class(&sig::Class::Scalar(name))? {
+        Some(class) => Ok(Some(Value::Reference(class_loader.get_mirror(&class)))),
+        None => Ok(Some(Value::NullReference)),
+    }
+}
@@ -0,0 +1,580 @@
+use super::boxing;
+use super::class::{self, Class};
+use super::class_loader::ClassLoader;
+use super::constant_pool::{ConstantPool, ConstantPoolEntry};
+use super::exception;
+use super::invoke::{self, MethodHandle};
+use super::sig;
+use super::string;
+use super::symref;
+use super::value::{Scalar, Value};
+use super::super::model::info::attribute::BootstrapMethod;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Flags passed to `LambdaMetafactory.altMetafactory`
+const FLAG_SERIALIZABLE: i32 = 1 << 0;
+const FLAG_MARKERS: i32 = 1 << 1;
+const FLAG_BRIDGES: i32 = 1 << 2;
+
+/// The tags `StringConcatFactory.makeConcatWithConstants` recipes use to refer to arguments and
+/// constants
+const TAG_ARG: char = '\u{1}';
+const TAG_CONST: char = '\u{2}';
+
+/// A linked `invokedynamic` call site.
+///
+/// Rather than running bootstrap methods in Java, the VM recognizes the bootstrap methods javac
+/// emits and links their call sites natively.
+#[derive(Debug)]
+pub enum CallSite {
+    /// Creates an instance of a lambda class, storing the call site's arguments in its fields
+    Lambda {
+        class: Rc<Class>,
+        captured: Vec<sig::Field>,
+    },
+    /// Concatenates the call site's arguments and constants into a new `String`
+    StringConcat(Vec<RecipeElement>),
+}
+
+#[derive(Debug)]
+pub enum RecipeElement {
+    Argument(sig::Type),
+    Constant(String),
+}
+
+/// Creates the `BootstrapMethodError` thrown when the arguments of a bootstrap method are not
+/// what the bootstrap method expects.
+fn bad_bootstrap_argument(class_loader: &mut ClassLoader, message: &str) -> Rc<RefCell<Scalar>> {
+    exception::new(class_loader, "java/lang/BootstrapMethodError", Some(message))
+}
+
+/// Returns the static argument at `index` of a bootstrap method.
+fn bootstrap_argument(class_loader: &mut ClassLoader,
+                      args: &[u16],
+                      index: usize)
+                      -> Result<u16, Rc<RefCell<Scalar>>> {
+    args.get(index).cloned().ok_or_else(|| {
+        let message = format!("missing bootstrap argument {}", index);
+        bad_bootstrap_argument(class_loader, &message)
+    })
+}
+
+fn force_bootstrap_symref<'a>(class_loader: &mut ClassLoader,
+                              constant_pool: &'a ConstantPool,
+                              bootstrap_method: &BootstrapMethod)
+                              -> Result<&'a symref::Method, Rc<RefCell<Scalar>>> {
+    match constant_pool[bootstrap_method.bootstrap_method_ref] {
+        Some(ConstantPoolEntry::MethodHandle(MethodHandle::InvokeStatic(ref symref))) => Ok(symref),
+        ref entry => {
+            let message = format!("bootstrap method must be invokestatic, found {:?}", entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+fn force_method_handle(class_loader: &mut ClassLoader,
+                       constant_pool: &ConstantPool,
+                       index: u16)
+                       -> Result<MethodHandle, Rc<RefCell<Scalar>>> {
+    match constant_pool[index] {
+        Some(ConstantPoolEntry::MethodHandle(ref handle)) => Ok(handle.clone()),
+        ref entry => {
+            let message = format!("item at index {} must be a MethodHandle, found {:?}",
+                                  index,
+                                  entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+fn force_class_ref(class_loader: &mut ClassLoader,
+                   constant_pool: &ConstantPool,
+                   index: u16)
+                   -> Result<sig::Class, Rc<RefCell<Scalar>>> {
+    match constant_pool[index] {
+        Some(ConstantPoolEntry::ClassRef(ref symref)) => Ok(symref.sig.clone()),
+        ref entry => {
+            let message = format!("item at index {} must be a ClassRef, found {:?}", index, entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+fn force_method_type(class_loader: &mut ClassLoader,
+                     constant_pool: &ConstantPool,
+                     index: u16)
+                     -> Result<String, Rc<RefCell<Scalar>>> {
+    match constant_pool[index] {
+        Some(ConstantPoolEntry::MethodType(ref descriptor)) => Ok(descriptor.clone()),
+        ref entry => {
+            let message = format!("item at index {} must be a MethodType, found {:?}",
+                                  index,
+                                  entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+fn force_string<'a>(class_loader: &mut ClassLoader,
+                    constant_pool: &'a ConstantPool,
+                    index: u16)
+                    -> Result<&'a String, Rc<RefCell<Scalar>>> {
+    match constant_pool[index] {
+        Some(ConstantPoolEntry::UnresolvedString { string_index, .. }) => {
+            Ok(constant_pool.lookup_utf8(string_index))
+        }
+        ref entry => {
+            let message = format!("item at index {} must be a String, found {:?}", index, entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+fn force_int(class_loader: &mut ClassLoader,
+             constant_pool: &ConstantPool,
+             index: u16)
+             -> Result<i32, Rc<RefCell<Scalar>>> {
+    match constant_pool[index] {
+        Some(ConstantPoolEntry::Literal(Value::Int(value))) => Ok(value.0),
+        ref entry => {
+            let message = format!("item at index {} must be an int, found {:?}", index, entry);
+            Err(bad_bootstrap_argument(class_loader, &message))
+        }
+    }
+}
+
+/// Returns the class and method names of a bootstrap method.
+fn bootstrap_name(bootstrap_symref: &symref::Method) -> (&str, &str) {
+    let class_name = match bootstrap_symref.class.sig {
+        sig::Class::Scalar(ref name) => name.as_str(),
+        sig::Class::Array(_) => "",
+    };
+    (class_name, bootstrap_symref.sig.name.as_str())
+}
+
+fn unsupported_bootstrap_method(class_loader: &mut ClassLoader,
+                                bootstrap_symref: &symref::Method)
+                                -> Rc<RefCell<Scalar>> {
+    let message = format!("bootstrap method {} is not supported", bootstrap_symref);
+    exception::new(class_loader, "java/lang/BootstrapMethodError", Some(&message))
+}
+
+/// Resolves the dynamically-computed constant `sig` of `class`, as described in JVMS 5.4.3.6.
+///
+/// As with call sites, the VM recognizes the bootstrap methods of
+/// `java.lang.invoke.ConstantBootstraps` and computes their constants natively.
+pub fn resolve_dynamic_constant(class: &Class,
+                                class_loader: &mut ClassLoader,
+                                bootstrap_method: &BootstrapMethod,
+                                sig: &sig::Field)
+                                -> Result<Value, Rc<RefCell<Scalar>>> {
+    let constant_pool = class.get_constant_pool();
+    let args = &bootstrap_method.bootstrap_arguments;
+    let bootstrap_symref = force_bootstrap_symref(class_loader, constant_pool, bootstrap_method)?;
+    match bootstrap_name(bootstrap_symref) {
+        ("java/lang/invoke/ConstantBootstraps", "nullConstant") => {
+            match sig.ty {
+                sig::Type::Reference(_) => Ok(Value::NullReference),
+                ref ty => {
+                    let message = format!("not reference: {}", ty);
+                    Err(exception::new(class_loader,
+                                       "java/lang/BootstrapMethodError",
+                                       Some(&message)))
+                }
+            }
+        }
+        ("java/lang/invoke/ConstantBootstraps", "getStaticFinal") |
+        ("java/lang/invoke/ConstantBootstraps", "enumConstant") => {
+            // The field is declared by the class given as an argument, or else by the class of
+            // the constant's own type
+            let declaring_sig = match (args.first(), &sig.ty) {
+                (Some(&arg), _) => force_class_ref(class_loader, constant_pool, arg)?,
+                (None, sig::Type::Reference(class_sig)) => class_sig.clone(),
+                (None, ty) => {
+                    let message = format!("no declaring class for field of type {}", ty);
+                    return Err(exception::new(class_loader,
+                                              "java/lang/BootstrapMethodError",
+                                              Some(&message)));
+                }
+            };
+            let declaring_class = class_loader.resolve_class_in(class.get_defining_loader(),
+                                                                &declaring_sig)?;
+            let field_symref = symref::Field {
+                class: declaring_class.symref.clone(),
+                sig: sig.clone(),
+            };
+            declaring_class.get_field(class_loader, &field_symref)
+        }
+        ("java/lang/invoke/ConstantBootstraps", "invoke") => {
+            let handle_index = bootstrap_argument(class_loader, args, 0)?;
+            let handle = force_method_handle(class_loader, constant_pool, handle_index)?;
+            let mut handle_args = Vec::with_capacity(args.len() - 1);
+            for &arg in &args[1..] {
+                handle_args.push(class.resolve_constant(class_loader, arg)?);
+            }
+            match handle.invoke(class_loader, class.get_defining_loader(), handle_args) {
+                Ok(value) => Ok(value.unwrap_or_else(|| sig.ty.get_default())),
+                Err(exception) => Err(wrap_bootstrap_exception(class_loader, exception)),
+            }
+        }
+        _ => Err(unsupported_bootstrap_method(class_loader, bootstrap_symref)),
+    }
+}
+
+/// Wraps an exception thrown by a bootstrap method in a `BootstrapMethodError`, unless it is
+/// already an `Error`.
+fn wrap_bootstrap_exception(class_loader: &mut ClassLoader,
+                            exception: Rc<RefCell<Scalar>>)
+                            -> Rc<RefCell<Scalar>> {
+    let error_sig = sig::Class::Scalar(String::from("java/lang/Error"));
+    let error = class_loader.resolve_runtime_class(&error_sig);
+    if exception.borrow().get_class().is_subclass_of(&error) {
+        return exception;
+    }
+    exception::new_with_cause(class_loader, "java/lang/BootstrapMethodError", exception)
+}
+
+impl CallSite {
+    /// Links the call site described by the `InvokeDynamic` constant at `index` in the constant
+    /// pool of `class`, as described in JVMS 5.4.3.6.
+    pub fn link(class: &Class,
+                class_loader: &mut ClassLoader,
+                index: u16,
+                bootstrap_method: &BootstrapMethod,
+                sig: &sig::Method)
+                -> Result<Self, Rc<RefCell<Scalar>>> {
+        let constant_pool = class.get_constant_pool();
+        let args = &bootstrap_method.bootstrap_arguments;
+        let bootstrap_symref =
+            force_bootstrap_symref(class_loader, constant_pool, bootstrap_method)?;
+        match bootstrap_name(bootstrap_symref) {
+            ("java/lang/invoke/LambdaMetafactory", "metafactory") => {
+                Self::link_lambda(class, class_loader, index, args, 0, sig)
+            }
+            ("java/lang/invoke/LambdaMetafactory", "altMetafactory") => {
+                let flags_index = bootstrap_argument(class_loader, args, 3)?;
+                let flags = force_int(class_loader, constant_pool, flags_index)?;
+                Self::link_lambda(class, class_loader, index, args, flags, sig)
+            }
+            ("java/lang/invoke/StringConcatFactory", "makeConcat") => {
+                let recipe = sig.params.iter().cloned().map(RecipeElement::Argument).collect();
+                Ok(CallSite::StringConcat(recipe))
+            }
+            ("java/lang/invoke/StringConcatFactory", "makeConcatWithConstants") => {
+                let recipe = Self::parse_recipe(class_loader, constant_pool, args, sig)?;
+                Ok(CallSite::StringConcat(recipe))
+            }
+            _ => Err(unsupported_bootstrap_method(class_loader, bootstrap_symref)),
+        }
+    }
+
+    /// Links a call site bootstrapped by `LambdaMetafactory`, creating a lambda class that
+    /// implements the functional interface returned by the call site.
+    fn link_lambda(class: &Class,
+                   class_loader: &mut ClassLoader,
+                   index: u16,
+                   args: &[u16],
+                   flags: i32,
+                   sig: &sig::Method)
+                   -> Result<Self, Rc<RefCell<Scalar>>> {
+        let constant_pool = class.get_constant_pool();
+        let handle_index = bootstrap_argument(class_loader, args, 1)?;
+        let handle = force_method_handle(class_loader, constant_pool, handle_index)?;
+        let descriptor_index = bootstrap_argument(class_loader, args, 0)?;
+        let descriptor = force_method_type(class_loader, constant_pool, descriptor_index)?;
+        let instantiated_index = bootstrap_argument(class_loader, args, 2)?;
+        let instantiated = force_method_type(class_loader, constant_pool, instantiated_index)?;
+        let instantiated_sig = sig::Method::new(sig.name.clone(), instantiated);
+        let mut descriptors = vec![descriptor];
+        let mut interface_sigs = match sig.return_type {
+            Some(sig::Type::Reference(ref interface_sig)) => vec![interface_sig.clone()],
+            ref ty => {
+                let message = format!("lambda call site must return an interface, found {:?}", ty);
+                return Err(bad_bootstrap_argument(class_loader, &message));
+            }
+        };
+
+        // altMetafactory appends marker interfaces and bridge methods to the standard arguments
+        if flags & FLAG_SERIALIZABLE != 0 {
+            interface_sigs.push(sig::Class::Scalar(String::from("java/io/Serializable")));
+        }
+        let mut next = 4;
+        if flags & FLAG_MARKERS != 0 {
+            let count = Self::force_count(class_loader, constant_pool, args, next)?;
+            for &arg in &args[next + 1..next + 1 + count] {
+                interface_sigs.push(force_class_ref(class_loader, constant_pool, arg)?);
+            }
+            next += count + 1;
+        }
+        if flags & FLAG_BRIDGES != 0 {
+            let count = Self::force_count(class_loader, constant_pool, args, next)?;
+            for &arg in &args[next + 1..next + 1 + count] {
+                descriptors.push(force_method_type(class_loader, constant_pool, arg)?);
+            }
+        }
+
+        // Each interface method casts its arguments to the instantiated method type, and the
+        // lambda invokes the implementation with the captured arguments followed by them
+        let handle_sig = sig::Method::new(String::from("invoke"), handle.method_type());
+        let mut checked = Self::check_conversions(&sig.params, &instantiated_sig, &handle_sig);
+        for descriptor in &descriptors {
+            let interface_sig = sig::Method::new(sig.name.clone(), descriptor.clone());
+            if interface_sig.params.len() != instantiated_sig.params.len() {
+                checked = Err(format!("{} cannot be instantiated as {}",
+                                      interface_sig.descriptor(),
+                                      instantiated_sig.descriptor()));
+            }
+        }
+        if let Err(message) = checked {
+            let exception = exception::new(class_loader,
+                                           "java/lang/invoke/LambdaConversionException",
+                                           Some(&message));
+            return Err(wrap_bootstrap_exception(class_loader, exception));
+        }
+
+        let captured: Vec<sig::Field> = sig.params
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| sig::Field::new(format!("arg${}", i + 1), ty.clone()))
+            .collect();
+        let symref = symref::Class {
+            sig: sig::Class::Scalar(format!("{}$$Lambda${}", class.symref.sig, index)),
+        };
+        let methods = descriptors.into_iter()
+            .map(|descriptor| {
+                class::Method::new_lambda(symref::Method {
+                                              class: symref.clone(),
+                                              sig: sig::Method::new(sig.name.clone(), descriptor),
+                                          },
+                                          handle.clone(),
+                                          captured.clone(),
+                                          instantiated_sig.clone())
+            })
+            .collect();
+
+        let object_sig = sig::Class::Scalar(String::from("java/lang/Object"));
+        let object = class_loader.resolve_runtime_class(&object_sig);
+        let loader = class.get_defining_loader();
+        let mut interfaces = Vec::with_capacity(interface_sigs.len());
+        for interface_sig in &interface_sigs {
+            interfaces.push(class_loader.resolve_class_in(loader, interface_sig)?);
+        }
+        let lambda_class = Class::new_lambda(symref,
+                                             object,
+                                             interfaces,
+                                             methods,
+                                             &captured,
+                                             loader.clone());
+        Ok(CallSite::Lambda {
+            class: Rc::new(lambda_class),
+            captured,
+        })
+    }
+
+    /// Checks that a lambda instantiating `interface_sig` can call `handle_sig` by passing the
+    /// captured arguments unchanged and adapting the interface method's arguments and result
+    /// with `boxing::convert`, as described by `LambdaMetafactory`.
+    fn check_conversions(captured: &[sig::Type],
+                         interface_sig: &sig::Method,
+                         handle_sig: &sig::Method)
+                         -> Result<(), String> {
+        let arity = captured.len() + interface_sig.params.len();
+        if arity != handle_sig.params.len() {
+            return Err(format!("{} cannot be invoked with {} arguments",
+                               handle_sig.descriptor(),
+                               arity));
+        }
+        let (captured_params, params) = handle_sig.params.split_at(captured.len());
+        for (from, to) in captured.iter().zip(captured_params) {
+            let is_reference = matches!((from, to),
+                                        (&sig::Type::Reference(_), &sig::Type::Reference(_)));
+            if from != to && !is_reference {
+                return Err(format!("captured argument of type {} cannot be passed as {}",
+                                   from,
+                                   to));
+            }
+        }
+        for (from, to) in interface_sig.params.iter().zip(params) {
+            if !boxing::is_convertible(from, to) {
+                return Err(format!("argument of type {} cannot be converted to {}", from, to));
+            }
+        }
+        match (&handle_sig.return_type, &interface_sig.return_type) {
+            (_, &None) => Ok(()),
+            (Some(from), Some(to)) if boxing::is_convertible(from, to) => Ok(()),
+            _ => {
+                Err(format!("return type of {} cannot be converted to that of {}",
+                            handle_sig.descriptor(),
+                            interface_sig.descriptor()))
+            }
+        }
+    }
+
+    fn parse_recipe(class_loader: &mut ClassLoader,
+                    constant_pool: &ConstantPool,
+                    args: &[u16],
+                    sig: &sig::Method)
+                    -> Result<Vec<RecipeElement>, Rc<RefCell<Scalar>>> {
+        let recipe_index = bootstrap_argument(class_loader, args, 0)?;
+        let recipe = force_string(class_loader, constant_pool, recipe_index)?;
+        let mut params = sig.params.iter();
+        let mut constants = args[1..].iter();
+        let mut elements = vec![];
+        let mut literal = String::new();
+        for c in recipe.chars() {
+            let element = match c {
+                TAG_ARG => {
+                    match params.next() {
+                        Some(ty) => RecipeElement::Argument(ty.clone()),
+                        None => {
+                            let message = "recipe refers to more arguments than the call site has";
+                            return Err(bad_bootstrap_argument(class_loader, message));
+                        }
+                    }
+                }
+                TAG_CONST => {
+                    let index = match constants.next() {
+                        Some(&index) => index,
+                        None => {
+                            let message = "recipe refers to more constants than were given";
+                            return Err(bad_bootstrap_argument(class_loader, message));
+                        }
+                    };
+                    let constant = match constant_pool[index] {
+                        Some(ConstantPoolEntry::Literal(Value::Int(value))) => value.to_string(),
+                        Some(ConstantPoolEntry::Literal(Value::Long(value))) => value.to_string(),
+                        Some(ConstantPoolEntry::Literal(Value::Float(value))) => {
+                            string::float_to_string(value)
+                        }
+                        Some(ConstantPoolEntry::Literal(Value::Double(value))) => {
+                            string::double_to_string(value)
+                        }
+                        _ => force_string(class_loader, constant_pool, index)?.clone(),
+                    };
+                    RecipeElement::Constant(constant)
+                }
+                _ => {
+                    literal.push(c);
+                    continue;
+                }
+            };
+            if !literal.is_empty() {
+                elements.push(RecipeElement::Constant(literal.split_off(0)));
+            }
+            elements.push(element);
+        }
+        if !literal.is_empty() {
+            elements.push(RecipeElement::Constant(literal));
+        }
+        Ok(elements)
+    }
+
+    /// Returns the count at `index` in the arguments of `altMetafactory`, checking that that many
+    /// arguments follow it.
+    fn force_count(class_loader: &mut ClassLoader,
+                   constant_pool: &ConstantPool,
+                   args: &[u16],
+                   index: usize)
+                   -> Result<usize, Rc<RefCell<Scalar>>> {
+        let count_index = bootstrap_argument(class_loader, args, index)?;
+        let count = force_int(class_loader, constant_pool, count_index)?;
+        if count < 0 || index + count as usize >= args.len() {
+            let message = format!("bad count {} at bootstrap argument {}", count, index);
+            return Err(bad_bootstrap_argument(class_loader, &message));
+        }
+        Ok(count as usize)
+    }
+
+    /// Invokes the call site with `args`, the values popped by `invokedynamic`.
+    pub fn invoke(&self,
+                  class_loader: &mut ClassLoader,
+                  args: Vec<Value>)
+                  -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+        match *self {
+            CallSite::Lambda { ref class, ref captured } => {
+                let mut lambda = Scalar::new(class.clone());
+                for (field, value) in captured.iter().zip(args) {
+                    let field_symref = symref::Field {
+                        class: class.symref.clone(),
+                        sig: field.clone(),
+                    };
+                    lambda.put_field(&field_symref, value);
+                }
+                Ok(Some(Value::Reference(Rc::new(RefCell::new(lambda)))))
+            }
+            CallSite::StringConcat(ref recipe) => {
+                let mut result = Vec::new();
+                let mut args = args.into_iter();
+                for element in recipe {
+                    match *element {
+                        RecipeElement::Argument(ref ty) => {
+                            let value = args.next().unwrap();
+                            result.extend(Self::stringify(class_loader, ty, value)?);
+                        }
+                        RecipeElement::Constant(ref constant) => {
+                            result.extend(constant.encode_utf16())
+                        }
+                    }
+                }
+                Ok(Some(string::from_utf16(class_loader, &result)))
+            }
+        }
+    }
+
+    /// Converts a value to the UTF-16 code units of a string, as done by `String.valueOf`.
+    fn stringify(class_loader: &mut ClassLoader,
+                 ty: &sig::Type,
+                 value: Value)
+                 -> Result<Vec<u16>, Rc<RefCell<Scalar>>> {
+        let string = match (ty, value) {
+            (&sig::Type::Boolean, Value::Int(value)) => (value.0 != 0).to_string(),
+            // A char is a single UTF-16 code unit, which may be half of a surrogate pair
+            (&sig::Type::Char, Value::Int(value)) => return Ok(vec![value.0 as u16]),
+            (_, Value::Int(value)) => value.to_string(),
+            (_, Value::Long(value)) => value.to_string(),
+            (_, Value::Float(value)) => string::float_to_string(value),
+            (_, Value::Double(value)) => string::double_to_string(value),
+            (_, Value::NullReference) => String::from("null"),
+            (_, Value::Reference(object)) => {
+                if object.borrow().get_class().symref.sig ==
+                   sig::Class::Scalar(String::from("java/lang/String")) {
+                    return Ok(string::to_utf16(&object.borrow()));
+                }
+                return Self::object_to_string(class_loader, Value::Reference(object));
+            }
+            (_, array) => return Self::object_to_string(class_loader, array),
+        };
+        Ok(string.encode_utf16().collect())
+    }
+
+    /// Calls `toString()` on an object, falling back to the format of `Object.toString` for
+    /// classes that do not override it.
+    fn object_to_string(class_loader: &mut ClassLoader,
+                        value: Value)
+                        -> Result<Vec<u16>, Rc<RefCell<Scalar>>> {
+        let (class, address) = match value {
+            Value::Reference(ref object) => {
+                (object.borrow().get_class().clone(), object.as_ptr() as usize)
+            }
+            Value::ArrayReference(ref array) => {
+                (array.borrow().get_class().clone(), array.as_ptr() as usize)
+            }
+            ref v => panic!("Expected a reference, found {:?}", v),
+        };
+        let symref = symref::Method {
+            class: class.symref.clone(),
+            sig: sig::Method::new(String::from("toString"), String::from("()Ljava/lang/String;")),
+        };
+        if class.find_virtual_method(&symref).is_none() {
+            let name = format!("{}", class.symref.sig).replace('/', ".");
+            return Ok(format!("{}@{:x}", name, address as u32).encode_utf16().collect());
+        }
+        let loader = class.get_defining_loader();
+        match invoke::invoke_virtual(class_loader, loader, &symref, vec![value])? {
+            Some(Value::Reference(string)) => Ok(string::to_utf16(&string.borrow())),
+            _ => Ok("null".encode_utf16().collect()),
+        }
+    }
+}
@@ -1,44 +1,84 @@
 use super::ClassLoader;
+use super::boxing;
+use super::builtin::Builtin;
 use super::ConstantPool;
+use super::call_site::{self, CallSite};
+use super::class_loader::Loader;
+use super::constant_pool::ConstantPoolEntry;
+use super::exception;
 use super::frame;
+use super::invoke::MethodHandle;
 use super::super::model;
+use super::super::model::info::attribute::{BootstrapMethod, ExceptionHandler};
 use super::native;
 use super::sig;
 use super::symref;
-use super::value::Value;
+use super::value::{Scalar, Value};
 
 use lib::Library;
 
-use std::collections::{HashMap, HashSet};
-use std::cell::RefCell;
+use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::iter;
 use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Class {
     pub symref: symref::Class,
     pub access_flags: u16,
-    pub superclass: Option<Box<Class>>,
+    pub superclass: Option<Rc<Class>>,
+    pub interfaces: Vec<Rc<Class>>,
+    /// The class of the components of an array class with reference components
+    component_class: Option<Rc<Class>>,
+    /// Every interface implemented by this class, directly or through superclasses and
+    /// superinterfaces
+    superinterfaces: Vec<Rc<Class>>,
     constant_pool: ConstantPool,
-    methods: HashMap<sig::Method, RefCell<Method>>,
+    methods: Vec<RefCell<Method>>,
+    method_indices: HashMap<sig::Method, usize>,
+    vtable: Vec<VtableEntry>,
+    vtable_indices: HashMap<sig::Method, usize>,
+    /// Maps each superinterface to the methods selected for it, indexed by the interface's own
+    /// method indices. `None` marks methods that cannot be selected.
+    itables: HashMap<sig::Class, Vec<Option<VtableEntry>>>,
     fields: HashMap<sig::Field, u16>,
+    /// The instance field layout: the fields of every object of this class, starting with those
+    /// inherited from its superclasses
+    instance_fields: Vec<sig::Field>,
+    /// Maps the instance fields declared by this class to their indices in `instance_fields`
+    instance_field_indices: HashMap<sig::Field, usize>,
     field_constants: HashMap<sig::Field, u16>,
-    field_values: RefCell<Option<HashMap<sig::Field, Value>>>,
+    field_values: RefCell<HashMap<sig::Field, Value>>,
+    initialization_state: Cell<InitializationState>,
+    bootstrap_methods: Box<[BootstrapMethod]>,
+    /// Linked `invokedynamic` call sites, keyed by the index of their `InvokeDynamic` constant
+    call_sites: RefCell<HashMap<u16, Rc<CallSite>>>,
+    /// The values of the constants loaded by `ldc` that are resolved on first use, keyed by
+    /// their index in the constant pool
+    resolved_constants: RefCell<HashMap<u16, Value>>,
+    /// The class loader that defined this class, which resolves the classes it refers to
+    defining_loader: Loader,
+    /// The `java.lang.Class` object representing this class, once it has been created
+    mirror: RefCell<Option<Rc<RefCell<Scalar>>>>,
 }
 
 impl Class {
     pub fn new(symref: symref::Class,
-               superclass: Option<Box<Class>>,
+               superclass: Option<Rc<Class>>,
+               interfaces: Vec<Rc<Class>>,
                constant_pool: ConstantPool,
-               class: model::class::Class)
+               class: &model::class::Class,
+               defining_loader: Loader)
                -> (Self, Vec<sig::Method>) {
-        let mut fields = HashMap::new();
+        let mut fields = Vec::new();
         let mut field_constants = HashMap::new();
         for field_info in class.fields.iter() {
             let name = constant_pool.lookup_utf8(field_info.name_index);
+            // Descriptors are checked when the class file is parsed
             let ty = sig::Type::new(constant_pool.lookup_utf8(field_info.descriptor_index))
                 .unwrap();
             let sig = sig::Field::new(name.clone(), ty);
-            fields.insert(sig.clone(), field_info.access_flags);
+            fields.push((sig.clone(), field_info.access_flags));
             // If the field is static, add to field_constants
             if field_info.access_flags & model::info::field::ACC_STATIC != 0 {
                 for attr in field_info.attributes.iter() {
@@ -49,7 +89,7 @@ impl Class {
             }
         }
 
-        let mut methods = HashMap::new();
+        let mut methods = Vec::new();
         let mut unbound_natives = Vec::new();
         for method_info in class.methods.iter() {
             let name = constant_pool.lookup_utf8(method_info.name_index);
@@ -62,100 +102,514 @@ impl Class {
                                      },
                                      method_info);
 
-            methods.insert(sig.clone(), RefCell::new(method));
+            methods.push(RefCell::new(method));
 
             if method_info.access_flags & model::info::method::ACC_NATIVE != 0 {
                 unbound_natives.push(sig);
             }
         }
-        (Class {
-             symref: symref,
-             access_flags: 0,
-             superclass: superclass,
-             constant_pool: constant_pool,
-             methods: methods,
-             fields: fields,
-             field_constants: field_constants,
-             field_values: RefCell::new(None),
-         },
+
+        let bootstrap_methods = class.attributes
+            .iter()
+            .filter_map(|attr| match *attr {
+                model::info::Attribute::BootstrapMethods { ref bootstrap_methods, .. } => {
+                    Some(bootstrap_methods.clone())
+                }
+                _ => None,
+            })
+            .next()
+            .unwrap_or_default();
+
+        (Self::with_members(symref,
+                            class.access_flags,
+                            superclass,
+                            interfaces,
+                            constant_pool,
+                            methods,
+                            fields,
+                            field_constants,
+                            bootstrap_methods,
+                            defining_loader),
          unbound_natives)
     }
 
-    pub fn new_array(component: sig::Type) -> Self {
-        // TODO: Length field, access flags
+    /// Creates an array class. As described in JVMS 4.10.1.2, array classes extend
+    /// `java/lang/Object` and implement `java/lang/Cloneable` and `java/io/Serializable`.
+    /// `component_class` is the class of the components if they are references, and
+    /// `defining_loader` is its defining loader.
+    pub fn new_array(component: sig::Type,
+                     component_class: Option<Rc<Class>>,
+                     object: Rc<Class>,
+                     interfaces: Vec<Rc<Class>>,
+                     defining_loader: Loader)
+                     -> Self {
+        // TODO: Length field
         let sig = sig::Class::Array(Box::new(component));
-        let symref = symref::Class { sig: sig };
+        let symref = symref::Class { sig };
 
         let constant_pool: Vec<model::info::Constant> = Vec::new();
         // TODO: Optimize this
+        let mut class = Self::with_members(symref,
+                                           model::class::ACC_PUBLIC | model::class::ACC_FINAL |
+                                           model::class::ACC_ABSTRACT,
+                                           Some(object),
+                                           interfaces,
+                                           ConstantPool::new(&constant_pool.into_boxed_slice()),
+                                           Vec::new(),
+                                           Vec::new(),
+                                           HashMap::new(),
+                                           Box::new([]),
+                                           defining_loader);
+        class.component_class = component_class;
+        class
+    }
+
+    /// Creates the class of the lambdas produced by an `invokedynamic` call site. Lambda
+    /// objects store their captured arguments in `captured`, and implement `interfaces` through
+    /// `methods`. Lambda classes are defined by the loader of the class containing the call
+    /// site.
+    pub fn new_lambda(symref: symref::Class,
+                      object: Rc<Class>,
+                      interfaces: Vec<Rc<Class>>,
+                      methods: Vec<Method>,
+                      captured: &[sig::Field],
+                      defining_loader: Loader)
+                      -> Self {
+        let fields = captured.iter()
+            .map(|field| {
+                (field.clone(), model::info::field::ACC_PRIVATE | model::info::field::ACC_FINAL)
+            })
+            .collect();
+        let constant_pool: Vec<model::info::Constant> = Vec::new();
+        Self::with_members(symref,
+                           model::class::ACC_FINAL | model::class::ACC_SUPER |
+                           model::class::ACC_SYNTHETIC,
+                           Some(object),
+                           interfaces,
+                           ConstantPool::new(&constant_pool.into_boxed_slice()),
+                           methods.into_iter().map(RefCell::new).collect(),
+                           fields,
+                           HashMap::new(),
+                           Box::new([]),
+                           defining_loader)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn with_members(symref: symref::Class,
+                    access_flags: u16,
+                    superclass: Option<Rc<Class>>,
+                    interfaces: Vec<Rc<Class>>,
+                    constant_pool: ConstantPool,
+                    methods: Vec<RefCell<Method>>,
+                    fields: Vec<(sig::Field, u16)>,
+                    field_constants: HashMap<sig::Field, u16>,
+                    bootstrap_methods: Box<[BootstrapMethod]>,
+                    defining_loader: Loader)
+                    -> Self {
+        let mut instance_fields = superclass.as_ref()
+            .map(|superclass| superclass.instance_fields.clone())
+            .unwrap_or_default();
+        let mut instance_field_indices = HashMap::new();
+        // Static fields hold their default values until the class is initialized
+        let mut field_values = HashMap::new();
+        for &(ref sig, access_flags) in &fields {
+            if access_flags & model::info::field::ACC_STATIC == 0 {
+                instance_field_indices.insert(sig.clone(), instance_fields.len());
+                instance_fields.push(sig.clone());
+            } else {
+                field_values.insert(sig.clone(), sig.ty.get_default());
+            }
+        }
+
+        let method_indices = methods.iter()
+            .enumerate()
+            .map(|(index, method)| (method.borrow().symref.sig.clone(), index))
+            .collect();
+        let superinterfaces = Self::collect_superinterfaces(superclass.as_ref(), &interfaces);
+        let (vtable, vtable_indices) =
+            Self::build_vtable(superclass.as_ref(), &superinterfaces, &methods);
+        let itables = if access_flags & model::class::ACC_INTERFACE == 0 {
+            Self::build_itables(&superinterfaces, &vtable, &vtable_indices)
+        } else {
+            HashMap::new()
+        };
         Class {
-            symref: symref,
-            access_flags: 0,
-            superclass: None,
-            constant_pool: ConstantPool::new(&constant_pool.into_boxed_slice()),
-            methods: HashMap::new(),
-            fields: HashMap::new(),
-            field_constants: HashMap::new(),
-            field_values: RefCell::new(None),
+            symref,
+            access_flags,
+            superclass,
+            interfaces,
+            component_class: None,
+            superinterfaces,
+            constant_pool,
+            methods,
+            method_indices,
+            vtable,
+            vtable_indices,
+            itables,
+            fields: fields.into_iter().collect(),
+            instance_fields,
+            instance_field_indices,
+            field_constants,
+            field_values: RefCell::new(field_values),
+            initialization_state: Cell::new(InitializationState::Uninitialized),
+            bootstrap_methods,
+            call_sites: RefCell::new(HashMap::new()),
+            resolved_constants: RefCell::new(HashMap::new()),
+            defining_loader,
+            mirror: RefCell::new(None),
         }
     }
 
-    pub fn initialize(&self, class_loader: &mut ClassLoader) {
-        // Initialize all the field_values
-        let run_clinit = match *self.field_values.borrow() {
-            None => true,
-            Some(_) => false,
-        };
-        if run_clinit {
+    fn collect_superinterfaces(superclass: Option<&Rc<Class>>,
+                               interfaces: &[Rc<Class>])
+                               -> Vec<Rc<Class>> {
+        let mut superinterfaces = superclass.map(|superclass| superclass.superinterfaces.clone())
+            .unwrap_or_default();
+        for interface in interfaces {
+            for candidate in iter::once(interface).chain(interface.superinterfaces.iter()) {
+                if !superinterfaces.iter().any(|other| other.symref == candidate.symref) {
+                    superinterfaces.push(candidate.clone());
+                }
+            }
+        }
+        superinterfaces
+    }
+
+    /// Finds the maximally-specific superinterface methods for `sig`, as defined in JVMS
+    /// 5.4.3.3: methods declared in one of `superinterfaces` that are not also declared in one of
+    /// its subinterfaces.
+    fn maximally_specific_methods(superinterfaces: &[Rc<Class>],
+                                  sig: &sig::Method)
+                                  -> Vec<(Rc<Class>, usize)> {
+        let candidates: Vec<(Rc<Class>, usize)> = superinterfaces.iter()
+            .filter_map(|interface| {
+                interface.method_indices
+                    .get(sig)
+                    .filter(|&&index| interface.methods[index].borrow().is_virtual())
+                    .map(|&index| (interface.clone(), index))
+            })
+            .collect();
+        candidates.iter()
+            .filter(|&(interface, _)| {
+                !candidates.iter().any(|(other, _)| {
+                    other.symref != interface.symref &&
+                    other.superinterfaces.iter().any(|i| i.symref == interface.symref)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Selects the default method a class inherits for `sig` from its superinterfaces, following
+    /// the selection rules of JVMS 5.4.6.
+    fn select_default_method(superinterfaces: &[Rc<Class>], sig: &sig::Method) -> DefaultMethod {
+        let methods = Self::maximally_specific_methods(superinterfaces, sig);
+        let mut defaults = methods.iter()
+            .filter(|&&(ref interface, index)| !interface.methods[index].borrow().is_abstract());
+        match (defaults.next(), defaults.next()) {
+            (Some(&(ref interface, index)), None) => {
+                DefaultMethod::Selected(VtableEntry {
+                    class: Some(interface.clone()),
+                    method_index: index,
+                })
+            }
+            (Some(_), Some(_)) => DefaultMethod::Ambiguous,
+            (None, _) => {
+                match methods.first() {
+                    Some(&(ref interface, index)) => {
+                        DefaultMethod::Abstract(VtableEntry {
+                            class: Some(interface.clone()),
+                            method_index: index,
+                        })
+                    }
+                    None => DefaultMethod::Ambiguous,
+                }
+            }
+        }
+    }
 
-            let mut field_values = HashMap::new();
-            for (sig, index) in &self.field_constants {
-                let value = self.constant_pool.resolve_literal(*index, class_loader);
-                field_values.insert(sig.clone(), value.clone());
+    /// Builds the virtual method table for a class, starting from a copy of its superclass's
+    /// table. Methods that override a superclass method take over its index, and the remaining
+    /// virtual methods are appended in declaration order, followed by any default or abstract
+    /// methods inherited from superinterfaces.
+    fn build_vtable(superclass: Option<&Rc<Class>>,
+                    superinterfaces: &[Rc<Class>],
+                    methods: &[RefCell<Method>])
+                    -> (Vec<VtableEntry>, HashMap<sig::Method, usize>) {
+        let (mut vtable, mut vtable_indices) = match superclass {
+            None => (Vec::new(), HashMap::new()),
+            Some(superclass) => {
+                let vtable = superclass.vtable
+                    .iter()
+                    .map(|entry| {
+                        VtableEntry {
+                            class: Some(entry.class.clone().unwrap_or_else(|| superclass.clone())),
+                            method_index: entry.method_index,
+                        }
+                    })
+                    .collect();
+                (vtable, superclass.vtable_indices.clone())
             }
-            *self.field_values.borrow_mut() = Some(field_values);
+        };
 
-            let clinit_sig = sig::Method {
-                name: String::from("<clinit>"),
-                params: vec![],
-                return_type: None,
+        for (method_index, method) in methods.iter().enumerate() {
+            let method = method.borrow();
+            if !method.is_virtual() {
+                continue;
+            }
+            let entry = VtableEntry {
+                class: None,
+                method_index,
             };
-            match self.methods.get(&clinit_sig) {
-                None => (),
-                Some(method) => {
-                    let _ = method.borrow().invoke(self, class_loader, None);
+            match vtable_indices.get(&method.symref.sig) {
+                Some(&index) => vtable[index] = entry,
+                None => {
+                    vtable_indices.insert(method.symref.sig.clone(), vtable.len());
+                    vtable.push(entry);
+                }
+            }
+        }
+
+        for interface in superinterfaces {
+            for method in &interface.methods {
+                let method = method.borrow();
+                if !method.is_virtual() || vtable_indices.contains_key(&method.symref.sig) {
+                    continue;
+                }
+                let sig = &method.symref.sig;
+                // Abstract methods also take a slot, so that subclasses override them through it
+                match Self::select_default_method(superinterfaces, sig) {
+                    DefaultMethod::Selected(entry) |
+                    DefaultMethod::Abstract(entry) => {
+                        vtable_indices.insert(sig.clone(), vtable.len());
+                        vtable.push(entry);
+                    }
+                    DefaultMethod::Ambiguous => (),
+                }
+            }
+        }
+        (vtable, vtable_indices)
+    }
+
+    /// Builds the interface method tables of a class from its vtable. Interface methods without
+    /// an implementation are mapped to an abstract method so that invoking them throws
+    /// `AbstractMethodError`.
+    fn build_itables(superinterfaces: &[Rc<Class>],
+                     vtable: &[VtableEntry],
+                     vtable_indices: &HashMap<sig::Method, usize>)
+                     -> HashMap<sig::Class, Vec<Option<VtableEntry>>> {
+        let mut itables = HashMap::new();
+        for interface in superinterfaces {
+            let itable = interface.methods
+                .iter()
+                .map(|method| {
+                    let method = method.borrow();
+                    if !method.is_virtual() {
+                        return None;
+                    }
+                    match vtable_indices.get(&method.symref.sig) {
+                        Some(&index) => Some(vtable[index].clone()),
+                        None => {
+                            match Self::select_default_method(superinterfaces,
+                                                              &method.symref.sig) {
+                                DefaultMethod::Selected(entry) |
+                                DefaultMethod::Abstract(entry) => Some(entry),
+                                DefaultMethod::Ambiguous => None,
+                            }
+                        }
+                    }
+                })
+                .collect();
+            itables.insert(interface.symref.sig.clone(), itable);
+        }
+        itables
+    }
+
+    /// Initializes the class by running its static initializer, following the procedure of
+    /// JVMS 5.5. Classes must be initialized just before the first `new`, `getstatic`,
+    /// `putstatic` or `invokestatic` that refers to them, and before any of their subclasses.
+    ///
+    /// Returns the exception thrown if initialization fails. Exceptions that are not `Error`s
+    /// are wrapped in `ExceptionInInitializerError`, and any later attempt to initialize the
+    /// class throws `NoClassDefFoundError`.
+    pub fn initialize(&self, class_loader: &mut ClassLoader) -> Result<(), Rc<RefCell<Scalar>>> {
+        match self.initialization_state.get() {
+            // A recursive request from the static initializer completes immediately
+            InitializationState::InProgress |
+            InitializationState::Initialized => return Ok(()),
+            InitializationState::Erroneous => {
+                let message = format!("Could not initialize class {}",
+                                      format!("{}", self.symref.sig).replace('/', "."));
+                return Err(exception::new(class_loader,
+                                          "java/lang/NoClassDefFoundError",
+                                          Some(&message)));
+            }
+            InitializationState::Uninitialized => (),
+        }
+        self.initialization_state.set(InitializationState::InProgress);
+
+        for (sig, index) in &self.field_constants {
+            let value = self.constant_pool.resolve_literal(*index, class_loader);
+            self.field_values.borrow_mut().insert(sig.clone(), value);
+        }
+
+        // Superinterfaces are only initialized if they declare default methods, and interfaces do
+        // not initialize their superinterfaces at all
+        if !self.is_interface() {
+            let superinterfaces = self.superinterfaces
+                .iter()
+                .filter(|interface| interface.declares_default_methods());
+            for class in self.superclass.iter().chain(superinterfaces) {
+                if let Err(exception) = class.initialize(class_loader) {
+                    self.initialization_state.set(InitializationState::Erroneous);
+                    return Err(exception);
+                }
+            }
+        }
+
+        let clinit_sig = sig::Method {
+            name: String::from("<clinit>"),
+            params: vec![],
+            return_type: None,
+        };
+        if let Some(&index) = self.method_indices.get(&clinit_sig) {
+            let result = self.methods[index].borrow().invoke(self, class_loader, None);
+            if let Err(exception) = result {
+                self.initialization_state.set(InitializationState::Erroneous);
+                let error_sig = sig::Class::Scalar(String::from("java/lang/Error"));
+                let error_class = class_loader.resolve_runtime_class(&error_sig);
+                if exception.borrow().get_class().is_subclass_of(&error_class) {
+                    return Err(exception);
                 }
+                return Err(exception::new_with_cause(class_loader,
+                                                     "java/lang/ExceptionInInitializerError",
+                                                     exception));
             }
         }
+        self.initialization_state.set(InitializationState::Initialized);
+        Ok(())
+    }
+
+    fn declares_default_methods(&self) -> bool {
+        self.methods.iter().any(|method| {
+            let method = method.borrow();
+            method.is_virtual() && !method.is_abstract()
+        })
     }
 
     pub fn bind_native_method(&self, sig: sig::Method, library: Rc<Library>) {
-        let mut method = self.methods[&sig].borrow_mut();
+        let mut method = self.methods[self.method_indices[&sig]].borrow_mut();
         method.bind_native(library);
     }
 
+    pub fn bind_builtin_method(&self, sig: sig::Method, builtin: Builtin) {
+        let mut method = self.methods[self.method_indices[&sig]].borrow_mut();
+        method.bind_builtin(builtin);
+    }
+
+    pub fn get_defining_loader(&self) -> &Loader {
+        &self.defining_loader
+    }
+
+    pub fn get_mirror(&self) -> Option<Rc<RefCell<Scalar>>> {
+        self.mirror.borrow().clone()
+    }
+
+    pub fn set_mirror(&self, mirror: Rc<RefCell<Scalar>>) {
+        *self.mirror.borrow_mut() = Some(mirror);
+    }
+
+    /// Returns true if this class and `other` are the same runtime class: that is, they have the
+    /// same name and the same defining loader, as described in JVMS 5.3.
+    pub fn is_same_class(&self, other: &Class) -> bool {
+        self.symref == other.symref &&
+        match (&self.defining_loader, &other.defining_loader) {
+            (None, None) => true,
+            (Some(loader), Some(other_loader)) => Rc::ptr_eq(loader, other_loader),
+            _ => false,
+        }
+    }
+
     pub fn get_constant_pool(&self) -> &ConstantPool {
         &self.constant_pool
     }
 
-    pub fn collect_instance_fields(&self) -> HashSet<sig::Field> {
-        // TODO: Superclass fields
-        let mut fields = HashSet::new();
-        for (sig, access_flags) in &self.fields {
-            if access_flags & model::info::field::ACC_STATIC == 0 {
-                fields.insert(sig.clone());
+    pub fn is_interface(&self) -> bool {
+        self.access_flags & model::class::ACC_INTERFACE != 0
+    }
+
+    pub fn get_component_class(&self) -> Option<&Rc<Class>> {
+        self.component_class.as_ref()
+    }
+
+    /// Returns true if a reference to an instance of this class can be assigned to a variable of
+    /// class `other`, following the rules of `checkcast` in JVMS 6.5.
+    pub fn is_assignable_to(&self, other: &Class) -> bool {
+        match (&self.symref.sig, &other.symref.sig) {
+            (sig::Class::Array(component), sig::Class::Array(other_component)) => {
+                match (&self.component_class, &other.component_class) {
+                    (Some(class), Some(other_class)) => class.is_assignable_to(other_class),
+                    // Arrays of primitives are only assignable to arrays of the same primitive
+                    _ => component == other_component,
+                }
             }
+            _ if other.is_interface() => {
+                self.is_same_class(other) ||
+                self.superinterfaces.iter().any(|interface| interface.is_same_class(other))
+            }
+            _ => self.is_subclass_of(other),
+        }
+    }
+
+    /// Returns true if this class is `other` or one of its subclasses.
+    pub fn is_subclass_of(&self, other: &Class) -> bool {
+        if self.is_same_class(other) {
+            return true;
+        }
+        match self.superclass {
+            Some(ref superclass) => superclass.is_subclass_of(other),
+            None => false,
+        }
+    }
+
+    /// Returns the instance field layout of the class, including inherited fields.
+    pub fn get_instance_fields(&self) -> &[sig::Field] {
+        &self.instance_fields
+    }
+
+    /// Returns the index in the instance field layout of the field named by `field_symref`,
+    /// resolving it from the referenced class as described in JVMS 5.4.3.2. The referenced class
+    /// must be this class or one of its superclasses.
+    pub fn find_instance_field(&self, field_symref: &symref::Field) -> Option<usize> {
+        let mut class = self;
+        while class.symref != field_symref.class {
+            class = class.superclass.as_ref()?;
+        }
+        while !class.instance_field_indices.contains_key(&field_symref.sig) {
+            class = class.superclass.as_ref()?;
+        }
+        Some(class.instance_field_indices[&field_symref.sig])
+    }
+
+    /// Finds the class declaring the static field `sig`, searching this class, then its
+    /// superinterfaces, then its superclasses, as described in JVMS 5.4.3.2.
+    fn find_static_field(&self, sig: &sig::Field) -> Option<&Class> {
+        if self.fields.contains_key(sig) {
+            return Some(self);
         }
-        fields
+        self.interfaces
+            .iter()
+            .filter_map(|interface| interface.find_static_field(sig))
+            .next()
+            .or_else(|| {
+                self.superclass.as_ref().and_then(|superclass| superclass.find_static_field(sig))
+            })
     }
 
-    pub fn find_method(&self,
-                       class_loader: &mut ClassLoader,
-                       method_symref: &symref::Method)
-                       -> &RefCell<Method> {
-        self.initialize(class_loader);
-        self.methods
+    pub fn find_method(&self, method_symref: &symref::Method) -> &RefCell<Method> {
+        self.method_indices
             .get(&method_symref.sig)
+            .map(|&index| &self.methods[index])
             .unwrap_or_else(|| {
                 panic!("{:?} is not in this class({:?})",
                        method_symref.sig,
@@ -163,25 +617,197 @@ impl Class {
             })
     }
 
-    pub fn get_field(&self, class_loader: &mut ClassLoader, field_symref: &symref::Field) -> Value {
-        self.initialize(class_loader);
-        let map_opt = self.field_values.borrow();
-        let map = map_opt.as_ref().unwrap();
-        map.get(&field_symref.sig).unwrap().clone()
-        // TODO: Superclass stuff
+    /// Resolves the method named by `method_symref` in `class`, as described in JVMS 5.4.3.3:
+    /// methods declared by the class or its superclasses take precedence over those of its
+    /// superinterfaces. Returns the class declaring the method along with the method's index
+    /// within that class.
+    pub fn resolve_method(class: &Rc<Class>,
+                          method_symref: &symref::Method)
+                          -> Option<(Rc<Class>, usize)> {
+        let mut current = Some(class.clone());
+        while let Some(class) = current {
+            if let Some(&index) = class.method_indices.get(&method_symref.sig) {
+                return Some((class, index));
+            }
+            current = class.superclass.clone();
+        }
+        let methods = Self::maximally_specific_methods(&class.superinterfaces, &method_symref.sig);
+        methods.iter()
+            .find(|&&(ref class, index)| !class.methods[index].borrow().is_abstract())
+            .or_else(|| methods.first())
+            .cloned()
+    }
+
+    /// Returns the vtable index of the virtual method named by `method_symref`, or `None` if the
+    /// method cannot be dispatched virtually (for example because it is private).
+    pub fn find_virtual_method(&self, method_symref: &symref::Method) -> Option<usize> {
+        self.vtable_indices.get(&method_symref.sig).cloned()
+    }
+
+    /// Selects the method at `index` in the vtable of `receiver`, returning the class that
+    /// declares the selected method along with the method's index within that class.
+    pub fn dispatch_virtual(receiver: &Rc<Class>, index: usize) -> (Rc<Class>, usize) {
+        let entry = &receiver.vtable[index];
+        let class = entry.class.clone().unwrap_or_else(|| receiver.clone());
+        (class, entry.method_index)
+    }
+
+    /// Resolves the interface method named by `method_symref` in `interface`, as described in
+    /// JVMS 5.4.3.4. Returns the class declaring the method along with the method's index within
+    /// that class; the declaring class is `java/lang/Object` for its public methods.
+    pub fn resolve_interface_method(interface: &Rc<Class>,
+                                    method_symref: &symref::Method)
+                                    -> Option<(Rc<Class>, usize)> {
+        if let Some(&index) = interface.method_indices.get(&method_symref.sig) {
+            return Some((interface.clone(), index));
+        }
+        if let Some(ref object) = interface.superclass {
+            if let Some(&index) = object.method_indices.get(&method_symref.sig) {
+                let method = object.methods[index].borrow();
+                if method.access_flags & model::info::method::ACC_PUBLIC != 0 &&
+                   method.access_flags & model::info::method::ACC_STATIC == 0 {
+                    return Some((object.clone(), index));
+                }
+            }
+        }
+        let methods = Self::maximally_specific_methods(&interface.superinterfaces,
+                                                       &method_symref.sig);
+        methods.iter()
+            .find(|&&(ref class, index)| !class.methods[index].borrow().is_abstract())
+            .or_else(|| methods.first())
+            .cloned()
+    }
+
+    /// Selects the implementation of method `method_index` of `interface` for `receiver`. Returns
+    /// `None` if no method can be selected, such as when more than one default method applies.
+    pub fn dispatch_interface(receiver: &Rc<Class>,
+                              interface: &Class,
+                              method_index: usize)
+                              -> Option<(Rc<Class>, usize)> {
+        receiver.itables
+            .get(&interface.symref.sig)
+            .and_then(|itable| itable[method_index].clone())
+            .map(|entry| {
+                let class = entry.class.unwrap_or_else(|| receiver.clone());
+                (class, entry.method_index)
+            })
+    }
+
+    /// Returns the call site of the `invokedynamic` instructions that refer to the
+    /// `InvokeDynamic` constant at `index`, linking it on first use.
+    pub fn link_call_site(&self,
+                          class_loader: &mut ClassLoader,
+                          index: u16)
+                          -> Result<Rc<CallSite>, Rc<RefCell<Scalar>>> {
+        if let Some(call_site) = self.call_sites.borrow().get(&index) {
+            return Ok(call_site.clone());
+        }
+        let call_site = match self.constant_pool[index] {
+            Some(ConstantPoolEntry::InvokeDynamic { bootstrap_method_attr_index, ref sig }) => {
+                let bootstrap_method = &self.bootstrap_methods[bootstrap_method_attr_index as
+                                                               usize];
+                Rc::new(CallSite::link(self, class_loader, index, bootstrap_method, sig)?)
+            }
+            _ => panic!("Item at index {} must be ConstantPoolEntry::InvokeDynamic", index),
+        };
+        self.call_sites.borrow_mut().insert(index, call_site.clone());
+        Ok(call_site)
+    }
+
+    /// Resolves the constant at `index` for `ldc`, `ldc_w` or `ldc2_w`, as described in JVMS
+    /// 5.4.3. Class, method type, method handle and dynamically-computed constants are resolved
+    /// on first use, and always produce the same value afterwards.
+    pub fn resolve_constant(&self,
+                            class_loader: &mut ClassLoader,
+                            index: u16)
+                            -> Result<Value, Rc<RefCell<Scalar>>> {
+        if let Some(value) = self.resolved_constants.borrow().get(&index) {
+            return Ok(value.clone());
+        }
+        let value = match self.constant_pool[index] {
+            Some(ConstantPoolEntry::ClassRef(ref symref)) => {
+                let class = class_loader.resolve_class_in(&self.defining_loader, &symref.sig)?;
+                Value::Reference(class_loader.get_mirror(&class))
+            }
+            Some(ConstantPoolEntry::MethodType(ref descriptor)) => {
+                // The classes a method type refers to are resolved along with it
+                let method_sig = sig::Method::new(String::new(), descriptor.clone());
+                for ty in method_sig.params.iter().chain(method_sig.return_type.iter()) {
+                    if let sig::Type::Reference(ref class_sig) = *ty {
+                        class_loader.resolve_class_in(&self.defining_loader, class_sig)?;
+                    }
+                }
+                Value::Reference(class_loader.new_method_type(descriptor))
+            }
+            Some(ConstantPoolEntry::MethodHandle(ref handle)) => {
+                class_loader.resolve_class_in(&self.defining_loader, &handle.member_class().sig)?;
+                let object = class_loader.new_method_handle(handle.clone(),
+                                                            self.defining_loader.clone());
+                Value::Reference(object)
+            }
+            Some(ConstantPoolEntry::Dynamic { bootstrap_method_attr_index, ref sig }) => {
+                let bootstrap_method = &self.bootstrap_methods[bootstrap_method_attr_index as
+                                                               usize];
+                call_site::resolve_dynamic_constant(self, class_loader, bootstrap_method, sig)?
+            }
+            _ => return Ok(self.constant_pool.resolve_literal(index, class_loader)),
+        };
+        self.resolved_constants.borrow_mut().insert(index, value.clone());
+        Ok(value)
+    }
+
+    pub fn get_method(&self, index: usize) -> &RefCell<Method> {
+        &self.methods[index]
     }
 
+    /// Resolves the instance field named by `field_symref` in this class or its superclasses,
+    /// throwing `NoSuchFieldError` if there is no such field.
+    pub fn resolve_instance_field(&self,
+                                  class_loader: &mut ClassLoader,
+                                  field_symref: &symref::Field)
+                                  -> Result<(), Rc<RefCell<Scalar>>> {
+        match self.find_instance_field(field_symref) {
+            Some(_) => Ok(()),
+            None => Err(no_such_field(class_loader, field_symref)),
+        }
+    }
+
+    /// Reads the static field named by `field_symref`, which may be declared by a superclass or
+    /// superinterface of this class.
+    pub fn get_field(&self,
+                     class_loader: &mut ClassLoader,
+                     field_symref: &symref::Field)
+                     -> Result<Value, Rc<RefCell<Scalar>>> {
+        let class = self.find_static_field(&field_symref.sig)
+            .ok_or_else(|| no_such_field(class_loader, field_symref))?;
+        class.initialize(class_loader)?;
+        let value = class.field_values.borrow()[&field_symref.sig].clone();
+        Ok(value)
+    }
+
+    /// Writes the static field named by `field_symref`, which may be declared by a superclass or
+    /// superinterface of this class.
     pub fn put_field(&self,
                      class_loader: &mut ClassLoader,
                      field_symref: &symref::Field,
-                     value: Value) {
-        self.initialize(class_loader);
-        let mut map_opt = self.field_values.borrow_mut();
-        let mut map = map_opt.as_mut().unwrap();
-        map.insert(field_symref.sig.clone(), value);
+                     value: Value)
+                     -> Result<(), Rc<RefCell<Scalar>>> {
+        let class = self.find_static_field(&field_symref.sig)
+            .ok_or_else(|| no_such_field(class_loader, field_symref))?;
+        class.initialize(class_loader)?;
+        class.field_values.borrow_mut().insert(field_symref.sig.clone(), value);
+        Ok(())
     }
 }
 
+/// Returns the `NoSuchFieldError` thrown when the field named by `field_symref` cannot be resolved.
+fn no_such_field(class_loader: &mut ClassLoader,
+                 field_symref: &symref::Field)
+                 -> Rc<RefCell<Scalar>> {
+    let message = format!("{}", field_symref);
+    exception::new(class_loader, "java/lang/NoSuchFieldError", Some(&message))
+}
+
 #[derive(Debug)]
 pub struct Method {
     pub symref: symref::Method,
@@ -194,63 +820,188 @@ impl Method {
         let method_code = {
             if info.access_flags & model::info::method::ACC_NATIVE != 0 {
                 MethodCode::UnresolvedNative
+            } else if info.access_flags & model::info::method::ACC_ABSTRACT != 0 {
+                MethodCode::Abstract
             } else {
                 info.attributes
                     .iter()
                     .fold(None, |code, attr| {
                         code.or(match *attr {
-                            model::info::Attribute::Code { max_locals, ref code, .. } => {
+                            model::info::Attribute::Code { max_locals,
+                                                           ref code,
+                                                           ref exception_table,
+                                                           .. } => {
                                 Some(MethodCode::Java {
-                                    max_locals: max_locals,
+                                    max_locals,
                                     code: code.clone(),
+                                    exception_table: exception_table.clone(),
                                 })
                             }
                             _ => None,
                         })
                     })
-                    .unwrap()
+                    .expect("methods that are neither native nor abstract have a Code attribute")
             }
         };
         Method {
-            symref: symref,
+            symref,
             access_flags: info.access_flags,
             code: method_code,
         }
     }
 
+    /// Creates a method of a lambda class, which invokes `handle` with the lambda's captured
+    /// arguments followed by its own.
+    pub fn new_lambda(symref: symref::Method,
+                      handle: MethodHandle,
+                      captured: Vec<sig::Field>,
+                      instantiated: sig::Method)
+                      -> Self {
+        Method {
+            symref,
+            access_flags: model::info::method::ACC_PUBLIC,
+            code: MethodCode::Lambda {
+                handle,
+                captured,
+                instantiated,
+            },
+        }
+    }
+
+    /// Returns true if the method is selected through the vtable: that is, it is neither static,
+    /// private nor an instance initializer.
+    pub fn is_virtual(&self) -> bool {
+        self.access_flags & (model::info::method::ACC_STATIC | model::info::method::ACC_PRIVATE) ==
+        0 && self.symref.sig.name != "<init>"
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.access_flags & model::info::method::ACC_ABSTRACT != 0
+    }
+
     pub fn bind_native(&mut self, lib: Rc<Library>) {
         self.code = MethodCode::Native(lib);
     }
 
+    pub fn bind_builtin(&mut self, builtin: Builtin) {
+        self.code = MethodCode::Builtin(builtin);
+    }
+
     pub fn invoke(&self,
                   class: &Class,
                   class_loader: &mut ClassLoader,
                   args_opt: Option<Vec<Value>>)
-                  -> Option<Value> {
+                  -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
         match self.code {
-            MethodCode::Native(ref lib) => native::invoke(&lib.clone(), &self.symref, args_opt),
+            MethodCode::Native(ref lib) => Ok(native::invoke(lib, &self.symref, args_opt)),
+            MethodCode::Builtin(builtin) => builtin(class_loader, args_opt.unwrap_or_default()),
             MethodCode::UnresolvedNative => panic!("{:?} native not loaded!", self.symref.sig),
-            MethodCode::Java { max_locals, ref code } => {
+            MethodCode::Abstract => {
+                let message = format!("{}", self.symref);
+                Err(exception::new(class_loader, "java/lang/AbstractMethodError", Some(&message)))
+            }
+            MethodCode::Lambda { ref handle, ref captured, ref instantiated } => {
+                let mut args = args_opt.unwrap();
+                let lambda = match args.remove(0) {
+                    Value::Reference(lambda) => lambda,
+                    v => panic!("Lambda receiver must be a reference, found {:?}", v),
+                };
+                let mut handle_args: Vec<Value> = captured.iter()
+                    .map(|field| {
+                        let field_symref = symref::Field {
+                            class: self.symref.class.clone(),
+                            sig: field.clone(),
+                        };
+                        lambda.borrow().get_field(&field_symref)
+                    })
+                    .collect();
+                // Cast the arguments and result to the instantiated types, and adapt them between
+                // those and the implementation types, which the call site checked when it was
+                // linked
+                let loader = &class.defining_loader;
+                let handle_sig = sig::Method::new(String::from("invoke"), handle.method_type());
+                let handle_params = &handle_sig.params[captured.len()..];
+                let params = instantiated.params.iter().zip(handle_params);
+                for (value, (from, to)) in args.into_iter().zip(params) {
+                    let value = boxing::cast(class_loader, loader, value, from)?;
+                    handle_args.push(boxing::convert(class_loader, value, from, to)?);
+                }
+                let result = handle.invoke(class_loader, loader, handle_args)?;
+                match (result, &handle_sig.return_type, &instantiated.return_type) {
+                    (Some(value), Some(from), Some(to)) => {
+                        let value = boxing::convert(class_loader, value, from, to)?;
+                        Ok(Some(boxing::cast(class_loader, loader, value, to)?))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            MethodCode::Java { max_locals, ref code, ref exception_table } => {
                 let max_locals = max_locals as usize;
                 let mut locals = Vec::with_capacity(max_locals);
                 if let Some(args) = args_opt {
+                    // Arguments are passed in consecutive local variables, with a long or double
+                    // taking up two, as described in JVMS 2.6.1
                     for value in args {
+                        let is_category2 = value.is_category2();
                         locals.push(Some(value));
+                        if is_category2 {
+                            locals.push(None);
+                        }
                     }
                 }
                 while locals.len() < max_locals {
                     locals.push(None);
                 }
-                let frame = frame::Frame::new(class, &*code, locals);
-                frame.run(class_loader)
+                class_loader.enter_frame()?;
+                let frame = frame::Frame::new(class, code, exception_table, locals);
+                let result = frame.run(class_loader);
+                class_loader.exit_frame();
+                result
             }
         }
     }
 }
 
+/// The initialization state of a class, as described in JVMS 5.5
+#[derive(Clone, Copy, Debug)]
+enum InitializationState {
+    Uninitialized,
+    InProgress,
+    Initialized,
+    /// Initialization failed, and the class cannot be used
+    Erroneous,
+}
+
+#[derive(Clone, Debug)]
+struct VtableEntry {
+    /// The class declaring the method, or `None` if it is declared by the class owning the vtable
+    class: Option<Rc<Class>>,
+    method_index: usize,
+}
+
+enum DefaultMethod {
+    Selected(VtableEntry),
+    /// No default method applies, so the entry is one of the abstract declarations
+    Abstract(VtableEntry),
+    Ambiguous,
+}
+
 #[derive(Debug)]
 enum MethodCode {
     Native(Rc<Library>),
+    /// A native method implemented by the VM itself
+    Builtin(Builtin),
     UnresolvedNative,
-    Java { max_locals: u16, code: Box<[u8]> },
+    Abstract,
+    Lambda {
+        handle: MethodHandle,
+        captured: Vec<sig::Field>,
+        /// The instantiated method type, which the erased arguments and result are cast to
+        instantiated: sig::Method,
+    },
+    Java {
+        max_locals: u16,
+        code: Box<[u8]>,
+        exception_table: Box<[ExceptionHandler]>,
+    },
 }
@@ -1,98 +1,314 @@
+use super::builtin;
 use super::class;
+use super::class_source::{self, ClassSource};
+use super::exception;
+use super::invoke::{self, MethodHandle};
 use super::native;
 use super::super::model;
 use super::sig;
+use super::string;
 use super::symref;
 use super::constant_pool::{ConstantPool, ConstantPoolEntry};
+use super::value::{Scalar, Value};
+use super::verifier;
 
 use lib::Library;
 
-use std::collections::HashMap;
-use std::io::Read;
-use std::fs::File;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
+/// An estimate of the most native stack a Java frame takes up, including the VM functions that
+/// invoke it. Unoptimized builds take up far more.
+const FRAME_STACK_SIZE: usize = if cfg!(debug_assertions) { 48 * 1024 } else { 8 * 1024 };
+
+/// The number of frames' worth of native stack kept free for constructing the
+/// `StackOverflowError` and handling it
+const RESERVED_FRAMES: usize = 8;
+
+/// The native stack size assumed until `set_stack_size` is called, which is the default for
+/// threads spawned by Rust
+const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// A `java.lang.ClassLoader` object, or `None` for the bootstrap class loader
+pub type Loader = Option<Rc<RefCell<Scalar>>>;
+
+/// Identifies a class loader by the address of its object, with 0 for the bootstrap class loader
+fn loader_id(loader: &Loader) -> usize {
+    loader.as_ref().map_or(0, |loader| loader.as_ptr() as usize)
+}
+
+/// Loads classes for the VM. The bootstrap class loader is implemented natively and searches
+/// the class sources, while other class loaders are Java objects whose `loadClass` method is
+/// called to load classes, as described in JVMS 5.3.
 #[derive(Debug)]
 pub struct ClassLoader {
-    class_paths: Vec<PathBuf>,
-    classes: HashMap<sig::Class, Rc<class::Class>>,
+    /// Where class files are looked up, in search order
+    sources: Vec<Box<dyn ClassSource>>,
+    /// Loaded classes, keyed by their name and a loader that initiated their loading. Runtime
+    /// classes are identified by their name together with their defining loader.
+    classes: HashMap<(sig::Class, usize), Rc<class::Class>>,
+    /// Classes whose superclass and interfaces are being resolved, keyed by their name and
+    /// defining loader
+    loading: HashSet<(sig::Class, usize)>,
+    /// Every class loader that has defined or initiated the loading of a class, keyed by id and
+    /// kept alive so that their ids stay unique
+    loaders: HashMap<usize, Rc<RefCell<Scalar>>>,
+    /// Maps the addresses of `java.lang.Class` objects to the classes they represent
+    mirrors: HashMap<usize, Rc<class::Class>>,
+    /// Maps the addresses of `java.lang.invoke.MethodHandle` objects to the handles they
+    /// represent, together with the defining loader of the class that created them. The objects
+    /// are held weakly, which keeps their addresses from being reused until their entries are
+    /// removed.
+    method_handles: HashMap<usize, (Weak<RefCell<Scalar>>, MethodHandle, Loader)>,
+    /// The number of Java frames being run
+    frame_depth: usize,
+    /// The number of Java frames that may be run before `StackOverflowError` is thrown
+    max_frame_depth: usize,
+    /// Whether a `StackOverflowError` is being constructed, whose constructors run beyond the
+    /// maximum depth
+    overflowing: bool,
 
     natives: Vec<Rc<Library>>,
-    unbound_natives: Vec<symref::Method>,
+    unbound_natives: Vec<(Rc<class::Class>, sig::Method)>,
 }
 
 impl ClassLoader {
+    /// Creates a class loader that searches the given directories and archives, in order.
     pub fn new(class_paths: Vec<PathBuf>) -> ClassLoader {
+        ClassLoader::with_sources(class_paths.into_iter().map(class_source::from_path).collect())
+    }
+
+    /// Creates a class loader that searches the given class sources, in order.
+    pub fn with_sources(sources: Vec<Box<dyn ClassSource>>) -> ClassLoader {
         ClassLoader {
-            class_paths: class_paths,
+            sources,
             classes: HashMap::new(),
+            loading: HashSet::new(),
+            loaders: HashMap::new(),
+            mirrors: HashMap::new(),
+            method_handles: HashMap::new(),
+            frame_depth: 0,
+            max_frame_depth: Self::max_frame_depth(DEFAULT_STACK_SIZE),
+            overflowing: false,
             natives: Vec::new(),
             unbound_natives: Vec::new(),
         }
     }
 
-    fn find_class_bytes(&self, name: &str) -> Result<Vec<u8>, &'static str> {
-        for path in &self.class_paths {
-            let path = {
-                let mut path = path.clone();
-                path.push(name);
-                path.set_extension("class");
-                path
-            };
+    /// The number of Java frames that fit in a native stack of `stack_size` bytes.
+    fn max_frame_depth(stack_size: usize) -> usize {
+        (stack_size / FRAME_STACK_SIZE).saturating_sub(RESERVED_FRAMES)
+    }
 
-            if path.exists() {
-                return File::open(path)
-                    .and_then(|mut file| {
-                        let mut buf = Vec::new();
-                        file.read_to_end(&mut buf)?;
-                        Ok(buf)
-                    })
-                    .or(Err("Could not load class"));
-            }
+    /// Limits the depth of Java frames to what fits in a native stack of `stack_size` bytes, which
+    /// should be the size of the stack of the thread running the VM.
+    pub fn set_stack_size(&mut self, stack_size: usize) {
+        self.max_frame_depth = Self::max_frame_depth(stack_size);
+    }
+
+    /// Records that a Java frame is about to be run, or returns a `StackOverflowError` if there
+    /// is no room for it. Each successful call must be followed by a call to `exit_frame`.
+    pub fn enter_frame(&mut self) -> Result<(), Rc<RefCell<Scalar>>> {
+        if self.frame_depth >= self.max_frame_depth && !self.overflowing {
+            self.overflowing = true;
+            let error = exception::new(self, "java/lang/StackOverflowError", None);
+            self.overflowing = false;
+            return Err(error);
         }
-        Err("Class not found")
+        self.frame_depth += 1;
+        Ok(())
     }
 
-    fn load_class_bytes(&mut self, sig: &sig::Class, bytes: Vec<u8>) -> Rc<class::Class> {
-        let class = model::Class::new(bytes);
-        self.load_class(sig, class)
+    /// Records that a Java frame entered with `enter_frame` has completed.
+    pub fn exit_frame(&mut self) {
+        self.frame_depth -= 1;
     }
 
-    fn load_class(&mut self, sig: &sig::Class, model: model::Class) -> Rc<class::Class> {
-        let rcp = ConstantPool::new(&model.constant_pool);
-        let sigs_match = {
-            if let Some(ConstantPoolEntry::ClassRef(ref symref)) = rcp[model.this_class] {
-                *sig == symref.sig
-            } else {
-                panic!("this_class({}) must point to a ClassRef", model.this_class);
+    /// Adds a class source, which is searched after all existing sources.
+    pub fn add_source(&mut self, source: Box<dyn ClassSource>) {
+        self.sources.push(source);
+    }
+
+    /// Defines the class called `name` from the contents of a class file with the bootstrap class
+    /// loader, without looking it up in any class source.
+    pub fn define_class(&mut self,
+                        name: &str,
+                        bytes: Vec<u8>)
+                        -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        self.define_class_in(&None, Some(name), bytes)
+    }
+
+    /// Defines a class with `loader` as its defining loader, as done by `ClassLoader.defineClass`.
+    /// If `name` is `None`, the class is named by its class file.
+    pub fn define_class_in(&mut self,
+                           loader: &Loader,
+                           name: Option<&str>,
+                           bytes: Vec<u8>)
+                           -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        let model = self.parse_class(name.unwrap_or("<unnamed>"), bytes)?;
+        let name = match name {
+            Some(name) => String::from(name),
+            None => {
+                let name = model.name();
+                match name {
+                    Some(name) => String::from(name),
+                    None => {
+                        let message = "Invalid this_class in class file <unnamed>";
+                        return Err(exception::new(self,
+                                                  "java/lang/ClassFormatError",
+                                                  Some(message)));
+                    }
+                }
             }
         };
-        if sigs_match {
-            let symref = symref::Class { sig: sig.clone() };
-            let (class, unbound_natives) = class::Class::new(symref.clone(), None, rcp, model);
-
-            for method in unbound_natives {
-                let method_symref = symref::Method {
-                    class: symref.clone(),
-                    sig: method.clone(),
-                };
-                let lib = self.natives
-                    .iter()
-                    .find(|lib| native::has_method(&lib, &method_symref.clone()));
-                if lib.is_some() {
-                    class.bind_native_method(method, lib.unwrap().clone());
-                } else {
-                    self.unbound_natives.push(method_symref);
+        let sig = sig::Class::Scalar(name.clone());
+        if loader.is_some() && name.starts_with("java/") {
+            let package = name[..name.rfind('/').unwrap()].replace('/', ".");
+            let message = format!("Prohibited package name: {}", package);
+            return Err(exception::new(self, "java/lang/SecurityException", Some(&message)));
+        }
+        if self.classes.contains_key(&(sig.clone(), loader_id(loader))) {
+            let message = format!("duplicate class definition for name: \"{}\"", name);
+            return Err(exception::new(self, "java/lang/LinkageError", Some(&message)));
+        }
+        self.load_class(&sig, model, loader.clone())
+    }
+
+    /// Returns the class called `sig` if `loader` has been recorded as an initiating loader for
+    /// it, as done by `ClassLoader.findLoadedClass`.
+    pub fn find_loaded_class(&self, loader: &Loader, sig: &sig::Class) -> Option<Rc<class::Class>> {
+        self.classes.get(&(sig.clone(), loader_id(loader))).cloned()
+    }
+
+    fn find_class_bytes(&self, name: &str) -> Option<io::Result<Vec<u8>>> {
+        self.sources.iter().filter_map(|source| source.find_class(name)).next()
+    }
+
+    /// Parses the class file of the class called `name`, throwing `ClassFormatError` if it is
+    /// malformed.
+    fn parse_class(&mut self,
+                   name: &str,
+                   bytes: Vec<u8>)
+                   -> Result<model::Class, Rc<RefCell<Scalar>>> {
+        model::Class::parse(bytes).map_err(|e| {
+            let message = format!("{} in class file {}", e, name);
+            exception::new(self, "java/lang/ClassFormatError", Some(&message))
+        })
+    }
+
+    fn load_class_bytes(&mut self,
+                        sig: &sig::Class,
+                        bytes: Vec<u8>,
+                        loader: Loader)
+                        -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        let class = self.parse_class(&format!("{}", sig), bytes)?;
+        self.load_class(sig, class, loader)
+    }
+
+    /// Creates the class `sig` from its class file, resolving its superclass and interfaces with
+    /// its defining loader `loader`, as described in JVMS 5.3.5, and verifies it. Throws
+    /// `ClassCircularityError` if the class is its own superclass or superinterface.
+    fn load_class(&mut self,
+                  sig: &sig::Class,
+                  model: model::Class,
+                  loader: Loader)
+                  -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        let key = (sig.clone(), loader_id(&loader));
+        if !self.loading.insert(key.clone()) {
+            let message = format!("{}", sig);
+            return Err(exception::new(self, "java/lang/ClassCircularityError", Some(&message)));
+        }
+        let result = self.create_class(sig, model, loader);
+        self.loading.remove(&key);
+        result
+    }
+
+    fn create_class(&mut self,
+                    sig: &sig::Class,
+                    model: model::Class,
+                    loader: Loader)
+                    -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        let rcp = ConstantPool::new(&model.constant_pool);
+        match rcp[model.this_class] {
+            Some(ConstantPoolEntry::ClassRef(ref symref)) => {
+                if *sig != symref.sig {
+                    let message = format!("{} (wrong name: {})", sig, symref.sig);
+                    return Err(exception::new(self,
+                                              "java/lang/NoClassDefFoundError",
+                                              Some(&message)));
                 }
             }
+            _ => return Err(self.bad_class_index(sig, "this_class", model.this_class)),
+        }
 
-            let rc = Rc::new(class);
-            self.classes.insert(sig.clone(), rc.clone());
-            rc
+        let superclass = if model.super_class == 0 {
+            None
+        } else if let Some(ConstantPoolEntry::ClassRef(ref symref)) = rcp[model.super_class] {
+            Some(self.resolve_class_in(&loader, &symref.sig)?)
         } else {
-            panic!("Class signature mismatch: given {:?}", sig);
+            return Err(self.bad_class_index(sig, "super_class", model.super_class));
+        };
+
+        let mut interfaces = Vec::with_capacity(model.interfaces.len());
+        for &index in model.interfaces.iter() {
+            if let Some(ConstantPoolEntry::ClassRef(ref symref)) = rcp[index] {
+                interfaces.push(self.resolve_class_in(&loader, &symref.sig)?);
+            } else {
+                return Err(self.bad_class_index(sig, "interfaces", index));
+            }
+        }
+
+        let symref = symref::Class { sig: sig.clone() };
+        let (class, unbound_natives) = class::Class::new(symref.clone(),
+                                                         superclass,
+                                                         interfaces,
+                                                         rcp,
+                                                         &model,
+                                                         loader.clone());
+        let rc = Rc::new(class);
+
+        self.record_loader(&loader);
+        // The class is registered before it is verified, so that the classes loaded while
+        // verifying it can refer to it
+        let key = (sig.clone(), loader_id(&loader));
+        self.classes.insert(key.clone(), rc.clone());
+        if let Err(exception) = verifier::verify(self, &rc, &model) {
+            self.classes.remove(&key);
+            return Err(exception);
+        }
+
+        for method in unbound_natives {
+            let method_symref = symref::Method {
+                class: symref.clone(),
+                sig: method.clone(),
+            };
+            if let Some(builtin) = builtin::find(&method_symref) {
+                rc.bind_builtin_method(method, builtin);
+                continue;
+            }
+            let lib = self.natives
+                .iter()
+                .find(|lib| native::has_method(lib, &method_symref));
+            match lib {
+                Some(lib) => rc.bind_native_method(method, lib.clone()),
+                None => self.unbound_natives.push((rc.clone(), method)),
+            }
         }
+        Ok(rc)
+    }
+
+    /// Creates the `ClassFormatError` for an `item` of the class file of `sig` that does not
+    /// refer to a class.
+    fn bad_class_index(&mut self,
+                       sig: &sig::Class,
+                       item: &str,
+                       index: u16)
+                       -> Rc<RefCell<Scalar>> {
+        let message = format!("Invalid {} index {} in class file {}", item, index, sig);
+        exception::new(self, "java/lang/ClassFormatError", Some(&message))
     }
 
     pub fn load_library(&mut self, path: &str) {
@@ -102,38 +318,261 @@ impl ClassLoader {
 
     pub fn bind_native_methods(&mut self) {
         let natives = self.natives.clone();
-        let mut to_bind: HashMap<symref::Method, Rc<Library>> = HashMap::new();
-        self.unbound_natives.retain(|method| {
-            let lib = natives.iter().find(|lib| native::has_method(lib, method));
-            if lib.is_some() {
-                to_bind.insert(method.clone(), lib.unwrap().clone());
-                true
-            } else {
-                false
+        self.unbound_natives.retain(|(class, method)| {
+            let method_symref = symref::Method {
+                class: class.symref.clone(),
+                sig: method.clone(),
+            };
+            match natives.iter().find(|lib| native::has_method(lib, &method_symref)) {
+                Some(lib) => {
+                    class.bind_native_method(method.clone(), lib.clone());
+                    false
+                }
+                None => true,
             }
         });
+    }
 
-        for (method, lib) in to_bind {
-            let class = self.resolve_class(&method.class.sig);
-            class.bind_native_method(method.sig, lib.clone());
+    /// Resolves a class that the VM itself depends on, such as `java/lang/Object` or the class of
+    /// an exception it throws, with the bootstrap class loader. These classes come with the
+    /// runtime, so failing to load one means the VM is broken rather than the program.
+    pub(crate) fn resolve_runtime_class(&mut self, sig: &sig::Class) -> Rc<class::Class> {
+        match self.find_bootstrap_class(sig) {
+            Ok(Some(class)) => class,
+            Ok(None) => panic!("could not load runtime class {}: Class not found", sig),
+            Err(e) => {
+                panic!("could not load runtime class {}: {}", sig, exception::describe(&e.borrow()))
+            }
         }
     }
 
-    pub fn resolve_class(&mut self, sig: &sig::Class) -> Rc<class::Class> {
-        if let Some(class) = self.classes.get(sig) {
+    /// Loads the class `sig` with the bootstrap class loader, returning `None` if no class source
+    /// has it, or the exception thrown if it could not be loaded.
+    pub fn find_bootstrap_class(&mut self,
+                                sig: &sig::Class)
+                                -> Result<Option<Rc<class::Class>>, Rc<RefCell<Scalar>>> {
+        if let Some(class) = self.classes.get(&(sig.clone(), 0)) {
             // the class has been resolved
-            return class.clone();
+            return Ok(Some(class.clone()));
         }
 
         match *sig {
             sig::Class::Scalar(ref name) => {
-                let class_bytes = self.find_class_bytes(name)
-                    .expect(&format!("could not load class {}", name));
-                self.load_class_bytes(sig, class_bytes)
+                match self.find_class_bytes(name) {
+                    Some(Ok(class_bytes)) => {
+                        self.load_class_bytes(sig, class_bytes, None).map(Some)
+                    }
+                    Some(Err(e)) => {
+                        let message = format!("{} ({})", name, e);
+                        Err(exception::new(self, "java/lang/NoClassDefFoundError", Some(&message)))
+                    }
+                    None => Ok(None),
+                }
             }
             sig::Class::Array(ref component) => {
-                Rc::new(class::Class::new_array(*component.clone()))
+                let component_class = match **component {
+                    sig::Type::Reference(ref component_sig) => {
+                        match self.find_bootstrap_class(component_sig)? {
+                            Some(component_class) => Some(component_class),
+                            None => return Ok(None),
+                        }
+                    }
+                    _ => None,
+                };
+                Ok(Some(self.new_array_class(sig, component_class)))
             }
         }
     }
+
+    /// Creates the array class `sig`, whose defining loader is that of its component class.
+    fn new_array_class(&mut self,
+                       sig: &sig::Class,
+                       component_class: Option<Rc<class::Class>>)
+                       -> Rc<class::Class> {
+        let component = match *sig {
+            sig::Class::Array(ref component) => (**component).clone(),
+            sig::Class::Scalar(_) => panic!("{} is not an array class", sig),
+        };
+        let loader = component_class.as_ref()
+            .and_then(|class| class.get_defining_loader().clone());
+        let object_sig = sig::Class::Scalar(String::from("java/lang/Object"));
+        let object = self.resolve_runtime_class(&object_sig);
+        let cloneable_sig = sig::Class::Scalar(String::from("java/lang/Cloneable"));
+        let serializable_sig = sig::Class::Scalar(String::from("java/io/Serializable"));
+        let interfaces = vec![self.resolve_runtime_class(&cloneable_sig),
+                              self.resolve_runtime_class(&serializable_sig)];
+        let class = Rc::new(class::Class::new_array(component,
+                                                    component_class,
+                                                    object,
+                                                    interfaces,
+                                                    loader.clone()));
+        self.classes.insert((sig.clone(), loader_id(&loader)), class.clone());
+        class
+    }
+
+    /// Resolves the class `sig` referenced from a class whose defining loader is `loader`, as
+    /// described in JVMS 5.4.3.1. Classes are loaded by calling the loader's `loadClass` method,
+    /// unless it is the bootstrap class loader, and `loader` is recorded as an initiating loader
+    /// of the result.
+    pub fn resolve_class_in(&mut self,
+                            loader: &Loader,
+                            sig: &sig::Class)
+                            -> Result<Rc<class::Class>, Rc<RefCell<Scalar>>> {
+        let loader_object = match *loader {
+            None => {
+                return match self.find_bootstrap_class(sig)? {
+                    Some(class) => Ok(class),
+                    None => {
+                        let message = format!("{}", sig);
+                        Err(exception::new(self, "java/lang/NoClassDefFoundError", Some(&message)))
+                    }
+                };
+            }
+            Some(ref loader_object) => loader_object.clone(),
+        };
+        if let Some(class) = self.find_loaded_class(loader, sig) {
+            return Ok(class);
+        }
+
+        let class = match *sig {
+            sig::Class::Scalar(ref name) => {
+                let load_class_symref = symref::Method {
+                    class: symref::Class {
+                        sig: sig::Class::Scalar(String::from("java/lang/ClassLoader")),
+                    },
+                    sig: sig::Method::new(String::from("loadClass"),
+                                          String::from("(Ljava/lang/String;)Ljava/lang/Class;")),
+                };
+                let args = vec![Value::Reference(loader_object),
+                                string::new(self, &name.replace('/', "."))];
+                let result = match invoke::invoke_virtual(self, &None, &load_class_symref, args) {
+                    Ok(result) => result,
+                    Err(exception) => {
+                        // A class that the loader cannot find is reported as NoClassDefFoundError
+                        let not_found_sig =
+                            sig::Class::Scalar(String::from("java/lang/ClassNotFoundException"));
+                        let not_found = self.resolve_runtime_class(&not_found_sig);
+                        if !exception.borrow().get_class().is_subclass_of(&not_found) {
+                            return Err(exception);
+                        }
+                        let message = format!("{}", sig);
+                        return Err(exception::new(self,
+                                                  "java/lang/NoClassDefFoundError",
+                                                  Some(&message)));
+                    }
+                };
+                match result {
+                    Some(Value::Reference(ref mirror)) => {
+                        let class = self.get_mirror_class(mirror);
+                        if class.symref.sig != *sig {
+                            let message = format!("{} (wrong name: {})", sig, class.symref.sig);
+                            return Err(exception::new(self,
+                                                      "java/lang/NoClassDefFoundError",
+                                                      Some(&message)));
+                        }
+                        class
+                    }
+                    _ => {
+                        let message = format!("{}", sig);
+                        return Err(exception::new(self,
+                                                  "java/lang/NoClassDefFoundError",
+                                                  Some(&message)));
+                    }
+                }
+            }
+            sig::Class::Array(ref component) => {
+                match **component {
+                    sig::Type::Reference(ref component_sig) => {
+                        let component_class = self.resolve_class_in(loader, component_sig)?;
+                        let component_loader = component_class.get_defining_loader().clone();
+                        match self.find_loaded_class(&component_loader, sig) {
+                            Some(class) => class,
+                            None => self.new_array_class(sig, Some(component_class)),
+                        }
+                    }
+                    _ => self.resolve_runtime_class(sig),
+                }
+            }
+        };
+        self.record_loader(loader);
+        self.classes.insert((sig.clone(), loader_id(loader)), class.clone());
+        Ok(class)
+    }
+
+    /// Keeps `loader` alive for as long as classes are recorded under its id, so that no other
+    /// loader can be allocated at its address and take over its classes.
+    fn record_loader(&mut self, loader: &Loader) {
+        if let Some(ref object) = *loader {
+            self.loaders.entry(loader_id(loader)).or_insert_with(|| object.clone());
+        }
+    }
+
+    /// Returns the `java.lang.Class` object representing `class`, creating it on first use.
+    pub fn get_mirror(&mut self, class: &Rc<class::Class>) -> Rc<RefCell<Scalar>> {
+        if let Some(mirror) = class.get_mirror() {
+            return mirror;
+        }
+        let class_sig = sig::Class::Scalar(String::from("java/lang/Class"));
+        let class_class = self.resolve_runtime_class(&class_sig);
+        let mirror = Rc::new(RefCell::new(Scalar::new(class_class)));
+        class.set_mirror(mirror.clone());
+        self.mirrors.insert(mirror.as_ptr() as usize, class.clone());
+        mirror
+    }
+
+    /// Returns the class represented by a `java.lang.Class` object.
+    pub fn get_mirror_class(&self, mirror: &Rc<RefCell<Scalar>>) -> Rc<class::Class> {
+        self.mirrors
+            .get(&(mirror.as_ptr() as usize))
+            .cloned()
+            .unwrap_or_else(|| panic!("{:?} is not a class mirror", mirror))
+    }
+
+    /// Creates a `java.lang.invoke.MethodType` object for a method descriptor.
+    pub fn new_method_type(&mut self, descriptor: &str) -> Rc<RefCell<Scalar>> {
+        let method_type_sig = sig::Class::Scalar(String::from("java/lang/invoke/MethodType"));
+        let method_type_class = self.resolve_runtime_class(&method_type_sig);
+        let string_ty = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+        let descriptor_symref = symref::Field {
+            class: method_type_class.symref.clone(),
+            sig: sig::Field::new(String::from("descriptor"), string_ty),
+        };
+        let descriptor = string::new(self, descriptor);
+        let mut method_type = Scalar::new(method_type_class);
+        method_type.put_field(&descriptor_symref, descriptor);
+        Rc::new(RefCell::new(method_type))
+    }
+
+    /// Creates a `java.lang.invoke.MethodHandle` object for `handle`, which resolves classes with
+    /// `loader` when it is invoked.
+    pub fn new_method_handle(&mut self,
+                             handle: MethodHandle,
+                             loader: Loader)
+                             -> Rc<RefCell<Scalar>> {
+        let handle_sig = sig::Class::Scalar(String::from("java/lang/invoke/MethodHandle"));
+        let handle_class = self.resolve_runtime_class(&handle_sig);
+        let method_type_sig = sig::Class::Scalar(String::from("java/lang/invoke/MethodType"));
+        let type_symref = symref::Field {
+            class: handle_class.symref.clone(),
+            sig: sig::Field::new(String::from("type"), sig::Type::Reference(method_type_sig)),
+        };
+        let method_type = self.new_method_type(&handle.method_type());
+        let mut object = Scalar::new(handle_class);
+        object.put_field(&type_symref, Value::Reference(method_type));
+        let object = Rc::new(RefCell::new(object));
+        // Forget the handles whose objects have been dropped
+        self.method_handles.retain(|_, (weak, _, _)| weak.upgrade().is_some());
+        self.method_handles
+            .insert(object.as_ptr() as usize, (Rc::downgrade(&object), handle, loader));
+        object
+    }
+
+    /// Returns the handle represented by a `java.lang.invoke.MethodHandle` object, and the loader
+    /// it resolves classes with.
+    pub fn get_method_handle(&self, object: &Rc<RefCell<Scalar>>) -> (MethodHandle, Loader) {
+        self.method_handles
+            .get(&(object.as_ptr() as usize))
+            .map(|(_, handle, loader)| (handle.clone(), loader.clone()))
+            .unwrap_or_else(|| panic!("{:?} is not a method handle", object))
+    }
 }
@@ -0,0 +1,148 @@
+use super::archive::Archive;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// A place that class files can be loaded from, such as a directory or a JAR file. A class
+/// loader searches its sources in order for the first one that has the requested class.
+pub trait ClassSource: fmt::Debug {
+    /// Returns the contents of the class file for the class called `name`, such as
+    /// `java/lang/Object`, or `None` if this source does not have the class.
+    fn find_class(&self, name: &str) -> Option<io::Result<Vec<u8>>>;
+}
+
+/// Creates the source for a class path entry, which is either a directory or an archive.
+pub fn from_path(path: PathBuf) -> Box<dyn ClassSource> {
+    if path.is_dir() {
+        Box::new(DirectorySource::new(path))
+    } else {
+        Box::new(ArchiveSource::new(path))
+    }
+}
+
+/// A directory containing class files, laid out by package
+#[derive(Debug)]
+pub struct DirectorySource {
+    path: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(path: PathBuf) -> Self {
+        DirectorySource { path }
+    }
+}
+
+impl ClassSource for DirectorySource {
+    fn find_class(&self, name: &str) -> Option<io::Result<Vec<u8>>> {
+        let path = self.path.join(format!("{}.class", name));
+        if !path.is_file() {
+            return None;
+        }
+        Some(File::open(path).and_then(|mut file| {
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            Ok(buf)
+        }))
+    }
+}
+
+/// A JAR or ZIP archive containing class files. The archive is opened the first time a class is
+/// looked up and stays open afterwards. The entries of its manifest's `Class-Path` attribute are
+/// searched after the archive itself.
+#[derive(Debug)]
+pub struct ArchiveSource {
+    path: PathBuf,
+    /// The archives whose `Class-Path` led to this one, which are skipped to avoid cycles
+    referrers: Vec<PathBuf>,
+    opened: RefCell<Option<OpenedArchive>>,
+}
+
+#[derive(Debug)]
+struct OpenedArchive {
+    /// The archive, or `None` if it could not be read
+    archive: Option<Archive>,
+    class_path: Vec<Box<dyn ClassSource>>,
+}
+
+impl ArchiveSource {
+    pub fn new(path: PathBuf) -> Self {
+        ArchiveSource::with_referrers(path, vec![])
+    }
+
+    fn with_referrers(path: PathBuf, referrers: Vec<PathBuf>) -> Self {
+        ArchiveSource {
+            path,
+            referrers,
+            opened: RefCell::new(None),
+        }
+    }
+
+    fn open(&self) -> OpenedArchive {
+        let archive = Archive::open(&self.path).ok();
+        let mut class_path: Vec<Box<dyn ClassSource>> = Vec::new();
+        if let Some(Ok(Some(manifest))) = archive.as_ref().map(|archive| archive.manifest()) {
+            let base = self.path.parent().unwrap_or_else(|| Path::new(""));
+            let mut referrers = self.referrers.clone();
+            referrers.push(self.path.clone());
+            for entry in manifest.get("Class-Path").unwrap_or("").split_whitespace() {
+                let path = base.join(entry);
+                if referrers.contains(&path) {
+                    continue;
+                }
+                if path.is_dir() {
+                    class_path.push(Box::new(DirectorySource::new(path)));
+                } else {
+                    class_path.push(Box::new(ArchiveSource::with_referrers(path,
+                                                                           referrers.clone())));
+                }
+            }
+        }
+        OpenedArchive {
+            archive,
+            class_path,
+        }
+    }
+}
+
+impl ClassSource for ArchiveSource {
+    fn find_class(&self, name: &str) -> Option<io::Result<Vec<u8>>> {
+        if self.opened.borrow().is_none() {
+            *self.opened.borrow_mut() = Some(self.open());
+        }
+
+        let opened = self.opened.borrow();
+        let opened = opened.as_ref().unwrap();
+        let entry_name = format!("{}.class", name);
+        match opened.archive {
+            Some(ref archive) if archive.contains(&entry_name) => Some(archive.read(&entry_name)),
+            _ => opened.class_path.iter().filter_map(|source| source.find_class(name)).next(),
+        }
+    }
+}
+
+/// Class files held in memory, such as bytecode generated at runtime
+#[derive(Debug, Default)]
+pub struct MemorySource {
+    classes: HashMap<String, Vec<u8>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        MemorySource { classes: HashMap::new() }
+    }
+
+    /// Adds the class file for the class called `name`, replacing any previous one.
+    pub fn insert(&mut self, name: &str, bytes: Vec<u8>) {
+        self.classes.insert(String::from(name), bytes);
+    }
+}
+
+impl ClassSource for MemorySource {
+    fn find_class(&self, name: &str) -> Option<io::Result<Vec<u8>>> {
+        self.classes.get(name).map(|bytes| Ok(bytes.clone()))
+    }
+}
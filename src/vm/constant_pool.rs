@@ -1,10 +1,11 @@
-use std::cell::RefCell;
+use std::cell::Cell;
 use std::ops::Index;
 use std::num::Wrapping;
-use std::rc::Rc;
 
 use super::class_loader::ClassLoader;
-use super::value::{Array, Scalar, Value};
+use super::invoke::MethodHandle;
+use super::string;
+use super::value::Value;
 use super::symref;
 use super::sig;
 
@@ -15,14 +16,38 @@ pub enum ConstantPoolEntry {
     Literal(Value),
     ClassRef(symref::Class),
     MethodRef(symref::Method),
+    InterfaceMethodRef(symref::Method),
     FieldRef(symref::Field),
     StringValue(String),
-    UnresolvedString(u16),
+    /// A string literal, with the index of its `Utf8` constant and its UTF-16 code units
+    UnresolvedString {
+        string_index: u16,
+        chars: Box<[u16]>,
+    },
+    MethodHandle(MethodHandle),
+    /// A method descriptor
+    MethodType(String),
+    /// A dynamically-computed constant, resolved by `ldc` on first use
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        sig: sig::Field,
+    },
+    InvokeDynamic {
+        bootstrap_method_attr_index: u16,
+        sig: sig::Method,
+    },
+    /// The name of a module, only used by the `Module` attributes of `module-info` classes
+    Module(String),
+    /// The internal name of a package exported or opened by a module
+    Package(String),
 }
 
 #[derive(Debug)]
 pub struct ConstantPool {
     entries: Vec<Option<ConstantPoolEntry>>,
+    /// The vtable index each `MethodRef` resolved to when first invoked by `invokevirtual`, so
+    /// that later invocations can index the receiver's vtable without resolving it again
+    vtable_indices: Vec<Cell<Option<usize>>>,
 }
 
 impl Index<u16> for ConstantPool {
@@ -34,7 +59,7 @@ impl Index<u16> for ConstantPool {
 }
 
 impl ConstantPool {
-    pub fn new(constant_pool: &Box<[Constant]>) -> Self {
+    pub fn new(constant_pool: &[Constant]) -> Self {
         let mut entries = vec![];
         for constant in constant_pool.iter() {
             let entry = match *constant {
@@ -46,6 +71,10 @@ impl ConstantPool {
                     let symref = Self::force_method_ref(constant_pool, constant);
                     Some(ConstantPoolEntry::MethodRef(symref))
                 }
+                Constant::InterfaceMethodref { .. } => {
+                    let symref = Self::force_method_ref(constant_pool, constant);
+                    Some(ConstantPoolEntry::InterfaceMethodRef(symref))
+                }
                 Constant::Fieldref { .. } => {
                     let symref = Self::force_field_ref(constant_pool, constant);
                     Some(ConstantPoolEntry::FieldRef(symref))
@@ -67,18 +96,65 @@ impl ConstantPool {
                     Some(ConstantPoolEntry::StringValue(symref.clone()))
                 }
                 Constant::String { string_index } => {
-                    Some(ConstantPoolEntry::UnresolvedString(string_index))
+                    let chars = match constant_pool[(string_index - 1) as usize] {
+                        Constant::Utf8 { ref chars, .. } => chars.clone(),
+                        ref info => panic!("Constant {:?} must be Constant::Utf8", info),
+                    };
+                    Some(ConstantPoolEntry::UnresolvedString {
+                        string_index,
+                        chars,
+                    })
+                }
+                Constant::MethodHandle { .. } => {
+                    let handle = Self::force_method_handle(constant_pool, constant);
+                    Some(ConstantPoolEntry::MethodHandle(handle))
+                }
+                Constant::MethodType { descriptor_index } => {
+                    let descriptor =
+                        Self::force_string(&constant_pool[(descriptor_index - 1) as usize]);
+                    Some(ConstantPoolEntry::MethodType(descriptor.clone()))
                 }
-                _ => {
-                    panic!("Unimplemented constant: {:#?}", constant);
+                Constant::Dynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                    let (name, descriptor) =
+                        Self::force_name_and_type(constant_pool,
+                                                  &constant_pool[(name_and_type_index - 1) as
+                                                                 usize]);
+                    let ty = sig::Type::new(descriptor)
+                        .unwrap_or_else(|| panic!("Invalid field descriptor {}", descriptor));
+                    Some(ConstantPoolEntry::Dynamic {
+                        bootstrap_method_attr_index,
+                        sig: sig::Field::new(name.clone(), ty),
+                    })
+                }
+                Constant::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => {
+                    let (name, descriptor) =
+                        Self::force_name_and_type(constant_pool,
+                                                  &constant_pool[(name_and_type_index - 1) as
+                                                                 usize]);
+                    Some(ConstantPoolEntry::InvokeDynamic {
+                        bootstrap_method_attr_index,
+                        sig: sig::Method::new(name.clone(), descriptor.clone()),
+                    })
+                }
+                Constant::Module { name_index } => {
+                    let name = Self::force_string(&constant_pool[(name_index - 1) as usize]);
+                    Some(ConstantPoolEntry::Module(name.clone()))
+                }
+                Constant::Package { name_index } => {
+                    let name = Self::force_string(&constant_pool[(name_index - 1) as usize]);
+                    Some(ConstantPoolEntry::Package(name.clone()))
                 }
             };
             entries.push(entry);
         }
-        ConstantPool { entries: entries }
+        let vtable_indices = entries.iter().map(|_| Cell::new(None)).collect();
+        ConstantPool {
+            entries,
+            vtable_indices,
+        }
     }
 
-    fn force_class_ref(constant_pool: &Box<[Constant]>, info: &Constant) -> symref::Class {
+    fn force_class_ref(constant_pool: &[Constant], info: &Constant) -> symref::Class {
         match *info {
             Constant::Class { name_index } => {
                 let name = Self::force_string(&constant_pool[(name_index - 1) as usize]);
@@ -90,26 +166,28 @@ impl ConstantPool {
         }
     }
 
-    fn force_method_ref(constant_pool: &Box<[Constant]>, info: &Constant) -> symref::Method {
+    fn force_method_ref(constant_pool: &[Constant], info: &Constant) -> symref::Method {
         match *info {
-            Constant::Methodref { class_index, name_and_type_index } => {
+            Constant::Methodref { class_index, name_and_type_index } |
+            Constant::InterfaceMethodref { class_index, name_and_type_index } => {
                 let class = Self::force_class_ref(constant_pool,
                                                   &constant_pool[(class_index - 1) as usize]);
                 let (name, descriptor) =
                     Self::force_name_and_type(constant_pool,
                                               &constant_pool[(name_and_type_index - 1) as usize]);
                 symref::Method {
-                    class: class,
+                    class,
                     sig: sig::Method::new(name.clone(), descriptor.clone()),
                 }
             }
             _ => {
-                panic!("Constant {:?} must be Constant::Class", info);
+                panic!("Constant {:?} must be Constant::Methodref or Constant::InterfaceMethodref",
+                       info);
             }
         }
     }
 
-    fn force_field_ref(constant_pool: &Box<[Constant]>, info: &Constant) -> symref::Field {
+    fn force_field_ref(constant_pool: &[Constant], info: &Constant) -> symref::Field {
         match *info {
             Constant::Fieldref { class_index, name_and_type_index } => {
                 let class = Self::force_class_ref(constant_pool,
@@ -121,7 +199,7 @@ impl ConstantPool {
                 let (name, descriptor) = (name.clone(), descriptor.clone());
                 let ty = sig::Type::new(&descriptor);
                 symref::Field {
-                    class: class,
+                    class,
                     sig: sig::Field::new(name, ty.unwrap()),
                 }
             }
@@ -131,16 +209,54 @@ impl ConstantPool {
         }
     }
 
+    fn force_method_handle(constant_pool: &[Constant], info: &Constant) -> MethodHandle {
+        match *info {
+            Constant::MethodHandle { reference_kind, reference_index } => {
+                let reference = &constant_pool[(reference_index - 1) as usize];
+                match reference_kind {
+                    1 => MethodHandle::GetField(Self::force_field_ref(constant_pool, reference)),
+                    2 => MethodHandle::GetStatic(Self::force_field_ref(constant_pool, reference)),
+                    3 => MethodHandle::PutField(Self::force_field_ref(constant_pool, reference)),
+                    4 => MethodHandle::PutStatic(Self::force_field_ref(constant_pool, reference)),
+                    5 => {
+                        MethodHandle::InvokeVirtual(Self::force_method_ref(constant_pool,
+                                                                           reference))
+                    }
+                    6 => {
+                        MethodHandle::InvokeStatic(Self::force_method_ref(constant_pool,
+                                                                          reference))
+                    }
+                    7 => {
+                        MethodHandle::InvokeSpecial(Self::force_method_ref(constant_pool,
+                                                                           reference))
+                    }
+                    8 => {
+                        MethodHandle::NewInvokeSpecial(Self::force_method_ref(constant_pool,
+                                                                              reference))
+                    }
+                    9 => {
+                        MethodHandle::InvokeInterface(Self::force_method_ref(constant_pool,
+                                                                             reference))
+                    }
+                    _ => panic!("Unknown method handle reference kind {}", reference_kind),
+                }
+            }
+            _ => {
+                panic!("Constant {:?} must be Constant::MethodHandle", info);
+            }
+        }
+    }
+
     fn force_string(info: &Constant) -> &String {
         match *info {
-            Constant::Utf8 { length, ref value } => value,
+            Constant::Utf8 { ref value, .. } => value,
             _ => {
                 panic!("Constant {:?} must be Constant::Utf8", info);
             }
         }
     }
 
-    fn force_name_and_type<'a>(constant_pool: &'a Box<[Constant]>,
+    fn force_name_and_type<'a>(constant_pool: &'a [Constant],
                                info: &'a Constant)
                                -> (&'a String, &'a String) {
         match *info {
@@ -159,50 +275,8 @@ impl ConstantPool {
     pub fn resolve_literal(&self, index: u16, class_loader: &mut ClassLoader) -> Value {
         match self.entries[(index - 1) as usize] {
             Some(ConstantPoolEntry::Literal(ref value)) => value.clone(),
-            Some(ConstantPoolEntry::UnresolvedString(value)) => {
-                let array_sig = sig::Class::Array(Box::new(sig::Type::Char));
-                let array_class = class_loader.resolve_class(&array_sig);
-
-                let chars = {
-                    if let Some(ConstantPoolEntry::StringValue(ref string)) =
-                        self.entries[(value - 1) as usize] {
-                        string.clone().into_bytes()
-                    } else {
-                        panic!("UnresolvedString {} must point to a StringValue", value);
-                    }
-                };
-
-                // CONVERT TO UTF-8
-                let mut array = Array::new(array_class, chars.len() as i32);
-                for (i, c) in chars.iter().enumerate() {
-                    array.insert(i, Value::Int(Wrapping(*c as i32)));
-                }
-                let array_rc = Rc::new(RefCell::new(array));
-
-                let string_sig = sig::Class::Scalar(String::from("java/lang/String"));
-                let string_symref = symref::Class { sig: string_sig.clone() };
-                let string_class = class_loader.resolve_class(&string_sig);
-                let string = Scalar::new(string_class.clone());
-                let string_rc = Rc::new(RefCell::new(string));
-
-                let constructor_sig = sig::Method {
-                    name: String::from("<init>"),
-                    params: vec![sig::Type::Reference(array_sig.clone())],
-                    return_type: None,
-                };
-                let constructor_symref = symref::Method {
-                    class: string_symref,
-                    sig: constructor_sig,
-                };
-                let constructor = string_class.find_method(class_loader, &constructor_symref);
-                let args = Some(vec![Value::Reference(string_rc.clone()),
-                                     Value::ArrayReference(array_rc)]);
-                let result = constructor.borrow().invoke(string_class.as_ref(), class_loader, args);
-                match result {
-                    None => (),
-                    Some(_) => panic!("<init> returned a value"),
-                }
-                Value::Reference(string_rc)
+            Some(ConstantPoolEntry::UnresolvedString { ref chars, .. }) => {
+                string::from_utf16(class_loader, chars)
             }
             ref value => {
                 panic!("Item at index {} must be ConstantPoolEntry::Literal found {:?}",
@@ -212,6 +286,25 @@ impl ConstantPool {
         }
     }
 
+    /// Returns the entry at `index`, or `None` if there is no entry at `index`.
+    pub fn get(&self, index: u16) -> Option<&ConstantPoolEntry> {
+        if index == 0 {
+            return None;
+        }
+        self.entries.get((index - 1) as usize).and_then(Option::as_ref)
+    }
+
+    /// Returns the vtable index recorded by `set_vtable_index` for the `MethodRef` at `index`.
+    pub fn get_vtable_index(&self, index: u16) -> Option<usize> {
+        self.vtable_indices[(index - 1) as usize].get()
+    }
+
+    /// Records that the `MethodRef` at `index` resolved to the method at `vtable_index` in the
+    /// vtable of its class and its subclasses.
+    pub fn set_vtable_index(&self, index: u16, vtable_index: usize) {
+        self.vtable_indices[(index - 1) as usize].set(Some(vtable_index));
+    }
+
     pub fn lookup_utf8(&self, index: u16) -> &String {
         match self.entries[(index - 1) as usize] {
             Some(ConstantPoolEntry::StringValue(ref string)) => string,
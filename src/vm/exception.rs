@@ -0,0 +1,87 @@
+use super::class_loader::ClassLoader;
+use super::sig;
+use super::string;
+use super::symref;
+use super::value::{Scalar, Value};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Allocates and constructs an exception of the given class, such as
+/// `java/lang/NullPointerException`, so that it can be thrown by the VM.
+pub fn new(class_loader: &mut ClassLoader,
+           class_name: &str,
+           message: Option<&str>)
+           -> Rc<RefCell<Scalar>> {
+    match message {
+        None => construct(class_loader, class_name, vec![], vec![]),
+        Some(message) => {
+            let string_sig = sig::Class::Scalar(String::from("java/lang/String"));
+            let string_ty = sig::Type::Reference(string_sig);
+            let message = string::new(class_loader, message);
+            construct(class_loader, class_name, vec![string_ty], vec![message])
+        }
+    }
+}
+
+/// Allocates and constructs an exception of the given class that wraps `cause`, such as the
+/// `java/lang/ExceptionInInitializerError` thrown when a static initializer fails.
+pub fn new_with_cause(class_loader: &mut ClassLoader,
+                      class_name: &str,
+                      cause: Rc<RefCell<Scalar>>)
+                      -> Rc<RefCell<Scalar>> {
+    let throwable_sig = sig::Class::Scalar(String::from("java/lang/Throwable"));
+    let throwable_ty = sig::Type::Reference(throwable_sig);
+    construct(class_loader,
+              class_name,
+              vec![throwable_ty],
+              vec![Value::Reference(cause)])
+}
+
+fn construct(class_loader: &mut ClassLoader,
+             class_name: &str,
+             params: Vec<sig::Type>,
+             mut args: Vec<Value>)
+             -> Rc<RefCell<Scalar>> {
+    let class_sig = sig::Class::Scalar(String::from(class_name));
+    let class = class_loader.resolve_runtime_class(&class_sig);
+    if class.initialize(class_loader).is_err() {
+        panic!("{} could not be initialized", class_name);
+    }
+    let exception = Rc::new(RefCell::new(Scalar::new(class.clone())));
+    args.insert(0, Value::Reference(exception.clone()));
+
+    let constructor_symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from("<init>"),
+            params,
+            return_type: None,
+        },
+    };
+    let constructor = class.find_method(&constructor_symref);
+    let result = constructor.borrow().invoke(class.as_ref(), class_loader, Some(args));
+    match result {
+        Ok(None) => (),
+        Ok(Some(_)) => panic!("<init> returned a value"),
+        Err(_) => panic!("{}.<init> threw an exception", class_name),
+    }
+    exception
+}
+
+/// Describes an exception the way `Throwable.toString` does, as the class name followed by the
+/// detail message, if there is one.
+pub fn describe(exception: &Scalar) -> String {
+    let class_name = format!("{}", exception.get_class().symref.sig).replace('/', ".");
+    let string_ty = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+    let message_symref = symref::Field {
+        class: symref::Class { sig: sig::Class::Scalar(String::from("java/lang/Throwable")) },
+        sig: sig::Field::new(String::from("message"), string_ty),
+    };
+    match exception.get_field(&message_symref) {
+        Value::Reference(message) => {
+            format!("{}: {}", class_name, string::to_rust_string(&message.borrow()))
+        }
+        _ => class_name,
+    }
+}
@@ -1,10 +1,13 @@
 use super::class::Class;
 use super::class_loader::ClassLoader;
 use super::constant_pool::ConstantPoolEntry;
+use super::exception;
+use super::invoke;
 use super::opcode;
 use super::sig;
 use super::value;
 use super::value::Value;
+use super::super::model::info::attribute::ExceptionHandler;
 
 use std::cell::RefCell;
 use std::f32;
@@ -16,6 +19,7 @@ use std::rc::Rc;
 pub struct Frame<'a> {
     class: &'a Class,
     code: &'a [u8],
+    exception_table: &'a [ExceptionHandler],
     pc: u16,
     local_variables: Vec<Option<Value>>,
     operand_stack: Vec<Value>,
@@ -33,12 +37,17 @@ impl<'a> fmt::Debug for Frame<'a> {
 }
 
 impl<'a> Frame<'a> {
-    pub fn new(class: &'a Class, code: &'a [u8], local_variables: Vec<Option<Value>>) -> Self {
+    pub fn new(class: &'a Class,
+               code: &'a [u8],
+               exception_table: &'a [ExceptionHandler],
+               local_variables: Vec<Option<Value>>)
+               -> Self {
         Frame {
-            class: class,
-            code: code,
+            class,
+            code,
+            exception_table,
             pc: 0,
-            local_variables: local_variables,
+            local_variables,
             operand_stack: vec![],
         }
     }
@@ -63,7 +72,60 @@ impl<'a> Frame<'a> {
         self.operand_stack.drain(start..).collect()
     }
 
-    pub fn run(mut self, class_loader: &mut ClassLoader) -> Option<Value> {
+    /// Pops the values at the top of the operand stack that take up `slots` units of depth, with
+    /// `long` and `double` values taking up two, as done by the stack instructions of JVMS 6.5.
+    fn pop_slots(&mut self, slots: usize) -> Vec<Value> {
+        let mut start = self.operand_stack.len();
+        let mut size = 0;
+        while size < slots {
+            start -= 1;
+            size += if self.operand_stack[start].is_category2() { 2 } else { 1 };
+        }
+        if size != slots {
+            panic!("Cannot take {} slots from the operand stack without splitting {:?}",
+                   slots,
+                   self.operand_stack[start]);
+        }
+        self.operand_stack.drain(start..).collect()
+    }
+
+    /// Finds the handler for an exception thrown by the instruction at `pc`, as described in
+    /// JVMS 2.10. Handlers are searched in the order they appear in the exception table.
+    fn find_exception_handler(&self,
+                              class_loader: &mut ClassLoader,
+                              pc: u16,
+                              exception: &Rc<RefCell<value::Scalar>>)
+                              -> Result<Option<u16>, Rc<RefCell<value::Scalar>>> {
+        for handler in self.exception_table {
+            if pc < handler.start_pc || pc >= handler.end_pc {
+                continue;
+            }
+            // A catch_type of 0 catches everything, and is used to implement finally
+            if handler.catch_type == 0 {
+                return Ok(Some(handler.handler_pc));
+            }
+            if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
+                self.class.get_constant_pool()[handler.catch_type] {
+                let catch_class =
+                    class_loader.resolve_class_in(self.class.get_defining_loader(), &symref.sig)?;
+                if exception.borrow().get_class().is_assignable_to(&catch_class) {
+                    return Ok(Some(handler.handler_pc));
+                }
+            } else {
+                // The verifier rejects such handlers before the method can run
+                let message = format!("catch_type {} is not a class", handler.catch_type);
+                return Err(exception::new(class_loader, "java/lang/VerifyError", Some(&message)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn run(mut self,
+               class_loader: &mut ClassLoader)
+               -> Result<Option<Value>, Rc<RefCell<value::Scalar>>> {
+        // pc of the instruction currently being executed, used to find exception handlers
+        let mut current_pc;
+
         macro_rules! push {
             ($v: expr) => ({
                 self.operand_stack.push($v);
@@ -83,34 +145,162 @@ impl<'a> Frame<'a> {
 
         macro_rules! store {
             ($index: expr) => ({
+                let index = $index as usize;
                 let value = pop!();
-                // the local variables at index and index+1 are set to value for long and double
-                match value {
-                    Value::Long(_) | Value::Double(_) => {
-                        self.local_variables[($index + 1) as usize] = None;
-                    },
-                    _ => ()
-                }
-                self.local_variables[$index as usize] = Some(value);
+                // A long or double takes up the local variables at index and index+1, and is held
+                // by the first with the second left empty
+                if value.is_category2() {
+                    self.local_variables[index + 1] = None;
+                }
+                // Overwriting either half of a long or double makes the whole value unusable
+                if index > 0 &&
+                   self.local_variables[index - 1].as_ref().is_some_and(Value::is_category2) {
+                    self.local_variables[index - 1] = None;
+                }
+                self.local_variables[index] = Some(value);
             });
         }
 
         macro_rules! load {
             ($index: expr) => ({
-                let local = self.local_variables[$index as usize].clone().unwrap();
-                //println!("{:?}", self.operand_stack);
+                let local = self.local_variables[$index as usize]
+                    .clone()
+                    .unwrap_or_else(|| panic!("Local variable {} is not loadable", $index));
                 push!(local);
             });
         }
 
+        // Inserts a copy of the top_slots units of the operand stack beneath the under_slots units
+        // below them, as done by the dup instructions of JVMS 6.5
+        macro_rules! dup {
+            ($top_slots: expr, $under_slots: expr) => ({
+                let top = self.pop_slots($top_slots);
+                let under = self.pop_slots($under_slots);
+                self.operand_stack.extend(top.iter().cloned());
+                self.operand_stack.extend(under);
+                self.operand_stack.extend(top);
+            });
+        }
+
+        macro_rules! iinc {
+            ($index: expr, $const_incr: expr) => ({
+                match self.local_variables[$index as usize] {
+                    Some(Value::Int(ref mut value)) => {
+                        *value += Wrapping($const_incr);
+                    }
+                    _ => panic!("Cannot IINC on non-integer at index: {}", $index),
+                };
+            });
+        }
+
+        macro_rules! ret {
+            ($index: expr) => ({
+                match self.local_variables[$index as usize] {
+                    Some(Value::ReturnAddress(address)) => self.pc = address,
+                    ref v => panic!("Cannot RET to non-returnAddress {:?} at index: {}", v, $index),
+                };
+            });
+        }
+
+        macro_rules! compare {
+            ($val1: expr, $val2: expr, $nan_result: expr) => ({
+                if $val1 > $val2 {
+                    1
+                } else if $val1 < $val2 {
+                    -1
+                } else if $val1 == $val2 {
+                    0
+                } else {
+                    $nan_result
+                }
+            });
+        }
+
         macro_rules! branch {
             ($pc: expr, $offset: expr) => ({
-                self.pc = ($pc as i16 + $offset as i16) as u16;
+                self.pc = ($pc as i32 + $offset as i32) as u16;
+            });
+        }
+
+        macro_rules! throw {
+            ($exception: expr) => ({
+                let exception = $exception;
+                match self.find_exception_handler(class_loader, current_pc, &exception) {
+                    Ok(Some(handler_pc)) => {
+                        // The operand stack is cleared and the exception is pushed for the handler
+                        self.operand_stack.clear();
+                        push!(Value::Reference(exception));
+                        self.pc = handler_pc;
+                        continue;
+                    }
+                    Ok(None) => return Err(exception),
+                    // The exception thrown while resolving a catch type replaces the original
+                    Err(exception) => return Err(exception),
+                }
+            });
+        }
+
+        macro_rules! throw_new {
+            ($class_name: expr) => ({
+                throw!(exception::new(class_loader, $class_name, None));
+            });
+            ($class_name: expr, $message: expr) => ({
+                throw!(exception::new(class_loader, $class_name, Some($message)));
+            });
+        }
+
+        macro_rules! resolve_class {
+            ($sig: expr) => ({
+                match class_loader.resolve_class_in(self.class.get_defining_loader(), $sig) {
+                    Ok(class) => class,
+                    Err(exception) => throw!(exception),
+                }
+            });
+        }
+
+        macro_rules! pop_nonnull {
+            ($value_variant: path) => ({
+                match pop!() {
+                    $value_variant(v) => v,
+                    Value::NullReference => throw_new!("java/lang/NullPointerException"),
+                    v => panic!("Expected to pop a value of type {}, but was {:?}",
+                                stringify!($value_variant), v),
+                }
+            });
+        }
+
+        macro_rules! check_array_index {
+            ($arrayref: expr, $index: expr) => ({
+                let length = $arrayref.borrow().len();
+                if $index < 0 || $index >= length {
+                    let message = format!("Index {} out of bounds for length {}", $index, length);
+                    throw_new!("java/lang/ArrayIndexOutOfBoundsException", &message);
+                }
+            });
+        }
+
+        macro_rules! check_divisor {
+            ($divisor: expr) => ({
+                if $divisor.0 == 0 {
+                    throw_new!("java/lang/ArithmeticException", "/ by zero");
+                }
+            });
+        }
+
+        macro_rules! push_result {
+            ($result: expr) => ({
+                match $result {
+                    Ok(None) => (),
+                    Ok(Some(value)) => push!(value),
+                    Err(exception) => throw!(exception),
+                }
             });
         }
 
         loop {
-            match self.read_u8() {
+            current_pc = self.pc;
+            let ins = self.read_u8();
+            match ins {
                 opcode::NOP => (),
                 opcode::ACONST_NULL => push!(Value::NullReference),
                 opcode::ICONST_M1 => push!(Value::Int(Wrapping(-1))),
@@ -137,17 +327,11 @@ impl<'a> Frame<'a> {
                 }
                 opcode::LDC => {
                     let index = self.read_u8();
-                    push!(self.class
-                        .get_constant_pool()
-                        .resolve_literal(index as u16, class_loader)
-                        .clone());
+                    push_result!(self.class.resolve_constant(class_loader, index as u16).map(Some));
                 }
                 opcode::LDC_W | opcode::LDC2_W => {
                     let index = self.read_u16();
-                    push!(self.class
-                        .get_constant_pool()
-                        .resolve_literal(index, class_loader)
-                        .clone());
+                    push_result!(self.class.resolve_constant(class_loader, index).map(Some));
                 }
                 opcode::ILOAD | opcode::LLOAD | opcode::FLOAD | opcode::DLOAD | opcode::ALOAD => {
                     let index = self.read_u8();
@@ -163,9 +347,10 @@ impl<'a> Frame<'a> {
                 opcode::ALOAD_3 => load!(3),
                 opcode::IALOAD | opcode::LALOAD | opcode::FALOAD | opcode::DALOAD |
                 opcode::AALOAD | opcode::BALOAD | opcode::CALOAD | opcode::SALOAD => {
-                    let index = pop!(Value::Int).0 as usize;
-                    let arrayref = pop!(Value::ArrayReference);
-                    push!(arrayref.borrow().get(index));
+                    let index = pop!(Value::Int).0;
+                    let arrayref = pop_nonnull!(Value::ArrayReference);
+                    check_array_index!(arrayref, index);
+                    push!(arrayref.borrow().get(index as usize));
                 }
                 opcode::ISTORE | opcode::LSTORE | opcode::FSTORE | opcode::DSTORE |
                 opcode::ASTORE => {
@@ -180,131 +365,69 @@ impl<'a> Frame<'a> {
                 opcode::ASTORE_2 => store!(2),
                 opcode::ISTORE_3 | opcode::LSTORE_3 | opcode::FSTORE_3 | opcode::DSTORE_3 |
                 opcode::ASTORE_3 => store!(3),
-                opcode::IASTORE | opcode::LASTORE | opcode::FASTORE | opcode::DASTORE |
-                opcode::AASTORE | opcode::BASTORE | opcode::CASTORE | opcode::SASTORE => {
+                opcode::IASTORE | opcode::LASTORE | opcode::FASTORE | opcode::DASTORE => {
                     let value = pop!();
-                    let index = pop!(Value::Int).0 as usize;
-                    let arrayref = pop!(Value::ArrayReference);
-                    arrayref.borrow_mut().insert(index, value);
-                }
-                opcode::POP => {
-                    pop!();
+                    let index = pop!(Value::Int).0;
+                    let arrayref = pop_nonnull!(Value::ArrayReference);
+                    check_array_index!(arrayref, index);
+                    arrayref.borrow_mut().insert(index as usize, value);
                 }
-                opcode::POP2 => {
-                    match pop!() {
-                        Value::Long(_) | Value::Double(_) => (),
-                        _ => {
-                            pop!();
+                opcode::BASTORE | opcode::CASTORE | opcode::SASTORE => {
+                    let value = pop!(Value::Int).0;
+                    let index = pop!(Value::Int).0;
+                    let arrayref = pop_nonnull!(Value::ArrayReference);
+                    check_array_index!(arrayref, index);
+                    // The value is truncated to the component type, and to its lowest bit for
+                    // boolean arrays, which share bastore with byte arrays
+                    let value = match arrayref.borrow().get_class().symref.sig {
+                        sig::Class::Array(ref component) => {
+                            match **component {
+                                sig::Type::Boolean => value & 1,
+                                sig::Type::Byte => value as i8 as i32,
+                                sig::Type::Char => value as u16 as i32,
+                                sig::Type::Short => value as i16 as i32,
+                                ref ty => panic!("Cannot store an int in an array of {}", ty),
+                            }
                         }
+                        ref sig => panic!("Expected an array class, got {}", sig),
                     };
+                    arrayref.borrow_mut().insert(index as usize, Value::Int(Wrapping(value)));
                 }
-                opcode::DUP => {
-                    let operand = self.operand_stack.last().unwrap().clone();
-                    push!(operand);
-                }
-                opcode::DUP_X1 => {
-                    let value1 = pop!();
-                    let value2 = pop!();
-                    push!(value1.clone());
-                    push!(value2);
-                    push!(value1);
-                }
-                opcode::DUP_X2 => {
-                    let value1 = pop!();
-                    let value2 = pop!();
-                    match value2 {
-                        Value::Long(_) | Value::Double(_) => {
-                            push!(value1.clone());
-                            push!(value2);
-                            push!(value1);
-                        }
-                        _ => {
-                            let value3 = pop!();
-                            push!(value1.clone());
-                            push!(value3);
-                            push!(value2);
-                            push!(value1);
-                        }
-                    }
-                }
-                opcode::DUP2 => {
-                    let value1 = pop!();
-                    match value1 {
-                        Value::Long(_) | Value::Double(_) => {
-                            push!(value1.clone());
-                            push!(value1);
-                        }
-                        _ => {
-                            let value2 = pop!();
-                            push!(value1.clone());
-                            push!(value2.clone());
-                            push!(value1);
-                            push!(value2);
-                        }
-                    }
-                }
-                opcode::DUP2_X1 => {
-                    let value1 = pop!();
-                    let value2 = pop!();
-                    match value1 {
-                        Value::Long(_) | Value::Double(_) => {
-                            push!(value1.clone());
-                            push!(value2);
-                            push!(value1);
+                opcode::AASTORE => {
+                    let value = pop!();
+                    let index = pop!(Value::Int).0;
+                    let arrayref = pop_nonnull!(Value::ArrayReference);
+                    check_array_index!(arrayref, index);
+                    let value_class = match value {
+                        Value::Reference(ref object) => Some(object.borrow().get_class().clone()),
+                        Value::ArrayReference(ref array) => {
+                            Some(array.borrow().get_class().clone())
                         }
-                        _ => {
-                            let value3 = pop!();
-                            push!(value2.clone());
-                            push!(value1.clone());
-                            push!(value3);
-                            push!(value2);
-                            push!(value1);
+                        Value::NullReference => None,
+                        ref v => panic!("AASTORE of non-reference {:?}", v),
+                    };
+                    if let Some(value_class) = value_class {
+                        let array_class = arrayref.borrow().get_class().clone();
+                        let component_class = array_class.get_component_class().unwrap();
+                        if !value_class.is_assignable_to(component_class) {
+                            let message = format!("{}", value_class.symref.sig).replace('/', ".");
+                            throw_new!("java/lang/ArrayStoreException", &message);
                         }
                     }
+                    arrayref.borrow_mut().insert(index as usize, value);
                 }
-                opcode::DUP2_X2 => {
-                    let value1 = pop!();
-                    let value2 = pop!();
-                    match value1 {
-                        Value::Long(_) | Value::Double(_) => {
-                            match value2 {
-                                Value::Long(_) | Value::Double(_) => {
-                                    push!(value1.clone());
-                                    push!(value2);
-                                    push!(value1);
-                                }
-                                _ => {
-                                    let value3 = pop!();
-                                    push!(value1.clone());
-                                    push!(value3);
-                                    push!(value2);
-                                    push!(value1);
-                                }
-                            }
-                        }
-                        _ => {
-                            let value3 = pop!();
-                            match value3 {
-                                Value::Long(_) | Value::Double(_) => {
-                                    push!(value2.clone());
-                                    push!(value1.clone());
-                                    push!(value3);
-                                    push!(value2);
-                                    push!(value1);
-                                }
-                                _ => {
-                                    let value4 = pop!();
-                                    push!(value2.clone());
-                                    push!(value1.clone());
-                                    push!(value4);
-                                    push!(value3);
-                                    push!(value2);
-                                    push!(value1);
-                                }
-                            }
-                        }
-                    }
+                opcode::POP => {
+                    self.pop_slots(1);
                 }
+                opcode::POP2 => {
+                    self.pop_slots(2);
+                }
+                opcode::DUP => dup!(1, 0),
+                opcode::DUP_X1 => dup!(1, 1),
+                opcode::DUP_X2 => dup!(1, 2),
+                opcode::DUP2 => dup!(2, 0),
+                opcode::DUP2_X1 => dup!(2, 1),
+                opcode::DUP2_X2 => dup!(2, 2),
                 opcode::SWAP => {
                     let val2 = pop!();
                     let val1 = pop!();
@@ -374,11 +497,13 @@ impl<'a> Frame<'a> {
                 opcode::IDIV => {
                     let val2 = pop!(Value::Int);
                     let val1 = pop!(Value::Int);
+                    check_divisor!(val2);
                     push!(Value::Int(val1 / val2));
                 }
                 opcode::LDIV => {
                     let val2 = pop!(Value::Long);
                     let val1 = pop!(Value::Long);
+                    check_divisor!(val2);
                     push!(Value::Long(val1 / val2));
                 }
                 opcode::FDIV => {
@@ -394,11 +519,13 @@ impl<'a> Frame<'a> {
                 opcode::IREM => {
                     let val2 = pop!(Value::Int);
                     let val1 = pop!(Value::Int);
+                    check_divisor!(val2);
                     push!(Value::Int(val1 % val2));
                 }
                 opcode::LREM => {
                     let val2 = pop!(Value::Long);
                     let val1 = pop!(Value::Long);
+                    check_divisor!(val2);
                     push!(Value::Long(val1 % val2));
                 }
                 opcode::FREM => {
@@ -447,16 +574,15 @@ impl<'a> Frame<'a> {
                     let value = pop!(Value::Long);
                     push!(Value::Long(value >> (shift & 0x3F) as usize));
                 }
-                // TODO: Check correctness for logical shifts
                 opcode::IUSHR => {
                     let Wrapping(shift) = pop!(Value::Int);
                     let value = pop!(Value::Int).0 as u32;
-                    push!(Value::Int(Wrapping((value << (shift & 0x1F) as usize) as i32)));
+                    push!(Value::Int(Wrapping((value >> (shift & 0x1F) as usize) as i32)));
                 }
                 opcode::LUSHR => {
                     let Wrapping(shift) = pop!(Value::Int);
                     let value = pop!(Value::Long).0 as u64;
-                    push!(Value::Long(Wrapping((value << (shift & 0x3F) as usize) as i64)));
+                    push!(Value::Long(Wrapping((value >> (shift & 0x3F) as usize) as i64)));
                 }
                 opcode::IAND => {
                     let val2 = pop!(Value::Int);
@@ -491,12 +617,7 @@ impl<'a> Frame<'a> {
                 opcode::IINC => {
                     let index = self.read_u8();
                     let const_incr = (self.read_u8() as i8) as i32;
-                    match self.local_variables[index as usize] {
-                        Some(Value::Int(ref mut value)) => {
-                            *value += Wrapping(const_incr);
-                        }
-                        _ => panic!("Cannot IINC on non-integer at index: {}", index),
-                    };
+                    iinc!(index, const_incr);
                 }
                 opcode::I2L => {
                     match pop!() {
@@ -566,11 +687,10 @@ impl<'a> Frame<'a> {
                 }
                 opcode::D2F => {
                     match pop!() {
-                        Value::Double(v) => push!(Value::Double(v as f64)),
+                        Value::Double(v) => push!(Value::Float(v as f32)),
                         v => panic!("Expected Double, got {:?}", v), 
                     };
                 }
-                // TODO: Check if these narrowing conversions are valid
                 opcode::I2B => {
                     match pop!() {
                         Value::Int(v) => push!(Value::Int(Wrapping(v.0 as i8 as i32))),
@@ -600,49 +720,18 @@ impl<'a> Frame<'a> {
                         push!(Value::Int(Wrapping(0)));
                     }
                 }
-                opcode::FCMPL => {
-                    let val2 = pop!(Value::Float);
-                    let val1 = pop!(Value::Float);
-                    if val1.is_nan() || val2.is_nan() || val1 < val2 {
-                        push!(Value::Float(-1.0));
-                    } else if val1 > val2 {
-                        push!(Value::Float(1.0));
-                    } else {
-                        push!(Value::Float(0.0));
-                    }
-                }
-                opcode::FCMPG => {
+                // The l and g variants differ only in the result pushed when either value is NaN
+                opcode::FCMPL | opcode::FCMPG => {
                     let val2 = pop!(Value::Float);
                     let val1 = pop!(Value::Float);
-                    if val1.is_nan() || val2.is_nan() || val1 > val2 {
-                        push!(Value::Float(1.0));
-                    } else if val1 < val2 {
-                        push!(Value::Float(-1.0));
-                    } else {
-                        push!(Value::Float(0.0));
-                    }
+                    let nan_result = if ins == opcode::FCMPL { -1 } else { 1 };
+                    push!(Value::Int(Wrapping(compare!(val1, val2, nan_result))));
                 }
-                opcode::DCMPL => {
+                opcode::DCMPL | opcode::DCMPG => {
                     let val2 = pop!(Value::Double);
                     let val1 = pop!(Value::Double);
-                    if val1.is_nan() || val2.is_nan() || val1 < val2 {
-                        push!(Value::Double(-1.0));
-                    } else if val1 > val2 {
-                        push!(Value::Double(1.0));
-                    } else {
-                        push!(Value::Double(0.0));
-                    }
-                }
-                opcode::DCMPG => {
-                    let val2 = pop!(Value::Double);
-                    let val1 = pop!(Value::Double);
-                    if val1.is_nan() || val2.is_nan() || val1 > val2 {
-                        push!(Value::Double(1.0));
-                    } else if val1 < val2 {
-                        push!(Value::Double(-1.0));
-                    } else {
-                        push!(Value::Double(0.0));
-                    }
+                    let nan_result = if ins == opcode::DCMPL { -1 } else { 1 };
+                    push!(Value::Int(Wrapping(compare!(val1, val2, nan_result))));
                 }
                 opcode::IFEQ => {
                     let pc = self.pc - 1; // pc is incremented for each byte read
@@ -746,22 +835,43 @@ impl<'a> Frame<'a> {
                         branch!(pc, offset);
                     }
                 }
-                // TODO: IF_ACMPEQ, IF_ACMPNE
+                opcode::IF_ACMPEQ => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u16() as i16;
+                    let val2 = pop!();
+                    let val1 = pop!();
+                    if val1.is_same_reference(&val2) {
+                        branch!(pc, offset);
+                    }
+                }
+                opcode::IF_ACMPNE => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u16() as i16;
+                    let val2 = pop!();
+                    let val1 = pop!();
+                    if !val1.is_same_reference(&val2) {
+                        branch!(pc, offset);
+                    }
+                }
                 opcode::GOTO => {
                     let pc = self.pc - 1; // pc is incremented for each byte read
                     let offset = self.read_u16() as i16;
                     branch!(pc, offset);
                 }
                 opcode::JSR => {
-                    panic!("JSR is not supported in this jvm");
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u16() as i16;
+                    push!(Value::ReturnAddress(self.pc));
+                    branch!(pc, offset);
                 }
                 opcode::RET => {
-                    panic!("RET is not supported in this jvm");
+                    let index = self.read_u8();
+                    ret!(index);
                 }
                 opcode::TABLESWITCH => {
                     let pc = self.pc - 1;
                     // Align
-                    while self.pc % 4 != 0 {
+                    while !self.pc.is_multiple_of(4) {
                         self.pc += 1;
                     }
                     let default = self.read_u32() as i32;
@@ -769,7 +879,7 @@ impl<'a> Frame<'a> {
                     let high = self.read_u32() as i32;
 
                     let size = (high - low + 1) as usize;
-                    let mut offsets = vec![0 as u32; size];
+                    let mut offsets = Vec::with_capacity(size);
                     for _ in 0..size {
                         offsets.push(self.read_u32());
                     }
@@ -785,7 +895,7 @@ impl<'a> Frame<'a> {
                 opcode::LOOKUPSWITCH => {
                     let pc = self.pc - 1;
                     // Align
-                    while self.pc % 4 != 0 {
+                    while !self.pc.is_multiple_of(4) {
                         self.pc += 1;
                     }
                     let default = self.read_u32() as i32;
@@ -810,18 +920,20 @@ impl<'a> Frame<'a> {
                 }
                 opcode::IRETURN | opcode::LRETURN | opcode::FRETURN | opcode::DRETURN |
                 opcode::ARETURN => {
-                    return Some(pop!());
+                    return Ok(Some(pop!()));
                 }
                 opcode::RETURN => {
-                    return None;
+                    return Ok(None);
                 }
                 opcode::GETSTATIC => {
                     let index = self.read_u16();
                     if let Some(ConstantPoolEntry::FieldRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
-                        let owning_class = class_loader.resolve_class(&symref.class.sig);
-                        let value = owning_class.get_field(class_loader, symref);
-                        push!(value);
+                        let owning_class = resolve_class!(&symref.class.sig);
+                        match owning_class.get_field(class_loader, symref) {
+                            Ok(value) => push!(value),
+                            Err(exception) => throw!(exception),
+                        }
                     } else {
                         panic!("GETSTATIC {} must point to a FieldRef", index);
                     }
@@ -831,8 +943,11 @@ impl<'a> Frame<'a> {
                     if let Some(ConstantPoolEntry::FieldRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
                         let value = pop!();
-                        let owning_class = class_loader.resolve_class(&symref.class.sig);
-                        owning_class.put_field(class_loader, symref, value);
+                        let owning_class = resolve_class!(&symref.class.sig);
+                        let result = owning_class.put_field(class_loader, symref, value);
+                        if let Err(exception) = result {
+                            throw!(exception);
+                        }
                     } else {
                         panic!("PUTSTATIC {} must point to a FieldRef", index);
                     }
@@ -841,13 +956,14 @@ impl<'a> Frame<'a> {
                     let index = self.read_u16();
                     if let Some(ConstantPoolEntry::FieldRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
-                        match pop!() {
-                            Value::Reference(object) => {
-                                let value = object.borrow().get_field(&symref.sig);
-                                push!(value);
-                            }
-                            v => panic!("TODO: Some kind of implementation for this: {:?}", v),
+                        let owning_class = resolve_class!(&symref.class.sig);
+                        let result = owning_class.resolve_instance_field(class_loader, symref);
+                        if let Err(exception) = result {
+                            throw!(exception);
                         }
+                        let object = pop_nonnull!(Value::Reference);
+                        let value = object.borrow().get_field(symref);
+                        push!(value);
                     } else {
                         panic!("GETFIELD {} must point to a FieldRef", index);
                     }
@@ -856,75 +972,94 @@ impl<'a> Frame<'a> {
                     let index = self.read_u16();
                     if let Some(ConstantPoolEntry::FieldRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
-                        let value = pop!();
-                        match pop!() {
-                            Value::Reference(object) => {
-                                object.borrow_mut().put_field(symref.sig.clone(), value);
-                            }
-                            v => panic!("TODO: Some kind of implementation for this: {:?}", v),
+                        let owning_class = resolve_class!(&symref.class.sig);
+                        let result = owning_class.resolve_instance_field(class_loader, symref);
+                        if let Err(exception) = result {
+                            throw!(exception);
                         }
+                        let value = pop!();
+                        let object = pop_nonnull!(Value::Reference);
+                        object.borrow_mut().put_field(symref, value);
                     } else {
                         panic!("PUTFIELD {} must point to a FieldRef", index);
                     }
                 }
                 opcode::INVOKEVIRTUAL => {
-                    // TODO: Polymorphic invokevirtual
                     let index = self.read_u16();
                     if let Some(ConstantPoolEntry::MethodRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
                         let num_args = symref.sig.params.len();
                         let args = self.pop_count(num_args + 1); // include objectref
-
-                        let owning_class = class_loader.resolve_class(&symref.class.sig);
-                        let method = owning_class.find_method(class_loader, symref);
-
-                        let result = method.borrow()
-                            .invoke(owning_class.as_ref(), class_loader, Some(args));
-                        match result {
-                            None => (),
-                            Some(value) => push!(value),
-                        }
+                        push_result!(invoke::invoke_virtual_constant(class_loader,
+                                                                     self.class,
+                                                                     index,
+                                                                     args));
                     } else {
                         panic!("invokevirtual must refer to a MethodRef");
                     }
                 }
                 opcode::INVOKESPECIAL => {
                     let index = self.read_u16();
-                    if let Some(ConstantPoolEntry::MethodRef(ref symref)) =
+                    if let Some(ConstantPoolEntry::MethodRef(ref symref)) |
+                           Some(ConstantPoolEntry::InterfaceMethodRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
                         let num_args = symref.sig.params.len();
                         let args = self.pop_count(num_args + 1); // include objectref
-
-                        let owning_class = class_loader.resolve_class(&symref.class.sig);
-                        let method = owning_class.find_method(class_loader, symref);
-
-                        let result = method.borrow()
-                            .invoke(owning_class.as_ref(), class_loader, Some(args));
-                        match result {
-                            None => (),
-                            Some(value) => push!(value),
-                        }
+                        push_result!(invoke::invoke_special(class_loader,
+                                                            self.class.get_defining_loader(),
+                                                            symref,
+                                                            args));
                     } else {
-                        panic!("invokespecial must refer to a MethodRef");
+                        panic!("invokespecial must refer to a MethodRef or InterfaceMethodRef");
                     }
                 }
                 opcode::INVOKESTATIC => {
                     let index = self.read_u16();
-                    if let Some(ConstantPoolEntry::MethodRef(ref symref)) =
+                    if let Some(ConstantPoolEntry::MethodRef(ref symref)) |
+                           Some(ConstantPoolEntry::InterfaceMethodRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
-                        let owning_class = class_loader.resolve_class(&symref.class.sig);
-                        let method = owning_class.find_method(class_loader, symref);
                         let num_args = symref.sig.params.len();
                         let args = self.pop_count(num_args);
-
-                        let result = method.borrow()
-                            .invoke(owning_class.as_ref(), class_loader, Some(args));
-                        match result {
-                            None => (),
-                            Some(value) => push!(value),
-                        }
+                        push_result!(invoke::invoke_static(class_loader,
+                                                           self.class.get_defining_loader(),
+                                                           symref,
+                                                           args));
+                    } else {
+                        panic!("invokestatic must refer to a MethodRef or InterfaceMethodRef");
+                    }
+                }
+                opcode::INVOKEINTERFACE => {
+                    let index = self.read_u16();
+                    // The count and the trailing zero byte are redundant
+                    self.read_u8();
+                    self.read_u8();
+                    if let Some(ConstantPoolEntry::InterfaceMethodRef(ref symref)) =
+                        self.class.get_constant_pool()[index] {
+                        let num_args = symref.sig.params.len();
+                        let args = self.pop_count(num_args + 1); // include objectref
+                        push_result!(invoke::invoke_interface(class_loader,
+                                                              self.class.get_defining_loader(),
+                                                              symref,
+                                                              args));
                     } else {
-                        panic!("invokestatic must refer to a MethodRef");
+                        panic!("invokeinterface must refer to an InterfaceMethodRef");
+                    }
+                }
+                opcode::INVOKEDYNAMIC => {
+                    let index = self.read_u16();
+                    // The two trailing zero bytes are reserved
+                    self.read_u8();
+                    self.read_u8();
+                    if let Some(ConstantPoolEntry::InvokeDynamic { ref sig, .. }) =
+                        self.class.get_constant_pool()[index] {
+                        let call_site = match self.class.link_call_site(class_loader, index) {
+                            Ok(call_site) => call_site,
+                            Err(exception) => throw!(exception),
+                        };
+                        let args = self.pop_count(sig.params.len());
+                        push_result!(call_site.invoke(class_loader, args));
+                    } else {
+                        panic!("invokedynamic must refer to an InvokeDynamic");
                     }
                 }
                 // TODO: A bunch of stuff
@@ -932,7 +1067,10 @@ impl<'a> Frame<'a> {
                     let index = self.read_u16();
                     if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
                         self.class.get_constant_pool()[index] {
-                        let class = class_loader.resolve_class(&symref.sig);
+                        let class = resolve_class!(&symref.sig);
+                        if let Err(exception) = class.initialize(class_loader) {
+                            throw!(exception);
+                        }
                         push!(Value::Reference(Rc::new(RefCell::new(value::Scalar::new(class)))));
                     } else {
                         panic!("new must refer to a ClassRef");
@@ -952,19 +1090,163 @@ impl<'a> Frame<'a> {
                     };
 
                     let count = pop!(Value::Int).0;
+                    if count < 0 {
+                        throw_new!("java/lang/NegativeArraySizeException", &count.to_string());
+                    }
 
                     let class_sig = sig::Class::Array(Box::new(atype));
-                    let class = class_loader.resolve_class(&class_sig);
+                    let class = resolve_class!(&class_sig);
                     let array = value::Array::new(class, count);
                     push!(Value::ArrayReference(Rc::new(RefCell::new(array))));
                 }
+                opcode::ANEWARRAY => {
+                    let index = self.read_u16();
+                    if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
+                        self.class.get_constant_pool()[index] {
+                        let count = pop!(Value::Int).0;
+                        if count < 0 {
+                            throw_new!("java/lang/NegativeArraySizeException",
+                                       &count.to_string());
+                        }
+
+                        let component = sig::Type::Reference(symref.sig.clone());
+                        let class_sig = sig::Class::Array(Box::new(component));
+                        let class = resolve_class!(&class_sig);
+                        let array = value::Array::new(class, count);
+                        push!(Value::ArrayReference(Rc::new(RefCell::new(array))));
+                    } else {
+                        panic!("anewarray must refer to a ClassRef");
+                    }
+                }
                 opcode::ARRAYLENGTH => {
-                    let array_ref = pop!(Value::ArrayReference);
+                    let array_ref = pop_nonnull!(Value::ArrayReference);
                     push!(Value::Int(Wrapping(array_ref.borrow().len())));
                 }
+                opcode::MULTIANEWARRAY => {
+                    let index = self.read_u16();
+                    let dimensions = self.read_u8() as usize;
+                    if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
+                        self.class.get_constant_pool()[index] {
+                        let mut counts = vec![0; dimensions];
+                        for count in counts.iter_mut().rev() {
+                            *count = pop!(Value::Int).0;
+                        }
+                        if let Some(count) = counts.iter().find(|&&count| count < 0) {
+                            throw_new!("java/lang/NegativeArraySizeException",
+                                       &count.to_string());
+                        }
+
+                        let class = resolve_class!(&symref.sig);
+                        let array = value::Array::new_multi(class, &counts);
+                        push!(Value::ArrayReference(Rc::new(RefCell::new(array))));
+                    } else {
+                        panic!("multianewarray must refer to a ClassRef");
+                    }
+                }
+                opcode::CHECKCAST => {
+                    let index = self.read_u16();
+                    if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
+                        self.class.get_constant_pool()[index] {
+                        let object_class = match *self.operand_stack.last().unwrap() {
+                            Value::Reference(ref object) => object.borrow().get_class().clone(),
+                            Value::ArrayReference(ref array) => array.borrow().get_class().clone(),
+                            Value::NullReference => continue,
+                            ref v => panic!("CHECKCAST on non-reference {:?}", v),
+                        };
+                        let class = resolve_class!(&symref.sig);
+                        if !object_class.is_assignable_to(&class) {
+                            let message = format!("class {} cannot be cast to class {}",
+                                                  object_class.symref.sig,
+                                                  class.symref.sig);
+                            throw_new!("java/lang/ClassCastException", &message);
+                        }
+                    } else {
+                        panic!("checkcast must refer to a ClassRef");
+                    }
+                }
+                opcode::INSTANCEOF => {
+                    let index = self.read_u16();
+                    if let Some(ConstantPoolEntry::ClassRef(ref symref)) =
+                        self.class.get_constant_pool()[index] {
+                        let object_class = match pop!() {
+                            Value::Reference(ref object) => object.borrow().get_class().clone(),
+                            Value::ArrayReference(ref array) => array.borrow().get_class().clone(),
+                            Value::NullReference => {
+                                push!(Value::Int(Wrapping(0)));
+                                continue;
+                            }
+                            ref v => panic!("INSTANCEOF on non-reference {:?}", v),
+                        };
+                        let class = resolve_class!(&symref.sig);
+                        let result = if object_class.is_assignable_to(&class) { 1 } else { 0 };
+                        push!(Value::Int(Wrapping(result)));
+                    } else {
+                        panic!("instanceof must refer to a ClassRef");
+                    }
+                }
+                opcode::ATHROW => {
+                    let exception = pop_nonnull!(Value::Reference);
+                    throw!(exception);
+                }
+                // There is a single thread, so monitors are never contended and are not tracked
+                opcode::MONITORENTER | opcode::MONITOREXIT => {
+                    if let Value::NullReference = pop!() {
+                        throw_new!("java/lang/NullPointerException");
+                    }
+                }
+                opcode::IFNULL => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u16() as i16;
+                    if let Value::NullReference = pop!() {
+                        branch!(pc, offset);
+                    }
+                }
+                opcode::IFNONNULL => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u16() as i16;
+                    match pop!() {
+                        Value::NullReference => (),
+                        _ => branch!(pc, offset),
+                    }
+                }
+                opcode::WIDE => {
+                    // The modified instruction takes a 16-bit local variable index
+                    let opcode = self.read_u8();
+                    let index = self.read_u16();
+                    match opcode {
+                        opcode::ILOAD | opcode::LLOAD | opcode::FLOAD | opcode::DLOAD |
+                        opcode::ALOAD => load!(index),
+                        opcode::ISTORE | opcode::LSTORE | opcode::FSTORE | opcode::DSTORE |
+                        opcode::ASTORE => store!(index),
+                        opcode::IINC => {
+                            let const_incr = (self.read_u16() as i16) as i32;
+                            iinc!(index, const_incr);
+                        }
+                        opcode::RET => ret!(index),
+                        ins => {
+                            panic!("Invalid instruction for WIDE at pc {}: {:X} ({})",
+                                   current_pc,
+                                   ins,
+                                   opcode::mnemonic(ins).unwrap_or("undefined"));
+                        }
+                    }
+                }
+                opcode::GOTO_W => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u32() as i32;
+                    branch!(pc, offset);
+                }
+                opcode::JSR_W => {
+                    let pc = self.pc - 1; // pc is incremented for each byte read
+                    let offset = self.read_u32() as i32;
+                    push!(Value::ReturnAddress(self.pc));
+                    branch!(pc, offset);
+                }
                 ins => {
-                    println!("{:#?}", self.class);
-                    panic!("Unknown instruction at pc {}: {:X}", self.pc, ins);
+                    panic!("Unknown instruction at pc {}: {:X} ({})",
+                           current_pc,
+                           ins,
+                           opcode::mnemonic(ins).unwrap_or("undefined"));
                 }
             }
         }
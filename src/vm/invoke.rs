@@ -0,0 +1,356 @@
+use super::class::Class;
+use super::class_loader::{ClassLoader, Loader};
+use super::constant_pool::ConstantPoolEntry;
+use super::exception;
+use super::sig;
+use super::symref;
+use super::value::{Scalar, Value};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A method handle constant, as described in JVMS 4.4.8. The variant gives the reference kind,
+/// which determines how the referenced member is accessed or invoked.
+#[derive(Clone, Debug)]
+pub enum MethodHandle {
+    GetField(symref::Field),
+    GetStatic(symref::Field),
+    PutField(symref::Field),
+    PutStatic(symref::Field),
+    InvokeVirtual(symref::Method),
+    InvokeStatic(symref::Method),
+    InvokeSpecial(symref::Method),
+    NewInvokeSpecial(symref::Method),
+    InvokeInterface(symref::Method),
+}
+
+impl MethodHandle {
+    /// Returns the class of the field or method the handle refers to.
+    pub fn member_class(&self) -> &symref::Class {
+        match *self {
+            MethodHandle::GetField(ref symref) |
+            MethodHandle::GetStatic(ref symref) |
+            MethodHandle::PutField(ref symref) |
+            MethodHandle::PutStatic(ref symref) => &symref.class,
+            MethodHandle::InvokeVirtual(ref symref) |
+            MethodHandle::InvokeStatic(ref symref) |
+            MethodHandle::InvokeSpecial(ref symref) |
+            MethodHandle::NewInvokeSpecial(ref symref) |
+            MethodHandle::InvokeInterface(ref symref) => &symref.class,
+        }
+    }
+
+    /// Returns the descriptor of the handle's method type, whose parameters are the arguments
+    /// `invoke` takes, starting with the receiver for instance members.
+    pub fn method_type(&self) -> String {
+        let receiver = sig::Type::Reference(self.member_class().sig.clone());
+        let (params, return_type) = match *self {
+            MethodHandle::GetField(ref symref) => (vec![receiver], Some(symref.sig.ty.clone())),
+            MethodHandle::GetStatic(ref symref) => (vec![], Some(symref.sig.ty.clone())),
+            MethodHandle::PutField(ref symref) => (vec![receiver, symref.sig.ty.clone()], None),
+            MethodHandle::PutStatic(ref symref) => (vec![symref.sig.ty.clone()], None),
+            MethodHandle::InvokeStatic(ref symref) => {
+                (symref.sig.params.clone(), symref.sig.return_type.clone())
+            }
+            MethodHandle::InvokeVirtual(ref symref) |
+            MethodHandle::InvokeSpecial(ref symref) |
+            MethodHandle::InvokeInterface(ref symref) => {
+                let mut params = vec![receiver];
+                params.extend(symref.sig.params.iter().cloned());
+                (params, symref.sig.return_type.clone())
+            }
+            MethodHandle::NewInvokeSpecial(ref symref) => {
+                (symref.sig.params.clone(), Some(receiver))
+            }
+        };
+        let sig = sig::Method {
+            name: String::from("invoke"),
+            params,
+            return_type,
+        };
+        sig.descriptor()
+    }
+
+    /// Invokes the method handle with `args`, behaving like the bytecode instruction that
+    /// corresponds to its reference kind. Classes are resolved with `loader`, the defining loader
+    /// of the class that created the handle.
+    pub fn invoke(&self,
+                  class_loader: &mut ClassLoader,
+                  loader: &Loader,
+                  mut args: Vec<Value>)
+                  -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+        match *self {
+            MethodHandle::GetField(ref symref) => {
+                match args[0] {
+                    Value::Reference(ref object) => {
+                        Ok(Some(object.borrow().get_field(symref)))
+                    }
+                    _ => Err(exception::new(class_loader, "java/lang/NullPointerException", None)),
+                }
+            }
+            MethodHandle::GetStatic(ref symref) => {
+                let class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+                Ok(Some(class.get_field(class_loader, symref)?))
+            }
+            MethodHandle::PutField(ref symref) => {
+                let value = args.pop().unwrap();
+                match args[0] {
+                    Value::Reference(ref object) => {
+                        object.borrow_mut().put_field(symref, value);
+                        Ok(None)
+                    }
+                    _ => Err(exception::new(class_loader, "java/lang/NullPointerException", None)),
+                }
+            }
+            MethodHandle::PutStatic(ref symref) => {
+                let class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+                class.put_field(class_loader, symref, args.pop().unwrap())?;
+                Ok(None)
+            }
+            MethodHandle::InvokeVirtual(ref symref) => {
+                invoke_virtual(class_loader, loader, symref, args)
+            }
+            MethodHandle::InvokeStatic(ref symref) => {
+                invoke_static(class_loader, loader, symref, args)
+            }
+            MethodHandle::InvokeSpecial(ref symref) => {
+                invoke_special(class_loader, loader, symref, args)
+            }
+            MethodHandle::NewInvokeSpecial(ref symref) => {
+                let class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+                class.initialize(class_loader)?;
+                let object = Rc::new(RefCell::new(Scalar::new(class)));
+                args.insert(0, Value::Reference(object.clone()));
+                invoke_special(class_loader, loader, symref, args)?;
+                Ok(Some(Value::Reference(object)))
+            }
+            MethodHandle::InvokeInterface(ref symref) => {
+                invoke_interface(class_loader, loader, symref, args)
+            }
+        }
+    }
+}
+
+/// Returns the class of the receiver of an instance method invocation, which is passed as the
+/// first argument, or throws `NullPointerException` if it is null.
+fn receiver_class(class_loader: &mut ClassLoader,
+                  args: &[Value])
+                  -> Result<Rc<Class>, Rc<RefCell<Scalar>>> {
+    match args[0] {
+        Value::Reference(ref object) => Ok(object.borrow().get_class().clone()),
+        Value::ArrayReference(ref array) => Ok(array.borrow().get_class().clone()),
+        Value::NullReference => {
+            Err(exception::new(class_loader, "java/lang/NullPointerException", None))
+        }
+        ref v => panic!("Invoking an instance method on non-reference {:?}", v),
+    }
+}
+
+/// Returns the `NoSuchMethodError` thrown when the method named by `symref` cannot be resolved.
+fn no_such_method(class_loader: &mut ClassLoader, symref: &symref::Method) -> Rc<RefCell<Scalar>> {
+    let message = format!("{}.{}{}", symref.class, symref.sig.name, symref.sig.descriptor());
+    exception::new(class_loader, "java/lang/NoSuchMethodError", Some(&message))
+}
+
+fn resolve_method(class_loader: &mut ClassLoader,
+                  class: &Rc<Class>,
+                  symref: &symref::Method)
+                  -> Result<(Rc<Class>, usize), Rc<RefCell<Scalar>>> {
+    Class::resolve_method(class, symref).ok_or_else(|| no_such_method(class_loader, symref))
+}
+
+fn invoke_method(class: &Rc<Class>,
+                 method_index: usize,
+                 class_loader: &mut ClassLoader,
+                 args: Vec<Value>)
+                 -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let method = class.get_method(method_index);
+    let result = method.borrow().invoke(class.as_ref(), class_loader, Some(args));
+    result
+}
+
+/// Invokes a static method, as done by `invokestatic`. The method's class is resolved with
+/// `loader`, the defining loader of the calling class.
+pub fn invoke_static(class_loader: &mut ClassLoader,
+                     loader: &Loader,
+                     symref: &symref::Method,
+                     args: Vec<Value>)
+                     -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let owning_class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+    let (class, method_index) = resolve_method(class_loader, &owning_class, symref)?;
+    class.initialize(class_loader)?;
+    invoke_method(&class, method_index, class_loader, args)
+}
+
+/// Invokes an instance method without virtual dispatch, as done by `invokespecial` for instance
+/// initializers, private methods and superclass or superinterface method calls.
+pub fn invoke_special(class_loader: &mut ClassLoader,
+                      loader: &Loader,
+                      symref: &symref::Method,
+                      args: Vec<Value>)
+                      -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    receiver_class(class_loader, &args)?;
+    let owning_class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+    if owning_class.is_interface() {
+        // Private interface methods and Interface.super.method() calls
+        let (class, method_index) = Class::resolve_interface_method(&owning_class, symref)
+            .ok_or_else(|| no_such_method(class_loader, symref))?;
+        invoke_method(&class, method_index, class_loader, args)
+    } else {
+        let (class, method_index) = resolve_method(class_loader, &owning_class, symref)?;
+        invoke_method(&class, method_index, class_loader, args)
+    }
+}
+
+/// Returns whether `symref` is one of the signature polymorphic methods of
+/// `java.lang.invoke.MethodHandle`, whose descriptor is given by each call site.
+fn is_signature_polymorphic(symref: &symref::Method) -> bool {
+    symref.class.sig == sig::Class::Scalar(String::from("java/lang/invoke/MethodHandle")) &&
+    (symref.sig.name == "invokeExact" || symref.sig.name == "invoke")
+}
+
+/// Invokes the method handle that is the receiver of a call to `MethodHandle.invokeExact` or
+/// `MethodHandle.invoke`. Conversions between method types are not supported, so both require
+/// the descriptor at the call site to match the type of the handle.
+fn invoke_method_handle(class_loader: &mut ClassLoader,
+                        symref: &symref::Method,
+                        mut args: Vec<Value>)
+                        -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let object = match args.remove(0) {
+        Value::Reference(object) => object,
+        Value::NullReference => {
+            return Err(exception::new(class_loader, "java/lang/NullPointerException", None))
+        }
+        v => panic!("Invoking a method handle on {:?}", v),
+    };
+    let (handle, loader) = class_loader.get_method_handle(&object);
+    let method_type = handle.method_type();
+    let descriptor = symref.sig.descriptor();
+    if descriptor != method_type {
+        let message = format!("expected {} but found {}", method_type, descriptor);
+        return Err(exception::new(class_loader,
+                                  "java/lang/invoke/WrongMethodTypeException",
+                                  Some(&message)));
+    }
+    handle.invoke(class_loader, &loader, args)
+}
+
+/// Invokes an instance method, selecting it through the vtable of the receiver's class as done
+/// by `invokevirtual`.
+pub fn invoke_virtual(class_loader: &mut ClassLoader,
+                      loader: &Loader,
+                      symref: &symref::Method,
+                      args: Vec<Value>)
+                      -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    if is_signature_polymorphic(symref) {
+        return invoke_method_handle(class_loader, symref, args);
+    }
+    let receiver_class = receiver_class(class_loader, &args)?;
+    let owning_class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+    match owning_class.find_virtual_method(symref) {
+        Some(vtable_index) => {
+            let (class, method_index) = Class::dispatch_virtual(&receiver_class, vtable_index);
+            invoke_method(&class, method_index, class_loader, args)
+        }
+        None => {
+            let (class, method_index) = resolve_method(class_loader, &owning_class, symref)?;
+            let method = class.get_method(method_index);
+            if !method.borrow().is_virtual() {
+                // Private methods are invoked directly
+                return invoke_method(&class, method_index, class_loader, args);
+            }
+            // Otherwise the resolved method only shows that the method exists, and the method
+            // that runs is selected through the receiver's class
+            let selected = match receiver_class.find_virtual_method(symref) {
+                Some(index) => Some(Class::dispatch_virtual(&receiver_class, index)),
+                None if class.is_interface() => {
+                    Class::dispatch_interface(&receiver_class, &class, method_index)
+                }
+                None => None,
+            };
+            match selected {
+                Some((class, method_index)) => {
+                    invoke_method(&class, method_index, class_loader, args)
+                }
+                None => {
+                    let class_name = if method.borrow().is_abstract() {
+                        "java/lang/AbstractMethodError"
+                    } else {
+                        "java/lang/IncompatibleClassChangeError"
+                    };
+                    let message = format!("{}", symref);
+                    Err(exception::new(class_loader, class_name, Some(&message)))
+                }
+            }
+        }
+    }
+}
+
+/// Invokes the method named by the `MethodRef` at `index` in the constant pool of `class`, as
+/// done by `invokevirtual`. The vtable index of the method is recorded in the constant pool when
+/// the `MethodRef` is first resolved, and later invocations select the method from the
+/// receiver's vtable directly.
+pub fn invoke_virtual_constant(class_loader: &mut ClassLoader,
+                               class: &Class,
+                               index: u16,
+                               args: Vec<Value>)
+                               -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let constant_pool = class.get_constant_pool();
+    let vtable_index = match constant_pool.get_vtable_index(index) {
+        Some(vtable_index) => vtable_index,
+        None => {
+            let symref = match constant_pool[index] {
+                Some(ConstantPoolEntry::MethodRef(ref symref)) => symref,
+                _ => panic!("invokevirtual must refer to a MethodRef"),
+            };
+            let loader = class.get_defining_loader();
+            if is_signature_polymorphic(symref) {
+                return invoke_virtual(class_loader, loader, symref, args);
+            }
+            let owning_class = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+            match owning_class.find_virtual_method(symref) {
+                Some(vtable_index) => {
+                    constant_pool.set_vtable_index(index, vtable_index);
+                    vtable_index
+                }
+                None => return invoke_virtual(class_loader, loader, symref, args),
+            }
+        }
+    };
+    let receiver_class = receiver_class(class_loader, &args)?;
+    let (class, method_index) = Class::dispatch_virtual(&receiver_class, vtable_index);
+    invoke_method(&class, method_index, class_loader, args)
+}
+
+/// Invokes an interface method, selecting it through the itables of the receiver's class as done
+/// by `invokeinterface`.
+pub fn invoke_interface(class_loader: &mut ClassLoader,
+                        loader: &Loader,
+                        symref: &symref::Method,
+                        args: Vec<Value>)
+                        -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let receiver_class = receiver_class(class_loader, &args)?;
+    let interface = class_loader.resolve_class_in(loader, &symref.class.sig)?;
+    let (declaring_class, method_index) = Class::resolve_interface_method(&interface, symref)
+        .ok_or_else(|| no_such_method(class_loader, symref))?;
+    let selected = if !declaring_class.get_method(method_index).borrow().is_virtual() {
+        // Private interface methods are invoked directly
+        Some((declaring_class.clone(), method_index))
+    } else if declaring_class.is_interface() {
+        Class::dispatch_interface(&receiver_class, &declaring_class, method_index)
+    } else {
+        // Public methods of java/lang/Object
+        receiver_class.find_virtual_method(symref)
+            .map(|index| Class::dispatch_virtual(&receiver_class, index))
+    };
+
+    match selected {
+        Some((class, method_index)) => invoke_method(&class, method_index, class_loader, args),
+        None => {
+            let message = format!("{}", symref);
+            Err(exception::new(class_loader,
+                               "java/lang/IncompatibleClassChangeError",
+                               Some(&message)))
+        }
+    }
+}
@@ -1,15 +1,24 @@
+pub mod archive;
+pub mod boxing;
+pub mod builtin;
+pub mod call_site;
 pub mod class;
 pub mod class_loader;
+pub mod class_source;
 pub mod constant_pool;
+pub mod exception;
 pub mod frame;
+pub mod invoke;
 #[allow(dead_code)]
 pub mod opcode;
 pub mod native;
 pub mod runtime;
 pub mod sig;
+pub mod string;
 pub mod symref;
 pub mod value;
+pub mod verifier;
 
 pub use self::class_loader::ClassLoader;
 pub use self::constant_pool::ConstantPool;
-pub use self::runtime::Runtime;
+pub use self::runtime::{Runtime, StartError};
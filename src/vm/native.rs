@@ -7,7 +7,9 @@ use lib::{Library, Symbol};
 use std::ffi::CString;
 use std::ptr;
 
-pub type JniFn = unsafe fn(usize, *const Value) -> Option<Value>;
+/// The signature of native methods, which are exported from libraries with the C calling convention
+#[allow(improper_ctypes_definitions)]
+pub type JniFn = unsafe extern "C" fn(usize, *const Value) -> Option<Value>;
 
 pub fn load(path: &str) -> Library {
     Library::new(path).unwrap()
@@ -18,7 +20,7 @@ fn get_function_signature(symref: &symref::Method) -> CString {
     if let sig::Class::Scalar(ref class_sig) = symref.class.sig {
         for class_segment in class_sig.split('/') {
             signature.push_str(class_segment);
-            signature.push_str("_");
+            signature.push('_');
         }
     } else {
         panic!("Error invoking {:?}, only Scalar classes can have native methods",
@@ -41,11 +43,9 @@ pub fn invoke(lib: &Library,
 
     unsafe {
         let func: Symbol<JniFn> = lib.get(get_function_signature(symref).as_bytes()).unwrap();
-        if args_opt.is_none() {
-            func(0, ptr::null())
-        } else {
-            let args = args_opt.unwrap();
-            func(args.len(), args.as_ptr())
+        match args_opt {
+            None => func(0, ptr::null()),
+            Some(args) => func(args.len(), args.as_ptr()),
         }
     }
 }
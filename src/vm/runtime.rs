@@ -1,26 +1,117 @@
+use super::class::Class;
 use super::class_loader::ClassLoader;
+use super::super::model::info::method::{ACC_PUBLIC, ACC_STATIC};
 use super::sig;
+use super::string;
 use super::symref;
+use super::value::{Array, Scalar, Value};
 
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The directory of the build profile this crate was built with, where the natives library is
+/// built alongside it
+const PROFILE_DIR: &str = env!("PROFILE_DIR");
+
+/// Finds the library implementing the runtime's native methods. It is looked up next to the
+/// executable, as when installed, and then in the directory of the build profile.
+fn natives_path() -> PathBuf {
+    let file_name = format!("{}rjni_runtime{}", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX);
+    let exe_dir = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+    exe_dir.into_iter()
+        .chain(Some(PathBuf::from(PROFILE_DIR)))
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| Path::new(PROFILE_DIR).join(&file_name))
+}
+
+/// Why `Runtime::start` did not run `main` to completion
+#[derive(Debug)]
+pub enum StartError {
+    /// The main class could not be loaded, because of the given exception
+    MainClassNotLoaded(Rc<RefCell<Scalar>>),
+    /// The main class has no `public static void main(String[])` method
+    MainMethodNotFound,
+    /// An exception was thrown while initializing the main class or running `main`
+    Exception(Rc<RefCell<Scalar>>),
+}
+
+impl From<Rc<RefCell<Scalar>>> for StartError {
+    fn from(exception: Rc<RefCell<Scalar>>) -> Self {
+        StartError::Exception(exception)
+    }
+}
 
 pub struct Runtime {
     bootstrap_class_loader: ClassLoader,
+    /// System properties, as returned by `System.getProperty`
+    properties: Vec<(String, String)>,
 }
 
 impl Runtime {
     pub fn new(class_paths: Vec<PathBuf>) -> Self {
-        let mut class_loader = ClassLoader::new(class_paths);
-        class_loader.load_library("./target/debug/librjni_runtime.so");
-        Runtime { bootstrap_class_loader: class_loader }
+        Runtime::with_class_loader(ClassLoader::new(class_paths))
+    }
+
+    /// Creates a runtime whose bootstrap class loader is `class_loader`, such as one with custom
+    /// class sources.
+    pub fn with_class_loader(mut class_loader: ClassLoader) -> Self {
+        let natives_path = natives_path();
+        class_loader.load_library(&natives_path.to_string_lossy());
+        Runtime {
+            bootstrap_class_loader: class_loader,
+            properties: Vec::new(),
+        }
     }
 
-    pub fn start(mut self, main_class: symref::Class) {
-        let class = self.bootstrap_class_loader.resolve_class(&main_class.sig);
-        class.initialize(&mut self.bootstrap_class_loader);
+    /// Sets the size of the native stack of the thread that will run the VM, which limits how
+    /// deep Java frames may be nested before `StackOverflowError` is thrown.
+    pub fn set_stack_size(&mut self, stack_size: usize) {
+        self.bootstrap_class_loader.set_stack_size(stack_size);
+    }
 
+    /// Sets a system property, replacing any previous value for `key`.
+    pub fn set_property(&mut self, key: &str, value: &str) {
+        self.properties.retain(|(existing, _)| existing != key);
+        self.properties.push((String::from(key), String::from(value)));
+    }
+
+    /// Stores the system properties in `System.props` as alternating keys and values.
+    fn init_properties(&mut self) -> Result<(), Rc<RefCell<Scalar>>> {
+        let class_loader = &mut self.bootstrap_class_loader;
         let string_ty = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
-        let string_array_ty = sig::Type::Reference(sig::Class::Array(Box::new(string_ty)));
+        let string_array_sig = sig::Class::Array(Box::new(string_ty));
+        let string_array_class = class_loader.resolve_runtime_class(&string_array_sig);
+        let mut props = Array::new(string_array_class, 2 * self.properties.len() as i32);
+        for (i, (key, value)) in self.properties.iter().enumerate() {
+            props.insert(2 * i, string::new(class_loader, key));
+            props.insert(2 * i + 1, string::new(class_loader, value));
+        }
+
+        let system_sig = sig::Class::Scalar(String::from("java/lang/System"));
+        let props_symref = symref::Field {
+            class: symref::Class { sig: system_sig.clone() },
+            sig: sig::Field::new(String::from("props"), sig::Type::Reference(string_array_sig)),
+        };
+        let system = class_loader.resolve_runtime_class(&system_sig);
+        let props = Value::ArrayReference(Rc::new(RefCell::new(props)));
+        system.put_field(class_loader, &props_symref, props)
+    }
+
+    /// Runs `main` of the given class with `args` as its `String[]` argument, returning why it
+    /// could not be run or the exception that terminated it, if any.
+    pub fn start(mut self, main_class: symref::Class, args: &[String]) -> Result<(), StartError> {
+        self.init_properties()?;
+
+        let class = self.bootstrap_class_loader
+            .resolve_class_in(&None, &main_class.sig)
+            .map_err(StartError::MainClassNotLoaded)?;
+
+        let string_ty = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+        let string_array_sig = sig::Class::Array(Box::new(string_ty));
+        let string_array_ty = sig::Type::Reference(string_array_sig.clone());
         let main_sig = sig::Method {
             name: String::from("main"),
             params: vec![string_array_ty],
@@ -30,7 +121,27 @@ impl Runtime {
             class: class.symref.clone(),
             sig: main_sig,
         };
-        let method = class.find_method(&mut self.bootstrap_class_loader, &main_symref).borrow();
-        method.invoke(&class, &mut self.bootstrap_class_loader, None);
+        let (declaring_class, method_index) = match Class::resolve_method(&class, &main_symref) {
+            Some(method) => method,
+            None => return Err(StartError::MainMethodNotFound),
+        };
+        let method = declaring_class.get_method(method_index);
+        if method.borrow().access_flags & (ACC_PUBLIC | ACC_STATIC) != ACC_PUBLIC | ACC_STATIC {
+            return Err(StartError::MainMethodNotFound);
+        }
+        class.initialize(&mut self.bootstrap_class_loader)?;
+
+        let string_array_class =
+            self.bootstrap_class_loader.resolve_runtime_class(&string_array_sig);
+        let mut string_array = Array::new(string_array_class, args.len() as i32);
+        for (i, arg) in args.iter().enumerate() {
+            string_array.insert(i, string::new(&mut self.bootstrap_class_loader, arg));
+        }
+        let main_args = vec![Value::ArrayReference(Rc::new(RefCell::new(string_array)))];
+
+        let result = method.borrow().invoke(&declaring_class,
+                                            &mut self.bootstrap_class_loader,
+                                            Some(main_args));
+        result.map(|_| ()).map_err(StartError::Exception)
     }
 }
@@ -61,6 +61,24 @@ impl Type {
     }
 }
 
+impl fmt::Display for Type {
+    /// Formats the type as a field descriptor, as described in JVMS 4.3.2.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Char => write!(f, "C"),
+            Type::Byte => write!(f, "B"),
+            Type::Short => write!(f, "S"),
+            Type::Int => write!(f, "I"),
+            Type::Long => write!(f, "J"),
+            Type::Float => write!(f, "F"),
+            Type::Double => write!(f, "D"),
+            Type::Boolean => write!(f, "Z"),
+            Type::Reference(Class::Scalar(ref name)) => write!(f, "L{};", name),
+            Type::Reference(ref array) => write!(f, "{}", array),
+        }
+    }
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub enum Class {
     Scalar(String),
@@ -81,7 +99,7 @@ impl fmt::Display for Class {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Class::Scalar(ref string) => write!(f, "{}", string),
-            Class::Array(_) => Err(fmt::Error),
+            Class::Array(ref component) => write!(f, "[{}", component),
         }
     }
 }
@@ -119,10 +137,24 @@ impl Method {
         let return_type = Type::new_partial(&descriptor[(end_param + 1)..]).0;
 
         Method {
-            name: name,
+            name,
             params: types,
-            return_type: return_type,
+            return_type,
+        }
+    }
+
+    /// Returns the method descriptor of the method, as described in JVMS 4.3.3.
+    pub fn descriptor(&self) -> String {
+        let mut descriptor = String::from("(");
+        for param in &self.params {
+            descriptor.push_str(&param.to_string());
+        }
+        descriptor.push(')');
+        match self.return_type {
+            Some(ref ty) => descriptor.push_str(&ty.to_string()),
+            None => descriptor.push('V'),
         }
+        descriptor
     }
 }
 
@@ -141,8 +173,8 @@ pub struct Field {
 impl Field {
     pub fn new(name: String, ty: Type) -> Self {
         Field {
-            name: name,
-            ty: ty,
+            name,
+            ty,
         }
     }
 }
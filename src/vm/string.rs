@@ -0,0 +1,124 @@
+use super::class_loader::ClassLoader;
+use super::sig;
+use super::symref;
+use super::value::{Array, Scalar, Value};
+
+use std::cell::RefCell;
+use std::num::Wrapping;
+use std::rc::Rc;
+
+/// The `char[]` field holding the contents of a `java.lang.String`
+fn bytes_field() -> symref::Field {
+    let array_sig = sig::Class::Array(Box::new(sig::Type::Char));
+    symref::Field {
+        class: symref::Class { sig: sig::Class::Scalar(String::from("java/lang/String")) },
+        sig: sig::Field::new(String::from("bytes"), sig::Type::Reference(array_sig)),
+    }
+}
+
+/// Creates a new `java.lang.String` holding `value`.
+pub fn new(class_loader: &mut ClassLoader, value: &str) -> Value {
+    from_utf16(class_loader, &value.encode_utf16().collect::<Vec<u16>>())
+}
+
+/// Creates a new `java.lang.String` holding the UTF-16 code units `chars`, which may include
+/// unpaired surrogates.
+///
+/// The backing `char[]` is filled in directly rather than through a constructor, so strings can
+/// be created before any native methods are bound.
+pub fn from_utf16(class_loader: &mut ClassLoader, chars: &[u16]) -> Value {
+    let array_sig = sig::Class::Array(Box::new(sig::Type::Char));
+    let array_class = class_loader.resolve_runtime_class(&array_sig);
+
+    let mut array = Array::new(array_class, chars.len() as i32);
+    for (i, &c) in chars.iter().enumerate() {
+        array.insert(i, Value::Int(Wrapping(c as i32)));
+    }
+    let array_rc = Rc::new(RefCell::new(array));
+
+    let string_sig = sig::Class::Scalar(String::from("java/lang/String"));
+    let string_class = class_loader.resolve_runtime_class(&string_sig);
+    let mut string = Scalar::new(string_class);
+    string.put_field(&bytes_field(), Value::ArrayReference(array_rc));
+    Value::Reference(Rc::new(RefCell::new(string)))
+}
+
+/// Reads the UTF-16 code units of a `java.lang.String`.
+pub fn to_utf16(string: &Scalar) -> Vec<u16> {
+    match string.get_field(&bytes_field()) {
+        Value::ArrayReference(array) => {
+            let array = array.borrow();
+            (0..array.len())
+                .map(|i| match array.get(i as usize) {
+                    Value::Int(c) => c.0 as u16,
+                    v => panic!("Expected a char, found {:?}", v),
+                })
+                .collect()
+        }
+        v => panic!("String contents must be a char array, found {:?}", v),
+    }
+}
+
+/// Reads the contents of a `java.lang.String`, replacing unpaired surrogates with U+FFFD.
+pub fn to_rust_string(string: &Scalar) -> String {
+    String::from_utf16_lossy(&to_utf16(string))
+}
+
+/// Formats a float as done by `Float.toString`.
+pub fn float_to_string(value: f32) -> String {
+    format_floating(value.is_nan(),
+                    value.is_infinite(),
+                    value.is_sign_negative(),
+                    value.abs() as f64,
+                    format!("{:e}", value.abs()))
+}
+
+/// Formats a double as done by `Double.toString`.
+pub fn double_to_string(value: f64) -> String {
+    format_floating(value.is_nan(),
+                    value.is_infinite(),
+                    value.is_sign_negative(),
+                    value.abs(),
+                    format!("{:e}", value.abs()))
+}
+
+/// Formats a floating-point value given the shortest scientific representation of its
+/// magnitude, such as `1.5e-4`. Magnitudes in [10^-3, 10^7) are written in plain decimal and
+/// others in computerized scientific notation, always with at least one fractional digit.
+fn format_floating(nan: bool,
+                   infinite: bool,
+                   negative: bool,
+                   magnitude: f64,
+                   scientific: String)
+                   -> String {
+    if nan {
+        return String::from("NaN");
+    }
+    let sign = if negative { "-" } else { "" };
+    if infinite {
+        return format!("{}Infinity", sign);
+    }
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+    let exponent: i32 = exponent[1..].parse().unwrap();
+    let digits = mantissa.replace('.', "");
+
+    if magnitude == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        if exponent < 0 {
+            let zeros = "0".repeat((-exponent - 1) as usize);
+            format!("{}0.{}{}", sign, zeros, digits)
+        } else {
+            let point = exponent as usize + 1;
+            let mut digits = digits;
+            while digits.len() < point {
+                digits.push('0');
+            }
+            let (integer, fraction) = digits.split_at(point);
+            let fraction = if fraction.is_empty() { "0" } else { fraction };
+            format!("{}{}.{}", sign, integer, fraction)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let rest = if rest.is_empty() { "0" } else { rest };
+        format!("{}{}.{}E{}", sign, first, rest, exponent)
+    }
+}
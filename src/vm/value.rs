@@ -1,8 +1,8 @@
 use super::class::Class;
 use super::sig;
+use super::symref;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::num::Wrapping;
 use std::rc::Rc;
 
@@ -16,40 +16,70 @@ pub enum Value {
     Reference(Rc<RefCell<Scalar>>),
     ArrayReference(Rc<RefCell<Array>>),
     NullReference,
+    /// The pc of the instruction after a `jsr` or `jsr_w`, which `ret` returns to
+    ReturnAddress(u16),
+}
+
+impl Value {
+    /// Returns true for `long` and `double` values, which take up two local variables and two
+    /// units of operand stack depth, as described in JVMS 2.6.1 and 2.6.2.
+    pub fn is_category2(&self) -> bool {
+        matches!(*self, Value::Long(_) | Value::Double(_))
+    }
+
+    /// Returns whether both values are null or refer to the same object, as compared by
+    /// `if_acmpeq` and `if_acmpne`.
+    pub fn is_same_reference(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Reference(a), Value::Reference(b)) => Rc::ptr_eq(a, b),
+            (Value::ArrayReference(a), Value::ArrayReference(b)) => Rc::ptr_eq(a, b),
+            (Value::NullReference, Value::NullReference) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Scalar {
     class: Rc<Class>,
-    fields: HashMap<sig::Field, Value>,
+    /// Field values, laid out as described by the class's instance field layout
+    fields: Vec<Value>,
 }
 
 impl Scalar {
     pub fn new(class: Rc<Class>) -> Self {
         match class.symref.sig {
             sig::Class::Scalar(_) => {
-                let field_sigs = class.collect_instance_fields();
-                let mut fields = HashMap::new();
-                // TODO: load instance fields
-                for field in field_sigs {
-                    let value = field.ty.get_default();
-                    fields.insert(field, value);
-                }
+                let fields = class.get_instance_fields()
+                    .iter()
+                    .map(|field| field.ty.get_default())
+                    .collect();
                 Scalar {
-                    class: class,
-                    fields: fields,
+                    class,
+                    fields,
                 }
             }
             _ => panic!("Scalar value must be a scalar class"),
         }
     }
 
-    pub fn put_field(&mut self, sig: sig::Field, value: Value) {
-        self.fields.insert(sig, value);
+    pub fn get_class(&self) -> &Rc<Class> {
+        &self.class
     }
 
-    pub fn get_field(&self, sig: &sig::Field) -> Value {
-        self.fields[sig].clone()
+    fn field_index(&self, field_symref: &symref::Field) -> usize {
+        self.class
+            .find_instance_field(field_symref)
+            .unwrap_or_else(|| panic!("{} is not a field of {}", field_symref, self.class.symref))
+    }
+
+    pub fn put_field(&mut self, field_symref: &symref::Field, value: Value) {
+        let index = self.field_index(field_symref);
+        self.fields[index] = value;
+    }
+
+    pub fn get_field(&self, field_symref: &symref::Field) -> Value {
+        self.fields[self.field_index(field_symref)].clone()
     }
 }
 
@@ -71,9 +101,29 @@ impl Array {
             sig::Class::Scalar(_) => panic!("Array classes cannot be Scalar!"),
         }
         Array {
-            class: class,
-            array: array,
+            class,
+            array,
+        }
+    }
+
+    /// Creates a multi-dimensional array, as done by `multianewarray`. The first count gives the
+    /// length of the outermost array, and each following count gives the length of the arrays
+    /// nested one level deeper. Dimensions beyond the given counts are left null.
+    pub fn new_multi(class: Rc<Class>, counts: &[i32]) -> Self {
+        let mut array = Array::new(class.clone(), counts[0]);
+        if counts.len() > 1 {
+            let component_class = class.get_component_class()
+                .expect("Multi-dimensional arrays must have array components");
+            for element in &mut array.array {
+                let nested = Array::new_multi(component_class.clone(), &counts[1..]);
+                *element = Value::ArrayReference(Rc::new(RefCell::new(nested)));
+            }
         }
+        array
+    }
+
+    pub fn get_class(&self) -> &Rc<Class> {
+        &self.class
     }
 
     pub fn copy_from(&mut self, other: Rc<RefCell<Array>>, src: i32, dst: i32, len: i32) {
@@ -86,6 +136,10 @@ impl Array {
         self.array.len() as i32
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
     pub fn get(&self, index: usize) -> Value {
         self.array[index].clone()
     }
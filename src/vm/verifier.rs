@@ -0,0 +1,1657 @@
+use super::class::Class;
+use super::class_loader::ClassLoader;
+use super::constant_pool::{ConstantPool, ConstantPoolEntry};
+use super::exception;
+use super::opcode;
+use super::sig;
+use super::value::{Scalar, Value};
+use super::super::model;
+use super::super::model::info::Attribute;
+use super::super::model::info::attribute::ExceptionHandler;
+use super::super::model::info::stack_map::{StackMapFrame, VerificationType};
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+/// The first class file version whose methods must be verified by type checking, as described in
+/// JVMS 4.10. Older class files may have no stack map frames, and are verified by type inference.
+const TYPE_CHECKING_VERSION: u16 = 51;
+
+/// The class file version whose methods are type checked first, and have their types inferred
+/// if type checking fails, as described in JVMS 4.10
+const FAILOVER_VERSION: u16 = 50;
+
+/// Verifies every method of `class`, whose class file is `model`, throwing `VerifyError` if any of
+/// them is invalid. Methods are type checked against their `StackMapTable` attributes, as
+/// described in JVMS 4.10.1, or have their types inferred by data-flow analysis, as described in
+/// JVMS 4.10.2, if the class is older than version 51. Version 50 methods that fail type
+/// checking are verified again by type inference.
+pub fn verify(class_loader: &mut ClassLoader,
+              class: &Rc<Class>,
+              model: &model::Class)
+              -> ::std::result::Result<(), Rc<RefCell<Scalar>>> {
+    let constant_pool = class.get_constant_pool();
+    for method in model.methods.iter() {
+        for attribute in method.attributes.iter() {
+            if let Attribute::Code { max_stack,
+                                     max_locals,
+                                     ref code,
+                                     ref exception_table,
+                                     ref attributes,
+                                     .. } = *attribute {
+                let stack_map = attributes.iter()
+                    .filter_map(|attribute| match *attribute {
+                        Attribute::StackMapTable { ref entries, .. } => Some(&entries[..]),
+                        _ => None,
+                    })
+                    .next()
+                    .unwrap_or(&[]);
+                let name = constant_pool.lookup_utf8(method.name_index);
+                let descriptor = constant_pool.lookup_utf8(method.descriptor_index);
+                let result = {
+                    let mut verify_method = |inferring| {
+                        let mut verifier = MethodVerifier {
+                            class_loader,
+                            class,
+                            constant_pool,
+                            name,
+                            sig: sig::Method::new(name.clone(), descriptor.clone()),
+                            is_static: method.access_flags & model::info::method::ACC_STATIC != 0,
+                            max_stack: max_stack as usize,
+                            max_locals: max_locals as usize,
+                            code,
+                            exception_table,
+                            starts: Vec::new(),
+                            handlers: Vec::new(),
+                            frames: HashMap::new(),
+                            pc: 0,
+                            frame: Frame::default(),
+                            inferring,
+                            changed: BTreeSet::new(),
+                            callers: HashMap::new(),
+                            returns: HashMap::new(),
+                            subroutine_locals: HashMap::new(),
+                        };
+                        verifier.verify(stack_map)
+                    };
+                    let failover = model.major_version == FAILOVER_VERSION;
+                    match verify_method(model.major_version < FAILOVER_VERSION) {
+                        Err(Failure::Invalid { .. }) if failover => verify_method(true),
+                        result => result,
+                    }
+                };
+                match result {
+                    Ok(()) => (),
+                    Err(Failure::Exception(exception)) => return Err(exception),
+                    Err(Failure::Invalid { pc, reason, detail }) => {
+                        let message = format!("{} ({}.{}{} at pc {}): {}",
+                                              reason,
+                                              class.symref.sig,
+                                              name,
+                                              descriptor,
+                                              pc,
+                                              detail);
+                        return Err(exception::new(class_loader,
+                                                  "java/lang/VerifyError",
+                                                  Some(&message)));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The verification type of a local variable or operand stack entry, as described in JVMS
+/// 4.10.1.2. Longs and doubles take up two entries, the second of which is `Top`.
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Top,
+    Int,
+    Float,
+    Long,
+    Double,
+    Null,
+    /// The `this` of a constructor, before it calls another constructor
+    UninitializedThis,
+    /// An object created by the `new` instruction at the given offset, before its constructor
+    /// is called
+    Uninitialized(usize),
+    Reference(sig::Class),
+    /// The address a `jsr` to the subroutine at the given offset returns to, which only exists
+    /// when inferring types
+    ReturnAddress(usize),
+}
+
+impl Type {
+    fn new(ty: &sig::Type) -> Self {
+        match *ty {
+            sig::Type::Boolean | sig::Type::Byte | sig::Type::Char | sig::Type::Short |
+            sig::Type::Int => Type::Int,
+            sig::Type::Float => Type::Float,
+            sig::Type::Long => Type::Long,
+            sig::Type::Double => Type::Double,
+            sig::Type::Reference(ref class) => Type::Reference(class.clone()),
+        }
+    }
+
+    fn is_category2(&self) -> bool {
+        *self == Type::Long || *self == Type::Double
+    }
+
+    /// Returns true for `null` and references to initialized objects.
+    fn is_reference(&self) -> bool {
+        matches!(*self, Type::Null | Type::Reference(_))
+    }
+
+    /// Returns true for references, including those to uninitialized objects.
+    fn is_any_reference(&self) -> bool {
+        match *self {
+            Type::UninitializedThis | Type::Uninitialized(_) => true,
+            _ => self.is_reference(),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Top => write!(f, "top"),
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Long => write!(f, "long"),
+            Type::Double => write!(f, "double"),
+            Type::Null => write!(f, "null"),
+            Type::UninitializedThis => write!(f, "uninitializedThis"),
+            Type::Uninitialized(pc) => write!(f, "uninitialized({})", pc),
+            Type::Reference(ref class) => write!(f, "{}", class),
+            Type::ReturnAddress(_) => write!(f, "returnAddress"),
+        }
+    }
+}
+
+/// The types of the local variables and operand stack at an instruction
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Frame {
+    locals: Vec<Type>,
+    stack: Vec<Type>,
+    /// Whether `this` has not been initialized yet, in a constructor
+    this_uninit: bool,
+}
+
+/// An entry of the exception table, with the type of the exceptions it catches
+struct Handler {
+    start_pc: usize,
+    end_pc: usize,
+    handler_pc: usize,
+    catch_type: Type,
+}
+
+enum Failure {
+    /// The method is invalid
+    Invalid {
+        pc: usize,
+        reason: &'static str,
+        detail: String,
+    },
+    /// An exception was thrown while loading a class needed to verify the method
+    Exception(Rc<RefCell<Scalar>>),
+}
+
+type Result<T> = ::std::result::Result<T, Failure>;
+
+struct MethodVerifier<'a> {
+    class_loader: &'a mut ClassLoader,
+    class: &'a Rc<Class>,
+    constant_pool: &'a ConstantPool,
+    name: &'a str,
+    sig: sig::Method,
+    is_static: bool,
+    max_stack: usize,
+    max_locals: usize,
+    code: &'a [u8],
+    exception_table: &'a [ExceptionHandler],
+    /// Whether an instruction starts at each offset of the code
+    starts: Vec<bool>,
+    handlers: Vec<Handler>,
+    /// The frames of the `StackMapTable` attribute, or the inferred frames, keyed by the offsets
+    /// they apply at
+    frames: HashMap<usize, Frame>,
+    /// The offset of the instruction being verified
+    pc: usize,
+    /// The types before the instruction being verified, which it updates
+    frame: Frame,
+    /// Whether types are inferred rather than checked against stack map frames
+    inferring: bool,
+    /// The instructions whose inferred frames have changed since they were last verified
+    changed: BTreeSet<usize>,
+    /// Maps the start of each subroutine to the `jsr` instructions that call it
+    callers: HashMap<usize, BTreeSet<usize>>,
+    /// Maps the start of each subroutine to the `ret` instructions that return from it
+    returns: HashMap<usize, BTreeSet<usize>>,
+    /// Maps the start of each subroutine to the local variables it accesses
+    subroutine_locals: HashMap<usize, BTreeSet<usize>>,
+}
+
+impl<'a> MethodVerifier<'a> {
+    fn verify(&mut self, stack_map: &[StackMapFrame]) -> Result<()> {
+        let mut pc = 0;
+        self.starts = vec![false; self.code.len()];
+        while pc < self.code.len() {
+            self.pc = pc;
+            self.starts[pc] = true;
+            match opcode::instruction_length(self.code, pc) {
+                Some(length) => pc += length,
+                None => return self.invalid("Bad instruction", self.describe_opcode(pc)),
+            }
+        }
+
+        self.pc = 0;
+        let locals = self.initial_locals()?;
+        let initial = self.expand_frame(&locals, &[])?;
+        if self.inferring {
+            self.handlers = self.check_exception_table()?;
+            self.infer(initial)
+        } else {
+            self.frames = self.expand_stack_map(locals, stack_map)?;
+            self.handlers = self.check_exception_table()?;
+            self.frame = initial;
+            self.check()
+        }
+    }
+
+    /// Verifies each instruction in order, checking the types at each branch target against its
+    /// stack map frame.
+    fn check(&mut self) -> Result<()> {
+        let mut falls_through = true;
+        let mut pc = 0;
+        while pc < self.code.len() {
+            self.pc = pc;
+            match self.frames.get(&pc).cloned() {
+                Some(frame) => {
+                    if falls_through {
+                        let current = mem::take(&mut self.frame);
+                        self.check_frame(&current, &frame, "the stack map frame")?;
+                    }
+                    self.frame = frame;
+                }
+                None if !falls_through => {
+                    return self.invalid("Expecting a stack map frame",
+                                        String::from("after an unconditional branch"));
+                }
+                None => (),
+            }
+            falls_through = self.step()?;
+            pc += opcode::instruction_length(self.code, pc).unwrap();
+        }
+        if falls_through {
+            return self.falling_off();
+        }
+        Ok(())
+    }
+
+    /// Infers the types at each reachable instruction, starting from the arguments in `initial`.
+    /// Instructions are verified again whenever the types merged into their frames change, until
+    /// no frame changes.
+    fn infer(&mut self, initial: Frame) -> Result<()> {
+        self.frames.insert(0, initial);
+        self.changed.insert(0);
+        while let Some(pc) = self.changed.pop_first() {
+            self.pc = pc;
+            self.frame = self.frames[&pc].clone();
+            if self.step()? {
+                let next = pc + opcode::instruction_length(self.code, pc).unwrap();
+                let frame = mem::take(&mut self.frame);
+                self.flow(next, &frame, &format!("instruction {}", next))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the current instruction and the exception handlers covering it, returning false
+    /// if it never continues to the next instruction.
+    fn step(&mut self) -> Result<bool> {
+        let locals = self.frame.locals.clone();
+        self.check_handlers()?;
+        let falls_through = self.execute()?;
+        if self.frame.locals != locals {
+            // Instructions that change local variables can throw exceptions after doing so
+            self.check_handlers()?;
+        }
+        Ok(falls_through)
+    }
+
+    fn falling_off<T>(&self) -> Result<T> {
+        self.invalid("Falling off the end of the code",
+                     String::from("the last instruction continues to the next"))
+    }
+
+    fn invalid<T>(&self, reason: &'static str, detail: String) -> Result<T> {
+        Err(Failure::Invalid {
+            pc: self.pc,
+            reason,
+            detail,
+        })
+    }
+
+    fn describe_opcode(&self, pc: usize) -> String {
+        let opcode = self.code[pc];
+        match opcode::mnemonic(opcode) {
+            Some(mnemonic) => format!("truncated {} instruction", mnemonic),
+            None => format!("undefined opcode {:#04x}", opcode),
+        }
+    }
+
+    /// Returns the types of the arguments of the method, with longs and doubles taking up a
+    /// single entry.
+    fn initial_locals(&self) -> Result<Vec<Type>> {
+        let mut locals = Vec::new();
+        if !self.is_static {
+            let is_object = self.class.symref.sig ==
+                            sig::Class::Scalar(String::from("java/lang/Object"));
+            if self.name == "<init>" && !is_object {
+                locals.push(Type::UninitializedThis);
+            } else {
+                locals.push(Type::Reference(self.class.symref.sig.clone()));
+            }
+        }
+        locals.extend(self.sig.params.iter().map(Type::new));
+        let size = locals.iter().map(|ty| if ty.is_category2() { 2 } else { 1 }).sum::<usize>();
+        if size > self.max_locals {
+            return self.invalid("Arguments can't fit into locals",
+                                format!("{} argument slots, but max_locals is {}",
+                                        size,
+                                        self.max_locals));
+        }
+        Ok(locals)
+    }
+
+    /// Creates a frame from types in which longs and doubles take up a single entry.
+    fn expand_frame(&self, locals: &[Type], stack: &[Type]) -> Result<Frame> {
+        let expand = |types: &[Type]| {
+            let mut expanded = Vec::with_capacity(types.len());
+            for ty in types {
+                expanded.push(ty.clone());
+                if ty.is_category2() {
+                    expanded.push(Type::Top);
+                }
+            }
+            expanded
+        };
+        let mut locals = expand(locals);
+        let stack = expand(stack);
+        if locals.len() > self.max_locals {
+            return self.invalid("Bad stack map frame",
+                                format!("{} local variables, but max_locals is {}",
+                                        locals.len(),
+                                        self.max_locals));
+        }
+        if stack.len() > self.max_stack {
+            return self.invalid("Bad stack map frame",
+                                format!("a stack size of {}, but max_stack is {}",
+                                        stack.len(),
+                                        self.max_stack));
+        }
+        let this_uninit = locals.contains(&Type::UninitializedThis);
+        locals.resize(self.max_locals, Type::Top);
+        Ok(Frame {
+            locals,
+            stack,
+            this_uninit,
+        })
+    }
+
+    /// Returns the frames of the `StackMapTable` attribute, starting from the arguments of the
+    /// method, `locals`.
+    fn expand_stack_map(&mut self,
+                        mut locals: Vec<Type>,
+                        stack_map: &[StackMapFrame])
+                        -> Result<HashMap<usize, Frame>> {
+        let mut frames = HashMap::new();
+        let mut previous = None;
+        for entry in stack_map {
+            let delta = entry.offset_delta() as usize;
+            let pc = previous.map_or(delta, |previous| previous + delta + 1);
+            self.pc = pc;
+            let stack = match *entry {
+                StackMapFrame::Same { .. } |
+                StackMapFrame::SameExtended { .. } => vec![],
+                StackMapFrame::SameLocals1StackItem { ref stack, .. } |
+                StackMapFrame::SameLocals1StackItemExtended { ref stack, .. } => {
+                    vec![self.verification_type(stack)?]
+                }
+                StackMapFrame::Chop { frame_type, .. } => {
+                    let count = 251 - frame_type as usize;
+                    if count > locals.len() {
+                        return self.invalid("Bad stack map frame",
+                                            format!("cannot remove {} of {} local variables",
+                                                    count,
+                                                    locals.len()));
+                    }
+                    let len = locals.len() - count;
+                    locals.truncate(len);
+                    vec![]
+                }
+                StackMapFrame::Append { locals: ref appended, .. } => {
+                    for local in appended.iter() {
+                        locals.push(self.verification_type(local)?);
+                    }
+                    vec![]
+                }
+                StackMapFrame::Full { locals: ref full, ref stack, .. } => {
+                    locals = full.iter()
+                        .map(|local| self.verification_type(local))
+                        .collect::<Result<_>>()?;
+                    stack.iter()
+                        .map(|item| self.verification_type(item))
+                        .collect::<Result<_>>()?
+                }
+            };
+            if !self.is_instruction_start(pc) {
+                return self.invalid("Bad stack map frame",
+                                    format!("offset {} is not the start of an instruction", pc));
+            }
+            frames.insert(pc, self.expand_frame(&locals, &stack)?);
+            previous = Some(pc);
+        }
+        Ok(frames)
+    }
+
+    fn verification_type(&self, ty: &VerificationType) -> Result<Type> {
+        let ty = match *ty {
+            VerificationType::Top => Type::Top,
+            VerificationType::Integer => Type::Int,
+            VerificationType::Float => Type::Float,
+            VerificationType::Double => Type::Double,
+            VerificationType::Long => Type::Long,
+            VerificationType::Null => Type::Null,
+            VerificationType::UninitializedThis => Type::UninitializedThis,
+            VerificationType::Object { cpool_index } => {
+                Type::Reference(self.class_ref(cpool_index)?.clone())
+            }
+            VerificationType::Uninitialized { offset } => {
+                let offset = offset as usize;
+                if !self.is_instruction_start(offset) || self.code[offset] != opcode::NEW {
+                    return self.invalid("Bad stack map frame",
+                                        format!("no new instruction at offset {}", offset));
+                }
+                Type::Uninitialized(offset)
+            }
+        };
+        Ok(ty)
+    }
+
+    /// Checks the ranges of the exception table, and returns its entries with the types of the
+    /// exceptions they catch.
+    fn check_exception_table(&mut self) -> Result<Vec<Handler>> {
+        let throwable = Type::Reference(sig::Class::Scalar(String::from("java/lang/Throwable")));
+        let mut handlers = Vec::with_capacity(self.exception_table.len());
+        for handler in self.exception_table {
+            let start_pc = handler.start_pc as usize;
+            let end_pc = handler.end_pc as usize;
+            let handler_pc = handler.handler_pc as usize;
+            self.pc = start_pc;
+            if start_pc >= end_pc || !self.is_instruction_start(start_pc) ||
+               (end_pc != self.code.len() && !self.is_instruction_start(end_pc)) {
+                return self.invalid("Illegal exception table range",
+                                    format!("{} to {}", start_pc, end_pc));
+            }
+            if !self.is_instruction_start(handler_pc) {
+                return self.invalid("Illegal exception table handler",
+                                    format!("offset {} is not the start of an instruction",
+                                            handler_pc));
+            }
+            let catch_type = if handler.catch_type == 0 {
+                throwable.clone()
+            } else {
+                Type::Reference(self.class_ref(handler.catch_type)?.clone())
+            };
+            if !self.is_assignable(&catch_type, &throwable)? {
+                return self.invalid("Catch type is not a subclass of Throwable",
+                                    format!("{}", catch_type));
+            }
+            handlers.push(Handler {
+                start_pc,
+                end_pc,
+                handler_pc,
+                catch_type,
+            });
+        }
+        Ok(handlers)
+    }
+
+    fn is_instruction_start(&self, pc: usize) -> bool {
+        self.starts.get(pc).cloned().unwrap_or(false)
+    }
+
+    /// Checks that the frame of each exception handler covering the current instruction can be
+    /// reached with the current local variables.
+    fn check_handlers(&mut self) -> Result<()> {
+        for index in 0..self.handlers.len() {
+            let (handler_pc, catch_type) = {
+                let handler = &self.handlers[index];
+                if self.pc < handler.start_pc || self.pc >= handler.end_pc {
+                    continue;
+                }
+                (handler.handler_pc, handler.catch_type.clone())
+            };
+            let frame = Frame {
+                locals: self.frame.locals.clone(),
+                stack: vec![catch_type],
+                this_uninit: self.frame.this_uninit,
+            };
+            self.flow(handler_pc, &frame, &format!("exception handler {}", handler_pc))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that `frame` is assignable to `target`, where `target` is described by `context`.
+    fn check_frame(&mut self, frame: &Frame, target: &Frame, context: &str) -> Result<()> {
+        if frame.stack.len() != target.stack.len() {
+            return self.invalid("Inconsistent stack map frame",
+                                format!("{} has a stack size of {}, found {}",
+                                        context,
+                                        target.stack.len(),
+                                        frame.stack.len()));
+        }
+        for (index, (from, to)) in frame.locals.iter().zip(&target.locals).enumerate() {
+            if !self.is_assignable(from, to)? {
+                return self.invalid("Inconsistent stack map frame",
+                                    format!("{} expects {} in local {}, found {}",
+                                            context,
+                                            to,
+                                            index,
+                                            from));
+            }
+        }
+        for (index, (from, to)) in frame.stack.iter().zip(&target.stack).enumerate() {
+            if !self.is_assignable(from, to)? {
+                return self.invalid("Inconsistent stack map frame",
+                                    format!("{} expects {} at stack index {}, found {}",
+                                            context,
+                                            to,
+                                            index,
+                                            from));
+            }
+        }
+        if frame.this_uninit && !target.this_uninit {
+            return self.invalid("Inconsistent stack map frame",
+                                format!("{} expects this to be initialized", context));
+        }
+        Ok(())
+    }
+
+    /// Checks that control can pass to the instruction at `target` with `frame`.
+    fn check_target(&mut self, target: usize, frame: &Frame, context: &str) -> Result<()> {
+        match self.frames.get(&target).cloned() {
+            Some(target) => self.check_frame(frame, &target, context),
+            None => self.invalid("Expecting a stack map frame", format!("at {}", context)),
+        }
+    }
+
+    /// Passes control to the instruction at `target`, described by `context`, with `frame`.
+    /// When checking types, `frame` must be assignable to the stack map frame at `target`, and
+    /// when inferring them, `frame` is merged into the frame inferred for `target`.
+    fn flow(&mut self, target: usize, frame: &Frame, context: &str) -> Result<()> {
+        if !self.inferring {
+            return self.check_target(target, frame, context);
+        }
+        if target == self.code.len() {
+            return self.falling_off();
+        }
+        let merged = match self.frames.get(&target).cloned() {
+            Some(existing) => self.merge_frames(&existing, frame, context)?,
+            None => Some(frame.clone()),
+        };
+        if let Some(merged) = merged {
+            self.frames.insert(target, merged);
+            self.changed.insert(target);
+        }
+        Ok(())
+    }
+
+    /// Merges `frame` into the frame inferred for the target described by `context`, returning
+    /// `None` if the merged frame is the same as `existing`.
+    fn merge_frames(&mut self,
+                    existing: &Frame,
+                    frame: &Frame,
+                    context: &str)
+                    -> Result<Option<Frame>> {
+        if existing.stack.len() != frame.stack.len() {
+            return self.invalid("Inconsistent stack height",
+                                format!("{} has a stack size of {}, found {}",
+                                        context,
+                                        existing.stack.len(),
+                                        frame.stack.len()));
+        }
+        let mut merged = Frame {
+            locals: Vec::with_capacity(existing.locals.len()),
+            stack: Vec::with_capacity(existing.stack.len()),
+            this_uninit: existing.this_uninit || frame.this_uninit,
+        };
+        for (from, to) in frame.locals.iter().zip(&existing.locals) {
+            let ty = self.merge_types(from, to)?;
+            merged.locals.push(ty);
+        }
+        for (index, (from, to)) in frame.stack.iter().zip(&existing.stack).enumerate() {
+            let ty = self.merge_types(from, to)?;
+            if ty == Type::Top && from != to {
+                return self.invalid("Mismatched stack types",
+                                    format!("{} has {} at stack index {}, found {}",
+                                            context,
+                                            to,
+                                            index,
+                                            from));
+            }
+            merged.stack.push(ty);
+        }
+        Ok(if merged == *existing { None } else { Some(merged) })
+    }
+
+    /// Returns the most specific type that both `a` and `b` can be assigned to, or `Top` if they
+    /// have no such type.
+    fn merge_types(&mut self, a: &Type, b: &Type) -> Result<Type> {
+        match (a, b) {
+            _ if a == b => Ok(a.clone()),
+            (Type::Null, Type::Reference(_)) => Ok(b.clone()),
+            (Type::Reference(_), Type::Null) => Ok(a.clone()),
+            (Type::Reference(a), Type::Reference(b)) => {
+                Ok(Type::Reference(self.common_superclass(a, b)?))
+            }
+            _ => Ok(Type::Top),
+        }
+    }
+
+    /// Returns the most specific class that both `a` and `b` are subclasses of. Interfaces are
+    /// treated as `java/lang/Object`.
+    fn common_superclass(&mut self, a: &sig::Class, b: &sig::Class) -> Result<sig::Class> {
+        let object = sig::Class::Scalar(String::from("java/lang/Object"));
+        match (a, b) {
+            _ if a == b => Ok(a.clone()),
+            (sig::Class::Array(a), sig::Class::Array(b)) => {
+                match (&**a, &**b) {
+                    (sig::Type::Reference(a), sig::Type::Reference(b)) => {
+                        let component = self.common_superclass(a, b)?;
+                        Ok(sig::Class::Array(Box::new(sig::Type::Reference(component))))
+                    }
+                    _ => Ok(object),
+                }
+            }
+            (sig::Class::Scalar(_), sig::Class::Scalar(_)) => {
+                let a = self.resolve_class(a)?;
+                let b = self.resolve_class(b)?;
+                if a.is_interface() || b.is_interface() {
+                    return Ok(object);
+                }
+                let mut superclass = Some(a);
+                while let Some(class) = superclass {
+                    if b.is_subclass_of(&class) {
+                        return Ok(class.symref.sig.clone());
+                    }
+                    superclass = class.superclass.clone();
+                }
+                Ok(object)
+            }
+            _ => Ok(object),
+        }
+    }
+
+    /// Returns the offset of the instruction `offset` bytes from the current instruction, which
+    /// must be the start of an instruction.
+    fn branch_target(&self, offset: i32) -> Result<usize> {
+        let target = self.pc as i64 + offset as i64;
+        if target < 0 || !self.is_instruction_start(target as usize) {
+            return self.invalid("Illegal target of jump or branch", format!("{}", target));
+        }
+        Ok(target as usize)
+    }
+
+    /// Verifies a branch to the instruction `offset` bytes from the current instruction.
+    fn branch(&mut self, offset: i32) -> Result<()> {
+        let target = self.branch_target(offset)?;
+        let frame = self.frame.clone();
+        self.flow(target, &frame, &format!("branch target {}", target))
+    }
+
+    /// Verifies a `jsr` to the subroutine `offset` bytes from the current instruction, which
+    /// returns to the next instruction.
+    fn jsr(&mut self, offset: i32) -> Result<()> {
+        if !self.inferring {
+            return self.jsr_ret(self.code[self.pc]);
+        }
+        let target = self.branch_target(offset)?;
+        self.push(Type::ReturnAddress(target))?;
+        self.callers.entry(target).or_default().insert(self.pc);
+        // The subroutine now also returns to this caller
+        if let Some(returns) = self.returns.get(&target) {
+            self.changed.extend(returns.iter().cloned());
+        }
+        let frame = self.frame.clone();
+        self.flow(target, &frame, &format!("subroutine {}", target))
+    }
+
+    /// Verifies a `ret` to the return address in the local variable at `index`, which continues
+    /// after every `jsr` to the subroutine. The local variables the subroutine does not access
+    /// keep the types they had before the `jsr`.
+    fn ret(&mut self, index: usize) -> Result<()> {
+        if !self.inferring {
+            return self.jsr_ret(opcode::RET);
+        }
+        self.check_local(index, &Type::Int)?;
+        let subroutine = match self.frame.locals[index] {
+            Type::ReturnAddress(subroutine) => subroutine,
+            _ => return self.bad_local("returnAddress", index),
+        };
+        self.returns.entry(subroutine).or_default().insert(self.pc);
+        let accessed = self.accessed_locals(subroutine, &mut vec![])?;
+        let callers = self.callers.get(&subroutine).cloned().unwrap_or_default();
+        for caller in callers {
+            let mut locals = self.frames[&caller]
+                .locals
+                .iter()
+                .zip(&self.frame.locals)
+                .enumerate()
+                .map(|(index, (before, after))| {
+                    if accessed.contains(&index) {
+                        after.clone()
+                    } else {
+                        before.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+            // A long or double is lost if the subroutine overwrites its second half
+            for index in 1..locals.len() {
+                if locals[index - 1].is_category2() && locals[index] != Type::Top {
+                    locals[index - 1] = Type::Top;
+                }
+            }
+            let frame = Frame {
+                locals,
+                stack: self.frame.stack.clone(),
+                this_uninit: self.frame.this_uninit,
+            };
+            let next = caller + opcode::instruction_length(self.code, caller).unwrap();
+            self.flow(next, &frame, &format!("instruction {}", next))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the local variables accessed by the subroutine starting at `start`, including
+    /// those accessed by the subroutines it calls, by following its control flow up to its `ret`
+    /// instructions. `active` holds the subroutines whose calls lead to this one.
+    fn accessed_locals(&mut self,
+                       start: usize,
+                       active: &mut Vec<usize>)
+                       -> Result<BTreeSet<usize>> {
+        if let Some(locals) = self.subroutine_locals.get(&start) {
+            return Ok(locals.clone());
+        }
+        if active.contains(&start) {
+            return self.invalid("Recursive call to a subroutine",
+                                format!("subroutine {} calls itself", start));
+        }
+        active.push(start);
+        let mut locals = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![start];
+        while let Some(pc) = pending.pop() {
+            if !self.is_instruction_start(pc) || !visited.insert(pc) {
+                continue;
+            }
+            let opcode = self.code[pc];
+            let (opcode, index) = match opcode {
+                opcode::WIDE => (self.code[pc + 1], Some(self.read_u16(pc + 2) as usize)),
+                opcode::ILOAD..=opcode::ALOAD | opcode::ISTORE..=opcode::ASTORE |
+                opcode::RET | opcode::IINC => (opcode, Some(self.code[pc + 1] as usize)),
+                opcode::ILOAD_0..=opcode::ALOAD_3 => {
+                    let offset = opcode - opcode::ILOAD_0;
+                    (opcode::ILOAD + offset / 4, Some((offset % 4) as usize))
+                }
+                opcode::ISTORE_0..=opcode::ASTORE_3 => {
+                    let offset = opcode - opcode::ISTORE_0;
+                    (opcode::ISTORE + offset / 4, Some((offset % 4) as usize))
+                }
+                _ => (opcode, None),
+            };
+            if let Some(index) = index {
+                locals.insert(index);
+                if matches!(opcode,
+                            opcode::LLOAD | opcode::DLOAD | opcode::LSTORE | opcode::DSTORE) {
+                    locals.insert(index + 1);
+                }
+            }
+            let branch = |offset: i32| (pc as i64 + offset as i64) as usize;
+            let next = pc + opcode::instruction_length(self.code, pc).unwrap();
+            match opcode {
+                opcode::RET | opcode::IRETURN..=opcode::RETURN | opcode::ATHROW => (),
+                opcode::GOTO => pending.push(branch(self.read_u16(pc + 1) as i16 as i32)),
+                opcode::GOTO_W => pending.push(branch(self.read_i32(pc + 1))),
+                opcode::JSR | opcode::JSR_W => {
+                    let offset = if opcode == opcode::JSR {
+                        self.read_u16(pc + 1) as i16 as i32
+                    } else {
+                        self.read_i32(pc + 1)
+                    };
+                    locals.extend(self.accessed_locals(branch(offset), active)?);
+                    pending.push(next);
+                }
+                opcode::IFEQ..=opcode::IF_ACMPNE | opcode::IFNULL | opcode::IFNONNULL => {
+                    pending.push(branch(self.read_u16(pc + 1) as i16 as i32));
+                    pending.push(next);
+                }
+                opcode::TABLESWITCH | opcode::LOOKUPSWITCH => {
+                    for offset in self.switch_offsets(pc, opcode)? {
+                        pending.push(branch(offset));
+                    }
+                }
+                _ => pending.push(next),
+            }
+        }
+        active.pop();
+        self.subroutine_locals.insert(start, locals.clone());
+        Ok(locals)
+    }
+
+    /// Returns true if a value of type `from` can be assigned to a variable of type `to`, as
+    /// described in JVMS 4.10.1.2.
+    fn is_assignable(&mut self, from: &Type, to: &Type) -> Result<bool> {
+        match (from, to) {
+            _ if from == to => Ok(true),
+            (_, Type::Top) => Ok(true),
+            (Type::Null, Type::Reference(_)) => Ok(true),
+            (Type::Reference(from), Type::Reference(to)) => {
+                self.is_class_assignable(from, to)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns true if a reference to an instance of `from` can be assigned to a variable of
+    /// class `to`. Like other references, interfaces are treated as `java/lang/Object`.
+    fn is_class_assignable(&mut self, from: &sig::Class, to: &sig::Class) -> Result<bool> {
+        match (from, to) {
+            _ if from == to => Ok(true),
+            (_, sig::Class::Scalar(name)) if name == "java/lang/Object" => Ok(true),
+            (sig::Class::Array(_), sig::Class::Scalar(name)) => {
+                Ok(name == "java/lang/Cloneable" || name == "java/io/Serializable")
+            }
+            (sig::Class::Array(from), sig::Class::Array(to)) => {
+                match (&**from, &**to) {
+                    (sig::Type::Reference(from), sig::Type::Reference(to)) => {
+                        self.is_class_assignable(from, to)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            (sig::Class::Scalar(_), sig::Class::Array(_)) => Ok(false),
+            (sig::Class::Scalar(_), sig::Class::Scalar(_)) => {
+                let to = self.resolve_class(to)?;
+                if to.is_interface() {
+                    return Ok(true);
+                }
+                let from = self.resolve_class(from)?;
+                Ok(from.is_subclass_of(&to))
+            }
+        }
+    }
+
+    fn resolve_class(&mut self, sig: &sig::Class) -> Result<Rc<Class>> {
+        let loader = self.class.get_defining_loader().clone();
+        self.class_loader.resolve_class_in(&loader, sig).map_err(Failure::Exception)
+    }
+
+    fn constant(&self, index: u16) -> Result<&'a ConstantPoolEntry> {
+        match self.constant_pool.get(index) {
+            Some(entry) => Ok(entry),
+            None => self.invalid("Bad constant", format!("#{} is not a constant", index)),
+        }
+    }
+
+    fn class_ref(&self, index: u16) -> Result<&'a sig::Class> {
+        match *self.constant(index)? {
+            ConstantPoolEntry::ClassRef(ref symref) => Ok(&symref.sig),
+            _ => self.invalid("Bad constant", format!("#{} is not a class", index)),
+        }
+    }
+
+    fn read_u8(&self, at: usize) -> u8 {
+        self.code[at]
+    }
+
+    fn read_u16(&self, at: usize) -> u16 {
+        ((self.code[at] as u16) << 8) | self.code[at + 1] as u16
+    }
+
+    fn read_i32(&self, at: usize) -> i32 {
+        i32::from_be_bytes([self.code[at], self.code[at + 1], self.code[at + 2], self.code[at + 3]])
+    }
+
+    fn bad_type<T>(&self, expected: &str, found: &Type) -> Result<T> {
+        self.invalid("Bad type on operand stack",
+                     format!("expected {}, found {}", expected, found))
+    }
+
+    fn push_raw(&mut self, ty: Type) -> Result<()> {
+        self.frame.stack.push(ty);
+        if self.frame.stack.len() > self.max_stack {
+            return self.invalid("Operand stack overflow",
+                                format!("max_stack is {}", self.max_stack));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, ty: Type) -> Result<()> {
+        let is_category2 = ty.is_category2();
+        self.push_raw(ty)?;
+        if is_category2 {
+            self.push_raw(Type::Top)?;
+        }
+        Ok(())
+    }
+
+    fn pop_raw(&mut self) -> Result<Type> {
+        match self.frame.stack.pop() {
+            Some(ty) => Ok(ty),
+            None => self.invalid("Operand stack underflow", String::from("the stack is empty")),
+        }
+    }
+
+    /// Pops a value that can be assigned to `expected`, returning its type.
+    fn pop(&mut self, expected: &Type) -> Result<Type> {
+        let mut ty = self.pop_raw()?;
+        if expected.is_category2() && ty == Type::Top {
+            ty = self.pop_raw()?;
+        }
+        let is_assignable = if expected.is_category2() {
+            ty == *expected
+        } else {
+            ty != Type::Top && self.is_assignable(&ty, expected)?
+        };
+        if !is_assignable {
+            return self.bad_type(&expected.to_string(), &ty);
+        }
+        Ok(ty)
+    }
+
+    /// Pops `null` or a reference to an initialized object.
+    fn pop_reference(&mut self) -> Result<Type> {
+        let ty = self.pop_raw()?;
+        if !ty.is_reference() {
+            return self.bad_type("reference", &ty);
+        }
+        Ok(ty)
+    }
+
+    /// Pops a reference to an array, returning the type of its components, or `None` if it is
+    /// `null`.
+    fn pop_array(&mut self) -> Result<Option<sig::Type>> {
+        match self.pop_raw()? {
+            Type::Null => Ok(None),
+            Type::Reference(sig::Class::Array(component)) => Ok(Some(*component)),
+            ty => self.bad_type("array", &ty),
+        }
+    }
+
+    /// Pops an index and an array whose components are one of `components`.
+    fn pop_array_index(&mut self, components: &[sig::Type]) -> Result<()> {
+        self.pop(&Type::Int)?;
+        if let Some(component) = self.pop_array()? {
+            if !components.contains(&component) {
+                let expected = components.iter()
+                    .map(|component| format!("[{}", component))
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                let found = Type::Reference(sig::Class::Array(Box::new(component)));
+                return self.bad_type(&expected, &found);
+            }
+        }
+        Ok(())
+    }
+
+    fn array_load(&mut self, components: &[sig::Type]) -> Result<()> {
+        self.pop_array_index(components)?;
+        self.push(Type::new(&components[0]))
+    }
+
+    fn array_store(&mut self, components: &[sig::Type]) -> Result<()> {
+        self.pop(&Type::new(&components[0]))?;
+        self.pop_array_index(components)
+    }
+
+    /// Pops `count` values, and pushes them back in the order given by `indices`, where 0 is the
+    /// value that was on top of the stack. The stack must not separate the two halves of a long
+    /// or double at any of `depths`.
+    fn shuffle(&mut self, depths: &[usize], count: usize, indices: &[usize]) -> Result<()> {
+        for &depth in depths {
+            let len = self.frame.stack.len();
+            if depth > len {
+                return self.invalid("Operand stack underflow",
+                                    format!("expected {} values, found {}", depth, len));
+            }
+            if self.frame.stack[len - depth] == Type::Top {
+                // The boundary falls between the two halves of a long or double
+                let split = match (len - depth).checked_sub(1) {
+                    Some(index) => self.frame.stack[index].clone(),
+                    None => Type::Top,
+                };
+                return self.bad_type("a category 1 value", &split);
+            }
+        }
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            values.push(self.pop_raw()?);
+        }
+        for &index in indices {
+            self.push_raw(values[index].clone())?;
+        }
+        Ok(())
+    }
+
+    fn binary(&mut self, ty: Type) -> Result<()> {
+        self.pop(&ty)?;
+        self.pop(&ty)?;
+        self.push(ty)
+    }
+
+    fn unary(&mut self, from: Type, to: Type) -> Result<()> {
+        self.pop(&from)?;
+        self.push(to)
+    }
+
+    fn compare(&mut self, ty: Type) -> Result<()> {
+        self.pop(&ty)?;
+        self.pop(&ty)?;
+        self.push(Type::Int)
+    }
+
+    fn check_local(&self, index: usize, ty: &Type) -> Result<()> {
+        let size = if ty.is_category2() { 2 } else { 1 };
+        if index + size > self.max_locals {
+            return self.invalid("Illegal local variable number",
+                                format!("{}, but max_locals is {}", index, self.max_locals));
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, index: usize, expected: Type) -> Result<()> {
+        self.check_local(index, &expected)?;
+        if self.frame.locals[index] != expected {
+            return self.bad_local(&expected.to_string(), index);
+        }
+        self.push(expected)
+    }
+
+    /// Loads any reference, including those to uninitialized objects, keeping its type.
+    fn load_reference(&mut self, index: usize) -> Result<()> {
+        self.check_local(index, &Type::Null)?;
+        let ty = self.frame.locals[index].clone();
+        if !ty.is_any_reference() {
+            return self.bad_local("reference", index);
+        }
+        self.push(ty)
+    }
+
+    fn bad_local<T>(&self, expected: &str, index: usize) -> Result<T> {
+        self.invalid("Bad local variable type",
+                     format!("expected {} in local {}, found {}",
+                             expected,
+                             index,
+                             self.frame.locals[index]))
+    }
+
+    fn store(&mut self, index: usize, ty: Type) -> Result<()> {
+        self.check_local(index, &ty)?;
+        if index > 0 && self.frame.locals[index - 1].is_category2() {
+            self.frame.locals[index - 1] = Type::Top;
+        }
+        if ty.is_category2() {
+            self.frame.locals[index + 1] = Type::Top;
+        }
+        self.frame.locals[index] = ty;
+        Ok(())
+    }
+
+    /// Verifies a load, store or `iinc` of the local variable at `index`.
+    fn execute_local(&mut self, opcode: u8, index: usize) -> Result<()> {
+        match opcode {
+            opcode::ILOAD => self.load(index, Type::Int),
+            opcode::LLOAD => self.load(index, Type::Long),
+            opcode::FLOAD => self.load(index, Type::Float),
+            opcode::DLOAD => self.load(index, Type::Double),
+            opcode::ALOAD => self.load_reference(index),
+            opcode::ISTORE => {
+                self.pop(&Type::Int)?;
+                self.store(index, Type::Int)
+            }
+            opcode::LSTORE => {
+                self.pop(&Type::Long)?;
+                self.store(index, Type::Long)
+            }
+            opcode::FSTORE => {
+                self.pop(&Type::Float)?;
+                self.store(index, Type::Float)
+            }
+            opcode::DSTORE => {
+                self.pop(&Type::Double)?;
+                self.store(index, Type::Double)
+            }
+            opcode::ASTORE => {
+                // Subroutines store their return address with astore
+                let ty = self.pop_raw()?;
+                if !ty.is_any_reference() && !matches!(ty, Type::ReturnAddress(_)) {
+                    return self.bad_type("reference", &ty);
+                }
+                self.store(index, ty)
+            }
+            opcode::IINC => {
+                self.check_local(index, &Type::Int)?;
+                if self.frame.locals[index] != Type::Int {
+                    return self.bad_local("int", index);
+                }
+                Ok(())
+            }
+            _ => {
+                let mnemonic = opcode::mnemonic(opcode).unwrap_or("an undefined opcode");
+                self.invalid("Bad instruction", format!("wide cannot modify {}", mnemonic))
+            }
+        }
+    }
+
+    /// Verifies the current instruction, returning false if it never continues to the next
+    /// instruction.
+    fn execute(&mut self) -> Result<bool> {
+        let pc = self.pc;
+        let opcode = self.code[pc];
+        let int_components = [sig::Type::Int];
+        match opcode {
+            opcode::NOP => (),
+            opcode::ACONST_NULL => self.push(Type::Null)?,
+            opcode::ICONST_M1..=opcode::ICONST_5 | opcode::BIPUSH | opcode::SIPUSH => {
+                self.push(Type::Int)?
+            }
+            opcode::LCONST_0 | opcode::LCONST_1 => self.push(Type::Long)?,
+            opcode::FCONST_0..=opcode::FCONST_2 => self.push(Type::Float)?,
+            opcode::DCONST_0 | opcode::DCONST_1 => self.push(Type::Double)?,
+            opcode::LDC | opcode::LDC_W | opcode::LDC2_W => {
+                let index = if opcode == opcode::LDC {
+                    self.read_u8(pc + 1) as u16
+                } else {
+                    self.read_u16(pc + 1)
+                };
+                let ty = self.constant_type(index)?;
+                if ty.is_category2() != (opcode == opcode::LDC2_W) {
+                    let mnemonic = opcode::mnemonic(opcode).unwrap();
+                    return self.invalid("Bad constant",
+                                        format!("{} cannot load #{} of type {}",
+                                                mnemonic,
+                                                index,
+                                                ty));
+                }
+                self.push(ty)?;
+            }
+            opcode::ILOAD..=opcode::ALOAD | opcode::ISTORE..=opcode::ASTORE | opcode::IINC => {
+                let index = self.read_u8(pc + 1) as usize;
+                self.execute_local(opcode, index)?;
+            }
+            opcode::ILOAD_0..=opcode::ALOAD_3 => {
+                let offset = opcode - opcode::ILOAD_0;
+                self.execute_local(opcode::ILOAD + offset / 4, (offset % 4) as usize)?;
+            }
+            opcode::ISTORE_0..=opcode::ASTORE_3 => {
+                let offset = opcode - opcode::ISTORE_0;
+                self.execute_local(opcode::ISTORE + offset / 4, (offset % 4) as usize)?;
+            }
+            opcode::WIDE => {
+                let index = self.read_u16(pc + 2) as usize;
+                match self.read_u8(pc + 1) {
+                    opcode::RET => {
+                        self.ret(index)?;
+                        return Ok(false);
+                    }
+                    modified => self.execute_local(modified, index)?,
+                }
+            }
+            opcode::IALOAD => self.array_load(&int_components)?,
+            opcode::LALOAD => self.array_load(&[sig::Type::Long])?,
+            opcode::FALOAD => self.array_load(&[sig::Type::Float])?,
+            opcode::DALOAD => self.array_load(&[sig::Type::Double])?,
+            opcode::BALOAD => self.array_load(&[sig::Type::Byte, sig::Type::Boolean])?,
+            opcode::CALOAD => self.array_load(&[sig::Type::Char])?,
+            opcode::SALOAD => self.array_load(&[sig::Type::Short])?,
+            opcode::AALOAD => {
+                self.pop(&Type::Int)?;
+                match self.pop_array()? {
+                    None => self.push(Type::Null)?,
+                    Some(sig::Type::Reference(component)) => {
+                        self.push(Type::Reference(component))?
+                    }
+                    Some(component) => {
+                        let found = Type::Reference(sig::Class::Array(Box::new(component)));
+                        return self.bad_type("array of references", &found);
+                    }
+                }
+            }
+            opcode::IASTORE => self.array_store(&int_components)?,
+            opcode::LASTORE => self.array_store(&[sig::Type::Long])?,
+            opcode::FASTORE => self.array_store(&[sig::Type::Float])?,
+            opcode::DASTORE => self.array_store(&[sig::Type::Double])?,
+            opcode::BASTORE => self.array_store(&[sig::Type::Byte, sig::Type::Boolean])?,
+            opcode::CASTORE => self.array_store(&[sig::Type::Char])?,
+            opcode::SASTORE => self.array_store(&[sig::Type::Short])?,
+            opcode::AASTORE => {
+                self.pop_reference()?;
+                self.pop(&Type::Int)?;
+                match self.pop_array()? {
+                    None | Some(sig::Type::Reference(_)) => (),
+                    Some(component) => {
+                        let found = Type::Reference(sig::Class::Array(Box::new(component)));
+                        return self.bad_type("array of references", &found);
+                    }
+                }
+            }
+            opcode::POP => self.shuffle(&[1], 1, &[])?,
+            opcode::POP2 => self.shuffle(&[2], 2, &[])?,
+            opcode::DUP => self.shuffle(&[1], 1, &[0, 0])?,
+            opcode::DUP_X1 => self.shuffle(&[1, 2], 2, &[0, 1, 0])?,
+            opcode::DUP_X2 => self.shuffle(&[1, 3], 3, &[0, 2, 1, 0])?,
+            opcode::DUP2 => self.shuffle(&[2], 2, &[1, 0, 1, 0])?,
+            opcode::DUP2_X1 => self.shuffle(&[2, 3], 3, &[1, 0, 2, 1, 0])?,
+            opcode::DUP2_X2 => self.shuffle(&[2, 4], 4, &[1, 0, 3, 2, 1, 0])?,
+            opcode::SWAP => self.shuffle(&[1, 2], 2, &[0, 1])?,
+            opcode::IADD | opcode::ISUB | opcode::IMUL | opcode::IDIV | opcode::IREM |
+            opcode::ISHL | opcode::ISHR | opcode::IUSHR | opcode::IAND | opcode::IOR |
+            opcode::IXOR => self.binary(Type::Int)?,
+            opcode::LADD | opcode::LSUB | opcode::LMUL | opcode::LDIV | opcode::LREM |
+            opcode::LAND | opcode::LOR | opcode::LXOR => self.binary(Type::Long)?,
+            opcode::FADD | opcode::FSUB | opcode::FMUL | opcode::FDIV | opcode::FREM => {
+                self.binary(Type::Float)?
+            }
+            opcode::DADD | opcode::DSUB | opcode::DMUL | opcode::DDIV | opcode::DREM => {
+                self.binary(Type::Double)?
+            }
+            opcode::LSHL | opcode::LSHR | opcode::LUSHR => {
+                self.pop(&Type::Int)?;
+                self.unary(Type::Long, Type::Long)?;
+            }
+            opcode::INEG | opcode::I2B | opcode::I2C | opcode::I2S => {
+                self.unary(Type::Int, Type::Int)?
+            }
+            opcode::LNEG => self.unary(Type::Long, Type::Long)?,
+            opcode::FNEG => self.unary(Type::Float, Type::Float)?,
+            opcode::DNEG => self.unary(Type::Double, Type::Double)?,
+            opcode::I2L => self.unary(Type::Int, Type::Long)?,
+            opcode::I2F => self.unary(Type::Int, Type::Float)?,
+            opcode::I2D => self.unary(Type::Int, Type::Double)?,
+            opcode::L2I => self.unary(Type::Long, Type::Int)?,
+            opcode::L2F => self.unary(Type::Long, Type::Float)?,
+            opcode::L2D => self.unary(Type::Long, Type::Double)?,
+            opcode::F2I => self.unary(Type::Float, Type::Int)?,
+            opcode::F2L => self.unary(Type::Float, Type::Long)?,
+            opcode::F2D => self.unary(Type::Float, Type::Double)?,
+            opcode::D2I => self.unary(Type::Double, Type::Int)?,
+            opcode::D2L => self.unary(Type::Double, Type::Long)?,
+            opcode::D2F => self.unary(Type::Double, Type::Float)?,
+            opcode::LCMP => self.compare(Type::Long)?,
+            opcode::FCMPL | opcode::FCMPG => self.compare(Type::Float)?,
+            opcode::DCMPL | opcode::DCMPG => self.compare(Type::Double)?,
+            opcode::IFEQ..=opcode::IFLE => {
+                self.pop(&Type::Int)?;
+                self.branch(self.read_u16(pc + 1) as i16 as i32)?;
+            }
+            opcode::IF_ICMPEQ..=opcode::IF_ICMPLE => {
+                self.pop(&Type::Int)?;
+                self.pop(&Type::Int)?;
+                self.branch(self.read_u16(pc + 1) as i16 as i32)?;
+            }
+            opcode::IF_ACMPEQ | opcode::IF_ACMPNE => {
+                self.pop_reference()?;
+                self.pop_reference()?;
+                self.branch(self.read_u16(pc + 1) as i16 as i32)?;
+            }
+            opcode::IFNULL | opcode::IFNONNULL => {
+                self.pop_reference()?;
+                self.branch(self.read_u16(pc + 1) as i16 as i32)?;
+            }
+            opcode::GOTO => {
+                self.branch(self.read_u16(pc + 1) as i16 as i32)?;
+                return Ok(false);
+            }
+            opcode::GOTO_W => {
+                self.branch(self.read_i32(pc + 1))?;
+                return Ok(false);
+            }
+            opcode::JSR => {
+                self.jsr(self.read_u16(pc + 1) as i16 as i32)?;
+                return Ok(false);
+            }
+            opcode::JSR_W => {
+                self.jsr(self.read_i32(pc + 1))?;
+                return Ok(false);
+            }
+            opcode::RET => {
+                self.ret(self.read_u8(pc + 1) as usize)?;
+                return Ok(false);
+            }
+            opcode::TABLESWITCH | opcode::LOOKUPSWITCH => {
+                self.pop(&Type::Int)?;
+                for offset in self.switch_offsets(pc, opcode)? {
+                    self.branch(offset)?;
+                }
+                return Ok(false);
+            }
+            opcode::IRETURN..=opcode::RETURN => {
+                self.check_return(opcode)?;
+                return Ok(false);
+            }
+            opcode::GETSTATIC..=opcode::PUTFIELD => self.execute_field(opcode)?,
+            opcode::INVOKEVIRTUAL..=opcode::INVOKEDYNAMIC => self.execute_invoke(opcode)?,
+            opcode::NEW => {
+                let class = self.class_ref(self.read_u16(pc + 1))?;
+                if let sig::Class::Array(_) = *class {
+                    return self.invalid("Bad constant",
+                                        format!("new cannot create the array class {}", class));
+                }
+                // An object created by an earlier execution of this instruction is lost
+                for local in self.frame.locals.iter_mut() {
+                    if *local == Type::Uninitialized(pc) {
+                        *local = Type::Top;
+                    }
+                }
+                self.push(Type::Uninitialized(pc))?;
+            }
+            opcode::NEWARRAY => {
+                let component = match self.read_u8(pc + 1) {
+                    4 => sig::Type::Boolean,
+                    5 => sig::Type::Char,
+                    6 => sig::Type::Float,
+                    7 => sig::Type::Double,
+                    8 => sig::Type::Byte,
+                    9 => sig::Type::Short,
+                    10 => sig::Type::Int,
+                    11 => sig::Type::Long,
+                    atype => {
+                        return self.invalid("Bad instruction",
+                                            format!("newarray of unknown type {}", atype))
+                    }
+                };
+                self.pop(&Type::Int)?;
+                self.push(Type::Reference(sig::Class::Array(Box::new(component))))?;
+            }
+            opcode::ANEWARRAY => {
+                let class = self.class_ref(self.read_u16(pc + 1))?;
+                let component = sig::Type::Reference(class.clone());
+                self.pop(&Type::Int)?;
+                self.push(Type::Reference(sig::Class::Array(Box::new(component))))?;
+            }
+            opcode::MULTIANEWARRAY => {
+                let class = self.class_ref(self.read_u16(pc + 1))?;
+                let dimensions = self.read_u8(pc + 3) as usize;
+                let depth = class.to_string().chars().take_while(|&c| c == '[').count();
+                if dimensions == 0 || dimensions > depth {
+                    return self.invalid("Bad instruction",
+                                        format!("{} cannot be created with {} dimensions",
+                                                class,
+                                                dimensions));
+                }
+                for _ in 0..dimensions {
+                    self.pop(&Type::Int)?;
+                }
+                self.push(Type::Reference(class.clone()))?;
+            }
+            opcode::ARRAYLENGTH => {
+                self.pop_array()?;
+                self.push(Type::Int)?;
+            }
+            opcode::ATHROW => {
+                let throwable = sig::Class::Scalar(String::from("java/lang/Throwable"));
+                self.pop(&Type::Reference(throwable))?;
+                return Ok(false);
+            }
+            opcode::CHECKCAST => {
+                let class = self.class_ref(self.read_u16(pc + 1))?;
+                self.pop_reference()?;
+                self.push(Type::Reference(class.clone()))?;
+            }
+            opcode::INSTANCEOF => {
+                self.class_ref(self.read_u16(pc + 1))?;
+                self.pop_reference()?;
+                self.push(Type::Int)?;
+            }
+            opcode::MONITORENTER | opcode::MONITOREXIT => {
+                self.pop_reference()?;
+            }
+            _ => return self.invalid("Bad instruction", self.describe_unsupported(opcode)),
+        }
+        Ok(true)
+    }
+
+    fn describe_unsupported(&self, opcode: u8) -> String {
+        match opcode::mnemonic(opcode) {
+            Some(mnemonic) => format!("{} is reserved", mnemonic),
+            None => format!("undefined opcode {:#04x}", opcode),
+        }
+    }
+
+    fn jsr_ret<T>(&self, opcode: u8) -> Result<T> {
+        self.invalid("Bad instruction",
+                     format!("{} is not allowed in class files version {} or later",
+                             opcode::mnemonic(opcode).unwrap(),
+                             TYPE_CHECKING_VERSION))
+    }
+
+    /// Returns the type of the constant at `index` loaded by `ldc`, `ldc_w` or `ldc2_w`.
+    fn constant_type(&self, index: u16) -> Result<Type> {
+        let class = |name: &str| Type::Reference(sig::Class::Scalar(String::from(name)));
+        let ty = match *self.constant(index)? {
+            ConstantPoolEntry::Literal(ref value) => {
+                match *value {
+                    Value::Int(_) => Type::Int,
+                    Value::Float(_) => Type::Float,
+                    Value::Long(_) => Type::Long,
+                    Value::Double(_) => Type::Double,
+                    _ => return self.invalid("Bad constant", format!("#{} is not loadable", index)),
+                }
+            }
+            ConstantPoolEntry::UnresolvedString { .. } => class("java/lang/String"),
+            ConstantPoolEntry::ClassRef(_) => class("java/lang/Class"),
+            ConstantPoolEntry::MethodType(_) => class("java/lang/invoke/MethodType"),
+            ConstantPoolEntry::MethodHandle(_) => class("java/lang/invoke/MethodHandle"),
+            ConstantPoolEntry::Dynamic { ref sig, .. } => Type::new(&sig.ty),
+            _ => return self.invalid("Bad constant", format!("#{} is not loadable", index)),
+        };
+        Ok(ty)
+    }
+
+    /// Returns the offsets of the targets of the `tableswitch` or `lookupswitch` instruction at
+    /// `pc`, starting with the default target.
+    fn switch_offsets(&self, pc: usize, opcode: u8) -> Result<Vec<i32>> {
+        let operands = (pc + 4) & !3;
+        let mut offsets = vec![self.read_i32(operands)];
+        if opcode == opcode::TABLESWITCH {
+            let low = self.read_i32(operands + 4);
+            let high = self.read_i32(operands + 8);
+            if low > high {
+                return self.invalid("Bad instruction",
+                                    format!("tableswitch from {} to {}", low, high));
+            }
+            let count = (high as i64 - low as i64 + 1) as usize;
+            offsets.extend((0..count).map(|i| self.read_i32(operands + 12 + i * 4)));
+        } else {
+            let npairs = self.read_i32(operands + 4);
+            if npairs < 0 {
+                return self.invalid("Bad instruction",
+                                    format!("lookupswitch with {} pairs", npairs));
+            }
+            let pairs = operands + 8;
+            for i in 0..npairs as usize {
+                let key = self.read_i32(pairs + i * 8);
+                if i > 0 && key <= self.read_i32(pairs + (i - 1) * 8) {
+                    return self.invalid("Bad instruction",
+                                        String::from("lookupswitch keys are not sorted"));
+                }
+                offsets.push(self.read_i32(pairs + i * 8 + 4));
+            }
+        }
+        Ok(offsets)
+    }
+
+    fn check_return(&mut self, opcode: u8) -> Result<()> {
+        let mnemonic = opcode::mnemonic(opcode).unwrap();
+        let return_type = match self.sig.return_type {
+            Some(ref ty) => Type::new(ty),
+            None if opcode == opcode::RETURN => {
+                if self.frame.this_uninit {
+                    return self.invalid("Constructor must call super() or this() before return",
+                                        format!("this is {}", Type::UninitializedThis));
+                }
+                return Ok(());
+            }
+            None => {
+                return self.invalid("Bad return type", format!("{} in a void method", mnemonic))
+            }
+        };
+        let matches = matches!((opcode, &return_type),
+                               (opcode::IRETURN, Type::Int) | (opcode::LRETURN, Type::Long) |
+                               (opcode::FRETURN, Type::Float) |
+                               (opcode::DRETURN, Type::Double) |
+                               (opcode::ARETURN, Type::Reference(_)));
+        if !matches {
+            return self.invalid("Bad return type",
+                                format!("{} in a method returning {}", mnemonic, return_type));
+        }
+        self.pop(&return_type)?;
+        Ok(())
+    }
+
+    fn execute_field(&mut self, opcode: u8) -> Result<()> {
+        let index = self.read_u16(self.pc + 1);
+        let field = match *self.constant(index)? {
+            ConstantPoolEntry::FieldRef(ref symref) => symref,
+            _ => return self.invalid("Bad constant", format!("#{} is not a field", index)),
+        };
+        let ty = Type::new(&field.sig.ty);
+        let class = Type::Reference(field.class.sig.clone());
+        match opcode {
+            opcode::GETSTATIC => self.push(ty),
+            opcode::PUTSTATIC => self.pop(&ty).map(|_| ()),
+            opcode::GETFIELD => {
+                self.pop(&class)?;
+                self.push(ty)
+            }
+            _ => {
+                self.pop(&ty)?;
+                // Constructors can assign the fields of their own class before calling another
+                // constructor
+                let receiver = self.pop_raw()?;
+                if receiver == Type::UninitializedThis && field.class == self.class.symref {
+                    return Ok(());
+                }
+                if receiver == Type::Top || !self.is_assignable(&receiver, &class)? {
+                    return self.bad_type(&class.to_string(), &receiver);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn execute_invoke(&mut self, opcode: u8) -> Result<()> {
+        let index = self.read_u16(self.pc + 1);
+        let mnemonic = opcode::mnemonic(opcode).unwrap();
+        let (class, sig) = match (opcode, self.constant(index)?) {
+            (opcode::INVOKEDYNAMIC, ConstantPoolEntry::InvokeDynamic { sig, .. }) => {
+                (None, sig)
+            }
+            (opcode::INVOKEVIRTUAL, &ConstantPoolEntry::MethodRef(ref symref)) |
+            (opcode::INVOKESPECIAL, &ConstantPoolEntry::MethodRef(ref symref)) |
+            (opcode::INVOKESTATIC, &ConstantPoolEntry::MethodRef(ref symref)) |
+            (opcode::INVOKESPECIAL, &ConstantPoolEntry::InterfaceMethodRef(ref symref)) |
+            (opcode::INVOKESTATIC, &ConstantPoolEntry::InterfaceMethodRef(ref symref)) |
+            (opcode::INVOKEINTERFACE, &ConstantPoolEntry::InterfaceMethodRef(ref symref)) => {
+                (Some(&symref.class.sig), &symref.sig)
+            }
+            _ => {
+                return self.invalid("Bad constant",
+                                    format!("{} cannot invoke #{}", mnemonic, index));
+            }
+        };
+        let is_init = sig.name == "<init>";
+        if sig.name == "<clinit>" || (is_init && opcode != opcode::INVOKESPECIAL) {
+            return self.invalid("Bad method call",
+                                format!("{} cannot invoke {}", mnemonic, sig.name));
+        }
+        if opcode == opcode::INVOKEINTERFACE {
+            let slots = sig.params
+                .iter()
+                .map(|param| if Type::new(param).is_category2() { 2 } else { 1 })
+                .sum::<usize>() + 1;
+            let count = self.read_u8(self.pc + 3) as usize;
+            if count != slots || self.read_u8(self.pc + 4) != 0 {
+                return self.invalid("Bad instruction",
+                                    format!("invokeinterface count {}, expected {}",
+                                            count,
+                                            slots));
+            }
+        }
+        for param in sig.params.iter().rev() {
+            self.pop(&Type::new(param))?;
+        }
+        match opcode {
+            opcode::INVOKESPECIAL if is_init => self.initialize(class.unwrap())?,
+            opcode::INVOKESPECIAL => {
+                self.pop(&Type::Reference(self.class.symref.sig.clone()))?;
+            }
+            opcode::INVOKEVIRTUAL => {
+                self.pop(&Type::Reference(class.unwrap().clone()))?;
+            }
+            opcode::INVOKEINTERFACE => {
+                self.pop_reference()?;
+            }
+            _ => (),
+        }
+        match sig.return_type {
+            Some(ref ty) => self.push(Type::new(ty)),
+            None => Ok(()),
+        }
+    }
+
+    /// Verifies a call to a constructor of `class`, which initializes the object on top of the
+    /// stack.
+    fn initialize(&mut self, class: &sig::Class) -> Result<()> {
+        let receiver = self.pop_raw()?;
+        let initialized = match receiver {
+            Type::UninitializedThis => {
+                let superclass = self.class
+                    .superclass
+                    .as_ref()
+                    .map(|superclass| &superclass.symref.sig);
+                if *class != self.class.symref.sig && Some(class) != superclass {
+                    return self.invalid("Bad method call",
+                                        format!("{}.<init> cannot initialize this", class));
+                }
+                self.frame.this_uninit = false;
+                self.class.symref.sig.clone()
+            }
+            Type::Uninitialized(new_pc) => {
+                let created = self.class_ref(self.read_u16(new_pc + 1))?;
+                if class != created {
+                    return self.invalid("Bad method call",
+                                        format!("{}.<init> cannot initialize {}",
+                                                class,
+                                                created));
+                }
+                created.clone()
+            }
+            ty => return self.bad_type("uninitialized object", &ty),
+        };
+        let initialized = Type::Reference(initialized);
+        for ty in self.frame.locals.iter_mut().chain(self.frame.stack.iter_mut()) {
+            if *ty == receiver {
+                *ty = initialized.clone();
+            }
+        }
+        Ok(())
+    }
+}
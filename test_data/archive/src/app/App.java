@@ -0,0 +1,13 @@
+package app;
+
+import lib.Lib;
+
+public class App {
+    public static int run() {
+        return Lib.value() + 1;
+    }
+
+    public static void main(String[] args) {
+        System.exit(run());
+    }
+}
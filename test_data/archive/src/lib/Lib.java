@@ -0,0 +1,7 @@
+package lib;
+
+public class Lib {
+    public static int value() {
+        return 41;
+    }
+}
@@ -0,0 +1,84 @@
+public class MultiArray {
+    public static int strings() {
+        String[] strings = new String[3];
+        strings[1] = "abc";
+        int result = strings.length;
+        for (String s : strings) {
+            if (s == null) {
+                result += 10;
+            }
+        }
+        return result;
+    }
+
+    public static int matrix() {
+        int[][] matrix = new int[3][4];
+        for (int i = 0; i < matrix.length; i++) {
+            for (int j = 0; j < matrix[i].length; j++) {
+                matrix[i][j] = i * j;
+            }
+        }
+        int sum = 0;
+        for (int[] row : matrix) {
+            for (int value : row) {
+                sum += value;
+            }
+        }
+        return sum;
+    }
+
+    public static int defaults() {
+        long[][][] longs = new long[2][3][4];
+        boolean[][] flags = new boolean[2][2];
+        Object[][] objects = new Object[2][2];
+        int result = 0;
+        if (longs[1][2][3] == 0L && longs[1][2].length == 4) {
+            result += 1;
+        }
+        if (!flags[1][1]) {
+            result += 2;
+        }
+        if (objects[1][1] == null) {
+            result += 4;
+        }
+        return result;
+    }
+
+    public static int partial() {
+        Object[][] objects = new Object[3][];
+        int result = objects.length;
+        if (objects[2] == null) {
+            result += 10;
+        }
+        objects[2] = new String[5];
+        return result + objects[2].length;
+    }
+
+    public static int classes() {
+        Object strings = new String[1][1];
+        Object ints = new int[1][1];
+        int result = 0;
+        if (strings instanceof Object[][]) {
+            result += 1;
+        }
+        if (((Object[]) strings)[0] instanceof String[]) {
+            result += 2;
+        }
+        if (ints instanceof Object[]) {
+            result += 4;
+        }
+        if (((Object[]) ints)[0] instanceof int[]) {
+            result += 8;
+        }
+        return result;
+    }
+
+    public static int negative(int size) {
+        try {
+            int[][] matrix = new int[1][size];
+            return matrix.length;
+        } catch (NegativeArraySizeException e) {
+            return -1;
+        }
+    }
+}
@@ -0,0 +1,6 @@
+public interface Api {
+    int next();
+
+    /** Refers to a class that no loader can find */
+    int broken();
+}
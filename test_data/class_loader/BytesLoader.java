@@ -0,0 +1,30 @@
+/** A class loader that defines a single class from the bytes it was given */
+public class BytesLoader extends ClassLoader {
+    public final int id;
+    private final String name;
+    private final byte[] bytes;
+
+    public BytesLoader(ClassLoader parent, int id, String name, byte[] bytes) {
+        super(parent);
+        this.id = id;
+        this.name = name;
+        this.bytes = bytes;
+    }
+
+    @Override
+    protected Class<?> findClass(String name) throws ClassNotFoundException {
+        if (name.equals(this.name)) {
+            return define();
+        }
+        return super.findClass(name);
+    }
+
+    public Class<?> define() {
+        return define(bytes.length);
+    }
+
+    /** Defines the class from the first length bytes */
+    public Class<?> define(int length) {
+        return defineClass(name, bytes, 0, length);
+    }
+}
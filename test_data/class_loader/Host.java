@@ -0,0 +1,105 @@
+public class Host {
+    private static Api newPlugin(ClassLoader loader) throws Exception {
+        return (Api) loader.loadClass("Plugin").newInstance();
+    }
+
+    /** Each loader defines its own Plugin class, with its own static counter */
+    public static int isolation(byte[] plugin) throws Exception {
+        Api first = newPlugin(new BytesLoader(null, 1, "Plugin", plugin));
+        Api second = newPlugin(new BytesLoader(null, 2, "Plugin", plugin));
+        int a = first.next();
+        int b = first.next();
+        int c = second.next();
+        return a + 10 * b + 100 * c;
+    }
+
+    /** Classes are loaded by the parent before the child */
+    public static int delegation(byte[] plugin) throws Exception {
+        BytesLoader parent = new BytesLoader(null, 1, "Plugin", plugin);
+        BytesLoader child = new BytesLoader(parent, 2, "Plugin", plugin);
+        Class<?> c = child.loadClass("Plugin");
+        return ((BytesLoader) c.getClassLoader()).id;
+    }
+
+    public static int getClassLoader(byte[] plugin) throws Exception {
+        int result = 0;
+        if (new Host().getClass().getClassLoader() == null) {
+            result += 1;
+        }
+        Api api = newPlugin(new BytesLoader(null, 3, "Plugin", plugin));
+        ClassLoader loader = api.getClass().getClassLoader();
+        if (loader instanceof BytesLoader && ((BytesLoader) loader).id == 3) {
+            result += 2;
+        }
+        if (api.getClass().getName().equals("Plugin")) {
+            result += 4;
+        }
+        return result;
+    }
+
+    public static int duplicate(byte[] plugin) throws Exception {
+        BytesLoader loader = new BytesLoader(null, 1, "Plugin", plugin);
+        loader.loadClass("Plugin");
+        try {
+            loader.define();
+        } catch (LinkageError e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    public static int prohibitedPackage(byte[] plugin) {
+        try {
+            new BytesLoader(null, 1, "java.lang.Plugin", plugin).define();
+        } catch (SecurityException e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    public static int wrongName(byte[] plugin) {
+        try {
+            new BytesLoader(null, 1, "Other", plugin).define();
+        } catch (NoClassDefFoundError e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    public static int notFound(byte[] plugin) {
+        try {
+            new BytesLoader(null, 1, "Plugin", plugin).loadClass("Missing");
+        } catch (ClassNotFoundException e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    public static int missingClass(byte[] plugin) throws Exception {
+        Api api = newPlugin(new BytesLoader(null, 1, "Plugin", plugin));
+        try {
+            api.broken();
+        } catch (NoClassDefFoundError e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    public static int malformed(byte[] plugin) {
+        try {
+            new BytesLoader(null, 1, "Plugin", plugin).define(plugin.length / 2);
+        } catch (ClassFormatError e) {
+            return 1;
+        }
+        return 0;
+    }
+
+    /** Broken.class on the class path is truncated, so the bootstrap loader cannot define it */
+    public static int malformedBootstrap(byte[] plugin) {
+        try {
+            return Broken.value();
+        } catch (ClassFormatError e) {
+            return 1;
+        }
+    }
+}
@@ -0,0 +1,13 @@
+public class Plugin implements Api {
+    private static int counter;
+
+    public int next() {
+        counter++;
+        return counter;
+    }
+
+    public int broken() {
+        // Missing was only available when compiling
+        return Missing.value();
+    }
+}
@@ -0,0 +1,5 @@
+public class Generated {
+    public static int value() {
+        return 7;
+    }
+}
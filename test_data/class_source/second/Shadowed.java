@@ -0,0 +1,5 @@
+public class Shadowed {
+    public static int value() {
+        return 2;
+    }
+}
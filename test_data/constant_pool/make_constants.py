@@ -0,0 +1,203 @@
+#!/usr/bin/env python3
+"""Writes Constants.class, which loads constants that javac never emits `ldc` for: method types,
+method handles and dynamically-computed constants. Run it from this directory."""
+
+import struct
+
+LOOKUP = "Ljava/lang/invoke/MethodHandles$Lookup;"
+CONSTANT_BOOTSTRAPS = "java/lang/invoke/ConstantBootstraps"
+
+ACC_PUBLIC = 0x0001
+ACC_STATIC = 0x0008
+ACC_FINAL = 0x0010
+ACC_SUPER = 0x0020
+
+ICONST_2 = 0x05
+BIPUSH = 0x10
+LDC_W = 0x13
+LDC2_W = 0x14
+ILOAD_0 = 0x1A
+DUP = 0x59
+IMUL = 0x68
+IRETURN = 0xAC
+LRETURN = 0xAD
+ARETURN = 0xB0
+INVOKEVIRTUAL = 0xB6
+INVOKESPECIAL = 0xB7
+NEW = 0xBB
+ATHROW = 0xBF
+
+REF_INVOKE_STATIC = 6
+
+
+class ConstantPool:
+    def __init__(self):
+        self.entries = []
+        self.indices = {}
+
+    def add(self, key, data, size=1):
+        if key not in self.indices:
+            self.indices[key] = len(self.entries) + 1
+            self.entries.append(data)
+            # Long and double constants take up two entries
+            if size == 2:
+                self.entries.append(None)
+        return self.indices[key]
+
+    def utf8(self, value):
+        encoded = value.encode()
+        return self.add(("Utf8", value), struct.pack(">BH", 1, len(encoded)) + encoded)
+
+    def integer(self, value):
+        return self.add(("Integer", value), struct.pack(">Bi", 3, value))
+
+    def long(self, value):
+        return self.add(("Long", value), struct.pack(">Bq", 5, value), 2)
+
+    def class_(self, name):
+        return self.add(("Class", name), struct.pack(">BH", 7, self.utf8(name)))
+
+    def name_and_type(self, name, descriptor):
+        data = struct.pack(">BHH", 12, self.utf8(name), self.utf8(descriptor))
+        return self.add(("NameAndType", name, descriptor), data)
+
+    def methodref(self, class_name, name, descriptor):
+        data = struct.pack(">BHH", 10, self.class_(class_name),
+                           self.name_and_type(name, descriptor))
+        return self.add(("Methodref", class_name, name, descriptor), data)
+
+    def method_handle(self, class_name, name, descriptor):
+        data = struct.pack(">BBH", 15, REF_INVOKE_STATIC,
+                           self.methodref(class_name, name, descriptor))
+        return self.add(("MethodHandle", class_name, name, descriptor), data)
+
+    def method_type(self, descriptor):
+        return self.add(("MethodType", descriptor), struct.pack(">BH", 16, self.utf8(descriptor)))
+
+    def dynamic(self, bootstrap_index, name, descriptor):
+        data = struct.pack(">BHH", 17, bootstrap_index, self.name_and_type(name, descriptor))
+        return self.add(("Dynamic", bootstrap_index, name, descriptor), data)
+
+    def write(self):
+        data = struct.pack(">H", len(self.entries) + 1)
+        return data + b"".join(entry for entry in self.entries if entry is not None)
+
+
+pool = ConstantPool()
+bootstrap_methods = []
+
+
+def bootstrap(handle, *args):
+    bootstrap_methods.append((handle, args))
+    return len(bootstrap_methods) - 1
+
+
+def u16(value):
+    return struct.pack(">H", value)
+
+
+def attribute(name, info):
+    return u16(pool.utf8(name)) + struct.pack(">I", len(info)) + info
+
+
+def method(name, descriptor, max_stack, max_locals, code):
+    code = bytes(code)
+    info = struct.pack(">HHI", max_stack, max_locals, len(code)) + code + u16(0) + u16(0)
+    return (u16(ACC_PUBLIC | ACC_STATIC) + u16(pool.utf8(name)) + u16(pool.utf8(descriptor)) +
+            u16(1) + attribute("Code", info))
+
+
+def ldc(index, wide=False):
+    return [LDC2_W if wide else LDC_W, index >> 8, index & 0xFF]
+
+
+def invoke(opcode, class_name, name, descriptor):
+    index = pool.methodref(class_name, name, descriptor)
+    return [opcode, index >> 8, index & 0xFF]
+
+
+this_class = pool.class_("Constants")
+super_class = pool.class_("java/lang/Object")
+
+twice = pool.method_handle("Constants", "twice", "(I)I")
+fail = pool.method_handle("Constants", "fail", "()I")
+method_type = pool.method_type("(ILjava/lang/String;)V")
+
+null_constant = bootstrap(pool.method_handle(
+    CONSTANT_BOOTSTRAPS, "nullConstant",
+    "(" + LOOKUP + "Ljava/lang/String;Ljava/lang/Class;)Ljava/lang/Object;"))
+get_static_final = bootstrap(pool.method_handle(
+    CONSTANT_BOOTSTRAPS, "getStaticFinal",
+    "(" + LOOKUP + "Ljava/lang/String;Ljava/lang/Class;Ljava/lang/Class;)Ljava/lang/Object;"),
+    pool.class_("Constants"))
+invoke_descriptor = ("(" + LOOKUP + "Ljava/lang/String;Ljava/lang/Class;"
+                     "Ljava/lang/invoke/MethodHandle;[Ljava/lang/Object;)Ljava/lang/Object;")
+invoke_twice = bootstrap(pool.method_handle(CONSTANT_BOOTSTRAPS, "invoke", invoke_descriptor),
+                         twice, pool.integer(21))
+invoke_fail = bootstrap(pool.method_handle(CONSTANT_BOOTSTRAPS, "invoke", invoke_descriptor),
+                        fail)
+unsupported = bootstrap(pool.method_handle(
+    "Constants", "bootstrap",
+    "(" + LOOKUP + "Ljava/lang/String;Ljava/lang/Class;)Ljava/lang/Object;"))
+
+fields = [("VALUE", "I", pool.integer(7)), ("BIG", "J", pool.long(1 << 40))]
+
+methods = [
+    method("twice", "(I)I", 2, 1, [ILOAD_0, ICONST_2, IMUL, IRETURN]),
+    method("fail", "()I", 2, 0,
+           [NEW] + list(u16(pool.class_("java/lang/RuntimeException"))) + [DUP] +
+           invoke(INVOKESPECIAL, "java/lang/RuntimeException", "<init>", "()V") + [ATHROW]),
+    method("classLiteral", "()Ljava/lang/Class;", 1, 0,
+           ldc(pool.class_("Constants")) + [ARETURN]),
+    method("missingClass", "()Ljava/lang/Class;", 1, 0,
+           ldc(pool.class_("does/not/Exist")) + [ARETURN]),
+    method("methodType", "()Ljava/lang/invoke/MethodType;", 1, 0,
+           ldc(method_type) + [ARETURN]),
+    method("methodTypeDescriptor", "()Ljava/lang/String;", 1, 0,
+           ldc(method_type) +
+           invoke(INVOKEVIRTUAL, "java/lang/invoke/MethodType", "toMethodDescriptorString",
+                  "()Ljava/lang/String;") + [ARETURN]),
+    method("methodHandle", "()I", 2, 0,
+           ldc(twice) + [BIPUSH, 21] +
+           invoke(INVOKEVIRTUAL, "java/lang/invoke/MethodHandle", "invokeExact", "(I)I") +
+           [IRETURN]),
+    method("handleType", "()Ljava/lang/String;", 1, 0,
+           ldc(twice) +
+           invoke(INVOKEVIRTUAL, "java/lang/invoke/MethodHandle", "type",
+                  "()Ljava/lang/invoke/MethodType;") +
+           invoke(INVOKEVIRTUAL, "java/lang/invoke/MethodType", "toMethodDescriptorString",
+                  "()Ljava/lang/String;") + [ARETURN]),
+    method("wrongType", "()J", 2, 0,
+           ldc(twice) + [BIPUSH, 21] +
+           invoke(INVOKEVIRTUAL, "java/lang/invoke/MethodHandle", "invokeExact", "(I)J") +
+           [LRETURN]),
+    method("condyNull", "()Ljava/lang/Object;", 1, 0,
+           ldc(pool.dynamic(null_constant, "_", "Ljava/lang/Object;")) + [ARETURN]),
+    method("condyStatic", "()I", 1, 0,
+           ldc(pool.dynamic(get_static_final, "VALUE", "I")) + [IRETURN]),
+    method("condyLong", "()J", 2, 0,
+           ldc(pool.dynamic(get_static_final, "BIG", "J"), wide=True) + [LRETURN]),
+    method("condyInvoke", "()I", 1, 0,
+           ldc(pool.dynamic(invoke_twice, "_", "I")) + [IRETURN]),
+    method("condyFailure", "()I", 1, 0,
+           ldc(pool.dynamic(invoke_fail, "_", "I")) + [IRETURN]),
+    method("condyUnsupported", "()Ljava/lang/Object;", 1, 0,
+           ldc(pool.dynamic(unsupported, "_", "Ljava/lang/Object;")) + [ARETURN]),
+]
+
+field_data = b"".join(
+    u16(ACC_PUBLIC | ACC_STATIC | ACC_FINAL) + u16(pool.utf8(name)) + u16(pool.utf8(descriptor)) +
+    u16(1) + attribute("ConstantValue", u16(value))
+    for name, descriptor, value in fields)
+
+bootstrap_info = u16(len(bootstrap_methods)) + b"".join(
+    u16(handle) + u16(len(args)) + b"".join(u16(arg) for arg in args)
+    for handle, args in bootstrap_methods)
+class_attributes = attribute("BootstrapMethods", bootstrap_info)
+
+# Java 11, the first version with dynamically-computed constants
+body = (u16(ACC_PUBLIC | ACC_SUPER) + u16(this_class) + u16(super_class) + u16(0) +
+        u16(len(fields)) + field_data + u16(len(methods)) + b"".join(methods) +
+        u16(1) + class_attributes)
+with open("Constants.class", "wb") as f:
+    f.write(struct.pack(">IHH", 0xCAFEBABE, 0, 55) + pool.write() + body)
@@ -0,0 +1,89 @@
+public class Exceptions {
+    static class Resource implements AutoCloseable {
+        static int closed = 0;
+
+        public void close() {
+            closed++;
+        }
+    }
+
+    static void thrower() {
+        throw new TestException();
+    }
+
+    static void rethrower() {
+        thrower();
+    }
+
+    public static int catchThrown() {
+        try {
+            throw new TestException();
+        } catch (TestException e) {
+            return 1;
+        }
+    }
+
+    public static int catchSuperclass() {
+        try {
+            rethrower();
+        } catch (RuntimeException e) {
+            return 2;
+        }
+        return 0;
+    }
+
+    public static int catchFirstMatching() {
+        try {
+            thrower();
+        } catch (Error e) {
+            return 0;
+        } catch (Exception e) {
+            return 3;
+        }
+        return 0;
+    }
+
+    public static int finallyRuns() {
+        int x = 0;
+        try {
+            try {
+                thrower();
+            } finally {
+                x += 4;
+            }
+        } catch (TestException e) {
+            x += 1;
+        }
+        return x;
+    }
+
+    public static int tryWithResources() {
+        try (Resource r = new Resource()) {
+            thrower();
+        } catch (TestException e) {
+            return Resource.closed;
+        }
+        return 0;
+    }
+
+    static int recurse(int depth) {
+        return recurse(depth + 1);
+    }
+
+    public static int stackOverflow() {
+        try {
+            recurse(0);
+        } catch (StackOverflowError e) {
+            return 6;
+        }
+        return 0;
+    }
+
+    public static int uncaught() {
+        thrower();
+        return 0;
+    }
+}
+
+class TestException extends RuntimeException {
+}
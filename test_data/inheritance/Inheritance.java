@@ -0,0 +1,90 @@
+public class Inheritance {
+    interface Table {
+        int[] VALUES = {1, 2, 3};
+    }
+
+    static class Base {
+        static int created;
+        static int untouchedStatic;
+
+        int x;
+        int untouched;
+
+        Base(int x) {
+            this.x = x;
+            created++;
+        }
+
+        int getX() {
+            return x;
+        }
+
+        static int twice(int value) {
+            return 2 * value;
+        }
+    }
+
+    static class Derived extends Base implements Table {
+        int y;
+
+        Derived(int x, int y) {
+            super(x);
+            this.y = y;
+        }
+
+        int sum() {
+            return x + y;
+        }
+    }
+
+    static class Shadow extends Base {
+        int x;
+
+        Shadow(int a, int b) {
+            super(a);
+            this.x = b;
+        }
+
+        int both() {
+            return super.x * 10 + x;
+        }
+    }
+
+    static class Grandchild extends Derived {
+        Grandchild() {
+            super(1, 2);
+        }
+
+        int getX() {
+            return super.getX() + 100;
+        }
+    }
+
+    static int inheritedFields() {
+        Derived derived = new Derived(3, 4);
+        return derived.sum() + derived.untouched;
+    }
+
+    static int shadowedField() {
+        return new Shadow(1, 2).both();
+    }
+
+    static int inheritedStaticMethod() {
+        return Derived.twice(21);
+    }
+
+    static int inheritedStaticField() {
+        new Derived(1, 1);
+        new Grandchild();
+        return Derived.created + Grandchild.untouchedStatic;
+    }
+
+    static int interfaceStaticField() {
+        return Derived.VALUES[1];
+    }
+
+    static int superMethod() {
+        Base base = new Grandchild();
+        return base.getX();
+    }
+}
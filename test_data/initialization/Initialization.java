@@ -0,0 +1,133 @@
+public class Initialization {
+    static class Log {
+        static int value;
+
+        static int record(int step) {
+            value = value * 10 + step;
+            return step;
+        }
+    }
+
+    static class Parent {
+        static int parentField = Log.record(1);
+    }
+
+    static class Child extends Parent {
+        static int childField = Log.record(2);
+    }
+
+    static class Lazy {
+        static final int CONSTANT = 5;
+        static int field = Log.record(3);
+
+        int instanceMethod() {
+            return 7;
+        }
+    }
+
+    static class A {
+        static int a = B.b + 1;
+    }
+
+    static class B {
+        static int b = A.a + 10;
+    }
+
+    static class Bad {
+        static int value = 1 / zero();
+
+        static int zero() {
+            return 0;
+        }
+    }
+
+    static class FatalError extends Error {
+    }
+
+    static class Fatal {
+        static int value;
+
+        static {
+            if (Log.record(4) == 4) {
+                throw new FatalError();
+            }
+        }
+    }
+
+    interface Plain {
+        int X = Log.record(5);
+    }
+
+    interface WithDefault {
+        int Y = Log.record(6);
+
+        default int method() {
+            return Y;
+        }
+    }
+
+    static class PlainImpl implements Plain {
+    }
+
+    static class DefaultImpl implements WithDefault {
+    }
+
+    static int superclassFirst() {
+        new Child();
+        return Log.value;
+    }
+
+    static int inheritedField() {
+        int value = Child.parentField;
+        return Log.value * 10 + value;
+    }
+
+    static int notTriggered() {
+        int value = Lazy.CONSTANT;
+        Lazy lazy = null;
+        if (value == 6) {
+            lazy = new Lazy();
+        }
+        return Log.value * 10 + value;
+    }
+
+    static int recursive() {
+        return A.a * 100 + B.b;
+    }
+
+    static int exceptionInInitializer() {
+        int result = 0;
+        try {
+            result = Bad.value;
+        } catch (ExceptionInInitializerError e) {
+            result += 1;
+            if (e.getCause() != null) {
+                result += 100;
+            }
+        }
+        try {
+            result = Bad.value;
+        } catch (NoClassDefFoundError e) {
+            result += 10;
+        }
+        return result;
+    }
+
+    static int errorNotWrapped() {
+        try {
+            return Fatal.value;
+        } catch (FatalError e) {
+            return 1;
+        } catch (ExceptionInInitializerError e) {
+            return 2;
+        }
+    }
+
+    static int interfaces() {
+        new PlainImpl();
+        new DefaultImpl();
+        int before = Log.value;
+        int x = Plain.X;
+        return before * 10 + Log.value % 10;
+    }
+}
@@ -0,0 +1,112 @@
+public class Interfaces {
+    interface Shape {
+        int area();
+
+        default int scaled(int factor) {
+            return area() * factor;
+        }
+
+        static int unit() {
+            return 1;
+        }
+    }
+
+    interface Named {
+        default int id() {
+            return 1;
+        }
+    }
+
+    interface Special extends Named {
+        default int id() {
+            return 2;
+        }
+    }
+
+    interface Stack {
+        void push(int value);
+
+        int pop();
+    }
+
+    static class Square implements Shape, Special, Named {
+        int side;
+
+        Square(int side) {
+            this.side = side;
+        }
+
+        public int area() {
+            return side * side;
+        }
+    }
+
+    static class Circle implements Shape {
+        public int area() {
+            return 3;
+        }
+
+        public int scaled(int factor) {
+            return 100;
+        }
+    }
+
+    static class Delegating implements Named {
+        public int id() {
+            return Named.super.id() + 10;
+        }
+    }
+
+    static class ArrayStack implements Stack {
+        int[] values;
+        int size;
+
+        ArrayStack() {
+            values = new int[8];
+            size = 0;
+        }
+
+        public void push(int value) {
+            values[size++] = value;
+        }
+
+        public int pop() {
+            return values[--size];
+        }
+    }
+
+    static class SubStack extends ArrayStack {
+        public int pop() {
+            return super.pop() * 2;
+        }
+    }
+
+    public static int area() {
+        Shape shape = new Square(4);
+        return shape.area() + Shape.unit();
+    }
+
+    public static int defaultMethod() {
+        Shape square = new Square(2);
+        Shape circle = new Circle();
+        return square.scaled(10) + circle.scaled(10);
+    }
+
+    public static int maximallySpecific() {
+        Named named = new Square(1);
+        return named.id();
+    }
+
+    public static int superDefault() {
+        Named named = new Delegating();
+        return named.id();
+    }
+
+    public static int stack() {
+        Stack stack = new SubStack();
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        return stack.pop() * 100 + stack.pop() * 10 + stack.pop();
+    }
+}
@@ -0,0 +1,197 @@
+import java.io.Serializable;
+
+public class Lambdas {
+    interface IntOp {
+        int apply(int x);
+
+        default IntOp twice() {
+            return x -> apply(apply(x));
+        }
+    }
+
+    interface IntSupplier {
+        int get();
+    }
+
+    interface Consumer {
+        void accept(int x);
+    }
+
+    interface Generic<T> {
+        int size(T t);
+    }
+
+    interface Specific extends Generic<int[]> {
+        int size(int[] t);
+    }
+
+    interface ToInt<T> {
+        int of(T t);
+    }
+
+    interface Supplier<T> {
+        T get();
+    }
+
+    interface LongOp {
+        long apply(int x);
+    }
+
+    interface Factory {
+        Box make(int value);
+    }
+
+    interface Shape {
+        int area();
+    }
+
+    static class Square implements Shape {
+        int side;
+
+        Square(int side) {
+            this.side = side;
+        }
+
+        public int area() {
+            return side * side;
+        }
+    }
+
+    static class Box {
+        int value;
+
+        Box(int value) {
+            this.value = value;
+        }
+    }
+
+    int base;
+
+    Lambdas(int base) {
+        this.base = base;
+    }
+
+    static int twice(int x) {
+        return 2 * x;
+    }
+
+    static int six() {
+        return 6;
+    }
+
+    int plusBase(int x) {
+        return x + base;
+    }
+
+    int addBase(int y) {
+        IntOp op = x -> x + base;
+        return op.apply(y);
+    }
+
+    static int apply(IntOp op, int x) {
+        return op.apply(x);
+    }
+
+    static int nonCapturing() {
+        IntOp op = x -> x + 1;
+        return apply(op, 41);
+    }
+
+    static int capturing() {
+        int a = 10;
+        int b = 5;
+        IntOp op = x -> x * a + b;
+        return op.apply(3);
+    }
+
+    static int capturingThis() {
+        return new Lambdas(100).addBase(5);
+    }
+
+    static int staticMethodRef() {
+        return apply(Lambdas::twice, 21);
+    }
+
+    static int boundMethodRef() {
+        Lambdas lambdas = new Lambdas(7);
+        return apply(lambdas::plusBase, 3);
+    }
+
+    static int unboundMethodRef() {
+        ToInt<Shape> area = Shape::area;
+        return area.of(new Square(4));
+    }
+
+    static int constructorRef() {
+        Factory factory = Box::new;
+        return factory.make(9).value;
+    }
+
+    static int voidLambda() {
+        int[] total = new int[1];
+        Consumer consumer = x -> total[0] = total[0] + x;
+        consumer.accept(3);
+        consumer.accept(4);
+        return total[0];
+    }
+
+    static int sameCallSite() {
+        int sum = 0;
+        for (int i = 1; i <= 3; i++) {
+            int j = i;
+            IntSupplier square = () -> j * j;
+            sum += square.get();
+        }
+        return sum;
+    }
+
+    static int defaultMethodLambda() {
+        IntOp inc = x -> x + 1;
+        return inc.twice().apply(5);
+    }
+
+    static int bridges() {
+        Specific specific = array -> array.length;
+        Generic<int[]> generic = specific;
+        return specific.size(new int[3]) + generic.size(new int[4]);
+    }
+
+    static int serializable() {
+        IntOp op = (IntOp & Serializable) x -> x * 3;
+        return op.apply(2);
+    }
+
+    /** Boxes the int returned by six and unboxes the Integer passed to twice */
+    static int boxing() {
+        Supplier<Integer> supplier = Lambdas::six;
+        ToInt<Integer> unbox = Lambdas::twice;
+        return unbox.of(supplier.get());
+    }
+
+    /** Widens the int returned by twice to the long returned by the interface */
+    static int widening() {
+        LongOp op = Lambdas::twice;
+        return (int) op.apply(21);
+    }
+
+    /** Passes a Box where the lambda was instantiated to take a Square */
+    @SuppressWarnings({"rawtypes", "unchecked"})
+    static int castArgument() {
+        ToInt<Square> one = square -> 1;
+        ToInt raw = one;
+        try {
+            return raw.of(new Box(2));
+        } catch (ClassCastException e) {
+            return -1;
+        }
+    }
+
+    static int unboxNull() {
+        ToInt<Integer> unbox = Lambdas::twice;
+        try {
+            return unbox.of(null);
+        } catch (NullPointerException e) {
+            return -1;
+        }
+    }
+}
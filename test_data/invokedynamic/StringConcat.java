@@ -0,0 +1,24 @@
+public class StringConcat {
+    static String ints(int a, int b) {
+        return a + " + " + b + " = " + (a + b);
+    }
+
+    static String primitives(int i) {
+        long l = 1L << 40;
+        char c = 'x';
+        boolean z = i > 0;
+        double d = i / 2.0;
+        float f = 1e10f;
+        return "l=" + l + ",c=" + c + ",z=" + z + ",d=" + d + ",f=" + f + ",small=" + 1e-5;
+    }
+
+    static String nulls() {
+        String s = null;
+        String t = null;
+        return s + "/" + t;
+    }
+
+    static String tags(int x) {
+        return "tag\u0001" + x + "\u0002end";
+    }
+}
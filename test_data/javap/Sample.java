@@ -0,0 +1,55 @@
+import java.io.IOException;
+
+public class Sample {
+    static final long BIG = 1234567890123L;
+    static final String GREETING = "tab\there";
+    private double ratio = 1.5;
+    protected volatile float scale = 1e10f;
+
+    static int table(int x) {
+        switch (x) {
+            case 1: return 10;
+            case 2: return 20;
+            case 3: return 30;
+            default: return 0;
+        }
+    }
+
+    static int lookup(int x) {
+        switch (x) {
+            case 1: return 10;
+            case 1000: return 20;
+            default: return -1;
+        }
+    }
+
+    String describe(int[] values) throws IOException {
+        try {
+            return GREETING + values[0];
+        } catch (RuntimeException e) {
+            return null;
+        } finally {
+            ratio = 0.001;
+        }
+    }
+
+    synchronized long widen(int n, long total, String... names) {
+        Object grid = new int[2][3];
+        char[] chars = new char[n];
+        for (int i = 0; i < 300; i++) {
+            total += i * 100000;
+        }
+        return total + chars.length + names.length;
+    }
+
+    int manyLocals() {
+        int v0 = 0, v1 = 1, v2 = 2, v3 = 3, v4 = 4, v5 = 5, v6 = 6, v7 = 7, v8 = 8, v9 = 9;
+        int[] big = new int[300];
+        int sum = v0 + v1 + v2 + v3 + v4 + v5 + v6 + v7 + v8 + v9 + big.length;
+        Runnable r = new Runnable() {
+            public void run() {
+            }
+        };
+        return sum;
+    }
+}
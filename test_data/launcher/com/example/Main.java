@@ -0,0 +1,26 @@
+package com.example;
+
+public class Main {
+    public static void main(String[] args) {
+        if (args.length == 0) {
+            return;
+        }
+        if (args[0].equals("exit")) {
+            System.exit(3);
+        } else if (args[0].equals("throw")) {
+            throw new RuntimeException("failed");
+        } else if (args[0].equals("property")) {
+            if ("hello".equals(System.getProperty("greeting"))) {
+                System.exit(4);
+            }
+            System.exit(5);
+        } else if (args[0].equals("recurse")) {
+            recurse();
+        }
+        System.exit(2);
+    }
+
+    static void recurse() {
+        recurse();
+    }
+}
@@ -0,0 +1,6 @@
+package com.example;
+
+public class NoMain {
+    public static void run(String[] args) {
+    }
+}
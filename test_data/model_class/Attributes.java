@@ -0,0 +1,56 @@
+import java.io.IOException;
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+import java.util.function.IntSupplier;
+
+public class Attributes<T extends Comparable<T>> {
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Tag {
+        String value() default "none";
+        int[] counts() default {1, 2};
+    }
+
+    @Retention(RetentionPolicy.CLASS)
+    @Target(ElementType.TYPE_USE)
+    @interface Checked {}
+
+    class Inner {
+        int get() {
+            return count;
+        }
+    }
+
+    private static final int CONSTANT = 42;
+
+    @Tag("field")
+    private int count;
+
+    private List<@Checked String> names;
+
+    @Deprecated
+    @Tag(value = "method", counts = {3})
+    public int sum(@Tag("param") int limit, final T ignored) throws IOException {
+        int total = 0;
+        for (int i = 0; i < limit; i++) {
+            if (i % 2 == 0) {
+                total += i;
+            } else {
+                total -= 1;
+            }
+        }
+        return total;
+    }
+
+    public IntSupplier supplier() {
+        Object local = new Object() {
+            @Override
+            public String toString() {
+                return "anonymous";
+            }
+        };
+        return () -> local.hashCode() + CONSTANT;
+    }
+}
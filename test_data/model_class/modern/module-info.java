@@ -0,0 +1,6 @@
+module shapes {
+    requires java.base;
+    exports shapes;
+    uses shapes.Shape;
+    provides shapes.Shape with shapes.Shape.Circle;
+}
@@ -0,0 +1,17 @@
+package shapes;
+
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+
+public sealed interface Shape permits Shape.Circle, Shape.Square {
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Unit {}
+
+    record Circle(@Unit int radius) implements Shape {
+        public Circle() {
+            this(1);
+        }
+    }
+
+    record Square(int side) implements Shape {}
+}
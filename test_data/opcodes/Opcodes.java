@@ -0,0 +1,465 @@
+/**
+ * Exercises each instruction javac emits, with inputs passed as arguments so that javac cannot fold
+ * them into constants. jsr, ret, wide, goto_w, swap, nop and the forms of the stack instructions
+ * javac does not emit are exercised by the hand-assembled classes in test_data/verifier instead.
+ */
+public class Opcodes {
+    static long counter;
+    int field;
+    long longField;
+
+    public static int intConstants() {
+        int[] values = { -1, 0, 1, 2, 3, 4, 5, 100, -1000, 100000 };
+        int hash = 0;
+        for (int value : values) {
+            hash = hash * 31 + value;
+        }
+        return hash;
+    }
+
+    public static long longConstants() {
+        long[] values = { 0L, 1L, 1L << 40 };
+        return values[0] + values[1] + values[2];
+    }
+
+    public static float floatConstants() {
+        float[] values = { 0.0f, 1.0f, 2.0f, 0.25f };
+        return values[0] + values[1] + values[2] + values[3];
+    }
+
+    public static double doubleConstants() {
+        double[] values = { 0.0, 1.0, 0.125 };
+        return values[0] + values[1] + values[2];
+    }
+
+    public static Object nullConstant() {
+        return null;
+    }
+
+    public static int iadd(int a, int b) {
+        return a + b;
+    }
+
+    public static int isub(int a, int b) {
+        return a - b;
+    }
+
+    public static int imul(int a, int b) {
+        return a * b;
+    }
+
+    public static int idiv(int a, int b) {
+        return a / b;
+    }
+
+    public static int irem(int a, int b) {
+        return a % b;
+    }
+
+    public static int ineg(int a) {
+        return -a;
+    }
+
+    public static int ishl(int a, int shift) {
+        return a << shift;
+    }
+
+    public static int ishr(int a, int shift) {
+        return a >> shift;
+    }
+
+    public static int iushr(int a, int shift) {
+        return a >>> shift;
+    }
+
+    public static int iand(int a, int b) {
+        return a & b;
+    }
+
+    public static int ior(int a, int b) {
+        return a | b;
+    }
+
+    public static int ixor(int a, int b) {
+        return a ^ b;
+    }
+
+    public static int iinc(int a) {
+        a += 1000;
+        a -= 1;
+        return a;
+    }
+
+    /** Widens both operands, using Long.MIN_VALUE in place of an a of 0 */
+    private static long wide(int a) {
+        return a == 0 ? Long.MIN_VALUE : a;
+    }
+
+    public static long ladd(int a, int b) {
+        return wide(a) + b;
+    }
+
+    public static long lsub(int a, int b) {
+        return wide(a) - b;
+    }
+
+    public static long lmul(int a, int b) {
+        return wide(a) * b;
+    }
+
+    public static long ldiv(int a, int b) {
+        return wide(a) / b;
+    }
+
+    public static long lrem(int a, int b) {
+        return wide(a) % b;
+    }
+
+    public static long lneg(int a) {
+        return -wide(a);
+    }
+
+    public static long lshl(int a, int shift) {
+        return wide(a) << shift;
+    }
+
+    public static long lshr(int a, int shift) {
+        return wide(a) >> shift;
+    }
+
+    public static long lushr(int a, int shift) {
+        return wide(a) >>> shift;
+    }
+
+    public static long land(int a, int b) {
+        return wide(a) & ((long) b << 32 | b);
+    }
+
+    public static long lor(int a, int b) {
+        return wide(a) | ((long) b << 32);
+    }
+
+    public static long lxor(int a, int b) {
+        return wide(a) ^ ((long) b << 32);
+    }
+
+    public static int lcmp(int a, int b) {
+        long x = wide(a);
+        long y = b;
+        return x < y ? -1 : x == y ? 0 : 1;
+    }
+
+    public static float fadd(float a, float b) {
+        return a + b;
+    }
+
+    public static float fsub(float a, float b) {
+        return a - b;
+    }
+
+    public static float fmul(float a, float b) {
+        return a * b;
+    }
+
+    public static float fdiv(float a, float b) {
+        return a / b;
+    }
+
+    public static float frem(float a, float b) {
+        return a % b;
+    }
+
+    public static float fneg(float a) {
+        return -a;
+    }
+
+    /** Compiles to fcmpg, so that a NaN operand makes the comparison false */
+    public static boolean flt(float a, float b) {
+        return a < b;
+    }
+
+    /** Compiles to fcmpl, so that a NaN operand makes the comparison false */
+    public static boolean fgt(float a, float b) {
+        return a > b;
+    }
+
+    public static double dadd(int a, int b) {
+        return (double) a + b;
+    }
+
+    public static double dsub(int a, int b) {
+        return (double) a - b;
+    }
+
+    public static double dmul(int a, int b) {
+        return (double) a * b;
+    }
+
+    public static double ddiv(int a, int b) {
+        return (double) a / b;
+    }
+
+    public static double drem(int a, int b) {
+        return ((double) a + 0.5) % b;
+    }
+
+    public static double dneg(int a) {
+        return -(double) a;
+    }
+
+    /** Compiles to dcmpg, so that a NaN operand makes the comparison false */
+    public static boolean dlt(int a, int b) {
+        return (double) a / b < 1.0;
+    }
+
+    /** Compiles to dcmpl, so that a NaN operand makes the comparison false */
+    public static boolean dgt(int a, int b) {
+        return (double) a / b > 1.0;
+    }
+
+    public static long i2l(int a) {
+        return a;
+    }
+
+    public static float i2f(int a) {
+        return a;
+    }
+
+    public static double i2d(int a) {
+        return a;
+    }
+
+    public static int l2i(int a, int b) {
+        return (int) ((long) a << 32 | b);
+    }
+
+    public static float l2f(int a) {
+        return wide(a);
+    }
+
+    public static double l2d(int a) {
+        return wide(a);
+    }
+
+    public static int f2i(float a) {
+        return (int) a;
+    }
+
+    public static long f2l(float a) {
+        return (long) a;
+    }
+
+    public static double f2d(float a) {
+        return a;
+    }
+
+    public static int d2i(float a) {
+        return (int) ((double) a * 1e10);
+    }
+
+    public static long d2l(float a) {
+        return (long) ((double) a * 1e20);
+    }
+
+    public static float d2f(int a, int b) {
+        return (float) ((double) a / b);
+    }
+
+    public static int i2b(int a) {
+        return (byte) a;
+    }
+
+    public static int i2c(int a) {
+        return (char) a;
+    }
+
+    public static int i2s(int a) {
+        return (short) a;
+    }
+
+    /** Sets a bit for each comparison of a with zero, then of a with b, that holds */
+    public static int compare(int a, int b) {
+        int bits = 0;
+        if (a == 0) bits |= 1;
+        if (a != 0) bits |= 2;
+        if (a < 0) bits |= 4;
+        if (a >= 0) bits |= 8;
+        if (a > 0) bits |= 16;
+        if (a <= 0) bits |= 32;
+        if (a == b) bits |= 64;
+        if (a != b) bits |= 128;
+        if (a < b) bits |= 256;
+        if (a >= b) bits |= 512;
+        if (a > b) bits |= 1024;
+        if (a <= b) bits |= 2048;
+        return bits;
+    }
+
+    /** Sets a bit for each comparison of references that holds, where a selects the second */
+    public static int compareReferences(int a) {
+        Object first = new Object();
+        Object second = a == 0 ? null : a == 1 ? first : new Object();
+        int bits = 0;
+        if (first == second) bits |= 1;
+        if (first != second) bits |= 2;
+        if (second == null) bits |= 4;
+        if (second != null) bits |= 8;
+        return bits;
+    }
+
+    public static int tableSwitch(int a) {
+        switch (a) {
+            case 1: return 10;
+            case 2: return 20;
+            case 3: return 30;
+            case 4: return 40;
+            default: return -1;
+        }
+    }
+
+    public static int lookupSwitch(int a) {
+        switch (a) {
+            case -1000: return 1;
+            case 0: return 2;
+            case 1000000: return 3;
+            default: return -1;
+        }
+    }
+
+    public static int booleanArray(boolean a) {
+        boolean[] array = new boolean[1];
+        array[0] = a;
+        return array[0] ? 1 : 0;
+    }
+
+    public static int byteArray(byte a) {
+        byte[] array = new byte[1];
+        array[0] = a;
+        return array[0];
+    }
+
+    public static int charArray(char a) {
+        char[] array = new char[1];
+        array[0] = a;
+        return array[0];
+    }
+
+    public static int shortArray(short a) {
+        short[] array = new short[1];
+        array[0] = a;
+        return array[0];
+    }
+
+    public static long primitiveArrays(int a) {
+        int[] ints = new int[2];
+        long[] longs = new long[2];
+        float[] floats = new float[2];
+        double[] doubles = new double[2];
+        ints[1] = a;
+        longs[1] = wide(a);
+        floats[1] = a;
+        doubles[1] = a;
+        return ints[1] + longs[1] + (long) floats[1] + (long) doubles[1] + ints.length;
+    }
+
+    public static int referenceArray(int a) {
+        Object[] objects = new Object[a];
+        objects[0] = objects;
+        return objects[0] == objects ? objects.length : -1;
+    }
+
+    /** Takes long and double arguments, which each take up two local variables */
+    public static long longArguments(long a, int b, long c) {
+        return a * 100 + b * 10 + c;
+    }
+
+    public static double doubleArguments(double a, float b, double c) {
+        return a * 100 + b * 10 + c;
+    }
+
+    public long instanceArguments(long a, double b) {
+        return field + a + (long) b;
+    }
+
+    /** Passes long and double arguments to other methods */
+    public static long passArguments(int a) {
+        Opcodes instance = new Opcodes();
+        instance.field = a;
+        return longArguments(a, a, a) + (long) doubleArguments(a, a, a) +
+            instance.instanceArguments(a, a);
+    }
+
+    /** Reuses the local variables of a long for an int and a float */
+    public static int reuseLocals(int a) {
+        int sum;
+        {
+            long l = a;
+            sum = (int) l;
+        }
+        {
+            int i = sum + 1;
+            float f = i;
+            sum += (int) f;
+        }
+        return sum;
+    }
+
+    /** Stores a value of each type in a local variable, then loads it */
+    public static double locals(int a) {
+        long l = a;
+        float f = l;
+        double d = f;
+        Object o = null;
+        return l + f + d + (o == null ? 1 : 0);
+    }
+
+    /** Discards the result of each call, with pop and pop2 */
+    public static int discard(int a) {
+        iadd(a, a);
+        ladd(a, a);
+        return a;
+    }
+
+    /** Assigns a field in an expression, which duplicates the value with dup_x1 */
+    public static int assignField(int a) {
+        Opcodes instance = new Opcodes();
+        int result = instance.field = a;
+        return result + instance.field;
+    }
+
+    /** Increments a static long after reading it, which duplicates it with dup2 */
+    public static long postIncrement(int a) {
+        counter = a;
+        return counter++;
+    }
+
+    /** Increments an array element twice, duplicating the array and index with dup2 */
+    public static int incrementElement(int a) {
+        int[] array = { a };
+        array[0] += 5;
+        return array[0]++;
+    }
+
+    /** Duplicates the long element beneath the array and index with dup2_x2 */
+    public static long incrementLongElement(int a) {
+        long[] array = { a };
+        return array[0]++;
+    }
+
+    /** Duplicates the long value beneath the object with dup2_x1 */
+    public static long assignLongField(int a) {
+        Opcodes instance = new Opcodes();
+        return instance.longField = a;
+    }
+
+    public static int monitor(int a) {
+        Object lock = a == 0 ? null : new Object();
+        try {
+            synchronized (lock) {
+                return a + 1;
+            }
+        } catch (NullPointerException e) {
+            return -1;
+        }
+    }
+}
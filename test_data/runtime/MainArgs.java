@@ -0,0 +1,15 @@
+public class MainArgs {
+    public static void main(String[] args) {
+        if (args.length != 2) {
+            throw new RuntimeException();
+        }
+        byte[] first = args[0].getBytes();
+        byte[] second = args[1].getBytes();
+        if (first.length != 10 || first[1] != 'h' || first[9] != 'o') {
+            throw new RuntimeException();
+        }
+        if (second.length != 0) {
+            throw new RuntimeException();
+        }
+    }
+}
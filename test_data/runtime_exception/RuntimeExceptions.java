@@ -0,0 +1,96 @@
+public class RuntimeExceptions {
+    int value;
+
+    static class Animal {
+    }
+
+    static class Dog extends Animal {
+    }
+
+    static class Cat extends Animal {
+    }
+
+    public static int nullField() {
+        RuntimeExceptions instance = null;
+        try {
+            return instance.value;
+        } catch (NullPointerException e) {
+            return 1;
+        }
+    }
+
+    public static int nullArray() {
+        int[] array = null;
+        try {
+            return array.length;
+        } catch (NullPointerException e) {
+            return 2;
+        }
+    }
+
+    public static int arrayIndex(int index) {
+        int[] array = new int[4];
+        try {
+            array[index] = 1;
+            return array[index];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            return -1;
+        }
+    }
+
+    public static int divide(int a, int b) {
+        try {
+            return a / b;
+        } catch (ArithmeticException e) {
+            return -1;
+        }
+    }
+
+    public static int remainder(int a, int b) {
+        long x = a;
+        long y = b;
+        try {
+            return (int) (x % y);
+        } catch (ArithmeticException e) {
+            return -1;
+        }
+    }
+
+    public static int negativeArraySize(int size) {
+        try {
+            return new int[size].length;
+        } catch (NegativeArraySizeException e) {
+            return -1;
+        }
+    }
+
+    public static int badCast() {
+        Animal animal = new Cat();
+        try {
+            Dog dog = (Dog) animal;
+            return 0;
+        } catch (ClassCastException e) {
+            return 3;
+        }
+    }
+
+    public static int goodCast() {
+        Animal animal = new Dog();
+        Dog dog = (Dog) animal;
+        return 4;
+    }
+
+    public static int nullThrow() {
+        RuntimeException exception = null;
+        try {
+            throw exception;
+        } catch (NullPointerException e) {
+            return 5;
+        }
+    }
+
+    public static int uncaught() {
+        int[] array = new int[0];
+        return array[0];
+    }
+}
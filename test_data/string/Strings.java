@@ -0,0 +1,33 @@
+public class Strings {
+    static String nul() {
+        return "a\0b";
+    }
+
+    static String accented() {
+        return "Paramètres généraux";
+    }
+
+    static String cjk() {
+        return "日本語";
+    }
+
+    static String emoji() {
+        return "😀!";
+    }
+
+    static String lone() {
+        return "\uD800x";
+    }
+
+    static int emojiLength() {
+        return "😀".length();
+    }
+
+    static String concatChars(char high, char low) {
+        return "[" + high + low + "]";
+    }
+
+    static String concatAccented(int count) {
+        return "Éléments : " + count;
+    }
+}
@@ -0,0 +1,100 @@
+import java.io.Serializable;
+
+public class Subtypes {
+    interface Animal {
+    }
+
+    interface Pet extends Animal {
+    }
+
+    static class Dog implements Pet {
+    }
+
+    static class Puppy extends Dog {
+    }
+
+    static class Rock {
+    }
+
+    static int instanceOfClasses() {
+        Object puppy = new Puppy();
+        Object rock = new Rock();
+        Object nothing = null;
+        int result = 0;
+        if (puppy instanceof Dog) {
+            result += 1;
+        }
+        if (puppy instanceof Puppy) {
+            result += 2;
+        }
+        if (rock instanceof Dog) {
+            result += 4;
+        }
+        if (nothing instanceof Object) {
+            result += 8;
+        }
+        return result;
+    }
+
+    static int instanceOfInterfaces() {
+        Object puppy = new Puppy();
+        Object rock = new Rock();
+        int result = 0;
+        if (puppy instanceof Animal) {
+            result += 1;
+        }
+        if (puppy instanceof Pet) {
+            result += 2;
+        }
+        if (rock instanceof Animal) {
+            result += 4;
+        }
+        return result;
+    }
+
+    static int instanceOfArrays(Object dogs, Object ints) {
+        int result = 0;
+        if (dogs instanceof Animal[]) {
+            result += 1;
+        }
+        if (dogs instanceof Object[]) {
+            result += 2;
+        }
+        if (dogs instanceof Puppy[]) {
+            result += 4;
+        }
+        if (ints instanceof Object[]) {
+            result += 8;
+        }
+        if (ints instanceof int[]) {
+            result += 16;
+        }
+        if (ints instanceof Cloneable && dogs instanceof Serializable) {
+            result += 32;
+        }
+        return result;
+    }
+
+    static int checkCast(Object[] dogs) {
+        Object pet = new Puppy();
+        Animal animal = (Animal) pet;
+        Dog[] cast = (Dog[]) dogs;
+        try {
+            Rock rock = (Rock) pet;
+            return 0;
+        } catch (ClassCastException e) {
+            return cast.length;
+        }
+    }
+
+    static int arrayStore(Object[] dogs) {
+        dogs[0] = new Puppy();
+        dogs[1] = null;
+        try {
+            dogs[1] = new Rock();
+            return 0;
+        } catch (ArrayStoreException e) {
+            return dogs[1] == null ? 1 : 2;
+        }
+    }
+}
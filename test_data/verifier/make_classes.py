@@ -0,0 +1,272 @@
+#!/usr/bin/env python3
+"""Writes class files whose methods the verifier accepts or rejects, which javac cannot emit
+because it only produces valid bytecode, or only produces it for recent class file versions. Run it
+from this directory."""
+
+import struct
+
+ACC_PUBLIC = 0x0001
+ACC_STATIC = 0x0008
+ACC_SUPER = 0x0020
+
+NOP = 0x00
+ICONST_0 = 0x03
+ICONST_1 = 0x04
+ICONST_2 = 0x05
+FCONST_0 = 0x0B
+BIPUSH = 0x10
+LDC = 0x12
+ILOAD = 0x15
+ILOAD_0 = 0x1A
+ILOAD_1 = 0x1B
+FLOAD_0 = 0x22
+ALOAD_0 = 0x2A
+ALOAD_1 = 0x2B
+ALOAD_2 = 0x2C
+ISTORE = 0x36
+ASTORE = 0x3A
+ISTORE_0 = 0x3B
+ISTORE_1 = 0x3C
+ASTORE_0 = 0x4B
+ASTORE_1 = 0x4C
+POP = 0x57
+POP2 = 0x58
+DUP_X2 = 0x5B
+DUP2 = 0x5C
+DUP2_X1 = 0x5D
+DUP2_X2 = 0x5E
+SWAP = 0x5F
+IADD = 0x60
+ISUB = 0x64
+IMUL = 0x68
+IINC = 0x84
+I2L = 0x85
+L2I = 0x88
+IFEQ = 0x99
+IFLE = 0x9E
+IF_ICMPLT = 0xA1
+GOTO = 0xA7
+JSR = 0xA8
+RET = 0xA9
+IRETURN = 0xAC
+ARETURN = 0xB0
+RETURN = 0xB1
+WIDE = 0xC4
+GOTO_W = 0xC8
+JSR_W = 0xC9
+
+# Verification types
+FLOAT = 2
+
+
+class ClassFile:
+    def __init__(self, name, version=52):
+        self.version = version
+        self.entries = []
+        self.indices = {}
+        self.methods = []
+        self.this_class = self.class_(name)
+        self.super_class = self.class_("java/lang/Object")
+
+    def add(self, key, data):
+        if key not in self.indices:
+            self.indices[key] = len(self.entries) + 1
+            self.entries.append(data)
+        return self.indices[key]
+
+    def utf8(self, value):
+        encoded = value.encode()
+        return self.add(("Utf8", value), struct.pack(">BH", 1, len(encoded)) + encoded)
+
+    def class_(self, name):
+        return self.add(("Class", name), struct.pack(">BH", 7, self.utf8(name)))
+
+    def string(self, value):
+        return self.add(("String", value), struct.pack(">BH", 8, self.utf8(value)))
+
+    def attribute(self, name, info):
+        return u16(self.utf8(name)) + struct.pack(">I", len(info)) + info
+
+    def method(self, name, descriptor, max_stack, max_locals, code, frames=None,
+               access_flags=ACC_PUBLIC | ACC_STATIC, handlers=()):
+        """Adds a method, whose exception handlers are given as (start_pc, end_pc, handler_pc,
+        catch_type) tuples."""
+        code = bytes(code)
+        attributes = []
+        if frames is not None:
+            attributes.append(self.attribute("StackMapTable", u16(len(frames)) + b"".join(frames)))
+        exception_table = b"".join(struct.pack(">HHHH", *handler) for handler in handlers)
+        info = (struct.pack(">HHI", max_stack, max_locals, len(code)) + code +
+                u16(len(handlers)) + exception_table +
+                u16(len(attributes)) + b"".join(attributes))
+        self.methods.append(u16(access_flags) + u16(self.utf8(name)) + u16(self.utf8(descriptor)) +
+                            u16(1) + self.attribute("Code", info))
+
+    def write(self, path):
+        body = (u16(ACC_PUBLIC | ACC_SUPER) + u16(self.this_class) + u16(self.super_class) +
+                u16(0) + u16(0) + u16(len(self.methods)) + b"".join(self.methods) + u16(0))
+        pool = u16(len(self.entries) + 1) + b"".join(self.entries)
+        with open(path, "wb") as f:
+            f.write(struct.pack(">IHH", 0xCAFEBABE, 0, self.version) + pool + body)
+
+
+def u16(value):
+    return struct.pack(">H", value)
+
+
+def branch(offset):
+    return list(struct.pack(">h", offset))
+
+
+def branch_w(offset):
+    return list(struct.pack(">i", offset))
+
+
+def digits(pushes, instruction, result):
+    """Returns the code of a method that pushes ints and longs, given as (digit, is_long) pairs,
+    runs a stack instruction, then returns an int whose decimal digits are the values left on the
+    stack from the bottom, whose kinds are given by result."""
+    code = [ICONST_0, ISTORE_0, ICONST_1, ISTORE_1]
+    for digit, is_long in pushes:
+        code += [BIPUSH, digit] + ([I2L] if is_long else [])
+    code.append(instruction)
+    for is_long in reversed(result):
+        code += ([L2I] if is_long else []) + [ILOAD_1, IMUL, ILOAD_0, IADD, ISTORE_0,
+                                               ILOAD_1, BIPUSH, 10, IMUL, ISTORE_1]
+    return code + [ILOAD_0, IRETURN]
+
+
+def same_frame(offset_delta):
+    return bytes([offset_delta])
+
+
+def full_frame(offset_delta, locals):
+    return (bytes([255]) + u16(offset_delta) + u16(len(locals)) + bytes(locals) + u16(0))
+
+
+# Branches to an instruction whose stack map frame the branch is consistent with
+valid = ClassFile("Valid")
+valid.method("max", "(II)I", 2, 2,
+             [ILOAD_0, ILOAD_1, IF_ICMPLT, 0, 5, ILOAD_0, IRETURN, ILOAD_1, IRETURN],
+             [same_frame(7)])
+valid.method("identity", "(Ljava/lang/String;)Ljava/lang/Object;", 1, 1, [ALOAD_0, ARETURN])
+valid.write("Valid.class")
+
+bad_operand = ClassFile("BadOperand")
+bad_operand.method("add", "()I", 2, 0,
+                   [ICONST_1, LDC, bad_operand.string("one"), IADD, IRETURN])
+bad_operand.write("BadOperand.class")
+
+stack_overflow = ClassFile("StackOverflow")
+stack_overflow.method("add", "()I", 1, 0, [ICONST_1, ICONST_2, IADD, IRETURN])
+stack_overflow.write("StackOverflow.class")
+
+choose = [ILOAD_0, IFEQ, 0, 5, ICONST_1, IRETURN, ICONST_0, IRETURN]
+
+missing_frame = ClassFile("MissingFrame")
+missing_frame.method("choose", "(I)I", 1, 1, choose)
+missing_frame.write("MissingFrame.class")
+
+inconsistent_frame = ClassFile("InconsistentFrame")
+inconsistent_frame.method("choose", "(I)I", 1, 1, choose, [full_frame(6, [FLOAT])])
+inconsistent_frame.write("InconsistentFrame.class")
+
+bad_local = ClassFile("BadLocal")
+bad_local.method("load", "()V", 1, 1, [FLOAD_0, RETURN])
+bad_local.write("BadLocal.class")
+
+# A constructor that does not call the constructor of its superclass
+uninitialized = ClassFile("Uninitialized")
+uninitialized.method("<init>", "()V", 1, 1, [ALOAD_0, RETURN], access_flags=ACC_PUBLIC)
+uninitialized.write("Uninitialized.class")
+
+falls_off = ClassFile("FallsOff")
+falls_off.method("run", "()V", 0, 0, [NOP])
+falls_off.write("FallsOff.class")
+
+# Java 8 class files cannot call subroutines
+modern_jsr = ClassFile("ModernJsr")
+modern_jsr.method("run", "()V", 1, 1, [JSR, 0, 3, ASTORE_0, RETURN])
+modern_jsr.write("ModernJsr.class")
+
+# Java 6 class files that fail type checking are verified again by type inference, so they can
+# still call subroutines
+failover = ClassFile("Failover", 50)
+failover.method("twice", "(I)I", 2, 2,
+                [JSR] + branch(5) + [ILOAD_0, IRETURN,
+                                     ASTORE_1, ILOAD_0, ICONST_2, IMUL, ISTORE_0, RET, 1])
+failover.write("Failover.class")
+
+# A Java 6 class file that fails type inference as well is rejected with the inference error
+failover_mismatch = ClassFile("FailoverMismatch", 50)
+failover_mismatch.method("merge", "(I)V", 1, 1,
+                         [ILOAD_0, IFEQ] + branch(7) + [ICONST_0, GOTO] + branch(4) +
+                         [FCONST_0, POP, RETURN])
+failover_mismatch.write("FailoverMismatch.class")
+
+# The catch type of an exception handler must be a class
+bad_catch_type = ClassFile("BadCatchType")
+bad_catch_type.method("run", "()V", 1, 0, [NOP, RETURN],
+                      handlers=[(0, 1, 1, bad_catch_type.string("java/lang/Throwable"))])
+bad_catch_type.write("BadCatchType.class")
+
+# Java 5 class files have no stack map frames, so their types are inferred
+legacy = ClassFile("Legacy", 49)
+legacy.method("sum", "(I)I", 2, 2,
+              [ICONST_0, ISTORE_1, ILOAD_0, IFLE] + branch(13) +
+              [ILOAD_1, ILOAD_0, IADD, ISTORE_1, IINC, 0, 0xFF, GOTO] + branch(-11) +
+              [ILOAD_1, IRETURN])
+# The common superclass of RuntimeException and Error is Throwable
+legacy.method("pick", "(ZLjava/lang/RuntimeException;Ljava/lang/Error;)Ljava/lang/Throwable;",
+              1, 3, [ILOAD_0, IFEQ] + branch(7) + [ALOAD_1, GOTO] + branch(4) + [ALOAD_2, ARETURN])
+# Doubles its argument in a subroutine
+legacy.method("twice", "(I)I", 2, 2,
+              [JSR] + branch(5) + [ILOAD_0, IRETURN,
+                                   ASTORE_1, ILOAD_0, ICONST_2, IMUL, ISTORE_0, RET, 1])
+# Adds 1000 to its argument in local 299, which can only be reached by wide instructions
+legacy.method("widen", "(I)I", 1, 300,
+              [ILOAD_0, WIDE, ISTORE, 0x01, 0x2B, WIDE, IINC, 0x01, 0x2B, 0x03, 0xE8, GOTO_W] +
+              branch_w(7) + [ICONST_0, IRETURN, WIDE, ILOAD, 0x01, 0x2B, IRETURN])
+# Doubles its argument in a subroutine whose return address is in local 299
+legacy.method("twiceWide", "(I)I", 2, 300,
+              [JSR_W] + branch_w(7) + [ILOAD_0, IRETURN, WIDE, ASTORE, 0x01, 0x2B,
+                                       ILOAD_0, ICONST_2, IMUL, ISTORE_0, WIDE, RET, 0x01, 0x2B])
+# Subtracts its first argument from its second, which javac never compiles to swap
+legacy.method("difference", "(II)I", 2, 2, [NOP, ILOAD_0, ILOAD_1, SWAP, ISUB, IRETURN])
+legacy.write("Legacy.class")
+
+# Runs each form of the stack instructions that javac does not compile to, where longs take up
+# two units of stack depth
+INT = False
+LONG = True
+stack = ClassFile("Stack")
+for name, pushes, instruction, result in [
+        ("pop2", [(1, INT), (2, INT), (3, INT)], POP2, [INT]),
+        ("pop2Long", [(1, INT), (2, LONG)], POP2, [INT]),
+        ("dupX2", [(1, INT), (2, INT), (3, INT)], DUP_X2, [INT, INT, INT, INT]),
+        ("dupX2Long", [(1, LONG), (2, INT)], DUP_X2, [INT, LONG, INT]),
+        ("dup2", [(1, INT), (2, INT)], DUP2, [INT, INT, INT, INT]),
+        ("dup2Long", [(1, LONG)], DUP2, [LONG, LONG]),
+        ("dup2X1", [(1, INT), (2, INT), (3, INT)], DUP2_X1, [INT, INT, INT, INT, INT]),
+        ("dup2X1Long", [(1, INT), (2, LONG)], DUP2_X1, [LONG, INT, LONG]),
+        ("dup2X2", [(1, INT), (2, INT), (3, INT), (4, INT)], DUP2_X2, [INT] * 6),
+        ("dup2X2Long", [(1, INT), (2, INT), (3, LONG)], DUP2_X2, [LONG, INT, INT, LONG]),
+        ("dup2X2UnderLong", [(1, LONG), (2, INT), (3, INT)], DUP2_X2,
+         [INT, INT, LONG, INT, INT]),
+        ("dup2X2Longs", [(1, LONG), (2, LONG)], DUP2_X2, [LONG, LONG, LONG])]:
+    stack.method(name, "()I", 16, 2, digits(pushes, instruction, result))
+stack.write("Stack.class")
+
+legacy_mismatch = ClassFile("LegacyMismatch", 49)
+legacy_mismatch.method("merge", "(I)V", 1, 1,
+                       [ILOAD_0, IFEQ] + branch(7) + [ICONST_0, GOTO] + branch(4) +
+                       [FCONST_0, POP, RETURN])
+legacy_mismatch.write("LegacyMismatch.class")
+
+legacy_stack_height = ClassFile("LegacyStackHeight", 49)
+legacy_stack_height.method("run", "(I)V", 1, 1, [ILOAD_0, IFEQ] + branch(4) + [ICONST_0, RETURN])
+legacy_stack_height.write("LegacyStackHeight.class")
+
+legacy_bad_ret = ClassFile("LegacyBadRet", 49)
+legacy_bad_ret.method("run", "()V", 1, 1, [ICONST_0, ISTORE_0, RET, 0])
+legacy_bad_ret.write("LegacyBadRet.class")
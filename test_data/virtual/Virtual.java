@@ -0,0 +1,107 @@
+public class Virtual {
+    static abstract class Shape {
+        abstract int area();
+
+        int sides() {
+            return 0;
+        }
+
+        int describe() {
+            return area() * 100 + sides();
+        }
+    }
+
+    static class Rectangle extends Shape {
+        int width;
+        int height;
+
+        Rectangle(int width, int height) {
+            this.width = width;
+            this.height = height;
+        }
+
+        int area() {
+            return width * height;
+        }
+
+        int sides() {
+            return 4;
+        }
+    }
+
+    static class Square extends Rectangle {
+        Square(int side) {
+            super(side, side);
+        }
+
+        int area() {
+            return super.area() + 1;
+        }
+    }
+
+    static class Triangle extends Shape {
+        int area() {
+            return 3;
+        }
+    }
+
+    interface Measured {
+        int measure();
+    }
+
+    /** Inherits measure() from Measured without declaring it */
+    static abstract class Unmeasured implements Measured {
+    }
+
+    static class Ruler extends Unmeasured {
+        int length;
+
+        Ruler(int length) {
+            this.length = length;
+        }
+
+        public int measure() {
+            return length;
+        }
+    }
+
+    public static int area(int kind) {
+        Shape shape;
+        if (kind == 0) {
+            shape = new Rectangle(2, 3);
+        } else if (kind == 1) {
+            shape = new Square(3);
+        } else {
+            shape = new Triangle();
+        }
+        return shape.area();
+    }
+
+    public static int describe(int kind) {
+        Shape shape;
+        if (kind == 0) {
+            shape = new Rectangle(2, 3);
+        } else if (kind == 1) {
+            shape = new Square(3);
+        } else {
+            shape = new Triangle();
+        }
+        return shape.describe();
+    }
+
+    /** Calls area() on each kind of shape in turn from the same call site */
+    public static int totalArea(int count) {
+        Shape[] shapes = { new Rectangle(2, 3), new Square(3), new Triangle() };
+        int total = 0;
+        for (int i = 0; i < count; i++) {
+            total += shapes[i % shapes.length].area();
+        }
+        return total;
+    }
+
+    /** Calls measure() through the abstract class, whose vtable has no method of its own for it */
+    public static int measure(int length) {
+        Unmeasured unmeasured = new Ruler(length);
+        return unmeasured.measure();
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::archive::Archive;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+use std::path::Path;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+#[test]
+fn test_read_archive() {
+    let archive = Archive::open(Path::new("test_data/archive/app.jar")).unwrap();
+    assert!(archive.contains("app/App.class"));
+    assert!(!archive.contains("lib/Lib.class"));
+
+    let bytes = archive.read("app/App.class").unwrap();
+    assert_eq!(&bytes[..4], &[0xca, 0xfe, 0xba, 0xbe]);
+
+    let manifest = archive.manifest().unwrap().unwrap();
+    assert_eq!(manifest.get("Main-Class"), Some("app.App"));
+    assert_eq!(manifest.get("class-path"), Some("lib/lib.jar"));
+}
+
+#[test]
+fn test_load_class_from_archive() {
+    let mut class_loader = ClassLoader::new(vec!["test_data/archive/app.jar".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("app/App"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from("run"),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+    let method = class.find_method(&symref).borrow();
+    // lib/Lib is found through the Class-Path of app.jar's manifest
+    match method.invoke(&class, &mut class_loader, None) {
+        Ok(Some(Value::Int(value))) => assert_eq!(value.0, 42),
+        ret => panic!("Expected Int with value 42, got {:?}", ret),
+    }
+}
@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 extern crate rust_jvm;
 
 use std::num::Wrapping;
@@ -8,12 +7,13 @@ use rust_jvm::vm::sig;
 use rust_jvm::vm::symref;
 use rust_jvm::vm::value::Value;
 
-const RUNTIME_PATH: &'static str = concat!(env!("OUT_DIR"), "/runtime");
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
 
 #[test]
 fn test_fibonacci() {
     let mut class_loader = ClassLoader::new(vec!["test_data/array".into(), RUNTIME_PATH.into()]);
-    let class = class_loader.resolve_class(&sig::Class::Scalar(String::from("Fib")));
+    let class_sig = sig::Class::Scalar(String::from("Fib"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
 
     let sig = sig::Method {
         name: String::from("fib"),
@@ -23,15 +23,58 @@ fn test_fibonacci() {
 
     let symref = symref::Method {
         class: class.symref.clone(),
-        sig: sig,
+        sig,
     };
 
-    let method = class.find_method(&mut class_loader, &symref).borrow();
-    let mut args = vec![];
-    args.push(Value::Int(Wrapping(10)));
-    let ret = method.invoke(&class, &mut class_loader, Some(args)).unwrap();
+    let method = class.find_method(&symref).borrow();
+    let args = vec![Value::Int(Wrapping(10))];
+    let ret = method.invoke(&class, &mut class_loader, Some(args)).unwrap().unwrap();
     match ret {
         Value::Int(value) => assert_eq!(value.0, 55),
         _ => panic!("Expected Int with value 55, got {:?}", ret),
     }
 }
+
+fn invoke_multi_array(name: &str, args: Vec<Value>) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/array".into(), RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("MultiArray"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: args.iter().map(|_| sig::Type::Int).collect(),
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_reference_array() {
+    assert_eq!(invoke_multi_array("strings", vec![]), 23);
+}
+
+#[test]
+fn test_multi_array() {
+    assert_eq!(invoke_multi_array("matrix", vec![]), 18);
+    assert_eq!(invoke_multi_array("defaults", vec![]), 7);
+    assert_eq!(invoke_multi_array("partial", vec![]), 18);
+}
+
+#[test]
+fn test_multi_array_classes() {
+    assert_eq!(invoke_multi_array("classes", vec![]), 15);
+}
+
+#[test]
+fn test_negative_array_size() {
+    assert_eq!(invoke_multi_array("negative", vec![Value::Int(Wrapping(2))]), 1);
+    assert_eq!(invoke_multi_array("negative", vec![Value::Int(Wrapping(-1))]), -1);
+}
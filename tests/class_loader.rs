@@ -0,0 +1,79 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::fs;
+use std::num::Wrapping;
+use std::rc::Rc;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Array, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+/// Invokes a static method of `Host` with the contents of `Plugin.class` as a `byte[]`. The
+/// plugin is not on the class path, so it can only be defined by the loaders created by `Host`.
+fn invoke(name: &str) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/class_loader".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Host"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let bytes = fs::read("test_data/class_loader/plugin/Plugin.class").unwrap();
+    let byte_array_sig = sig::Class::Array(Box::new(sig::Type::Byte));
+    let byte_array_class = class_loader.resolve_class_in(&None, &byte_array_sig).unwrap();
+    let mut array = Array::new(byte_array_class, bytes.len() as i32);
+    for (i, &byte) in bytes.iter().enumerate() {
+        array.insert(i, Value::Int(Wrapping(byte as i8 as i32)));
+    }
+    let args = vec![Value::ArrayReference(Rc::new(RefCell::new(array)))];
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![sig::Type::Reference(byte_array_sig)],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_isolation() {
+    assert_eq!(invoke("isolation"), 121);
+}
+
+#[test]
+fn test_delegation() {
+    assert_eq!(invoke("delegation"), 1);
+}
+
+#[test]
+fn test_get_class_loader() {
+    assert_eq!(invoke("getClassLoader"), 7);
+}
+
+#[test]
+fn test_define_class_errors() {
+    assert_eq!(invoke("duplicate"), 1);
+    assert_eq!(invoke("prohibitedPackage"), 1);
+    assert_eq!(invoke("wrongName"), 1);
+    assert_eq!(invoke("notFound"), 1);
+}
+
+#[test]
+fn test_resolution_failure() {
+    assert_eq!(invoke("missingClass"), 1);
+}
+
+#[test]
+fn test_class_format_error() {
+    assert_eq!(invoke("malformed"), 1);
+    assert_eq!(invoke("malformedBootstrap"), 1);
+}
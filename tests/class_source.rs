@@ -0,0 +1,123 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::fs;
+use std::rc::Rc;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::class::Class;
+use rust_jvm::vm::exception;
+use rust_jvm::vm::class_source::{ArchiveSource, ClassSource, DirectorySource, MemorySource};
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn runtime_source() -> Box<dyn ClassSource> {
+    Box::new(DirectorySource::new(RUNTIME_PATH.into()))
+}
+
+fn invoke_value(class_loader: &mut ClassLoader, class: &Rc<Class>) -> i32 {
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from("value"),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(class, class_loader, None) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+fn resolve_and_invoke(class_loader: &mut ClassLoader, name: &str) -> i32 {
+    let class_sig = sig::Class::Scalar(String::from(name));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+    invoke_value(class_loader, &class)
+}
+
+#[test]
+fn test_define_class() {
+    let bytes = fs::read("test_data/class_source/Generated.class").unwrap();
+    let mut class_loader = ClassLoader::with_sources(vec![runtime_source()]);
+    let class = class_loader.define_class("Generated", bytes).unwrap();
+    assert_eq!(invoke_value(&mut class_loader, &class), 7);
+    // Later resolutions find the defined class
+    assert_eq!(resolve_and_invoke(&mut class_loader, "Generated"), 7);
+}
+
+#[test]
+fn test_define_class_twice() {
+    let bytes = fs::read("test_data/class_source/Generated.class").unwrap();
+    let mut class_loader = ClassLoader::with_sources(vec![runtime_source()]);
+    class_loader.define_class("Generated", bytes.clone()).unwrap();
+    match class_loader.define_class("Generated", bytes) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       "java.lang.LinkageError: duplicate class definition for name: \"Generated\"")
+        }
+        Ok(class) => panic!("Expected LinkageError, got {:?}", class),
+    }
+}
+
+#[test]
+fn test_define_malformed_class() {
+    let mut class_loader = ClassLoader::with_sources(vec![runtime_source()]);
+    match class_loader.define_class("Generated", vec![0xCA, 0xFE]) {
+        Err(exception) => {
+            assert_eq!(exception.borrow().get_class().symref.sig,
+                       sig::Class::Scalar(String::from("java/lang/ClassFormatError")))
+        }
+        Ok(class) => panic!("Expected ClassFormatError, got {:?}", class),
+    }
+}
+
+#[test]
+fn test_define_class_wrong_name() {
+    let bytes = fs::read("test_data/class_source/Generated.class").unwrap();
+    let mut class_loader = ClassLoader::with_sources(vec![runtime_source()]);
+    match class_loader.define_class("Other", bytes) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       "java.lang.NoClassDefFoundError: Other (wrong name: Generated)")
+        }
+        Ok(class) => panic!("Expected NoClassDefFoundError, got {:?}", class),
+    }
+}
+
+#[test]
+fn test_memory_source() {
+    let mut memory = MemorySource::new();
+    memory.insert("Generated", fs::read("test_data/class_source/Generated.class").unwrap());
+    let mut class_loader = ClassLoader::with_sources(vec![Box::new(memory), runtime_source()]);
+    assert_eq!(resolve_and_invoke(&mut class_loader, "Generated"), 7);
+}
+
+#[test]
+fn test_source_order() {
+    let first = || Box::new(DirectorySource::new("test_data/class_source/first".into()));
+    let second = || Box::new(DirectorySource::new("test_data/class_source/second".into()));
+
+    let mut class_loader = ClassLoader::with_sources(vec![first(), second(), runtime_source()]);
+    assert_eq!(resolve_and_invoke(&mut class_loader, "Shadowed"), 1);
+
+    let mut class_loader = ClassLoader::with_sources(vec![second(), first(), runtime_source()]);
+    assert_eq!(resolve_and_invoke(&mut class_loader, "Shadowed"), 2);
+
+    let mut class_loader = ClassLoader::with_sources(vec![runtime_source()]);
+    class_loader.add_source(second());
+    class_loader.add_source(first());
+    assert_eq!(resolve_and_invoke(&mut class_loader, "Shadowed"), 2);
+}
+
+#[test]
+fn test_archive_source() {
+    let archive = ArchiveSource::new("test_data/archive/app.jar".into());
+    assert!(archive.find_class("app/App").is_some());
+    // Found through the Class-Path of the archive's manifest
+    assert!(archive.find_class("lib/Lib").is_some());
+    assert!(archive.find_class("lib/Missing").is_none());
+}
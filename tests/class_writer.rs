@@ -0,0 +1,116 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::fs;
+use std::path::Path;
+use rust_jvm::model::Class;
+use rust_jvm::model::error::{Context, ErrorKind};
+use rust_jvm::model::info::Constant;
+use rust_jvm::model::info::attribute::Attribute;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::string;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn test_data() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data")
+}
+
+/// Checks that every class file under `dir`, except the deliberately truncated `Broken.class`,
+/// is written back to exactly the bytes it was parsed from
+fn assert_round_trips(dir: &Path) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            assert_round_trips(&path);
+        } else if path.extension().is_some_and(|extension| extension == "class") &&
+                  !path.ends_with("class_loader/Broken.class") {
+            let bytes = fs::read(&path).unwrap();
+            let class = Class::parse(bytes.clone()).unwrap();
+            let written = class.write().unwrap();
+            assert!(written == bytes, "{} did not round-trip", path.display());
+        }
+    }
+}
+
+fn find_utf8(class: &Class, value: &str) -> usize {
+    class.constant_pool
+        .iter()
+        .position(|constant| match *constant {
+            Constant::Utf8 { value: ref utf8, .. } => utf8 == value,
+            _ => false,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_round_trip() {
+    assert_round_trips(&test_data());
+}
+
+#[test]
+fn test_modified_class() {
+    let bytes = fs::read(test_data().join("string/Strings.class")).unwrap();
+    let mut class = Class::parse(bytes).unwrap();
+
+    // Lengthen a string literal, and drop the line numbers of every method, so that the lengths
+    // of the constant and of each Code attribute change
+    let index = find_utf8(&class, "日本語");
+    class.constant_pool[index] = Constant::utf8("日本語のテキスト");
+    for method in class.methods.iter_mut() {
+        for attribute in method.attributes.iter_mut() {
+            if let Attribute::Code { ref mut attributes, .. } = *attribute {
+                let kept = std::mem::take(attributes)
+                    .into_vec()
+                    .into_iter()
+                    .filter(|attribute| attribute.name() != Some("LineNumberTable"))
+                    .collect::<Vec<_>>();
+                *attributes = kept.into_boxed_slice();
+            }
+        }
+    }
+    let bytes = class.write().unwrap();
+    Class::parse(bytes.clone()).unwrap();
+
+    let mut class_loader = ClassLoader::new(vec![RUNTIME_PATH.into()]);
+    let class = class_loader.define_class("Strings", bytes).unwrap();
+    let string_type = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from("cjk"),
+            params: vec![],
+            return_type: Some(string_type),
+        },
+    };
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, None) {
+        Ok(Some(Value::Reference(string))) => {
+            assert_eq!(string::to_rust_string(&string.borrow()), "日本語のテキスト")
+        }
+        ret => panic!("Expected String, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_missing_attribute_name() {
+    let bytes = fs::read(test_data().join("string/Strings.class")).unwrap();
+    let mut class = Class::parse(bytes).unwrap();
+    let index = find_utf8(&class, "SourceFile");
+    class.constant_pool[index] = Constant::utf8("Renamed");
+    let error = class.write().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingAttributeName("SourceFile"));
+    assert_eq!(error.context, vec![]);
+
+    let mut class = Class::parse(fs::read(test_data().join("string/Strings.class")).unwrap())
+        .unwrap();
+    let index = find_utf8(&class, "LineNumberTable");
+    class.constant_pool[index] = Constant::utf8("Renamed");
+    let error = class.write().unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingAttributeName("LineNumberTable"));
+    assert_eq!(error.context,
+               vec![Context::Method(0), Context::Attribute(String::from("Code"))]);
+}
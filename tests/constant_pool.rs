@@ -0,0 +1,136 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+use rust_jvm::model;
+use rust_jvm::vm::{ClassLoader, ConstantPool};
+use rust_jvm::vm::constant_pool::ConstantPoolEntry;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::string;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Scalar, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn new_class_loader() -> ClassLoader {
+    ClassLoader::new(vec!["test_data/constant_pool".into(), RUNTIME_PATH.into()])
+}
+
+/// Invokes a static method of `Constants`, which is generated by `make_constants.py`
+fn invoke(class_loader: &mut ClassLoader,
+          name: &str,
+          return_type: &str)
+          -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class_sig = sig::Class::Scalar(String::from("Constants"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method::new(String::from(name), format!("(){}", return_type)),
+    };
+    let method = class.find_method(&symref).borrow();
+    method.invoke(&class, class_loader, Some(vec![]))
+}
+
+fn assert_returns_int(name: &str, expected: i32) {
+    match invoke(&mut new_class_loader(), name, "I") {
+        Ok(Some(Value::Int(value))) => assert_eq!(value.0, expected),
+        ret => panic!("Expected Int with value {}, got {:?}", expected, ret),
+    }
+}
+
+fn assert_returns_string(name: &str, expected: &str) {
+    match invoke(&mut new_class_loader(), name, "Ljava/lang/String;") {
+        Ok(Some(Value::Reference(string))) => {
+            assert_eq!(string::to_rust_string(&string.borrow()), expected)
+        }
+        ret => panic!("Expected String {}, got {:?}", expected, ret),
+    }
+}
+
+fn assert_throws(name: &str, return_type: &str, expected: &str) {
+    match invoke(&mut new_class_loader(), name, return_type) {
+        Err(exception) => {
+            assert_eq!(exception.borrow().get_class().symref.sig,
+                       sig::Class::Scalar(String::from(expected)))
+        }
+        ret => panic!("Expected {} to be thrown, got {:?}", expected, ret),
+    }
+}
+
+#[test]
+fn test_class_literal() {
+    let mut class_loader = new_class_loader();
+    let mirror = match invoke(&mut class_loader, "classLiteral", "Ljava/lang/Class;") {
+        Ok(Some(Value::Reference(mirror))) => mirror,
+        ret => panic!("Expected a Class, got {:?}", ret),
+    };
+    assert_eq!(class_loader.get_mirror_class(&mirror).symref.sig,
+               sig::Class::Scalar(String::from("Constants")));
+    assert_throws("missingClass", "Ljava/lang/Class;", "java/lang/NoClassDefFoundError");
+}
+
+#[test]
+fn test_method_type() {
+    assert_returns_string("methodTypeDescriptor", "(ILjava/lang/String;)V");
+
+    // A resolved constant always produces the same object
+    let mut class_loader = new_class_loader();
+    let return_type = "Ljava/lang/invoke/MethodType;";
+    match (invoke(&mut class_loader, "methodType", return_type),
+           invoke(&mut class_loader, "methodType", return_type)) {
+        (Ok(Some(Value::Reference(first))), Ok(Some(Value::Reference(second)))) => {
+            assert!(Rc::ptr_eq(&first, &second))
+        }
+        ret => panic!("Expected two MethodTypes, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_method_handle() {
+    assert_returns_int("methodHandle", 42);
+    assert_returns_string("handleType", "(I)I");
+    assert_throws("wrongType", "J", "java/lang/invoke/WrongMethodTypeException");
+}
+
+#[test]
+fn test_dynamic_constant() {
+    match invoke(&mut new_class_loader(), "condyNull", "Ljava/lang/Object;") {
+        Ok(Some(Value::NullReference)) => (),
+        ret => panic!("Expected null, got {:?}", ret),
+    }
+    assert_returns_int("condyStatic", 7);
+    match invoke(&mut new_class_loader(), "condyLong", "J") {
+        Ok(Some(Value::Long(value))) => assert_eq!(value.0, 1 << 40),
+        ret => panic!("Expected Long, got {:?}", ret),
+    }
+    assert_returns_int("condyInvoke", 42);
+}
+
+#[test]
+fn test_dynamic_constant_failure() {
+    assert_throws("condyFailure", "I", "java/lang/BootstrapMethodError");
+    assert_throws("condyUnsupported",
+                  "Ljava/lang/Object;",
+                  "java/lang/BootstrapMethodError");
+}
+
+#[test]
+fn test_module_constants() {
+    let bytes = fs::read("test_data/model_class/modern/module-info.class").unwrap();
+    let class = model::Class::parse(bytes).unwrap();
+    let constant_pool = ConstantPool::new(&class.constant_pool);
+    let mut modules = vec![];
+    let mut packages = vec![];
+    for index in 1..class.constant_pool_count {
+        match constant_pool[index] {
+            Some(ConstantPoolEntry::Module(ref name)) => modules.push(name.clone()),
+            Some(ConstantPoolEntry::Package(ref name)) => packages.push(name.clone()),
+            _ => (),
+        }
+    }
+    modules.sort();
+    assert_eq!(modules, vec!["java.base", "shapes"]);
+    assert_eq!(packages, vec!["shapes"]);
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Scalar, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str) -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let mut class_loader = ClassLoader::new(vec!["test_data/exception".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Exceptions"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    method.invoke(&class, &mut class_loader, None)
+}
+
+fn assert_returns(name: &str, expected: i32) {
+    match invoke(name) {
+        Ok(Some(Value::Int(value))) => assert_eq!(value.0, expected),
+        Ok(ret) => panic!("Expected Int with value {}, got {:?}", expected, ret),
+        Err(exception) => {
+            panic!("{} threw {:?}",
+                   name,
+                   exception.borrow().get_class().symref.sig)
+        }
+    }
+}
+
+#[test]
+fn test_catch_thrown() {
+    assert_returns("catchThrown", 1);
+}
+
+#[test]
+fn test_catch_superclass() {
+    assert_returns("catchSuperclass", 2);
+}
+
+#[test]
+fn test_catch_first_matching() {
+    assert_returns("catchFirstMatching", 3);
+}
+
+#[test]
+fn test_finally() {
+    assert_returns("finallyRuns", 5);
+}
+
+#[test]
+fn test_try_with_resources() {
+    assert_returns("tryWithResources", 1);
+}
+
+#[test]
+fn test_stack_overflow() {
+    assert_returns("stackOverflow", 6);
+}
+
+#[test]
+fn test_uncaught() {
+    match invoke("uncaught") {
+        Err(exception) => {
+            assert_eq!(exception.borrow().get_class().symref.sig,
+                       sig::Class::Scalar(String::from("TestException")))
+        }
+        ret => panic!("Expected TestException to be thrown, got {:?}", ret),
+    }
+}
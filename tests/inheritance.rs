@@ -0,0 +1,121 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::fs;
+use std::cell::RefCell;
+use std::rc::Rc;
+use rust_jvm::model::Class;
+use rust_jvm::model::info::Constant;
+use rust_jvm::model::info::constant;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::class_source::{DirectorySource, MemorySource};
+use rust_jvm::vm::exception;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Scalar, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/inheritance".into(),
+                                                 RUNTIME_PATH.into()]);
+    match invoke_in(&mut class_loader, name) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+fn invoke_in(class_loader: &mut ClassLoader,
+             name: &str)
+             -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let class_sig = sig::Class::Scalar(String::from("Inheritance"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    method.invoke(&class, class_loader, None)
+}
+
+#[test]
+fn test_inherited_fields() {
+    assert_eq!(invoke("inheritedFields"), 7);
+}
+
+#[test]
+fn test_shadowed_field() {
+    assert_eq!(invoke("shadowedField"), 12);
+}
+
+#[test]
+fn test_inherited_static_method() {
+    assert_eq!(invoke("inheritedStaticMethod"), 42);
+}
+
+#[test]
+fn test_inherited_static_field() {
+    assert_eq!(invoke("inheritedStaticField"), 2);
+    assert_eq!(invoke("interfaceStaticField"), 2);
+}
+
+#[test]
+fn test_super_method() {
+    assert_eq!(invoke("superMethod"), 101);
+}
+
+#[test]
+fn test_circular_superclass() {
+    // javac does not compile a class that is its own superclass, so make one from another class
+    let bytes = fs::read("test_data/inheritance/Inheritance$Base.class").unwrap();
+    let mut class = Class::parse(bytes).unwrap();
+    class.super_class = class.this_class;
+    let mut memory = MemorySource::new();
+    memory.insert("Inheritance$Base", class.write().unwrap());
+    let runtime = Box::new(DirectorySource::new(RUNTIME_PATH.into()));
+    let mut class_loader = ClassLoader::with_sources(vec![Box::new(memory), runtime]);
+
+    let class_sig = sig::Class::Scalar(String::from("Inheritance$Base"));
+    match class_loader.resolve_class_in(&None, &class_sig) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       "java.lang.ClassCircularityError: Inheritance$Base")
+        }
+        Ok(class) => panic!("Expected ClassCircularityError, got {:?}", class),
+    }
+    assert!(class_loader.find_loaded_class(&None, &class_sig).is_none());
+}
+
+#[test]
+fn test_missing_field() {
+    // Rename the fields of Base that Inheritance refers to, as if it had been recompiled without
+    // them
+    let bytes = fs::read("test_data/inheritance/Inheritance$Base.class").unwrap();
+    let mut class = Class::parse(bytes).unwrap();
+    for &(name, renamed) in &[("untouched", "renamed"), ("untouchedStatic", "renamedStatic")] {
+        let index = constant::find_utf8(&class.constant_pool, name).unwrap() as usize - 1;
+        class.constant_pool[index] = Constant::utf8(renamed);
+    }
+    let mut memory = MemorySource::new();
+    memory.insert("Inheritance$Base", class.write().unwrap());
+    let test_data = Box::new(DirectorySource::new("test_data/inheritance".into()));
+    let runtime = Box::new(DirectorySource::new(RUNTIME_PATH.into()));
+    let mut class_loader = ClassLoader::with_sources(vec![Box::new(memory), test_data, runtime]);
+
+    for &(method, field) in &[("inheritedFields", "Inheritance$Derived.untouched"),
+                              ("inheritedStaticField", "Inheritance$Grandchild.untouchedStatic")] {
+        match invoke_in(&mut class_loader, method) {
+            Err(exception) => {
+                assert_eq!(exception::describe(&exception.borrow()),
+                           format!("java.lang.NoSuchFieldError: {}", field))
+            }
+            ret => panic!("Expected NoSuchFieldError, got {:?}", ret),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/initialization".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Initialization"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, None) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_superclass_first() {
+    assert_eq!(invoke("superclassFirst"), 12);
+}
+
+#[test]
+fn test_inherited_field_initializes_declaring_class() {
+    assert_eq!(invoke("inheritedField"), 11);
+}
+
+#[test]
+fn test_not_triggered() {
+    assert_eq!(invoke("notTriggered"), 5);
+}
+
+#[test]
+fn test_recursive_initialization() {
+    assert_eq!(invoke("recursive"), 1110);
+}
+
+#[test]
+fn test_exception_in_initializer() {
+    assert_eq!(invoke("exceptionInInitializer"), 111);
+    assert_eq!(invoke("errorNotWrapped"), 1);
+}
+
+#[test]
+fn test_interfaces() {
+    assert_eq!(invoke("interfaces"), 65);
+}
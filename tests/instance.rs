@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 extern crate rust_jvm;
 
 use std::num::Wrapping;
@@ -8,12 +7,13 @@ use rust_jvm::vm::sig;
 use rust_jvm::vm::symref;
 use rust_jvm::vm::value::Value;
 
-const RUNTIME_PATH: &'static str = concat!(env!("OUT_DIR"), "/runtime");
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
 
 #[test]
 fn test_instance() {
     let mut class_loader = ClassLoader::new(vec!["test_data/instance".into(), RUNTIME_PATH.into()]);
-    let class = class_loader.resolve_class(&sig::Class::Scalar(String::from("Instance")));
+    let class_sig = sig::Class::Scalar(String::from("Instance"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
 
     let sig = sig::Method {
         name: String::from("setAndGetValue"),
@@ -23,13 +23,12 @@ fn test_instance() {
 
     let symref = symref::Method {
         class: class.symref.clone(),
-        sig: sig,
+        sig,
     };
 
-    let method = class.find_method(&mut class_loader, &symref).borrow();
-    let mut args = vec![];
-    args.push(Value::Int(Wrapping(69)));
-    let ret = method.invoke(&class, &mut class_loader, Some(args)).unwrap();
+    let method = class.find_method(&symref).borrow();
+    let args = vec![Value::Int(Wrapping(69))];
+    let ret = method.invoke(&class, &mut class_loader, Some(args)).unwrap().unwrap();
     match ret {
         Value::Int(value) => assert_eq!(value.0, 69),
         _ => panic!("Expected Int with value 69, got {:?}", ret),
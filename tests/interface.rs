@@ -0,0 +1,104 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::exception;
+use rust_jvm::vm::invoke;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Scalar, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn new_class_loader() -> ClassLoader {
+    ClassLoader::new(vec!["test_data/interface".into(), RUNTIME_PATH.into()])
+}
+
+fn missing_method(class_name: &str) -> symref::Method {
+    symref::Method {
+        class: symref::Class { sig: sig::Class::Scalar(String::from(class_name)) },
+        sig: sig::Method {
+            name: String::from("missing"),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    }
+}
+
+fn invoke(name: &str) -> i32 {
+    let mut class_loader = new_class_loader();
+    let class_sig = sig::Class::Scalar(String::from("Interfaces"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, None) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_invokeinterface() {
+    assert_eq!(invoke("area"), 17);
+}
+
+#[test]
+fn test_default_method() {
+    assert_eq!(invoke("defaultMethod"), 140);
+}
+
+#[test]
+fn test_maximally_specific() {
+    assert_eq!(invoke("maximallySpecific"), 2);
+}
+
+#[test]
+fn test_super_default() {
+    assert_eq!(invoke("superDefault"), 11);
+}
+
+#[test]
+fn test_inherited_implementation() {
+    assert_eq!(invoke("stack"), 642);
+}
+
+#[test]
+fn test_missing_static_method() {
+    let mut class_loader = new_class_loader();
+    let symref = missing_method("Interfaces");
+    match invoke::invoke_static(&mut class_loader, &None, &symref, vec![]) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       "java.lang.NoSuchMethodError: Interfaces.missing()I")
+        }
+        ret => panic!("Expected NoSuchMethodError, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_missing_interface_method() {
+    let mut class_loader = new_class_loader();
+    let class_sig = sig::Class::Scalar(String::from("Interfaces$Square"));
+    let square = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+    let receiver = Value::Reference(Rc::new(RefCell::new(Scalar::new(square))));
+    let symref = missing_method("Interfaces$Shape");
+    match invoke::invoke_interface(&mut class_loader, &None, &symref, vec![receiver]) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       "java.lang.NoSuchMethodError: Interfaces$Shape.missing()I")
+        }
+        ret => panic!("Expected NoSuchMethodError, got {:?}", ret),
+    }
+}
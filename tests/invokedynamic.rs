@@ -0,0 +1,105 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::num::Wrapping;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::string;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(class_name: &str,
+          name: &str,
+          params: Vec<sig::Type>,
+          return_type: sig::Type,
+          args: Vec<Value>)
+          -> Value {
+    let mut class_loader = ClassLoader::new(vec!["test_data/invokedynamic".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from(class_name));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params,
+            return_type: Some(return_type),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(value)) => value,
+        Ok(None) => panic!("{} did not return a value", name),
+        Err(exception) => {
+            panic!("{} threw {:?}",
+                   name,
+                   exception.borrow().get_class().symref.sig)
+        }
+    }
+}
+
+fn invoke_lambdas(name: &str) -> i32 {
+    match invoke("Lambdas", name, vec![], sig::Type::Int, vec![]) {
+        Value::Int(value) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+fn invoke_concat(name: &str, args: Vec<i32>) -> String {
+    let string_type = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+    let params = args.iter().map(|_| sig::Type::Int).collect();
+    let args = args.into_iter().map(|arg| Value::Int(Wrapping(arg))).collect();
+    match invoke("StringConcat", name, params, string_type, args) {
+        Value::Reference(string) => string::to_rust_string(&string.borrow()),
+        ret => panic!("Expected String, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_lambda() {
+    assert_eq!(invoke_lambdas("nonCapturing"), 42);
+    assert_eq!(invoke_lambdas("capturing"), 35);
+    assert_eq!(invoke_lambdas("capturingThis"), 105);
+    assert_eq!(invoke_lambdas("voidLambda"), 7);
+    assert_eq!(invoke_lambdas("sameCallSite"), 14);
+    assert_eq!(invoke_lambdas("defaultMethodLambda"), 7);
+    assert_eq!(invoke_lambdas("bridges"), 7);
+}
+
+#[test]
+fn test_method_reference() {
+    assert_eq!(invoke_lambdas("staticMethodRef"), 42);
+    assert_eq!(invoke_lambdas("boundMethodRef"), 10);
+    assert_eq!(invoke_lambdas("unboundMethodRef"), 16);
+    assert_eq!(invoke_lambdas("constructorRef"), 9);
+}
+
+#[test]
+fn test_boxing() {
+    assert_eq!(invoke_lambdas("boxing"), 12);
+    assert_eq!(invoke_lambdas("widening"), 42);
+    assert_eq!(invoke_lambdas("unboxNull"), -1);
+}
+
+#[test]
+fn test_instantiated_types() {
+    assert_eq!(invoke_lambdas("castArgument"), -1);
+}
+
+#[test]
+fn test_alt_metafactory() {
+    assert_eq!(invoke_lambdas("serializable"), 6);
+}
+
+#[test]
+fn test_string_concat() {
+    assert_eq!(invoke_concat("ints", vec![2, 3]), "2 + 3 = 5");
+    assert_eq!(invoke_concat("primitives", vec![3]),
+               "l=1099511627776,c=x,z=true,d=1.5,f=1.0E10,small=1.0E-5");
+    assert_eq!(invoke_concat("nulls", vec![]), "null/null");
+    assert_eq!(invoke_concat("tags", vec![7]), "tag\u{1}7\u{2}end");
+}
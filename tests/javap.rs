@@ -0,0 +1,38 @@
+use std::fs;
+use std::process::{Command, Output};
+
+const JAVAP: &str = env!("CARGO_BIN_EXE_rjvm-javap");
+
+fn javap(args: &[&str]) -> Output {
+    Command::new(JAVAP).args(args).output().expect("Could not run rjvm-javap")
+}
+
+#[test]
+fn test_matches_javap() {
+    let output = javap(&["test_data/javap/Sample.class"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (classfile, rest) = stdout.split_at(stdout.find('\n').unwrap() + 1);
+    assert!(classfile.starts_with("Classfile /"), "Unexpected header: {}", classfile);
+    assert!(classfile.trim_end().ends_with("test_data/javap/Sample.class"));
+
+    // The expected output is from `javap -c -v -p`, without the lines giving the path,
+    // modification time and checksum of the class file
+    let expected = fs::read_to_string("test_data/javap/Sample.javap").unwrap();
+    for (line, (actual, expected)) in rest.lines().zip(expected.lines()).enumerate() {
+        assert_eq!(actual, expected, "Line {} differs", line + 2);
+    }
+    assert_eq!(rest.lines().count(), expected.lines().count());
+}
+
+#[test]
+fn test_invalid_class_files() {
+    let output = javap(&["test_data/javap/Missing.class"]);
+    assert_eq!(output.status.code(), Some(1));
+    let output = javap(&["test_data/class_loader/Broken.class"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Error: test_data/class_loader/Broken.class: "),
+            "Unexpected stderr: {}",
+            stderr);
+}
@@ -0,0 +1,113 @@
+use std::process::{Command, Output};
+
+const LAUNCHER: &str = env!("CARGO_BIN_EXE_rust-jvm");
+
+fn launch(args: &[&str]) -> Output {
+    Command::new(LAUNCHER)
+        .args(args)
+        .env_remove("CLASSPATH")
+        .output()
+        .expect("Could not run the launcher")
+}
+
+fn exit_code(args: &[&str]) -> i32 {
+    launch(args).status.code().expect("The launcher was terminated by a signal")
+}
+
+#[test]
+fn test_dotted_main_class() {
+    assert_eq!(exit_code(&["-cp", "test_data/launcher", "com.example.Main"]), 0);
+    assert_eq!(exit_code(&["-cp", "test_data/launcher", "com/example/Main"]), 0);
+}
+
+#[test]
+fn test_system_exit() {
+    assert_eq!(exit_code(&["-cp", "test_data/launcher", "com.example.Main", "exit"]), 3);
+    assert_eq!(exit_code(&["-classpath", "test_data/launcher", "com.example.Main", "other"]), 2);
+}
+
+#[test]
+fn test_uncaught_exception() {
+    let output = launch(&["-cp", "test_data/launcher", "com.example.Main", "throw"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Exception in thread \"main\" java.lang.RuntimeException: failed"),
+            "Unexpected stderr: {}",
+            stderr);
+}
+
+#[test]
+fn test_stack_overflow() {
+    let output = launch(&["-cp", "test_data/launcher", "com.example.Main", "recurse"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Exception in thread \"main\" java.lang.StackOverflowError"),
+            "Unexpected stderr: {}",
+            stderr);
+    let args = ["-Xss64m", "-cp", "test_data/launcher", "com.example.Main", "recurse"];
+    assert_eq!(exit_code(&args), 1);
+}
+
+#[test]
+fn test_system_properties() {
+    let args = ["-Dgreeting=hello", "-cp", "test_data/launcher", "com.example.Main", "property"];
+    assert_eq!(exit_code(&args), 4);
+    let args = ["-Dgreeting=bye", "-cp", "test_data/launcher", "com.example.Main", "property"];
+    assert_eq!(exit_code(&args), 5);
+    let args = ["-cp", "test_data/launcher", "com.example.Main", "property"];
+    assert_eq!(exit_code(&args), 5);
+}
+
+#[test]
+fn test_class_path_entries() {
+    let class_path = "test_data/missing:test_data/launcher/*:test_data/launcher";
+    assert_eq!(exit_code(&["-cp", class_path, "com.example.Main", "exit"]), 3);
+}
+
+#[test]
+fn test_memory_options() {
+    let args = ["-Xss16m", "-Xmx1g", "-cp", "test_data/launcher", "com.example.Main", "exit"];
+    assert_eq!(exit_code(&args), 3);
+    assert_eq!(exit_code(&["-Xssbig", "-cp", "test_data/launcher", "com.example.Main"]), 1);
+    assert_eq!(exit_code(&["-Xmx", "-cp", "test_data/launcher", "com.example.Main"]), 1);
+}
+
+#[test]
+fn test_usage_errors() {
+    assert_eq!(exit_code(&[]), 1);
+    assert_eq!(exit_code(&["-cp"]), 1);
+    assert_eq!(exit_code(&["-unknown", "com.example.Main"]), 1);
+    assert_eq!(exit_code(&["-help"]), 0);
+}
+
+#[test]
+fn test_jar_without_main_class() {
+    let output = launch(&["-jar", "test_data/launcher/NoMain.jar"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no main manifest attribute"), "Unexpected stderr: {}", stderr);
+}
+
+#[test]
+fn test_jar() {
+    assert_eq!(exit_code(&["-jar", "test_data/archive/app.jar"]), 42);
+    assert_eq!(exit_code(&["-cp", "test_data/archive/*", "app.App"]), 42);
+}
+
+#[test]
+fn test_missing_main_class() {
+    let output = launch(&["-cp", "test_data/launcher", "com.example.Missing"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Error: Could not find or load main class com.example.Missing\n"),
+            "Unexpected stderr: {}",
+            stderr);
+}
+
+#[test]
+fn test_missing_main_method() {
+    let output = launch(&["-cp", "test_data/launcher", "com.example.NoMain"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "Error: Main method not found in class com.example.NoMain\n");
+}
@@ -1,11 +1,21 @@
 #[cfg(test)]
-
 extern crate rust_jvm;
 
 use std::fs::File;
+use std::fs;
 use std::io::Read;
+use std::path::Path;
 use rust_jvm::model::Class;
+use rust_jvm::model::error::{ClassFormatError, Context, ErrorKind};
 use rust_jvm::model::info::Constant;
+use rust_jvm::model::info::constant::{decode_modified_utf8, encode_modified_utf8};
+use rust_jvm::model::info::attribute::Attribute;
+use rust_jvm::model::info::method::Method;
+
+/// Reads `Test.class` by its absolute path, since `test_load_class` changes the working directory
+fn read_test_class() -> Vec<u8> {
+    fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/model_class/Test.class")).unwrap()
+}
 
 #[test]
 fn test_load_class() {
@@ -16,11 +26,412 @@ fn test_load_class() {
     let mut buf = Vec::new();
     assert!(file.read_to_end(&mut buf).is_ok());
 
-    let class = Class::new(buf);
+    let class = Class::parse(buf).unwrap();
     println!("{:#?}", class);
-    let constant = &class.constant_pool[7 as usize];
+    let constant = &class.constant_pool[7];
     match *constant {
         Constant::Long { value } => assert_eq!(value, 12312312i64),
         _ => panic!("Expected Long with value 12312312, got {:?}", constant),
     }
 }
+
+#[test]
+fn test_bad_magic() {
+    let mut bytes = read_test_class();
+    bytes[3] = 0xBF;
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error, ClassFormatError::new(ErrorKind::BadMagic(0xCAFEBABF), 0));
+}
+
+#[test]
+fn test_truncated() {
+    let bytes = read_test_class();
+    for length in 0..bytes.len() {
+        let error = Class::parse(bytes[..length].to_vec()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Truncated, "truncated to {} bytes", length);
+        assert!(error.offset <= length as u64);
+    }
+}
+
+#[test]
+fn test_extra_bytes() {
+    let mut bytes = read_test_class();
+    let length = bytes.len() as u64;
+    bytes.push(0);
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error, ClassFormatError::new(ErrorKind::ExtraBytes, length));
+}
+
+#[test]
+fn test_unknown_constant_tag() {
+    let mut bytes = read_test_class();
+    // The tag of the first constant follows the magic, the versions and constant_pool_count
+    bytes[10] = 99;
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::UnknownConstantTag(99));
+    assert_eq!(error.offset, 10);
+    assert_eq!(error.context, vec![Context::Constant(1)]);
+}
+
+#[test]
+fn test_bad_constant_index() {
+    let mut bytes = read_test_class();
+    // Point this_class, which follows access_flags, at the Utf8 constant holding the name
+    let header = [0x00, 0x21, 0x00, 0x03, 0x00, 0x04];
+    let offset = bytes.windows(6).position(|window| window == header).unwrap() + 2;
+    bytes[offset + 1] = 24;
+    let error = Class::parse(bytes).unwrap_err();
+    let kind = ErrorKind::BadConstantIndex(24, "Class");
+    assert_eq!(error, ClassFormatError::new(kind, offset as u64));
+}
+
+#[test]
+fn test_error_context() {
+    let mut bytes = read_test_class();
+    // The LineNumberTable of the first method, whose name is constant 18 and whose length is 6
+    let offset = bytes.windows(6).position(|window| window == [0, 18, 0, 0, 0, 6]).unwrap();
+    bytes[offset + 4] = 0xFF;
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Truncated);
+    assert_eq!(error.context,
+               vec![Context::Method(0),
+                    Context::Attribute(String::from("Code")),
+                    Context::Attribute(String::from("LineNumberTable"))]);
+    assert_eq!(format!("{}", error),
+               format!("Truncated class file at offset {} in method 0, attribute Code, \
+                        attribute LineNumberTable",
+                       offset + 6));
+}
+
+#[test]
+fn test_modified_utf8() {
+    // NUL takes two bytes, and supplementary characters are encoded as two surrogates
+    let chars = "a\0é日😀".encode_utf16().collect::<Vec<u16>>();
+    let bytes = encode_modified_utf8(&chars);
+    assert_eq!(bytes,
+               vec![0x61, 0xC0, 0x80, 0xC3, 0xA9, 0xE6, 0x97, 0xA5, 0xED, 0xA0, 0xBD, 0xED, 0xB8,
+                    0x80]);
+    assert_eq!(decode_modified_utf8(&bytes), Some(chars));
+    // Unpaired surrogates are valid
+    assert_eq!(decode_modified_utf8(&[0xED, 0xA0, 0x80]), Some(vec![0xD800]));
+
+    assert_eq!(decode_modified_utf8(&[0x61, 0x00]), None);
+    assert_eq!(decode_modified_utf8(&[0xF0, 0x9F, 0x98, 0x80]), None);
+    assert_eq!(decode_modified_utf8(&[0xE6, 0x97]), None);
+    assert_eq!(decode_modified_utf8(&[0xC3, 0x29]), None);
+}
+
+#[test]
+fn test_bad_utf8() {
+    let mut bytes = read_test_class();
+    // The first Utf8 constant, "ayy"
+    let offset = 26;
+    assert_eq!(bytes[offset..offset + 6], [1, 0, 3, b'a', b'y', b'y']);
+    bytes[offset + 3] = 0xFF;
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::BadUtf8);
+    assert_eq!(error.offset, offset as u64);
+    assert_eq!(error.context, vec![Context::Constant(5)]);
+}
+
+#[test]
+fn test_bad_descriptor() {
+    let mut bytes = read_test_class();
+    // The Utf8 constant "J", which is the descriptor of the first field
+    let offset = bytes.windows(4).position(|window| window == [1, 0, 1, b'J']).unwrap();
+    bytes[offset + 3] = b'Q';
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::BadDescriptor(String::from("Q")));
+    assert_eq!(error.context, vec![Context::Field(0)]);
+}
+
+#[test]
+fn test_missing_code() {
+    let mut bytes = read_test_class();
+    // Renaming the Utf8 constant "Code" leaves the methods without a Code attribute
+    let offset = bytes.windows(7).position(|window| window == b"\x01\x00\x04Code").unwrap();
+    bytes[offset + 4] = b'x';
+    let error = Class::parse(bytes).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::MissingCode);
+    assert_eq!(error.context, vec![Context::Method(0)]);
+}
+
+/// Parses a class file under `test_data/model_class` by its absolute path
+fn parse_test_class(path: &str) -> Class {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/model_class").join(path);
+    Class::parse(fs::read(path).unwrap()).unwrap()
+}
+
+fn utf8(class: &Class, index: u16) -> &str {
+    match class.constant_pool[index as usize - 1] {
+        Constant::Utf8 { ref value, .. } => value,
+        ref constant => panic!("Expected Utf8, got {:?}", constant),
+    }
+}
+
+fn find_method<'a>(class: &'a Class, name: &str) -> &'a Method {
+    class.methods.iter().find(|method| utf8(class, method.name_index) == name).unwrap()
+}
+
+fn find_attribute<F>(attributes: &[Attribute], matches: F) -> &Attribute
+    where F: Fn(&Attribute) -> bool
+{
+    attributes.iter().find(|attribute| matches(attribute)).unwrap()
+}
+
+fn assert_known(attributes: &[Attribute], path: &Path) {
+    for attribute in attributes.iter() {
+        match *attribute {
+            Attribute::Unknown { name_index, .. } => {
+                panic!("Unknown attribute {} in {}", name_index, path.display())
+            }
+            Attribute::Code { ref attributes, .. } => assert_known(attributes, path),
+            Attribute::Record { ref components, .. } => {
+                for component in components.iter() {
+                    assert_known(&component.attributes, path);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Checks that every class file under `dir`, except the deliberately truncated `Broken.class`,
+/// only has standard attributes
+fn assert_all_known(dir: &Path) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            assert_all_known(&path);
+        } else if path.extension().is_some_and(|extension| extension == "class") &&
+                  !path.ends_with("class_loader/Broken.class") {
+            let class = Class::parse(fs::read(&path).unwrap()).unwrap();
+            assert_known(&class.attributes, &path);
+            for field in class.fields.iter() {
+                assert_known(&field.attributes, &path);
+            }
+            for method in class.methods.iter() {
+                assert_known(&method.attributes, &path);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_no_unknown_attributes() {
+    assert_all_known(&Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data"));
+}
+
+#[test]
+fn test_class_attributes() {
+    let class = parse_test_class("Attributes.class");
+    match *find_attribute(&class.attributes, |a| matches!(a, Attribute::SourceFile { .. })) {
+        Attribute::SourceFile { sourcefile_index } => {
+            assert_eq!(utf8(&class, sourcefile_index), "Attributes.java")
+        }
+        _ => unreachable!(),
+    }
+    match *find_attribute(&class.attributes, |a| matches!(a, Attribute::Signature { .. })) {
+        Attribute::Signature { signature_index } => {
+            assert_eq!(utf8(&class, signature_index),
+                       "<T::Ljava/lang/Comparable<TT;>;>Ljava/lang/Object;")
+        }
+        _ => unreachable!(),
+    }
+    match *find_attribute(&class.attributes, |a| matches!(a, Attribute::InnerClasses { .. })) {
+        Attribute::InnerClasses { number_of_classes, ref classes } => {
+            assert_eq!(number_of_classes as usize, classes.len());
+            // Tag, Checked, Inner, the anonymous class and MethodHandles.Lookup for the lambda
+            assert_eq!(number_of_classes, 5);
+            assert!(classes.iter().any(|inner| inner.inner_name_index == 0));
+        }
+        _ => unreachable!(),
+    }
+
+    let anonymous = parse_test_class("Attributes$1.class");
+    let enclosing_method =
+        find_attribute(&anonymous.attributes, |a| matches!(a, Attribute::EnclosingMethod { .. }));
+    match *enclosing_method {
+        Attribute::EnclosingMethod { method_index, .. } => assert_ne!(method_index, 0),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_field_attributes() {
+    let class = parse_test_class("Attributes.class");
+    let field = |name| {
+        class.fields.iter().find(|field| utf8(&class, field.name_index) == name).unwrap()
+    };
+    let constant = field("CONSTANT");
+    match constant.attributes[0] {
+        Attribute::ConstantValue { value_index } => {
+            match class.constant_pool[value_index as usize - 1] {
+                Constant::Integer { value } => assert_eq!(value, 42),
+                ref constant => panic!("Expected Integer, got {:?}", constant),
+            }
+        }
+        ref attribute => panic!("Expected ConstantValue, got {:?}", attribute),
+    }
+    let count = field("count");
+    let annotations = find_attribute(&count.attributes,
+                                     |a| matches!(a, Attribute::RuntimeVisibleAnnotations { .. }));
+    match *annotations {
+        Attribute::RuntimeVisibleAnnotations { num_annotations, ref annotations } => {
+            assert_eq!(num_annotations, 1);
+            assert_eq!(utf8(&class, annotations[0].type_index), "LAttributes$Tag;");
+        }
+        _ => unreachable!(),
+    }
+    let names = field("names");
+    let type_annotations =
+        find_attribute(&names.attributes,
+                       |a| matches!(a, Attribute::RuntimeInvisibleTypeAnnotations { .. }));
+    match *type_annotations {
+        Attribute::RuntimeInvisibleTypeAnnotations { ref annotations, .. } => {
+            // The annotation is on the first type argument of the field type
+            assert_eq!(annotations[0].target_type, 0x13);
+            assert_eq!(annotations[0].target_path.path_length, 1);
+            assert_eq!(utf8(&class, annotations[0].type_index), "LAttributes$Checked;");
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_method_attributes() {
+    let class = parse_test_class("Attributes.class");
+    let sum = find_method(&class, "sum");
+    assert!(sum.attributes.iter().any(|a| matches!(a, Attribute::Deprecated)));
+    match *find_attribute(&sum.attributes, |a| matches!(a, Attribute::Exceptions { .. })) {
+        Attribute::Exceptions { number_of_exceptions, ref exception_index_table } => {
+            assert_eq!(number_of_exceptions, 1);
+            assert_eq!(exception_index_table.len(), 1);
+        }
+        _ => unreachable!(),
+    }
+    match *find_attribute(&sum.attributes, |a| matches!(a, Attribute::MethodParameters { .. })) {
+        Attribute::MethodParameters { parameters_count, ref parameters } => {
+            assert_eq!(parameters_count, 2);
+            assert_eq!(utf8(&class, parameters[0].name_index), "limit");
+            assert_eq!(utf8(&class, parameters[1].name_index), "ignored");
+            assert_eq!(parameters[1].access_flags, 0x0010);
+        }
+        _ => unreachable!(),
+    }
+    let parameter_annotations =
+        find_attribute(&sum.attributes,
+                       |a| matches!(a, Attribute::RuntimeVisibleParameterAnnotations { .. }));
+    match *parameter_annotations {
+        Attribute::RuntimeVisibleParameterAnnotations { num_parameters,
+                                                        ref parameter_annotations } => {
+            assert_eq!(num_parameters, 2);
+            assert_eq!(parameter_annotations[0].len(), 1);
+            assert_eq!(parameter_annotations[1].len(), 0);
+        }
+        _ => unreachable!(),
+    }
+
+    let code_attributes = match *find_attribute(&sum.attributes,
+                                                |a| matches!(a, Attribute::Code { .. })) {
+        Attribute::Code { ref attributes, .. } => attributes,
+        _ => unreachable!(),
+    };
+    assert!(code_attributes.iter().any(|a| matches!(a, Attribute::LineNumberTable { .. })));
+    let local_variables =
+        find_attribute(code_attributes, |a| matches!(a, Attribute::LocalVariableTable { .. }));
+    match *local_variables {
+        Attribute::LocalVariableTable { ref local_variable_table, .. } => {
+            let names = local_variable_table.iter()
+                .map(|variable| utf8(&class, variable.name_index))
+                .collect::<Vec<_>>();
+            assert_eq!(names, vec!["i", "this", "limit", "ignored", "total"]);
+        }
+        _ => unreachable!(),
+    }
+    let local_variable_types =
+        find_attribute(code_attributes,
+                       |a| matches!(a, Attribute::LocalVariableTypeTable { .. }));
+    match *local_variable_types {
+        Attribute::LocalVariableTypeTable { ref local_variable_type_table, .. } => {
+            assert!(local_variable_type_table.iter()
+                .any(|variable| utf8(&class, variable.signature_index) == "TT;"));
+        }
+        _ => unreachable!(),
+    }
+    let stack_map = find_attribute(code_attributes,
+                                   |a| matches!(a, Attribute::StackMapTable { .. }));
+    match *stack_map {
+        Attribute::StackMapTable { number_of_entries, ref entries } => {
+            assert_eq!(number_of_entries as usize, entries.len());
+            assert!(number_of_entries >= 3);
+        }
+        _ => unreachable!(),
+    }
+
+    let tag = parse_test_class("Attributes$Tag.class");
+    let counts = find_method(&tag, "counts");
+    match counts.attributes[0] {
+        Attribute::AnnotationDefault { ref default_value } => {
+            assert!(format!("{:?}", default_value).starts_with("Array"))
+        }
+        ref attribute => panic!("Expected AnnotationDefault, got {:?}", attribute),
+    }
+}
+
+#[test]
+fn test_java17_attributes() {
+    let shape = parse_test_class("modern/shapes/Shape.class");
+    match *find_attribute(&shape.attributes,
+                          |a| matches!(a, Attribute::PermittedSubclasses { .. })) {
+        Attribute::PermittedSubclasses { number_of_classes, .. } => {
+            assert_eq!(number_of_classes, 2)
+        }
+        _ => unreachable!(),
+    }
+    match *find_attribute(&shape.attributes, |a| matches!(a, Attribute::NestMembers { .. })) {
+        Attribute::NestMembers { number_of_classes, .. } => assert_eq!(number_of_classes, 3),
+        _ => unreachable!(),
+    }
+
+    let circle = parse_test_class("modern/shapes/Shape$Circle.class");
+    assert!(circle.attributes.iter().any(|a| matches!(a, Attribute::NestHost { .. })));
+    match *find_attribute(&circle.attributes, |a| matches!(a, Attribute::Record { .. })) {
+        Attribute::Record { components_count, ref components } => {
+            assert_eq!(components_count, 1);
+            assert_eq!(utf8(&circle, components[0].name_index), "radius");
+            assert_eq!(utf8(&circle, components[0].descriptor_index), "I");
+            assert!(components[0]
+                .attributes
+                .iter()
+                .any(|a| matches!(a, Attribute::RuntimeVisibleAnnotations { .. })));
+        }
+        _ => unreachable!(),
+    }
+
+
+    let module = parse_test_class("modern/module-info.class");
+    match *find_attribute(&module.attributes, |a| matches!(a, Attribute::Module { .. })) {
+        Attribute::Module { module_name_index,
+                            ref requires,
+                            ref exports,
+                            ref uses_index,
+                            ref provides,
+                            .. } => {
+            match module.constant_pool[module_name_index as usize - 1] {
+                Constant::Module { name_index } => assert_eq!(utf8(&module, name_index), "shapes"),
+                ref constant => panic!("Expected Module, got {:?}", constant),
+            }
+            assert_eq!(requires.len(), 1);
+            assert_eq!(exports.len(), 1);
+            match module.constant_pool[exports[0].package_index as usize - 1] {
+                Constant::Package { name_index } => assert_eq!(utf8(&module, name_index), "shapes"),
+                ref constant => panic!("Expected Package, got {:?}", constant),
+            }
+            assert_eq!(exports[0].to_count, 0);
+            assert_eq!(uses_index.len(), 1);
+            assert_eq!(provides[0].provides_with_count, 1);
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,296 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::f32;
+use std::f64;
+use std::num::Wrapping;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str, descriptor: &str, args: Vec<Value>) -> Option<Value> {
+    let mut class_loader = ClassLoader::new(vec!["test_data/opcodes".into(), RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Opcodes"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method::new(String::from(name), String::from(descriptor)),
+    };
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(ret) => ret,
+        Err(exception) => panic!("{} threw {:?}", name, exception.borrow().get_class().symref.sig),
+    }
+}
+
+fn invoke_int(name: &str, descriptor: &str, args: Vec<Value>) -> i32 {
+    match invoke(name, descriptor, args) {
+        Some(Value::Int(value)) => value.0,
+        ret => panic!("Expected Int from {}, got {:?}", name, ret),
+    }
+}
+
+fn invoke_long(name: &str, descriptor: &str, args: Vec<Value>) -> i64 {
+    match invoke(name, descriptor, args) {
+        Some(Value::Long(value)) => value.0,
+        ret => panic!("Expected Long from {}, got {:?}", name, ret),
+    }
+}
+
+fn invoke_float(name: &str, descriptor: &str, args: Vec<Value>) -> f32 {
+    match invoke(name, descriptor, args) {
+        Some(Value::Float(value)) => value,
+        ret => panic!("Expected Float from {}, got {:?}", name, ret),
+    }
+}
+
+fn invoke_double(name: &str, descriptor: &str, args: Vec<Value>) -> f64 {
+    match invoke(name, descriptor, args) {
+        Some(Value::Double(value)) => value,
+        ret => panic!("Expected Double from {}, got {:?}", name, ret),
+    }
+}
+
+fn int(value: i32) -> Value {
+    Value::Int(Wrapping(value))
+}
+
+fn float(value: f32) -> Value {
+    Value::Float(value)
+}
+
+fn long(value: i64) -> Value {
+    Value::Long(Wrapping(value))
+}
+
+fn double(value: f64) -> Value {
+    Value::Double(value)
+}
+
+#[test]
+fn test_constants() {
+    assert_eq!(invoke_int("intConstants", "()I", vec![]), -490740502);
+    assert_eq!(invoke_long("longConstants", "()J", vec![]), (1 << 40) + 1);
+    assert_eq!(invoke_float("floatConstants", "()F", vec![]), 3.25);
+    assert_eq!(invoke_double("doubleConstants", "()D", vec![]), 1.125);
+    match invoke("nullConstant", "()Ljava/lang/Object;", vec![]) {
+        Some(Value::NullReference) => (),
+        ret => panic!("Expected NullReference, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_int_arithmetic() {
+    assert_eq!(invoke_int("iadd", "(II)I", vec![int(i32::MAX), int(1)]), i32::MIN);
+    assert_eq!(invoke_int("isub", "(II)I", vec![int(i32::MIN), int(1)]), i32::MAX);
+    assert_eq!(invoke_int("imul", "(II)I", vec![int(65536), int(65537)]), 65536);
+    assert_eq!(invoke_int("idiv", "(II)I", vec![int(-7), int(2)]), -3);
+    assert_eq!(invoke_int("irem", "(II)I", vec![int(-7), int(2)]), -1);
+    assert_eq!(invoke_int("ineg", "(I)I", vec![int(i32::MIN)]), i32::MIN);
+    assert_eq!(invoke_int("iand", "(II)I", vec![int(12), int(10)]), 8);
+    assert_eq!(invoke_int("ior", "(II)I", vec![int(12), int(10)]), 14);
+    assert_eq!(invoke_int("ixor", "(II)I", vec![int(12), int(10)]), 6);
+    assert_eq!(invoke_int("iinc", "(I)I", vec![int(1)]), 1000);
+}
+
+#[test]
+fn test_int_division_overflow() {
+    assert_eq!(invoke_int("idiv", "(II)I", vec![int(i32::MIN), int(-1)]), i32::MIN);
+    assert_eq!(invoke_int("irem", "(II)I", vec![int(i32::MIN), int(-1)]), 0);
+}
+
+#[test]
+fn test_int_shifts() {
+    // Only the low 5 bits of the shift distance are used
+    assert_eq!(invoke_int("ishl", "(II)I", vec![int(1), int(33)]), 2);
+    assert_eq!(invoke_int("ishr", "(II)I", vec![int(-8), int(33)]), -4);
+    assert_eq!(invoke_int("iushr", "(II)I", vec![int(-1), int(28)]), 15);
+    assert_eq!(invoke_int("iushr", "(II)I", vec![int(-8), int(33)]), 2147483644);
+}
+
+#[test]
+fn test_long_arithmetic() {
+    // An argument of 0 stands for Long.MIN_VALUE
+    assert_eq!(invoke_long("ladd", "(II)J", vec![int(0), int(-1)]), i64::MAX);
+    assert_eq!(invoke_long("lsub", "(II)J", vec![int(0), int(1)]), i64::MAX);
+    assert_eq!(invoke_long("lmul", "(II)J", vec![int(1 << 30), int(1 << 30)]), 1 << 60);
+    assert_eq!(invoke_long("ldiv", "(II)J", vec![int(-7), int(2)]), -3);
+    assert_eq!(invoke_long("lrem", "(II)J", vec![int(-7), int(2)]), -1);
+    assert_eq!(invoke_long("ldiv", "(II)J", vec![int(0), int(-1)]), i64::MIN);
+    assert_eq!(invoke_long("lrem", "(II)J", vec![int(0), int(-1)]), 0);
+    assert_eq!(invoke_long("lneg", "(I)J", vec![int(0)]), i64::MIN);
+    assert_eq!(invoke_long("land", "(II)J", vec![int(-1), int(0xF0)]), 0xF0_0000_00F0);
+    assert_eq!(invoke_long("lor", "(II)J", vec![int(1), int(1)]), 0x1_0000_0001);
+    assert_eq!(invoke_long("lxor", "(II)J", vec![int(-1), int(-1)]), 0xFFFF_FFFF);
+    assert_eq!(invoke_int("lcmp", "(II)I", vec![int(0), int(5)]), -1);
+    assert_eq!(invoke_int("lcmp", "(II)I", vec![int(5), int(5)]), 0);
+    assert_eq!(invoke_int("lcmp", "(II)I", vec![int(6), int(5)]), 1);
+}
+
+#[test]
+fn test_long_shifts() {
+    // Only the low 6 bits of the shift distance are used
+    assert_eq!(invoke_long("lshl", "(II)J", vec![int(1), int(65)]), 2);
+    assert_eq!(invoke_long("lshr", "(II)J", vec![int(-8), int(65)]), -4);
+    assert_eq!(invoke_long("lushr", "(II)J", vec![int(-1), int(60)]), 15);
+}
+
+#[test]
+fn test_float_arithmetic() {
+    assert_eq!(invoke_float("fadd", "(FF)F", vec![float(0.5), float(0.25)]), 0.75);
+    assert_eq!(invoke_float("fsub", "(FF)F", vec![float(0.5), float(0.25)]), 0.25);
+    assert_eq!(invoke_float("fmul", "(FF)F", vec![float(1.5), float(4.0)]), 6.0);
+    assert_eq!(invoke_float("fdiv", "(FF)F", vec![float(1.0), float(0.0)]), f32::INFINITY);
+    assert_eq!(invoke_float("frem", "(FF)F", vec![float(-5.5), float(2.0)]), -1.5);
+    let negated = invoke_float("fneg", "(F)F", vec![float(0.0)]);
+    assert!(negated == 0.0 && negated.is_sign_negative());
+}
+
+#[test]
+fn test_float_comparisons() {
+    assert_eq!(invoke_int("flt", "(FF)Z", vec![float(1.0), float(2.0)]), 1);
+    assert_eq!(invoke_int("flt", "(FF)Z", vec![float(f32::NAN), float(2.0)]), 0);
+    assert_eq!(invoke_int("fgt", "(FF)Z", vec![float(3.0), float(2.0)]), 1);
+    assert_eq!(invoke_int("fgt", "(FF)Z", vec![float(f32::NAN), float(2.0)]), 0);
+}
+
+#[test]
+fn test_double_arithmetic() {
+    assert_eq!(invoke_double("dadd", "(II)D", vec![int(1), int(2)]), 3.0);
+    assert_eq!(invoke_double("dsub", "(II)D", vec![int(1), int(2)]), -1.0);
+    assert_eq!(invoke_double("dmul", "(II)D", vec![int(3), int(-2)]), -6.0);
+    assert_eq!(invoke_double("ddiv", "(II)D", vec![int(1), int(4)]), 0.25);
+    assert!(invoke_double("ddiv", "(II)D", vec![int(0), int(0)]).is_nan());
+    assert_eq!(invoke_double("drem", "(II)D", vec![int(-7), int(2)]), -0.5);
+    let negated = invoke_double("dneg", "(I)D", vec![int(0)]);
+    assert!(negated == 0.0 && negated.is_sign_negative());
+}
+
+#[test]
+fn test_double_comparisons() {
+    // 0 / 0 is NaN
+    assert_eq!(invoke_int("dlt", "(II)Z", vec![int(1), int(2)]), 1);
+    assert_eq!(invoke_int("dlt", "(II)Z", vec![int(0), int(0)]), 0);
+    assert_eq!(invoke_int("dgt", "(II)Z", vec![int(3), int(2)]), 1);
+    assert_eq!(invoke_int("dgt", "(II)Z", vec![int(0), int(0)]), 0);
+}
+
+#[test]
+fn test_widening_conversions() {
+    assert_eq!(invoke_long("i2l", "(I)J", vec![int(-1)]), -1);
+    assert_eq!(invoke_float("i2f", "(I)F", vec![int(16777217)]), 16777216.0);
+    assert_eq!(invoke_double("i2d", "(I)D", vec![int(-3)]), -3.0);
+    assert_eq!(invoke_float("l2f", "(I)F", vec![int(0)]), i64::MIN as f32);
+    assert_eq!(invoke_double("l2d", "(I)D", vec![int(0)]), i64::MIN as f64);
+    assert_eq!(invoke_double("f2d", "(F)D", vec![float(0.5)]), 0.5);
+}
+
+#[test]
+fn test_narrowing_conversions() {
+    assert_eq!(invoke_int("l2i", "(II)I", vec![int(1), int(-2)]), -2);
+    assert_eq!(invoke_float("d2f", "(II)F", vec![int(1), int(3)]), 1.0 / 3.0);
+    assert_eq!(invoke_int("i2b", "(I)I", vec![int(200)]), -56);
+    assert_eq!(invoke_int("i2c", "(I)I", vec![int(-1)]), 65535);
+    assert_eq!(invoke_int("i2s", "(I)I", vec![int(40000)]), -25536);
+}
+
+#[test]
+fn test_floating_point_to_integer() {
+    // NaN converts to 0, and values out of range to the nearest bound
+    assert_eq!(invoke_int("f2i", "(F)I", vec![float(f32::NAN)]), 0);
+    assert_eq!(invoke_int("f2i", "(F)I", vec![float(1e20)]), i32::MAX);
+    assert_eq!(invoke_int("f2i", "(F)I", vec![float(-2.7)]), -2);
+    assert_eq!(invoke_long("f2l", "(F)J", vec![float(-1e30)]), i64::MIN);
+    assert_eq!(invoke_long("f2l", "(F)J", vec![float(2.7)]), 2);
+    assert_eq!(invoke_int("d2i", "(F)I", vec![float(1.0)]), i32::MAX);
+    assert_eq!(invoke_int("d2i", "(F)I", vec![float(f32::NAN)]), 0);
+    assert_eq!(invoke_long("d2l", "(F)J", vec![float(1.0)]), i64::MAX);
+    assert_eq!(invoke_long("d2l", "(F)J", vec![float(-1.0)]), i64::MIN);
+}
+
+#[test]
+fn test_branches() {
+    assert_eq!(invoke_int("compare", "(II)I", vec![int(0), int(0)]), 2665);
+    assert_eq!(invoke_int("compare", "(II)I", vec![int(-1), int(2)]), 2470);
+    assert_eq!(invoke_int("compare", "(II)I", vec![int(3), int(2)]), 1690);
+}
+
+#[test]
+fn test_reference_branches() {
+    assert_eq!(invoke_int("compareReferences", "(I)I", vec![int(0)]), 6);
+    assert_eq!(invoke_int("compareReferences", "(I)I", vec![int(1)]), 9);
+    assert_eq!(invoke_int("compareReferences", "(I)I", vec![int(2)]), 10);
+}
+
+#[test]
+fn test_switches() {
+    assert_eq!(invoke_int("tableSwitch", "(I)I", vec![int(0)]), -1);
+    assert_eq!(invoke_int("tableSwitch", "(I)I", vec![int(3)]), 30);
+    assert_eq!(invoke_int("tableSwitch", "(I)I", vec![int(5)]), -1);
+    assert_eq!(invoke_int("lookupSwitch", "(I)I", vec![int(-1000)]), 1);
+    assert_eq!(invoke_int("lookupSwitch", "(I)I", vec![int(1000000)]), 3);
+    assert_eq!(invoke_int("lookupSwitch", "(I)I", vec![int(1)]), -1);
+}
+
+#[test]
+fn test_arrays() {
+    assert_eq!(invoke_long("primitiveArrays", "(I)J", vec![int(3)]), 14);
+    assert_eq!(invoke_int("referenceArray", "(I)I", vec![int(3)]), 3);
+}
+
+#[test]
+fn test_narrow_array_stores() {
+    // Values are truncated to the component type, and to the lowest bit for boolean arrays
+    assert_eq!(invoke_int("booleanArray", "(Z)I", vec![int(2)]), 0);
+    assert_eq!(invoke_int("booleanArray", "(Z)I", vec![int(3)]), 1);
+    assert_eq!(invoke_int("byteArray", "(B)I", vec![int(300)]), 44);
+    assert_eq!(invoke_int("charArray", "(C)I", vec![int(-1)]), 65535);
+    assert_eq!(invoke_int("shortArray", "(S)I", vec![int(65537)]), 1);
+}
+
+#[test]
+fn test_locals() {
+    assert_eq!(invoke_double("locals", "(I)D", vec![int(2)]), 7.0);
+}
+
+#[test]
+fn test_stack() {
+    assert_eq!(invoke_int("discard", "(I)I", vec![int(4)]), 4);
+    assert_eq!(invoke_int("assignField", "(I)I", vec![int(21)]), 42);
+}
+
+#[test]
+fn test_category2_stack() {
+    assert_eq!(invoke_long("postIncrement", "(I)J", vec![int(7)]), 7);
+    assert_eq!(invoke_int("incrementElement", "(I)I", vec![int(1)]), 6);
+    assert_eq!(invoke_long("incrementLongElement", "(I)J", vec![int(-3)]), -3);
+    assert_eq!(invoke_long("assignLongField", "(I)J", vec![int(9)]), 9);
+}
+
+#[test]
+fn test_category2_arguments() {
+    // Each long and double argument takes up two local variables
+    assert_eq!(invoke_long("longArguments",
+                           "(JIJ)J",
+                           vec![long(1 << 40), int(2), long(-3)]),
+               (1 << 40) * 100 + 17);
+    assert_eq!(invoke_double("doubleArguments",
+                             "(DFD)D",
+                             vec![double(0.5), float(0.25), double(1e10)]),
+               1e10 + 52.5);
+    assert_eq!(invoke_long("passArguments", "(I)J", vec![int(3)]), 675);
+}
+
+#[test]
+fn test_reused_locals() {
+    assert_eq!(invoke_int("reuseLocals", "(I)I", vec![int(20)]), 41);
+}
+
+#[test]
+fn test_monitors() {
+    assert_eq!(invoke_int("monitor", "(I)I", vec![int(1)]), 2);
+    assert_eq!(invoke_int("monitor", "(I)I", vec![int(0)]), -1);
+}
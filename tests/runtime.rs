@@ -0,0 +1,31 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use rust_jvm::vm::{Runtime, StartError};
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn run_main(args: &[&str]) -> Result<(), String> {
+    let runtime = Runtime::new(vec![RUNTIME_PATH.into(), "test_data/runtime".into()]);
+    let main_class = symref::Class { sig: sig::Class::Scalar(String::from("MainArgs")) };
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    runtime.start(main_class, &args).map_err(|error| match error {
+        StartError::Exception(exception) => {
+            format!("{}", exception.borrow().get_class().symref.sig)
+        }
+        error => panic!("Expected an exception, got {:?}", error),
+    })
+}
+
+#[test]
+fn test_main_args() {
+    assert_eq!(run_main(&["hello", ""]), Ok(()));
+}
+
+#[test]
+fn test_main_args_mismatch() {
+    assert_eq!(run_main(&[]), Err(String::from("java/lang/RuntimeException")));
+    assert_eq!(run_main(&["world", ""]), Err(String::from("java/lang/RuntimeException")));
+}
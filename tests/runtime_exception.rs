@@ -0,0 +1,96 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::num::Wrapping;
+use std::rc::Rc;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Scalar, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str,
+          params: Vec<sig::Type>,
+          args: Vec<Value>)
+          -> Result<Option<Value>, Rc<RefCell<Scalar>>> {
+    let mut class_loader = ClassLoader::new(vec!["test_data/runtime_exception".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("RuntimeExceptions"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params,
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    method.invoke(&class, &mut class_loader, Some(args))
+}
+
+fn assert_returns(name: &str, params: Vec<sig::Type>, args: Vec<Value>, expected: i32) {
+    match invoke(name, params, args) {
+        Ok(Some(Value::Int(value))) => assert_eq!(value.0, expected),
+        Ok(ret) => panic!("Expected Int with value {}, got {:?}", expected, ret),
+        Err(exception) => {
+            panic!("{} threw {:?}",
+                   name,
+                   exception.borrow().get_class().symref.sig)
+        }
+    }
+}
+
+fn int(value: i32) -> Value {
+    Value::Int(Wrapping(value))
+}
+
+#[test]
+fn test_null_pointer() {
+    assert_returns("nullField", vec![], vec![], 1);
+    assert_returns("nullArray", vec![], vec![], 2);
+    assert_returns("nullThrow", vec![], vec![], 5);
+}
+
+#[test]
+fn test_array_index_out_of_bounds() {
+    assert_returns("arrayIndex", vec![sig::Type::Int], vec![int(3)], 1);
+    assert_returns("arrayIndex", vec![sig::Type::Int], vec![int(4)], -1);
+    assert_returns("arrayIndex", vec![sig::Type::Int], vec![int(-1)], -1);
+}
+
+#[test]
+fn test_arithmetic() {
+    let int_params = vec![sig::Type::Int, sig::Type::Int];
+    assert_returns("divide", int_params.clone(), vec![int(7), int(2)], 3);
+    assert_returns("divide", int_params.clone(), vec![int(7), int(0)], -1);
+    assert_returns("remainder", int_params.clone(), vec![int(7), int(2)], 1);
+    assert_returns("remainder", int_params, vec![int(7), int(0)], -1);
+}
+
+#[test]
+fn test_negative_array_size() {
+    assert_returns("negativeArraySize", vec![sig::Type::Int], vec![int(2)], 2);
+    assert_returns("negativeArraySize", vec![sig::Type::Int], vec![int(-2)], -1);
+}
+
+#[test]
+fn test_class_cast() {
+    assert_returns("badCast", vec![], vec![], 3);
+    assert_returns("goodCast", vec![], vec![], 4);
+}
+
+#[test]
+fn test_uncaught() {
+    match invoke("uncaught", vec![], vec![]) {
+        Err(exception) => {
+            assert_eq!(exception.borrow().get_class().symref.sig,
+                       sig::Class::Scalar(String::from("java/lang/ArrayIndexOutOfBoundsException")))
+        }
+        ret => panic!("Expected ArrayIndexOutOfBoundsException to be thrown, got {:?}", ret),
+    }
+}
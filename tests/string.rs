@@ -0,0 +1,70 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::num::Wrapping;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::string;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str, params: Vec<sig::Type>, return_type: sig::Type, args: Vec<Value>) -> Value {
+    let mut class_loader = ClassLoader::new(vec!["test_data/string".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Strings"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params,
+            return_type: Some(return_type),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(value)) => value,
+        ret => panic!("{} returned {:?}", name, ret),
+    }
+}
+
+/// Invokes a method of `Strings` and returns the UTF-16 code units of the string it returns
+fn invoke_string(name: &str, params: Vec<sig::Type>, args: Vec<Value>) -> Vec<u16> {
+    let string_type = sig::Type::Reference(sig::Class::Scalar(String::from("java/lang/String")));
+    match invoke(name, params, string_type, args) {
+        Value::Reference(string) => string::to_utf16(&string.borrow()),
+        ret => panic!("Expected String, got {:?}", ret),
+    }
+}
+
+fn utf16(string: &str) -> Vec<u16> {
+    string.encode_utf16().collect()
+}
+
+#[test]
+fn test_literals() {
+    assert_eq!(invoke_string("nul", vec![], vec![]), vec![0x61, 0, 0x62]);
+    assert_eq!(invoke_string("accented", vec![], vec![]), utf16("Paramètres généraux"));
+    assert_eq!(invoke_string("cjk", vec![], vec![]), utf16("日本語"));
+    assert_eq!(invoke_string("emoji", vec![], vec![]), vec![0xD83D, 0xDE00, 0x21]);
+    assert_eq!(invoke_string("lone", vec![], vec![]), vec![0xD800, 0x78]);
+    match invoke("emojiLength", vec![], sig::Type::Int, vec![]) {
+        Value::Int(length) => assert_eq!(length.0, 2),
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_concat() {
+    let chars = vec![Value::Int(Wrapping(0xD83D)), Value::Int(Wrapping(0xDE00))];
+    assert_eq!(invoke_string("concatChars", vec![sig::Type::Char, sig::Type::Char], chars),
+               utf16("[😀]"));
+    assert_eq!(invoke_string("concatAccented",
+                             vec![sig::Type::Int],
+                             vec![Value::Int(Wrapping(3))]),
+               utf16("Éléments : 3"));
+}
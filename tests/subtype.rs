@@ -0,0 +1,92 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::{Array, Value};
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+/// An argument for an invoked method: an array of `component` with the declared type `param`
+struct ArrayArg {
+    param: sig::Type,
+    component: sig::Type,
+    length: i32,
+}
+
+fn reference(name: &str) -> sig::Type {
+    sig::Type::Reference(sig::Class::new(name))
+}
+
+fn invoke(name: &str, array_args: Vec<ArrayArg>) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/subtype".into(),
+                                                 RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Subtypes"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let mut params = vec![];
+    let mut args = vec![];
+    for arg in array_args {
+        let array_sig = sig::Class::Array(Box::new(arg.component));
+        let array_class = class_loader.resolve_class_in(&None, &array_sig).unwrap();
+        let array = Array::new(array_class, arg.length);
+        params.push(arg.param);
+        args.push(Value::ArrayReference(Rc::new(RefCell::new(array))));
+    }
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params,
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_instanceof() {
+    assert_eq!(invoke("instanceOfClasses", vec![]), 3);
+    assert_eq!(invoke("instanceOfInterfaces", vec![]), 3);
+
+    let dogs = ArrayArg {
+        param: reference("java/lang/Object"),
+        component: reference("Subtypes$Dog"),
+        length: 1,
+    };
+    let ints = ArrayArg {
+        param: reference("java/lang/Object"),
+        component: sig::Type::Int,
+        length: 1,
+    };
+    assert_eq!(invoke("instanceOfArrays", vec![dogs, ints]), 51);
+}
+
+#[test]
+fn test_checkcast() {
+    let puppies = ArrayArg {
+        param: reference("[Ljava/lang/Object;"),
+        component: reference("Subtypes$Puppy"),
+        length: 2,
+    };
+    assert_eq!(invoke("checkCast", vec![puppies]), 2);
+}
+
+#[test]
+fn test_array_store() {
+    let dogs = ArrayArg {
+        param: reference("[Ljava/lang/Object;"),
+        component: reference("Subtypes$Dog"),
+        length: 2,
+    };
+    assert_eq!(invoke("arrayStore", vec![dogs]), 1);
+}
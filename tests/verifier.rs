@@ -0,0 +1,183 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::num::Wrapping;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::exception;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn new_class_loader() -> ClassLoader {
+    ClassLoader::new(vec!["test_data/verifier".into(), RUNTIME_PATH.into()])
+}
+
+/// Loads a class generated by `make_classes.py`, checking that it is rejected with a
+/// `VerifyError` whose message is `expected`.
+fn assert_rejected(name: &str, expected: &str) {
+    let mut class_loader = new_class_loader();
+    match class_loader.find_bootstrap_class(&sig::Class::Scalar(String::from(name))) {
+        Err(exception) => {
+            assert_eq!(exception::describe(&exception.borrow()),
+                       format!("java.lang.VerifyError: {}", expected))
+        }
+        Ok(class) => panic!("Expected {} to be rejected, got {:?}", name, class),
+    }
+    // A class that fails verification is not loaded
+    let sig = sig::Class::Scalar(String::from(name));
+    assert!(class_loader.find_loaded_class(&None, &sig).is_none());
+}
+
+/// Invokes a static method of a class generated by `make_classes.py` that returns an int.
+fn invoke_int(class_name: &str, name: &str, descriptor: &str, args: Vec<i32>) -> i32 {
+    let mut class_loader = new_class_loader();
+    let class_sig = sig::Class::Scalar(String::from(class_name));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method::new(String::from(name), String::from(descriptor)),
+    };
+    let method = class.find_method(&symref).borrow();
+    let args = args.into_iter().map(|arg| Value::Int(Wrapping(arg))).collect();
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_valid() {
+    assert_eq!(invoke_int("Valid", "max", "(II)I", vec![3, 8]), 8);
+}
+
+#[test]
+fn test_inferred() {
+    assert_eq!(invoke_int("Legacy", "sum", "(I)I", vec![4]), 10);
+}
+
+#[test]
+fn test_subroutine() {
+    assert_eq!(invoke_int("Legacy", "twice", "(I)I", vec![21]), 42);
+    assert_eq!(invoke_int("Legacy", "twiceWide", "(I)I", vec![8]), 16);
+}
+
+#[test]
+fn test_wide() {
+    assert_eq!(invoke_int("Legacy", "widen", "(I)I", vec![5]), 1005);
+}
+
+#[test]
+fn test_swap() {
+    assert_eq!(invoke_int("Legacy", "difference", "(II)I", vec![3, 8]), 5);
+}
+
+#[test]
+fn test_stack_instructions() {
+    // Longs take up two units of stack depth, so they are moved as a pair of ints would be
+    for &(name, expected) in &[("pop2", 1),
+                               ("pop2Long", 1),
+                               ("dupX2", 3123),
+                               ("dupX2Long", 212),
+                               ("dup2", 1212),
+                               ("dup2Long", 11),
+                               ("dup2X1", 23123),
+                               ("dup2X1Long", 212),
+                               ("dup2X2", 341234),
+                               ("dup2X2Long", 3123),
+                               ("dup2X2UnderLong", 23123),
+                               ("dup2X2Longs", 212)] {
+        assert_eq!(invoke_int("Stack", name, "()I", vec![]), expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_bad_operand() {
+    assert_rejected("BadOperand",
+                    "Bad type on operand stack (BadOperand.add()I at pc 3): expected int, found \
+                     java/lang/String");
+}
+
+#[test]
+fn test_stack_overflow() {
+    assert_rejected("StackOverflow",
+                    "Operand stack overflow (StackOverflow.add()I at pc 1): max_stack is 1");
+}
+
+#[test]
+fn test_missing_frame() {
+    assert_rejected("MissingFrame",
+                    "Expecting a stack map frame (MissingFrame.choose(I)I at pc 1): at branch \
+                     target 6");
+}
+
+#[test]
+fn test_inconsistent_frame() {
+    assert_rejected("InconsistentFrame",
+                    "Inconsistent stack map frame (InconsistentFrame.choose(I)I at pc 1): branch \
+                     target 6 expects float in local 0, found int");
+}
+
+#[test]
+fn test_bad_local() {
+    assert_rejected("BadLocal",
+                    "Bad local variable type (BadLocal.load()V at pc 0): expected float in local \
+                     0, found top");
+}
+
+#[test]
+fn test_uninitialized_this() {
+    assert_rejected("Uninitialized",
+                    "Constructor must call super() or this() before return \
+                     (Uninitialized.<init>()V at pc 1): this is uninitializedThis");
+}
+
+#[test]
+fn test_falls_off_end() {
+    assert_rejected("FallsOff",
+                    "Falling off the end of the code (FallsOff.run()V at pc 0): the last \
+                     instruction continues to the next");
+}
+
+#[test]
+fn test_jsr_in_modern_class() {
+    assert_rejected("ModernJsr",
+                    "Bad instruction (ModernJsr.run()V at pc 0): jsr is not allowed in class \
+                     files version 51 or later");
+}
+
+#[test]
+fn test_failover_to_inference() {
+    assert_eq!(invoke_int("Failover", "twice", "(I)I", vec![21]), 42);
+    assert_rejected("FailoverMismatch",
+                    "Mismatched stack types (FailoverMismatch.merge(I)V at pc 8): instruction 9 \
+                     has int at stack index 0, found float");
+}
+
+#[test]
+fn test_bad_catch_type() {
+    assert_rejected("BadCatchType",
+                    "Bad constant (BadCatchType.run()V at pc 0): #6 is not a class");
+}
+
+#[test]
+fn test_inferred_mismatched_stack() {
+    assert_rejected("LegacyMismatch",
+                    "Mismatched stack types (LegacyMismatch.merge(I)V at pc 8): instruction 9 \
+                     has int at stack index 0, found float");
+}
+
+#[test]
+fn test_inferred_stack_height() {
+    assert_rejected("LegacyStackHeight",
+                    "Inconsistent stack height (LegacyStackHeight.run(I)V at pc 4): instruction \
+                     5 has a stack size of 0, found 1");
+}
+
+#[test]
+fn test_inferred_bad_ret() {
+    assert_rejected("LegacyBadRet",
+                    "Bad local variable type (LegacyBadRet.run()V at pc 2): expected \
+                     returnAddress in local 0, found int");
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+extern crate rust_jvm;
+
+use std::num::Wrapping;
+use rust_jvm::vm::ClassLoader;
+use rust_jvm::vm::sig;
+use rust_jvm::vm::symref;
+use rust_jvm::vm::value::Value;
+
+const RUNTIME_PATH: &str = concat!(env!("OUT_DIR"), "/runtime");
+
+fn invoke(name: &str, kind: i32) -> i32 {
+    let mut class_loader = ClassLoader::new(vec!["test_data/virtual".into(), RUNTIME_PATH.into()]);
+    let class_sig = sig::Class::Scalar(String::from("Virtual"));
+    let class = class_loader.resolve_class_in(&None, &class_sig).unwrap();
+
+    let symref = symref::Method {
+        class: class.symref.clone(),
+        sig: sig::Method {
+            name: String::from(name),
+            params: vec![sig::Type::Int],
+            return_type: Some(sig::Type::Int),
+        },
+    };
+
+    let method = class.find_method(&symref).borrow();
+    let args = vec![Value::Int(Wrapping(kind))];
+    match method.invoke(&class, &mut class_loader, Some(args)) {
+        Ok(Some(Value::Int(value))) => value.0,
+        ret => panic!("Expected Int, got {:?}", ret),
+    }
+}
+
+#[test]
+fn test_override() {
+    assert_eq!(invoke("area", 0), 6);
+    assert_eq!(invoke("area", 1), 10);
+    assert_eq!(invoke("area", 2), 3);
+}
+
+#[test]
+fn test_inherited_dispatch() {
+    assert_eq!(invoke("describe", 0), 604);
+    assert_eq!(invoke("describe", 1), 1004);
+    assert_eq!(invoke("describe", 2), 300);
+}
+
+#[test]
+fn test_call_site_receivers() {
+    // The vtable index recorded on the first call selects each receiver's own method
+    assert_eq!(invoke("totalArea", 6), 38);
+}
+
+#[test]
+fn test_inherited_interface_method() {
+    assert_eq!(invoke("measure", 12), 12);
+}